@@ -11,7 +11,7 @@ use near_sdk::{
     env,
     json_types::{U128, U64},
     serde::{Deserialize, Serialize},
-    AccountId, CryptoHash,
+    serde_json, AccountId, CryptoHash,
 };
 use std::collections::HashMap;
 
@@ -25,6 +25,15 @@ pub enum CorePanics {
     /// Thrown when a `Fraction` is more than `1`.
     #[panic_msg = "The fraction must be less or equal to 1"]
     FractionGreaterThanOne,
+    /// Thrown when basis points are more than `10000`, *i.e.*, more than `1`.
+    #[panic_msg = "Basis points must be less or equal to 10000, but was {}"]
+    BasisPointsGreaterThanOne { bps: u16 },
+    /// Thrown when increasing a supply exceeds `u16::MAX`.
+    #[panic_msg = "Supply overflow when adding {} to {}"]
+    SupplyOverflow { delta: u16, supply: u16 },
+    /// Thrown when decreasing a supply goes below `0`.
+    #[panic_msg = "Supply underflow when subtracting {} from {}"]
+    SupplyUnderflow { delta: u16, supply: u16 },
 }
 
 pub mod fraction {
@@ -32,10 +41,10 @@ pub mod fraction {
     use super::CorePanics;
     use near_sdk::{
         borsh::{self, BorshDeserialize, BorshSerialize},
-        serde::{Deserialize, Serialize},
+        serde::{self, Deserialize, Serialize},
         Balance,
     };
-    use std::{fmt::Display, num::ParseIntError, str::FromStr, u128};
+    use std::{convert::TryInto, fmt::Display, iter::Sum, num::ParseIntError, str::FromStr, u128};
 
     uint::construct_uint! {
         /// 256-bit unsigned integer.
@@ -56,7 +65,25 @@ pub mod fraction {
         pub den: u32,
     }
 
+    /// Number of basis points representing `1`.
+    pub const BASIS_POINTS: u16 = 10_000;
+
     impl Fraction {
+        /// Creates a `Fraction` from the given basis points, *i.e.*, `bps/10000`.
+        /// Panics if `bps` is greater than `10000`.
+        pub fn from_basis_points(bps: u16) -> Fraction {
+            if bps > BASIS_POINTS {
+                CorePanics::BasisPointsGreaterThanOne { bps }.panic();
+            }
+            Fraction { num: bps as u32, den: BASIS_POINTS as u32 }
+        }
+
+        /// Returns this `Fraction` expressed in basis points.
+        /// Any remainder smaller than one basis point is truncated.
+        pub fn to_basis_points(&self) -> u16 {
+            self.mult(BASIS_POINTS as Balance) as u16
+        }
+
         /// Checks the given `Fraction` is valid, *i.e.*,
         /// - Has a non-zero denominator, and
         /// - The `num` is less or equal than `den`ominator.
@@ -73,6 +100,121 @@ pub mod fraction {
         pub fn mult(&self, value: Balance) -> Balance {
             (U256::from(self.num) * U256::from(value) / U256::from(self.den)).as_u128()
         }
+
+        /// Returns this `Fraction` as a `"num/den"` string, *e.g.*, `"25/1000"`.
+        pub fn to_ratio_string(&self) -> String {
+            format!("{}/{}", self.num, self.den)
+        }
+
+        /// Returns this `Fraction` reduced to lowest terms, *e.g.*, `25/1000` into `1/40`.
+        /// A zero `Fraction` is reduced to `0/1`.
+        pub fn reduce(&self) -> Fraction {
+            Self::reduced(self.num as u64, self.den as u64).unwrap()
+        }
+
+        /// Adds `other` to this `Fraction`, reducing the result to lowest terms.
+        /// Returns `None` when the result is greater than `1`.
+        pub fn checked_add(&self, other: &Fraction) -> Option<Fraction> {
+            let lhs = self.num as u64 * other.den as u64;
+            let rhs = other.num as u64 * self.den as u64;
+            let num = lhs.checked_add(rhs)?;
+            let den = self.den as u64 * other.den as u64;
+            if num > den {
+                return None;
+            }
+            Self::reduced(num, den)
+        }
+
+        /// Subtracts `other` from this `Fraction`, reducing the result to lowest terms.
+        /// Returns `None` when `other` is greater than this `Fraction`.
+        pub fn checked_sub(&self, other: &Fraction) -> Option<Fraction> {
+            let lhs = self.num as u64 * other.den as u64;
+            let rhs = other.num as u64 * self.den as u64;
+            let num = lhs.checked_sub(rhs)?;
+            Self::reduced(num, self.den as u64 * other.den as u64)
+        }
+
+        /// Multiplies this `Fraction` by `other`, reducing the result to lowest terms.
+        pub fn checked_mul(&self, other: &Fraction) -> Option<Fraction> {
+            Self::reduced(self.num as u64 * other.num as u64, self.den as u64 * other.den as u64)
+        }
+
+        /// Creates the `Fraction` `num/den` reduced to lowest terms.
+        /// Returns `None` when `den` is `0` or the reduced `Fraction` does not fit into `u32`.
+        fn reduced(num: u64, den: u64) -> Option<Fraction> {
+            if den == 0 {
+                return None;
+            }
+            let gcd = gcd(num, den);
+            Some(Fraction { num: (num / gcd).try_into().ok()?, den: (den / gcd).try_into().ok()? })
+        }
+    }
+
+    /// Adds up `Fraction`s as in `checked_add`.
+    /// The sum of no `Fraction`s is `0/1`.
+    /// Returns `None` when any partial sum is greater than `1`.
+    impl<'a> Sum<&'a Fraction> for Option<Fraction> {
+        fn sum<I: Iterator<Item = &'a Fraction>>(mut iter: I) -> Self {
+            iter.try_fold(Fraction { num: 0, den: 1 }, |sum, fraction| sum.checked_add(fraction))
+        }
+    }
+
+    /// Greatest common divisor of `a` and `b`, using Euclid's algorithm.
+    fn gcd(mut a: u64, mut b: u64) -> u64 {
+        while b != 0 {
+            let r = a % b;
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    /// A `Fraction` serialized as a `"num/den"` string, *e.g.*, `"25/1000"`,
+    /// instead of the default `{"num":25,"den":1000}` object.
+    /// Deserializing an invalid `Fraction` panics as in `Fraction::check`.
+    #[derive(BorshDeserialize, BorshSerialize, PartialEq, Eq)]
+    #[cfg_attr(not(target_arch = "wasm"), derive(Debug, Clone, Copy))]
+    pub struct FractionStr(pub Fraction);
+
+    impl From<Fraction> for FractionStr {
+        fn from(fraction: Fraction) -> Self {
+            Self(fraction)
+        }
+    }
+
+    impl From<FractionStr> for Fraction {
+        fn from(fraction: FractionStr) -> Self {
+            fraction.0
+        }
+    }
+
+    impl serde::Serialize for FractionStr {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            serializer.serialize_str(&self.0.to_ratio_string())
+        }
+    }
+
+    impl<'de> serde::Deserialize<'de> for FractionStr {
+        fn deserialize<D>(deserializer: D) -> Result<Self, <D as serde::Deserializer<'de>>::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+            let mut parts = s.splitn(2, '/');
+            let mut parse = || -> Result<u32, D::Error> {
+                parts
+                    .next()
+                    .ok_or_else(|| serde::de::Error::custom(format!("Invalid fraction `{}`", s)))?
+                    .parse::<u32>()
+                    .map_err(|err| serde::de::Error::custom(err.to_string()))
+            };
+            let fraction = Fraction { num: parse()?, den: parse()? };
+            fraction.check();
+            Ok(Self(fraction))
+        }
     }
 
     impl PartialEq for Fraction {
@@ -110,6 +252,32 @@ pub mod fraction {
     }
 }
 
+/// Supply arithmetic for `Collectible`s.
+/// Every mutation of a supply counter should go through these functions,
+/// so that overflows and underflows are reported consistently.
+pub mod supply {
+
+    use super::CorePanics;
+
+    /// Returns `supply + delta`.
+    /// Panics with `SupplyOverflow` if the result does not fit in `u16`.
+    pub fn checked_add(supply: u16, delta: u16) -> u16 {
+        match supply.checked_add(delta) {
+            None => CorePanics::SupplyOverflow { delta, supply }.panic(),
+            Some(result) => result,
+        }
+    }
+
+    /// Returns `supply - delta`.
+    /// Panics with `SupplyUnderflow` if `delta` is greater than `supply`.
+    pub fn checked_sub(supply: u16, delta: u16) -> u16 {
+        match supply.checked_sub(delta) {
+            None => CorePanics::SupplyUnderflow { delta, supply }.panic(),
+            Some(result) => result,
+        }
+    }
+}
+
 pub mod gate {
 
     use near_sdk::{
@@ -271,6 +439,24 @@ pub fn crypto_hash(value: &String) -> CryptoHash {
     hash
 }
 
+/// Prefix used to log events, as defined by NEP-297.
+///
+/// <https://nomicon.io/Standards/EventsFormat.html>
+pub const EVENT_JSON: &str = "EVENT_JSON:";
+
+/// Logs the given `event` using the NEP-297 envelope.
+/// The `standard` indicates which contract emits the event, *e.g.*, `mg-market`.
+/// The `data` is serialized as JSON into the `data` field of the envelope.
+pub fn log_event<T: Serialize>(standard: &str, event: &str, data: T) {
+    let envelope = serde_json::json!({
+        "standard": standard,
+        "version": "1.0.0",
+        "event": event,
+        "data": data,
+    });
+    env::log(format!("{}{}", EVENT_JSON, envelope).as_bytes());
+}
+
 /// A `Collectible` represents something of value.
 /// `Token`s can be then minted from a given collectible.
 /// A collectible is identified by `gate_id`.
@@ -284,11 +470,53 @@ pub struct Collectible {
     pub creator_id: AccountId,
     /// Indicates how many `Token`s can be minted out of this `Collectible`.
     pub current_supply: u16,
+    /// When set, this `Collectible` is an open edition,
+    /// *i.e.*, any number of `Token`s can be minted out of it until this time (in milliseconds).
+    /// Open editions do not use `current_supply`.
+    pub open_until: Option<Timestamp>,
     /// The list of `TokenId`s actually minted out of this `Collectible`.
+    /// It is sorted by `TokenId`, since tokens are minted with increasing ids.
     pub minted_tokens: Vec<TokenId>,
     /// Indicates the royalty as percentage (in NEARs) to be paid to `creator_id`
     /// every time a minted token out of this `Collectible` is reselled.
     pub royalty: Fraction,
+    /// Indicates the royalty to be paid to `creator_id` on the first resale of a token,
+    /// if different from `royalty`.
+    /// Subsequent resales use `royalty`.
+    pub first_sale_royalty: Option<Fraction>,
+    /// Indicates whether the royalties of this `Collectible` are fixed for good,
+    /// *i.e.*, no `Token` can be minted out of it with a different royalty.
+    pub royalty_locked: bool,
+    /// Minimum price (in yoctoNEARs) set by `creator_id` under which
+    /// tokens of this `Collectible` cannot be approved for resale, if any.
+    pub min_resale_price: Option<U128>,
+    /// Indicates whether tokens of this `Collectible` can be approved with a zero `min_price`,
+    /// i.e., given away for free.
+    pub allow_zero_price: bool,
+    /// Indicates whether tokens of this `Collectible` can currently be claimed.
+    pub claimable: bool,
+    /// Maximum number of `Token`s each account can claim out of this `Collectible`, if any.
+    pub max_claims_per_account: Option<u16>,
+    /// Minimum time (in nanoseconds) each account must wait between two claims
+    /// of `Token`s out of this `Collectible`, if any.
+    pub claim_cooldown_ns: Option<u64>,
+    /// Price (in yoctoNEARs) paid to `creator_id` when claiming a `Token`, if any.
+    pub mint_price: Option<U128>,
+    /// Small inline image as a base64 data URL, if any.
+    pub thumbnail: Option<String>,
+    /// Template for the title of each `Token` minted out of this `Collectible`, if any,
+    /// *e.g.*, `"Parcel #{index}"`.
+    /// The `{index}` placeholder is replaced by the position (starting at `1`) of the token in `minted_tokens`.
+    pub title_template: Option<String>,
+    /// Indicates whether each `Token` minted out of this `Collectible`
+    /// records its serial number in its `extra` metadata, *e.g.*, `{"serial":1}`.
+    pub serial_numbers: bool,
+    /// Number of `Token`s ever minted out of this `Collectible`, burned tokens included.
+    /// The serial number of a `Token` is the value of this counter right after it was minted.
+    pub minted_count: u64,
+    /// Represents when this `Collectible` was created, in milliseconds.
+    /// Unlike `metadata.issued_at`, it is not meant to describe its tokens.
+    pub created_at: Timestamp,
     /// Additional info provided by NEP-177.
     pub metadata: Metadata,
 }
@@ -310,13 +538,17 @@ pub struct Token {
     /// Once this `Token` is minted, this field remains unchanged.
     pub created_at: Timestamp,
     /// Represents when this `Token` was last modified, in nanoseconds.
-    /// Either when created or transferred.
+    /// Either when created, transferred or its approvals changed.
     pub modified_at: Timestamp,
     /// Holds the list of accounts that can `transfer_token`s on behalf of the token's owner.
     /// It is mapped to the approval id and minimum amount that this token should be transfer for.
     pub approvals: HashMap<AccountId, TokenApproval>,
     /// Counter to assign next approval ID.
     pub approval_counter: U64,
+    /// Indicates whether this `Token` has already been sold through `nft_transfer_payout`.
+    /// Used to determine which royalty applies to the next sale.
+    /// Not present in the stored layout of previous versions, see *State Layout* in the README.
+    pub has_been_sold: bool,
 
     #[borsh_skip]
     /// Additional info defined by NEP-177.
@@ -324,6 +556,25 @@ pub struct Token {
     pub metadata: Metadata,
 }
 
+/// Tokens are ordered by `token_id`, so query results can be sorted off-chain.
+/// Not available in wasm to keep the contract size unaffected.
+#[cfg(not(target_arch = "wasm"))]
+impl Eq for Token {}
+
+#[cfg(not(target_arch = "wasm"))]
+impl PartialOrd for Token {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(not(target_arch = "wasm"))]
+impl Ord for Token {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.token_id.0.cmp(&other.token_id.0)
+    }
+}
+
 /// Associated metadata with a `GateId` as defined by NEP-177
 ///
 /// Doc-comments for these fields were taken from:
@@ -362,20 +613,23 @@ pub struct Metadata {
 }
 
 /// Represents an individual approval by some marketplace account id.
-#[derive(BorshDeserialize, BorshSerialize, Serialize)]
-#[cfg_attr(not(target_arch = "wasm"), derive(PartialEq, Debug, Deserialize))]
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm"), derive(PartialEq, Debug))]
 #[serde(crate = "near_sdk::serde")]
 pub struct TokenApproval {
     /// Id used to avoid selling the same token more than once.
     pub approval_id: U64,
     /// Minimum price a token should be sell for.
     pub min_price: U128,
+    /// UNIX epoch datetime (in miliseconds) after which this approval can no longer be used.
+    /// Not present in the stored layout of previous versions, see *State Layout* in the README.
+    pub expires_at: Option<Timestamp>,
 }
 
 impl TokenApproval {
     #[cfg(not(target_arch = "wasm"))]
     pub fn new(approval_id: u64, min_price: U128) -> Self {
-        Self { approval_id: approval_id.into(), min_price }
+        Self { approval_id: approval_id.into(), min_price, expires_at: None }
     }
 }
 
@@ -390,6 +644,7 @@ pub mod nep171 {
     use near_env::near_ext;
     use near_sdk::ext_contract;
     use near_sdk::json_types::{ValidAccountId, U128, U64};
+    use near_sdk::{AccountId, Promise};
 
     #[near_ext]
     #[ext_contract(nft)]
@@ -413,8 +668,31 @@ pub mod nep171 {
             balance: Option<U128>,
         ) -> Option<Payout>;
 
+        fn nft_transfer_call(
+            &mut self,
+            receiver_id: ValidAccountId,
+            token_id: TokenId,
+            approval_id: Option<U64>,
+            memo: Option<String>,
+            msg: String,
+        ) -> Promise;
+
         fn nft_token(&self, token_id: TokenId) -> Option<Token>;
     }
+
+    /// This interface defines the method to be called on the receiver of `nft_transfer_call`.
+    #[near_ext]
+    #[ext_contract(receiver)]
+    pub trait NonFungibleTokenReceiver {
+        /// Returns `true` if the token should be returned back to `previous_owner_id`.
+        fn nft_on_transfer(
+            &mut self,
+            sender_id: AccountId,
+            previous_owner_id: AccountId,
+            token_id: TokenId,
+            msg: String,
+        ) -> bool;
+    }
 }
 
 /// Non-Fungible Token Metadata (NEP-177) v1.0.0
@@ -531,6 +809,29 @@ pub mod nep181 {
     }
 }
 
+/// Contract Source Metadata (NEP-330) v1.0.0
+///
+/// <https://github.com/near/NEPs/blob/master/neps/nep-0330.md>
+pub mod nep330 {
+
+    use near_sdk::serde::{Deserialize, Serialize};
+
+    /// Describes the code a contract is running.
+    #[derive(Serialize, Deserialize)]
+    #[cfg_attr(not(target_arch = "wasm"), derive(PartialEq, Debug))]
+    #[serde(crate = "near_sdk::serde")]
+    pub struct SourceMetadata {
+        /// Version of the contract code, *e.g.*, the crate version.
+        pub version: Option<String>,
+        /// Link to the source code of the contract.
+        pub link: Option<String>,
+    }
+
+    pub trait ContractSourceMetadata {
+        fn contract_source_metadata(&self) -> SourceMetadata;
+    }
+}
+
 /// In our implementation of the standard,
 /// The `nft_approve` method must conform with the following:
 /// - The `msg` argument must contain a value, *i.e.*, cannot be `None`.
@@ -541,6 +842,8 @@ pub mod nep181 {
 pub struct NftApproveMsg {
     /// Indicates the minimum price (in NEARs) requested by owner to pay for the token.
     pub min_price: U128,
+    /// UNIX epoch datetime (in miliseconds) when the approval expires, if any.
+    pub expires_at: Option<Timestamp>,
 }
 
 /// Represents the payload that arrives to the Marketplace contract,
@@ -1,5 +1,5 @@
-use mg_core::fraction::Fraction;
-use near_sdk::{test_utils::VMContextBuilder, testing_env, MockedBlockchain};
+use mg_core::fraction::{Fraction, FractionStr};
+use near_sdk::{serde_json, test_utils::VMContextBuilder, testing_env, MockedBlockchain};
 use std::cmp::Ordering;
 
 #[test]
@@ -57,3 +57,143 @@ fn less_than_and_greater_than() {
     assert_eq!(Fraction { num: 1, den: 1 }.cmp(&Fraction { num: 9, den: 10 }), Ordering::Greater);
     assert_eq!(Fraction { num: 2, den: 3 }.cmp(&Fraction { num: 5, den: 10 }), Ordering::Greater);
 }
+
+#[test]
+fn from_basis_points() {
+    assert_eq!(Fraction::from_basis_points(0), Fraction { num: 0, den: 1 });
+    assert_eq!(Fraction::from_basis_points(250), Fraction { num: 25, den: 1000 });
+    assert_eq!(Fraction::from_basis_points(10_000), Fraction { num: 1, den: 1 });
+}
+
+#[test]
+fn to_basis_points() {
+    assert_eq!(Fraction { num: 1, den: 2 }.to_basis_points(), 5_000);
+    assert_eq!(Fraction { num: 1, den: 3 }.to_basis_points(), 3_333);
+    assert_eq!(Fraction::from_basis_points(250).to_basis_points(), 250);
+}
+
+#[test]
+#[should_panic(expected = "Basis points must be less or equal to 10000, but was 10001")]
+fn basis_points_greater_than_one_should_panic() {
+    testing_env!(VMContextBuilder::new().build());
+    Fraction::from_basis_points(10_001);
+}
+
+#[test]
+fn to_ratio_string() {
+    assert_eq!(Fraction { num: 25, den: 1000 }.to_ratio_string(), "25/1000");
+    assert_eq!(Fraction { num: 0, den: 1 }.to_ratio_string(), "0/1");
+}
+
+#[test]
+fn fraction_str_round_trip() {
+    let fraction: FractionStr = serde_json::from_str(r#""25/1000""#).unwrap();
+    assert_eq!(fraction, FractionStr(Fraction { num: 25, den: 1000 }));
+    assert_eq!(fraction.0.num, 25);
+    assert_eq!(fraction.0.den, 1000);
+    assert_eq!(serde_json::to_string(&fraction).unwrap(), r#""25/1000""#);
+}
+
+#[test]
+fn fraction_keeps_struct_representation() {
+    let fraction = Fraction { num: 25, den: 1000 };
+    assert_eq!(serde_json::to_string(&fraction).unwrap(), r#"{"num":25,"den":1000}"#);
+    assert_eq!(Fraction::from(FractionStr::from(fraction)), fraction);
+}
+
+#[test]
+fn fraction_str_with_invalid_format() {
+    assert!(serde_json::from_str::<FractionStr>(r#""25""#).is_err());
+    assert!(serde_json::from_str::<FractionStr>(r#""a/1000""#).is_err());
+    assert!(serde_json::from_str::<FractionStr>(r#"{"num":25,"den":1000}"#).is_err());
+}
+
+#[test]
+#[should_panic(expected = "Denominator must be a positive number, but was 0")]
+fn fraction_str_with_zero_denominator_should_panic() {
+    testing_env!(VMContextBuilder::new().build());
+    let _ = serde_json::from_str::<FractionStr>(r#""1/0""#);
+}
+
+#[test]
+#[should_panic(expected = "The fraction must be less or equal to 1")]
+fn fraction_str_greater_than_one_should_panic() {
+    testing_env!(VMContextBuilder::new().build());
+    let _ = serde_json::from_str::<FractionStr>(r#""2/1""#);
+}
+
+#[test]
+fn checked_sub_reduces_the_result() {
+    let fraction =
+        Fraction { num: 1, den: 1 }.checked_sub(&Fraction { num: 30, den: 100 }).unwrap();
+    assert_eq!((fraction.num, fraction.den), (7, 10));
+
+    let fraction = fraction.checked_sub(&Fraction { num: 25, den: 1000 }).unwrap();
+    assert_eq!((fraction.num, fraction.den), (27, 40));
+}
+
+#[test]
+fn checked_sub_to_zero() {
+    let fraction = Fraction { num: 1, den: 2 }.checked_sub(&Fraction { num: 2, den: 4 }).unwrap();
+    assert_eq!((fraction.num, fraction.den), (0, 1));
+}
+
+#[test]
+fn checked_sub_greater_fraction() {
+    assert_eq!(Fraction { num: 1, den: 3 }.checked_sub(&Fraction { num: 1, den: 2 }), None);
+}
+
+#[test]
+fn reduce() {
+    let fraction = Fraction { num: 25, den: 1000 }.reduce();
+    assert_eq!((fraction.num, fraction.den), (1, 40));
+    let fraction = Fraction { num: 0, den: 7 }.reduce();
+    assert_eq!((fraction.num, fraction.den), (0, 1));
+}
+
+#[test]
+fn checked_add() {
+    let fraction = Fraction { num: 1, den: 2 }.checked_add(&Fraction { num: 1, den: 3 }).unwrap();
+    assert_eq!((fraction.num, fraction.den), (5, 6));
+}
+
+#[test]
+fn checked_add_greater_than_one() {
+    assert_eq!(Fraction { num: 1, den: 2 }.checked_add(&Fraction { num: 2, den: 3 }), None);
+}
+
+#[test]
+fn checked_sub() {
+    let fraction = Fraction { num: 3, den: 4 }.checked_sub(&Fraction { num: 1, den: 4 }).unwrap();
+    assert_eq!((fraction.num, fraction.den), (1, 2));
+}
+
+#[test]
+fn checked_mul() {
+    let fraction = Fraction { num: 2, den: 3 }.checked_mul(&Fraction { num: 3, den: 4 }).unwrap();
+    assert_eq!((fraction.num, fraction.den), (1, 2));
+}
+
+#[test]
+fn checked_mul_does_not_overflow_u32() {
+    let big = Fraction { num: u32::MAX - 2, den: u32::MAX };
+    let fraction = big.checked_mul(&Fraction { num: 1, den: 2 });
+    assert_eq!(fraction, None);
+
+    let fraction = big.checked_mul(&Fraction { num: 1, den: 1 }).unwrap();
+    assert_eq!((fraction.num, fraction.den), (u32::MAX - 2, u32::MAX));
+}
+
+#[test]
+fn sum() {
+    let fractions =
+        [Fraction { num: 1, den: 2 }, Fraction { num: 1, den: 3 }, Fraction { num: 1, den: 6 }];
+    let sum = fractions.iter().sum::<Option<Fraction>>().unwrap();
+    assert_eq!((sum.num, sum.den), (1, 1));
+
+    let sum = [].iter().sum::<Option<Fraction>>().unwrap();
+    assert_eq!((sum.num, sum.den), (0, 1));
+
+    let fractions = [Fraction { num: 2, den: 3 }, Fraction { num: 2, den: 3 }];
+    assert_eq!(fractions.iter().sum::<Option<Fraction>>(), None);
+}
@@ -0,0 +1,30 @@
+use mg_core::supply::{checked_add, checked_sub};
+use near_sdk::{test_utils::VMContextBuilder, testing_env, MockedBlockchain};
+
+#[test]
+fn add_to_supply() {
+    assert_eq!(checked_add(0, 1), 1);
+    assert_eq!(checked_add(10, 5), 15);
+    assert_eq!(checked_add(u16::MAX - 1, 1), u16::MAX);
+}
+
+#[test]
+fn subtract_from_supply() {
+    assert_eq!(checked_sub(1, 1), 0);
+    assert_eq!(checked_sub(10, 5), 5);
+    assert_eq!(checked_sub(u16::MAX, 1), u16::MAX - 1);
+}
+
+#[test]
+#[should_panic(expected = "Supply overflow when adding 1 to 65535")]
+fn add_beyond_max_supply_should_panic() {
+    testing_env!(VMContextBuilder::new().build());
+    checked_add(u16::MAX, 1);
+}
+
+#[test]
+#[should_panic(expected = "Supply underflow when subtracting 1 from 0")]
+fn subtract_from_zero_supply_should_panic() {
+    testing_env!(VMContextBuilder::new().build());
+    checked_sub(0, 1);
+}
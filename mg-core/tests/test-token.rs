@@ -0,0 +1,30 @@
+use mg_core::{Metadata, Token};
+use near_sdk::json_types::U64;
+use std::collections::HashMap;
+
+fn token(token_id: u64) -> Token {
+    Token {
+        token_id: U64(token_id),
+        gate_id: "gate".to_string(),
+        owner_id: "alice".to_string(),
+        created_at: 0,
+        modified_at: 0,
+        approvals: HashMap::new(),
+        approval_counter: U64(0),
+        has_been_sold: false,
+        metadata: Metadata::default(),
+    }
+}
+
+#[test]
+fn sort_tokens_by_token_id() {
+    let mut tokens = [token(7), token(3), token(11), token(1), token(5)];
+    tokens.sort();
+    assert_eq!(tokens.iter().map(|t| t.token_id.0).collect::<Vec<_>>(), vec![1, 3, 5, 7, 11]);
+}
+
+#[test]
+fn compare_tokens_by_token_id() {
+    assert!(token(1) < token(2));
+    assert_eq!(token(4).max(token(9)).token_id, U64(9));
+}
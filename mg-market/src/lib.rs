@@ -2,39 +2,106 @@
 #![deny(warnings)]
 
 use std::{
+    cmp::Ordering,
+    collections::HashSet,
     convert::TryInto,
     fmt::{Debug, Display},
+    hash::{Hash, Hasher},
 };
 
 use mg_core::{
     crypto_hash,
+    fraction::Fraction,
     gate::{GateId, ValidGateId},
+    log_event,
     nep178::NonFungibleTokenApprovalsReceiver,
+    nep330::{ContractSourceMetadata, SourceMetadata},
     MarketApproveMsg, Payout, TokenId,
 };
 use near_env::{near_ext, near_log, PanicMessage};
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    collections::{LookupMap, UnorderedMap, UnorderedSet},
+    collections::{LookupMap, UnorderedMap, UnorderedSet, Vector},
     env, ext_contract,
     json_types::{ValidAccountId, U128, U64},
     near_bindgen,
     serde::{Deserialize, Serialize},
     serde_json, setup_alloc, AccountId, Balance, BorshStorageKey, CryptoHash, Gas, PanicOnDefault,
-    Promise, PromiseResult,
+    Promise, PromiseResult, Timestamp,
 };
 
 setup_alloc!();
 
-const GAS_FOR_ROYALTIES: Gas = 120_000_000_000_000;
 const NO_DEPOSIT: Balance = 0;
 
+/// Gas given to the `make_payouts` callback of each sale, both in `buy_token` and `batch_buy`.
+/// It covers one transfer per payout entry, *i.e.*, the seller, the creator and the fee accounts,
+/// plus the bounded updates of the purchase history and the recent sales.
+const GAS_FOR_MAKE_PAYOUTS: Gas = 40_000_000_000_000;
+
+/// Gas used by `buy_token` regardless of the gas forwarded to `nft_token` and `resolve_purchase`.
+const GAS_FOR_BUY_TOKEN: Gas = 30_000_000_000_000;
+
+/// Gas forwarded to `nft_token` to check the seller still owns the token being bought.
+const GAS_FOR_NFT_TOKEN: Gas = 10_000_000_000_000;
+
+/// Gas used by `resolve_purchase`
+/// regardless of the gas forwarded to `nft_transfer_payout` and `make_payouts`.
+const GAS_FOR_RESOLVE_PURCHASE: Gas = 30_000_000_000_000;
+
+/// Minimum gas forwarded to `nft_transfer_payout` when buying a token.
+const MIN_GAS_FOR_BUY_TOKEN_TRANSFER: Gas = 40_000_000_000_000;
+
+/// Minimum prepaid gas for `buy_token`.
+/// The `nft_token` ownership check is given `GAS_FOR_NFT_TOKEN`,
+/// and the `make_payouts` callback is given `GAS_FOR_MAKE_PAYOUTS`,
+/// whereas the remaining gas is forwarded to `nft_transfer_payout`.
+pub const MIN_GAS_FOR_BUY_TOKEN: Gas = GAS_FOR_BUY_TOKEN
+    + GAS_FOR_NFT_TOKEN
+    + GAS_FOR_RESOLVE_PURCHASE
+    + MIN_GAS_FOR_BUY_TOKEN_TRANSFER
+    + GAS_FOR_MAKE_PAYOUTS;
+
+/// Gas used by `batch_buy` regardless of the number of tokens to buy.
+const GAS_FOR_BATCH_BUY: Gas = 20_000_000_000_000;
+
+/// Gas given to `resolve_purchase` for each token bought in `batch_buy`.
+/// As in `buy_token`, it forwards `MIN_GAS_FOR_BUY_TOKEN_TRANSFER` to `nft_transfer_payout`
+/// and `GAS_FOR_MAKE_PAYOUTS` to `make_payouts`.
+const GAS_FOR_BATCH_BUY_RESOLVE_PURCHASE: Gas =
+    GAS_FOR_RESOLVE_PURCHASE + MIN_GAS_FOR_BUY_TOKEN_TRANSFER + GAS_FOR_MAKE_PAYOUTS;
+
+/// Minimum prepaid gas for each token bought in `batch_buy`.
+/// Each token goes through the same `nft_token` ownership check and `resolve_purchase` as in `buy_token`.
+/// Besides the gas given to both calls, it covers the cost of scheduling them.
+pub const MIN_GAS_FOR_BATCH_BUY_ITEM: Gas =
+    GAS_FOR_NFT_TOKEN + GAS_FOR_BATCH_BUY_RESOLVE_PURCHASE + 10_000_000_000_000;
+
+/// Maximum number of purchases kept in the history of each buyer.
+/// When exceeded, the oldest purchases are dropped.
+pub const MAX_PURCHASES_PER_BUYER: usize = 100;
+
+/// Maximum number of sales kept in the recent sales of this marketplace.
+/// When exceeded, the oldest sales are dropped.
+pub const MAX_RECENT_SALES: usize = 100;
+
+/// Maximum number of gate IDs given to `get_tokens_by_gate_ids`.
+pub const MAX_GATE_IDS_PER_QUERY: usize = 20;
+
+/// Maximum number of tokens given to `batch_update_price`, `batch_buy` and `batch_on_approve`.
+/// It covers the largest batch the NFT contract sends to `batch_on_approve`.
+pub const MAX_BATCH_SIZE: usize = 60;
+
+/// Default balance (in yoctoNEARs) kept on top of the storage staking requirement,
+/// *i.e.*, 1 NEAR.
+pub const DEFAULT_STORAGE_RESERVE_MARGIN: Balance = 1_000_000_000_000_000_000_000_000;
+
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct MarketContract {
     /// Lists all tokens for sale.
     tokens_for_sale: UnorderedMap<TokenKey, TokenForSale>,
-    /// Holds token IDs for sale by `gate_id`.
+    /// Holds token IDs for sale by `nft_contract_id`.
     tokens_by_nft_id: LookupMap<AccountId, UnorderedSet<TokenId>>,
     /// Holds token IDs for sale by `gate_id`.
     tokens_by_gate_id: LookupMap<GateId, UnorderedSet<TokenKey>>,
@@ -42,13 +109,84 @@ pub struct MarketContract {
     tokens_by_owner_id: LookupMap<AccountId, UnorderedSet<TokenKey>>,
     /// Holds token IDs for sale by `creator_id`.
     tokens_by_creator_id: LookupMap<AccountId, UnorderedSet<TokenKey>>,
+    /// Deposit (in yoctoNEARs) required to list a token for sale.
+    listing_fee: Balance,
+    /// Designated MintGate NEAR account id receiving the fee of each sale.
+    mintgate_fee_account_id: AccountId,
+    /// Sum of all fees (in yoctoNEARs) paid to `mintgate_fee_account_id` through this marketplace.
+    lifetime_fees_routed: Balance,
+    /// Sum of the `min_price` (in yoctoNEARs) of all tokens for sale.
+    /// It is kept up to date on every listing change, so it is not recomputed on each query.
+    total_listed_value: Balance,
+    /// Holds the most recent purchases of each buyer, oldest first.
+    /// Each purchase records the token bought, the price paid and when (in milliseconds).
+    purchases_by_buyer: LookupMap<AccountId, Vec<(TokenKey, U128, Timestamp)>>,
+    /// Sum of the listing fees (in yoctoNEARs) held for all tokens for sale.
+    /// These are owed to the token owners in case their listings are removed without a sale.
+    listing_fees_held: Balance,
+    /// Sum of the sale amounts (in yoctoNEARs) held while their purchases are resolved.
+    /// These are owed to the buyers or the payout receivers once `make_payouts` completes.
+    in_flight_sales: Balance,
+    /// Account allowed to perform administrative operations, *e.g.*, `admin_withdraw`.
+    admin_id: AccountId,
+    /// Promotional codes accepted by `buy_token_with_code`.
+    /// Each code maps to the discount on `min_price` and its expiration (in milliseconds).
+    discounts: UnorderedMap<String, (Fraction, Timestamp)>,
+    /// Balance (in yoctoNEARs) kept on top of the storage staking requirement,
+    /// so `admin_withdraw` cannot leave this contract unable to pay for its storage.
+    storage_reserve_margin: Balance,
+    /// Holds the most recent sales of this marketplace as a ring buffer of `MAX_RECENT_SALES`.
+    /// Each sale records the token sold, the buyer, the price paid and when (in milliseconds).
+    recent_sales: Vector<(TokenKey, AccountId, U128, Timestamp)>,
+    /// Index in `recent_sales` where the next sale is written,
+    /// *i.e.*, the oldest sale once the buffer is full.
+    recent_sales_next: u64,
+    /// Sum of the royalties (in yoctoNEARs) paid to each creator through this marketplace.
+    royalties_by_creator: LookupMap<AccountId, Balance>,
 }
 
 /// In marketplace contract, each token must be addressed by `<nft contract id, token id>`.
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Debug, Deserialize))]
 #[serde(crate = "near_sdk::serde")]
 pub struct TokenKey(AccountId, TokenId);
 
+impl TokenKey {
+    #[cfg(not(target_arch = "wasm"))]
+    pub fn new(nft_contract_id: AccountId, token_id: TokenId) -> Self {
+        Self(nft_contract_id, token_id)
+    }
+}
+
+/// `TokenId` (i.e., `U64`) does not implement `Eq`, `Hash` nor `Ord`,
+/// so these traits are implemented on its inner value.
+impl PartialEq for TokenKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0 && self.1 .0 == other.1 .0
+    }
+}
+
+impl Eq for TokenKey {}
+
+impl Hash for TokenKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+        self.1 .0.hash(state);
+    }
+}
+
+impl PartialOrd for TokenKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TokenKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.0, self.1 .0).cmp(&(&other.0, other.1 .0))
+    }
+}
+
 impl Display for TokenKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}:{:?}", self.0, self.1)
@@ -74,8 +212,101 @@ pub struct TokenForSale {
     pub gate_id: Option<GateId>,
     /// The `creator_id` of the collectible of this token, if any.
     pub creator_id: Option<AccountId>,
+    /// The listing fee paid when this token was listed.
+    /// It is refunded to `owner_id` when the listing is removed without a sale.
+    pub listing_fee: U128,
+    /// UNIX epoch datetime (in milliseconds) when this token was listed.
+    pub created_at: Timestamp,
+}
+
+/// Data logged in the `mg_list` event when a token is listed for sale.
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct ListEvent {
+    /// The contract account where the listed token has been minted.
+    pub nft_id: AccountId,
+    /// The token id being listed.
+    pub token_id: TokenId,
+    /// The owner of the listed token.
+    pub owner_id: AccountId,
+    /// The minimum price requested for the listed token.
+    pub min_price: U128,
+    /// The `gate_id` of the listed token, if any.
+    pub gate_id: Option<GateId>,
+    /// The `creator_id` of the collectible of the listed token, if any.
+    pub creator_id: Option<AccountId>,
+}
+
+/// Data logged in the `mg_delist` event when a token is removed from sale.
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct DelistEvent {
+    /// The contract account where the delisted token has been minted.
+    pub nft_id: AccountId,
+    /// The token id being delisted.
+    pub token_id: TokenId,
+    /// The owner of the delisted token.
+    pub owner_id: AccountId,
+}
+
+/// Data logged in the `mg_withdraw` event when the admin withdraws free balance.
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct WithdrawEvent {
+    /// The amount withdrawn (in yoctoNEARs).
+    pub amount: U128,
+    /// The account receiving the withdrawn amount.
+    pub receiver_id: AccountId,
+}
+
+/// Data logged in the `mg_payout_mismatch` event when a sale is paid in full to the seller
+/// because the payout returned by the NFT contract does not add up to the sale amount.
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct PayoutMismatchEvent {
+    /// The contract account where the sold token has been minted.
+    pub nft_id: AccountId,
+    /// The token id being sold.
+    pub token_id: TokenId,
+    /// The buyer of the token, which already holds it.
+    pub buyer_id: AccountId,
+    /// The seller paid the whole sale `amount` instead.
+    pub owner_id: AccountId,
+    /// The total of the payout returned by the NFT contract, if it could be parsed.
+    pub payout: Option<U128>,
+    /// The sale amount.
+    pub amount: U128,
+}
+
+/// A sale in progress, passed along the callbacks of `buy_token` and `batch_buy`.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct Sale {
+    /// The account buying the token.
+    pub buyer_id: AccountId,
+    /// The account selling the token, *i.e.*, its owner when listed.
+    pub owner_id: AccountId,
+    /// The contract account where the sold token has been minted.
+    pub nft_id: AccountId,
+    /// The token id being sold.
+    pub token_id: TokenId,
+    /// The sale amount paid out to the seller, the creator and MintGate.
+    pub amount: U128,
+    /// The deposit attached by the buyer, refunded when the sale fails.
+    /// It is less than `amount` when the sale is topped up by a discount code.
+    pub deposit: U128,
+    /// The `creator_id` of the collectible of the sold token, if any.
+    pub creator_id: Option<AccountId>,
 }
 
+/// Standard name used in the events logged by this contract.
+const EVENT_STANDARD: &str = "mg-market";
+
 #[derive(BorshSerialize, BorshStorageKey)]
 enum Keys {
     TokensForSale,
@@ -87,6 +318,10 @@ enum Keys {
     TokensByOwnerIdValue(CryptoHash),
     TokensByCreatorId,
     TokensByCreatorIdValue(CryptoHash),
+    PurchasesByBuyer,
+    Discounts,
+    RoyaltiesByCreator,
+    RecentSales,
 }
 
 /// The error variants thrown by *mg-market*.
@@ -99,12 +334,42 @@ pub enum Panics {
     /// Thrown when the `token_key` was not found.
     #[panic_msg = "Token Key `{}` was not found"]
     TokenKeyNotFound { token_key: TokenKey },
+    /// Thrown when the `token_key` is not listed by `owner_id`.
+    #[panic_msg = "Token Key `{}` is not listed by `{}`"]
+    TokenKeyNotOwnedBy { token_key: TokenKey, owner_id: AccountId },
     /// Thrown when buyer attempts to buy own token.
     #[panic_msg = "Buyer cannot buy own token"]
     BuyOwnTokenNotAllowed,
     /// Thrown when deposit is not enough to buy a token.
     #[panic_msg = "Not enough deposit to cover token minimum price"]
     NotEnoughDepositToBuyToken,
+    /// Thrown when the attached deposit does not cover the listing fee.
+    #[panic_msg = "Not enough deposit to cover the listing fee, `{:?}` is required"]
+    NotEnoughDepositToList { required: U128 },
+    /// Thrown when the prepaid gas is not enough to perform the cross-contract calls.
+    #[panic_msg = "Not enough gas attached, more than `{:?}` is required"]
+    InsufficientGas { required: U64 },
+    /// Thrown when the operation can only be performed by `admin_id`.
+    #[panic_msg = "Operation is allowed only for admin"]
+    AdminRestrictedOperation,
+    /// Thrown when withdrawing more than the free balance.
+    #[panic_msg = "Cannot withdraw `{:?}`, only `{:?}` is available, `{:?}` short"]
+    NotEnoughFreeBalance { amount: U128, available: U128, shortfall: U128 },
+    /// Thrown when the discount `code` does not exist.
+    #[panic_msg = "Discount code `{}` was not found"]
+    DiscountCodeNotFound { code: String },
+    /// Thrown when the discount `code` is past its expiration.
+    #[panic_msg = "Discount code `{}` has expired"]
+    DiscountCodeExpired { code: String },
+    /// Thrown when the free balance cannot cover a discount.
+    #[panic_msg = "Not enough promo budget to cover the discount `{:?}`, only `{:?}` is available"]
+    NotEnoughPromoBudget { required: U128, available: U128 },
+    /// Thrown when more than `MAX_GATE_IDS_PER_QUERY` gate IDs are queried at once.
+    #[panic_msg = "At most {} gate IDs are allowed to query at once"]
+    ExceedGateIdsToQuery { max_gate_ids: usize },
+    /// Thrown when more than `MAX_BATCH_SIZE` tokens are given to a batch method.
+    #[panic_msg = "At most {} tokens are allowed in batch"]
+    ExceedTokensInBatch { max_batch_size: usize },
 }
 
 /// Methods for the Marketplace contract.
@@ -113,25 +378,246 @@ pub enum Panics {
 #[near_bindgen]
 impl MarketContract {
     /// Initializes the Market contract.
+    /// The `listing_fee` is the deposit required to list each token for sale.
+    /// The `mintgate_fee_account_id` is used to keep track of the fees routed in each sale.
+    /// The `admin_id` is allowed to perform administrative operations,
+    /// it defaults to the account initializing the contract.
     #[init]
-    pub fn init() -> Self {
+    pub fn init(
+        listing_fee: U128,
+        mintgate_fee_account_id: ValidAccountId,
+        admin_id: Option<ValidAccountId>,
+    ) -> Self {
         Self {
             tokens_for_sale: UnorderedMap::new(Keys::TokensForSale),
             tokens_by_nft_id: LookupMap::new(Keys::TokensByNftId),
             tokens_by_gate_id: LookupMap::new(Keys::TokensByGateId),
             tokens_by_owner_id: LookupMap::new(Keys::TokensByOwnerId),
             tokens_by_creator_id: LookupMap::new(Keys::TokensByCreatorId),
+            listing_fee: listing_fee.0,
+            mintgate_fee_account_id: mintgate_fee_account_id.to_string(),
+            lifetime_fees_routed: 0,
+            total_listed_value: 0,
+            purchases_by_buyer: LookupMap::new(Keys::PurchasesByBuyer),
+            listing_fees_held: 0,
+            in_flight_sales: 0,
+            admin_id: admin_id.map_or_else(env::predecessor_account_id, Into::into),
+            discounts: UnorderedMap::new(Keys::Discounts),
+            storage_reserve_margin: DEFAULT_STORAGE_RESERVE_MARGIN,
+            recent_sales: Vector::new(Keys::RecentSales),
+            recent_sales_next: 0,
+            royalties_by_creator: LookupMap::new(Keys::RoyaltiesByCreator),
         }
     }
 
+    /// Returns the deposit required to list a token for sale.
+    pub fn get_listing_fee(&self) -> U128 {
+        self.listing_fee.into()
+    }
+
+    /// Returns the total amount of fees paid to `mintgate_fee_account_id` in all sales so far.
+    pub fn get_lifetime_fees(&self) -> U128 {
+        self.lifetime_fees_routed.into()
+    }
+
+    /// Returns the total amount of royalties paid to `creator_id` in all sales so far.
+    /// Sales of tokens by their own creator are not counted,
+    /// since their royalty cannot be told apart from the sale proceeds.
+    pub fn get_royalties_earned(&self, creator_id: ValidAccountId) -> U128 {
+        self.royalties_by_creator.get(creator_id.as_ref()).unwrap_or(0).into()
+    }
+
+    /// Returns the sum of the `min_price` of all tokens currently for sale.
+    pub fn get_total_listed_value(&self) -> U128 {
+        self.total_listed_value.into()
+    }
+
+    /// Returns the balance (in yoctoNEARs) that can be withdrawn with `admin_withdraw`.
+    /// That is, the contract balance not needed to cover its storage plus the storage reserve margin,
+    /// nor the listing fees held for the tokens for sale, nor the sale amounts in flight.
+    pub fn get_free_balance(&self) -> U128 {
+        let storage_reserve = Balance::from(env::storage_usage()) * env::storage_byte_cost();
+        env::account_balance()
+            .saturating_sub(storage_reserve)
+            .saturating_sub(self.storage_reserve_margin)
+            .saturating_sub(self.listing_fees_held)
+            .saturating_sub(self.in_flight_sales)
+            .into()
+    }
+
+    /// Returns the balance (in yoctoNEARs) kept on top of the storage staking requirement.
+    pub fn get_storage_reserve_margin(&self) -> U128 {
+        self.storage_reserve_margin.into()
+    }
+
+    /// Sets the balance (in yoctoNEARs) kept on top of the storage staking requirement,
+    /// see `get_free_balance`.
+    /// Only the contract `admin_id` is allowed to call this method.
+    pub fn admin_set_storage_reserve_margin(&mut self, margin: U128) {
+        if env::predecessor_account_id() != self.admin_id {
+            Panics::AdminRestrictedOperation.panic();
+        }
+        self.storage_reserve_margin = margin.0;
+    }
+
+    /// Transfers `amount` yoctoNEARs of the free balance to `to`,
+    /// *e.g.*, to recover funds stranded by a failed cross-contract call.
+    /// See `get_free_balance` for the amount that can be withdrawn.
+    /// Panics with the shortfall when `amount` would eat into the storage reserve, listing fees held
+    /// or sale amounts in flight.
+    /// Only the contract `admin_id` is allowed to call this method.
+    pub fn admin_withdraw(&mut self, amount: U128, to: ValidAccountId) -> Promise {
+        if env::predecessor_account_id() != self.admin_id {
+            Panics::AdminRestrictedOperation.panic();
+        }
+        let available = self.get_free_balance();
+        if amount.0 > available.0 {
+            let shortfall = U128(amount.0 - available.0);
+            Panics::NotEnoughFreeBalance { amount, available, shortfall }.panic();
+        }
+
+        log_event(
+            EVENT_STANDARD,
+            "mg_withdraw",
+            vec![WithdrawEvent { amount, receiver_id: to.to_string() }],
+        );
+        Promise::new(to.into()).transfer(amount.0)
+    }
+
+    /// Removes the listings of `token_ids` from the `nft_id` contract, regardless of their owner,
+    /// *e.g.*, tokens burned while their `nft_on_revoke` call failed.
+    /// The NFT contract is not called back, since the tokens are gone.
+    /// Listing fees held are refunded to the listing owners as in `nft_on_revoke`.
+    /// Token ids not listed are ignored.
+    /// Only the contract `admin_id` is allowed to call this method.
+    ///
+    /// Returns the number of listings removed.
+    pub fn admin_prune_orphans(&mut self, nft_id: ValidAccountId, token_ids: Vec<TokenId>) -> u32 {
+        if env::predecessor_account_id() != self.admin_id {
+            Panics::AdminRestrictedOperation.panic();
+        }
+
+        let mut pruned = 0;
+        for token_id in token_ids {
+            let token_key = TokenKey(nft_id.to_string(), token_id);
+            if let Some(token) = self.tokens_for_sale.get(&token_key) {
+                self.delist_token(&token_key, token);
+                pruned += 1;
+            }
+        }
+        pruned
+    }
+
+    /// Adds the discount `code`, or replaces it if it already exists.
+    /// Purchases made with `buy_token_with_code` get `discount` off the `min_price`
+    /// until `expires_at` (in milliseconds).
+    /// Only the contract `admin_id` is allowed to call this method.
+    pub fn admin_set_discount_code(
+        &mut self,
+        code: String,
+        discount: Fraction,
+        expires_at: Timestamp,
+    ) {
+        if env::predecessor_account_id() != self.admin_id {
+            Panics::AdminRestrictedOperation.panic();
+        }
+        discount.check();
+        self.discounts.insert(&code, &(discount, expires_at));
+    }
+
+    /// Removes the discount `code`.
+    /// Returns whether the code existed.
+    /// Only the contract `admin_id` is allowed to call this method.
+    pub fn admin_remove_discount_code(&mut self, code: String) -> bool {
+        if env::predecessor_account_id() != self.admin_id {
+            Panics::AdminRestrictedOperation.panic();
+        }
+        self.discounts.remove(&code).is_some()
+    }
+
+    /// Returns the discount and expiration (in milliseconds) of `code`, if any.
+    pub fn get_discount_code(&self, code: String) -> Option<(Fraction, Timestamp)> {
+        self.discounts.get(&code)
+    }
+
+    /// Returns all or paginated purchases made by `buyer_id` in this marketplace, oldest first.
+    /// Each purchase contains the token bought, the price paid and its timestamp (in milliseconds).
+    /// Only the last `MAX_PURCHASES_PER_BUYER` purchases are kept.
+    /// Pagination is given by:
+    ///
+    /// - `from_index` the index to start fetching purchases.
+    /// - `limit` indicates how many purchases will be at most returned.
+    pub fn get_purchases(
+        &self,
+        buyer_id: ValidAccountId,
+        from_index: Option<U64>,
+        limit: Option<u32>,
+    ) -> Vec<(TokenKey, U128, Timestamp)> {
+        self.purchases_by_buyer
+            .get(buyer_id.as_ref())
+            .unwrap_or_default()
+            .into_iter()
+            .skip(from_index.map_or(0, |s| s.0) as usize)
+            .take(limit.unwrap_or(u32::MAX) as usize)
+            .collect()
+    }
+
+    /// Returns up to `limit` of the most recent sales in this marketplace, newest first.
+    /// Each sale contains the token sold, the buyer, the price paid and its timestamp (in milliseconds).
+    /// Only the last `MAX_RECENT_SALES` sales are kept.
+    pub fn get_recent_sales(
+        &self,
+        limit: Option<u32>,
+    ) -> Vec<(TokenKey, AccountId, U128, Timestamp)> {
+        let len = self.recent_sales.len();
+        let count = len.min(limit.unwrap_or(u32::MAX) as u64);
+        (0..count)
+            .filter_map(|i| self.recent_sales.get((self.recent_sales_next + len - 1 - i) % len))
+            .collect()
+    }
+
     /// Returns all available tokens for sale.
     /// Use the `nft_on_approve` method to add a token for sale.
+    /// Tokens are sorted by `nft_contract_id` and then by `token_id`.
     pub fn get_tokens_for_sale(&self) -> Vec<TokenForSale> {
-        let mut result = Vec::new();
-        for (_, token) in self.tokens_for_sale.iter() {
-            result.push(token);
-        }
-        result
+        let mut result = self.tokens_for_sale.to_vec();
+        result.sort_by(|(a, _), (b, _)| a.cmp(b));
+        result.into_iter().map(|(_, token)| token).collect()
+    }
+
+    /// Returns the tokens for sale listed at or after `since` (in milliseconds).
+    ///
+    /// There is no index by listing time, so this method scans the listings.
+    /// To bound its cost, pagination is applied to the scanned listings, not to the result:
+    ///
+    /// - `from_index` the index of the first listing to scan.
+    /// - `limit` indicates how many listings will be at most scanned.
+    ///
+    /// Hence a page might contain less than `limit` tokens, even if more pages follow.
+    /// Scanning stops after `get_tokens_for_sale().len()` listings.
+    pub fn get_listings_since(
+        &self,
+        since: Timestamp,
+        from_index: Option<U64>,
+        limit: Option<u32>,
+    ) -> Vec<TokenForSale> {
+        let listings = self.tokens_for_sale.values_as_vector();
+        let start = from_index.map_or(0, |s| s.0).min(listings.len());
+        let end = start.saturating_add(limit.unwrap_or(u32::MAX) as u64).min(listings.len());
+        (start..end)
+            .filter_map(|index| listings.get(index))
+            .filter(|token| token.created_at >= since)
+            .collect()
+    }
+
+    /// Returns the token for sale identified by `nft_id:token_id`, if listed.
+    /// Otherwise returns `None`.
+    pub fn get_token_for_sale(
+        &self,
+        nft_id: ValidAccountId,
+        token_id: TokenId,
+    ) -> Option<TokenForSale> {
+        self.tokens_for_sale.get(&TokenKey(nft_id.to_string(), token_id))
     }
 
     /// Returns all tokens for sale owned by `owner_id`.
@@ -144,6 +630,80 @@ impl MarketContract {
         get_tokens_by(&self.tokens_for_sale, &self.tokens_by_gate_id, gate_id.as_ref())
     }
 
+    /// Returns the number of tokens for sale whose collectible's gate ID is `gate_id`,
+    /// without reading the listings themselves.
+    ///
+    /// The marketplace does not know the total supply of a collectible.
+    /// To compute how much of it is listed, use the `copies` in its metadata,
+    /// as returned by `get_collectible_by_gate_id` on the NFT contract.
+    pub fn get_gate_listed_count(&self, gate_id: ValidGateId) -> U64 {
+        self.tokens_by_gate_id.get(gate_id.as_ref()).map_or(0, |tids| tids.len()).into()
+    }
+
+    /// Returns the tokens for sale whose collectible's gate ID is any of `gate_ids`.
+    /// Tokens are returned grouped by gate ID, in the order given by `gate_ids`.
+    /// Repeated gate IDs are only considered once.
+    /// At most `MAX_GATE_IDS_PER_QUERY` gate IDs are allowed to bound the cost of this method.
+    /// Pagination is given by:
+    ///
+    /// - `from_index` the index to start fetching tokens, across all `gate_ids`.
+    /// - `limit` indicates how many tokens will be at most returned.
+    pub fn get_tokens_by_gate_ids(
+        &self,
+        gate_ids: Vec<ValidGateId>,
+        from_index: Option<U64>,
+        limit: Option<u32>,
+    ) -> Vec<TokenForSale> {
+        if gate_ids.len() > MAX_GATE_IDS_PER_QUERY {
+            Panics::ExceedGateIdsToQuery { max_gate_ids: MAX_GATE_IDS_PER_QUERY }.panic();
+        }
+
+        let mut skip = from_index.map_or(0, |s| s.0);
+        let limit = limit.unwrap_or(u32::MAX) as usize;
+        let mut result = Vec::new();
+        let mut seen = Vec::new();
+        for gate_id in gate_ids {
+            let gate_id: GateId = gate_id.into();
+            if result.len() >= limit {
+                break;
+            }
+            if seen.contains(&gate_id) {
+                continue;
+            }
+
+            if let Some(tids) = self.tokens_by_gate_id.get(&gate_id) {
+                let tids = tids.as_vector();
+                if skip >= tids.len() {
+                    skip -= tids.len();
+                } else {
+                    let end = tids.len().min(skip + (limit - result.len()) as u64);
+                    for token_key in (skip..end).filter_map(|index| tids.get(index)) {
+                        result.push(get_token_or_panic(&self.tokens_for_sale, token_key));
+                    }
+                    skip = 0;
+                }
+            }
+            seen.push(gate_id);
+        }
+        result
+    }
+
+    /// Returns the cheapest token for sale whose collectible's gate ID is `gate_id`,
+    /// i.e., the one with the lowest `min_price`.
+    /// Ties are broken by the lowest `token_id`.
+    /// Returns `None` when there are no tokens for sale for `gate_id`.
+    pub fn get_floor_listing(&self, gate_id: ValidGateId) -> Option<TokenForSale> {
+        get_tokens_by(&self.tokens_for_sale, &self.tokens_by_gate_id, gate_id.as_ref())
+            .into_iter()
+            .min_by(|a, b| {
+                (a.min_price.0, a.token_id.0, &a.nft_contract_id).cmp(&(
+                    b.min_price.0,
+                    b.token_id.0,
+                    &b.nft_contract_id,
+                ))
+            })
+    }
+
     /// Returns all tokens for sale whose collectible's creator ID is `creator_id`.
     pub fn get_tokens_by_creator_id(&self, creator_id: ValidAccountId) -> Vec<TokenForSale> {
         get_tokens_by(&self.tokens_for_sale, &self.tokens_by_creator_id, creator_id.as_ref())
@@ -154,13 +714,57 @@ impl MarketContract {
     /// The caller must attach at least `min_price` NEARs in order to pay for the given token.
     /// Moreover, the owner cannot buy his/her own tokens.
     ///
+    /// Before the token is transferred, `nft_contract_id::nft_token` is checked
+    /// to be still owned by the seller, see `resolve_purchase`.
     /// When the token is sold,
     /// royalties are paid by this marketplace according to `nft_contract_id::nft_transfer_payout`.
     #[payable]
     pub fn buy_token(&mut self, nft_contract_id: ValidAccountId, token_id: TokenId) {
+        self.purchase_token(nft_contract_id, token_id, None);
+    }
+
+    /// Buys the token `nft_contract_id:token_id` as in `buy_token`,
+    /// applying the discount of `code` to the required deposit.
+    ///
+    /// The seller, creator and MintGate are still paid according to the full `min_price`.
+    /// The discounted amount is covered by this marketplace out of its free balance,
+    /// see `get_free_balance`.
+    /// Panics if `code` is unknown or has expired.
+    #[payable]
+    pub fn buy_token_with_code(
+        &mut self,
+        nft_contract_id: ValidAccountId,
+        token_id: TokenId,
+        code: String,
+    ) {
+        let discount = match self.discounts.get(&code) {
+            None => Panics::DiscountCodeNotFound { code }.panic(),
+            Some((discount, expires_at)) => {
+                if env::block_timestamp() / 1_000_000 > expires_at {
+                    Panics::DiscountCodeExpired { code }.panic();
+                }
+                discount
+            }
+        };
+        self.purchase_token(nft_contract_id, token_id, Some(discount));
+    }
+
+    /// Buys the token `nft_contract_id:token_id` for `predecessor_account_id`.
+    /// When given, `discount` is taken off `min_price` and covered by the free balance.
+    fn purchase_token(
+        &mut self,
+        nft_contract_id: ValidAccountId,
+        token_id: TokenId,
+        discount: Option<Fraction>,
+    ) {
+        if env::prepaid_gas() <= MIN_GAS_FOR_BUY_TOKEN {
+            Panics::InsufficientGas { required: MIN_GAS_FOR_BUY_TOKEN.into() }.panic();
+        }
+
         let token_key = TokenKey(nft_contract_id.to_string(), token_id);
-        if let Some(TokenForSale { owner_id, min_price, gate_id, creator_id, .. }) =
-            self.tokens_for_sale.get(&token_key)
+        if let Some(TokenForSale {
+            owner_id, min_price, gate_id, creator_id, listing_fee, ..
+        }) = self.tokens_for_sale.get(&token_key)
         {
             let buyer_id = env::predecessor_account_id();
 
@@ -169,32 +773,170 @@ impl MarketContract {
             }
 
             let deposit = env::attached_deposit();
-            if deposit < min_price.0 {
+            let discount_amount = discount.map_or(0, |discount| discount.mult(min_price.0));
+            if deposit + discount_amount < min_price.0 {
                 Panics::NotEnoughDepositToBuyToken.panic();
             }
+            if discount_amount > 0 {
+                let available = self.get_free_balance().0.saturating_sub(deposit);
+                if discount_amount > available {
+                    Panics::NotEnoughPromoBudget {
+                        required: discount_amount.into(),
+                        available: available.into(),
+                    }
+                    .panic();
+                }
+            }
 
             self.remove_token_id(&token_key, &owner_id, &gate_id, &creator_id);
 
-            mg_core::nep171::nft::nft_transfer_payout(
-                buyer_id.try_into().unwrap(),
-                token_id,
-                None,
-                None,
-                Some(U128(deposit)),
-                &nft_contract_id,
-                0,
-                env::prepaid_gas() / 3,
-            )
-            .then(self_callback::make_payouts(
-                &env::current_account_id(),
-                NO_DEPOSIT,
-                GAS_FOR_ROYALTIES,
-            ));
+            let amount = deposit + discount_amount;
+            self.in_flight_sales += amount;
+            mg_core::nep171::nft::nft_token(token_id, &nft_contract_id, 0, GAS_FOR_NFT_TOKEN).then(
+                self_callback::resolve_purchase(
+                    Sale {
+                        buyer_id,
+                        owner_id,
+                        nft_id: nft_contract_id.to_string(),
+                        token_id,
+                        amount: U128(amount),
+                        deposit: U128(deposit),
+                        creator_id,
+                    },
+                    listing_fee,
+                    &env::current_account_id(),
+                    NO_DEPOSIT,
+                    env::prepaid_gas() - GAS_FOR_BUY_TOKEN - GAS_FOR_NFT_TOKEN,
+                ),
+            );
         } else {
             Panics::TokenKeyNotFound { token_key }.panic();
         }
     }
 
+    /// Buys several tokens at once, as in `buy_token`.
+    /// Each purchase contains the NFT contract and the `TokenId` to buy,
+    /// and each token is paid exactly its `min_price`.
+    /// The attached deposit must cover the sum of all `min_price`s, any excess is refunded.
+    /// Each token is checked to be still owned by its seller before its transfer, as in `buy_token`.
+    /// When the check or the transfer of a token fails, its `min_price` is refunded to the buyer,
+    /// and in the former case, the listing fee is refunded to the seller.
+    #[payable]
+    pub fn batch_buy(&mut self, purchases: Vec<(ValidAccountId, TokenId)>) {
+        check_batch_size(purchases.len());
+        let required_gas = GAS_FOR_BATCH_BUY + MIN_GAS_FOR_BATCH_BUY_ITEM * purchases.len() as Gas;
+        if env::prepaid_gas() <= required_gas {
+            Panics::InsufficientGas { required: required_gas.into() }.panic();
+        }
+
+        let buyer_id = env::predecessor_account_id();
+        let mut total: Balance = 0;
+        let mut items = Vec::new();
+        for (nft_contract_id, token_id) in purchases {
+            let token_key = TokenKey(nft_contract_id.to_string(), token_id);
+            match self.tokens_for_sale.get(&token_key) {
+                None => Panics::TokenKeyNotFound { token_key }.panic(),
+                Some(TokenForSale {
+                    owner_id,
+                    min_price,
+                    gate_id,
+                    creator_id,
+                    listing_fee,
+                    ..
+                }) => {
+                    if buyer_id == owner_id {
+                        Panics::BuyOwnTokenNotAllowed.panic();
+                    }
+                    total += min_price.0;
+                    self.remove_token_id(&token_key, &owner_id, &gate_id, &creator_id);
+                    items.push((token_key, owner_id, creator_id, min_price, listing_fee));
+                }
+            }
+        }
+
+        let deposit = env::attached_deposit();
+        if deposit < total {
+            Panics::NotEnoughDepositToBuyToken.panic();
+        }
+        if deposit > total {
+            Promise::new(buyer_id.clone()).transfer(deposit - total);
+        }
+        self.in_flight_sales += total;
+
+        for (TokenKey(nft_contract_id, token_id), owner_id, creator_id, min_price, listing_fee) in
+            items
+        {
+            mg_core::nep171::nft::nft_token(token_id, &nft_contract_id, 0, GAS_FOR_NFT_TOKEN).then(
+                self_callback::resolve_purchase(
+                    Sale {
+                        buyer_id: buyer_id.clone(),
+                        owner_id,
+                        nft_id: nft_contract_id.clone(),
+                        token_id,
+                        amount: min_price,
+                        deposit: min_price,
+                        creator_id,
+                    },
+                    listing_fee,
+                    &env::current_account_id(),
+                    NO_DEPOSIT,
+                    GAS_FOR_BATCH_BUY_RESOLVE_PURCHASE,
+                ),
+            );
+        }
+    }
+
+    /// Updates the `min_price` of the token `nft_id:token_id` listed by `predecessor_account_id`.
+    pub fn update_price(&mut self, nft_id: ValidAccountId, token_id: TokenId, min_price: U128) {
+        let token_key = TokenKey(nft_id.to_string(), token_id);
+        let owner_id = env::predecessor_account_id();
+        if let Err(err) = self.update_token_price(token_key, &owner_id, min_price) {
+            err.panic();
+        }
+    }
+
+    /// Updates the `min_price` of several listings, as in `update_price`.
+    /// Each update contains the NFT contract, the `TokenId` and the new minimum price.
+    /// Only listings of `predecessor_account_id` are updated.
+    /// Returns the updates that failed along with their error,
+    /// the remaining updates are applied nonetheless.
+    pub fn batch_update_price(
+        &mut self,
+        updates: Vec<(ValidAccountId, TokenId, U128)>,
+    ) -> Vec<(TokenKey, Panics)> {
+        check_batch_size(updates.len());
+        let owner_id = env::predecessor_account_id();
+        let mut errs = Vec::new();
+        for (nft_id, token_id, min_price) in updates {
+            let token_key = TokenKey(nft_id.to_string(), token_id);
+            if let Err(err) = self.update_token_price(token_key.clone(), &owner_id, min_price) {
+                errs.push((token_key, err));
+            }
+        }
+        errs
+    }
+
+    /// Checks whether the listing `nft_id:token_id` is still owned by its `owner_id`.
+    /// The current owner is fetched from `nft_id::nft_token`,
+    /// and the listing is removed if the token has changed hands outside this marketplace,
+    /// or if it no longer exists.
+    pub fn sync_listing(&mut self, nft_id: ValidAccountId, token_id: TokenId) -> Promise {
+        let token_key = TokenKey(nft_id.to_string(), token_id);
+        if self.tokens_for_sale.get(&token_key).is_none() {
+            Panics::TokenKeyNotFound { token_key }.panic();
+        }
+
+        mg_core::nep171::nft::nft_token(token_id, nft_id.as_ref(), 0, env::prepaid_gas() / 3).then(
+            self_callback::resolve_sync_listing(
+                nft_id.to_string(),
+                token_id,
+                &env::current_account_id(),
+                NO_DEPOSIT,
+                env::prepaid_gas() / 3,
+            ),
+        )
+    }
+
     fn remove_token_id(
         &mut self,
         token_key: &TokenKey,
@@ -202,7 +944,10 @@ impl MarketContract {
         gate_id: &Option<GateId>,
         creator_id: &Option<AccountId>,
     ) {
-        self.tokens_for_sale.remove(&token_key);
+        if let Some(token) = self.tokens_for_sale.remove(&token_key) {
+            self.total_listed_value -= token.min_price.0;
+            self.listing_fees_held -= token.listing_fee.0;
+        }
         remove_token_id_from(&mut self.tokens_by_nft_id, &token_key, &token_key.0, &token_key.1);
         remove_token_id_from(&mut self.tokens_by_owner_id, &token_key, &owner_id, token_key);
         if let Some(gate_id) = gate_id {
@@ -219,29 +964,175 @@ impl MarketContract {
     }
 }
 
+/// Callbacks this contract attaches to its own cross-contract calls.
 #[near_ext]
 #[ext_contract(self_callback)]
-trait SelfCallback {
-    fn make_payouts(&mut self);
+pub trait SelfCallback {
+    fn make_payouts(&mut self, sale: Sale);
+    fn resolve_sync_listing(&mut self, nft_id: AccountId, token_id: TokenId) -> bool;
+    fn resolve_purchase(&mut self, sale: Sale, listing_fee: U128) -> bool;
 }
 
 #[near_log(skip_args, only_pub)]
 #[near_bindgen]
 impl SelfCallback for MarketContract {
+    /// Transfers the `amount` of `sale` according to the payout returned by `nft_transfer_payout`,
+    /// releasing it from the sale amounts in flight.
+    /// If `nft_transfer_payout` failed, the `deposit` of the buyer is refunded to `buyer_id`,
+    /// while any discount topping it up stays in the promo budget.
+    /// When the payout does not add up exactly to `amount`, the `mg_payout_mismatch` event is logged
+    /// and the whole `amount` is paid to the seller `owner_id` instead,
+    /// since the token has already been transferred to `buyer_id`.
+    /// The amount paid to `mintgate_fee_account_id` is added to the lifetime fees,
+    /// and the purchase of `nft_id:token_id` is recorded in the history of `buyer_id`
+    /// as well as in the recent sales.
+    /// The amount paid to `creator_id`, if given, is added to its royalties earned.
     #[private]
-    fn make_payouts(&mut self) {
-        match env::promise_result(0) {
+    fn make_payouts(&mut self, sale: Sale) {
+        let Sale { buyer_id, owner_id, nft_id, token_id, amount, deposit, creator_id } = sale;
+        self.in_flight_sales = self.in_flight_sales.saturating_sub(amount.0);
+        let payout = match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
-            PromiseResult::Failed => unreachable!(),
+            PromiseResult::Failed => {
+                Promise::new(buyer_id).transfer(deposit.0);
+                return;
+            }
+            PromiseResult::Successful(value) => serde_json::from_slice::<Payout>(&value).ok(),
+        };
+        let total = payout.as_ref().map(|payout| {
+            payout.values().fold(0u128, |total, amount| total.saturating_add(amount.0))
+        });
+        let payout = match payout {
+            Some(payout) if total == Some(amount.0) => payout,
+            _ => {
+                let event = PayoutMismatchEvent {
+                    nft_id,
+                    token_id,
+                    buyer_id,
+                    owner_id: owner_id.clone(),
+                    payout: total.map(U128),
+                    amount,
+                };
+                log_event(EVENT_STANDARD, "mg_payout_mismatch", vec![event]);
+                Promise::new(owner_id).transfer(amount.0);
+                return;
+            }
+        };
+
+        if let Some(creator_id) = creator_id {
+            if let Some(royalty) = payout.get(&creator_id) {
+                let earned = self.royalties_by_creator.get(&creator_id).unwrap_or(0);
+                self.royalties_by_creator.insert(&creator_id, &(earned + royalty.0));
+            }
+        }
+        if let Some(fee) = payout.get(&self.mintgate_fee_account_id) {
+            self.lifetime_fees_routed += fee.0;
+        }
+        for (receiver_id, amount) in payout {
+            Promise::new(receiver_id).transfer(amount.0);
+        }
+
+        let mut purchases = self.purchases_by_buyer.get(&buyer_id).unwrap_or_default();
+        if purchases.len() >= MAX_PURCHASES_PER_BUYER {
+            purchases.drain(..=purchases.len() - MAX_PURCHASES_PER_BUYER);
+        }
+        let now = env::block_timestamp() / 1_000_000;
+        let token_key = TokenKey(nft_id, token_id);
+        purchases.push((token_key.clone(), amount, now));
+        self.purchases_by_buyer.insert(&buyer_id, &purchases);
+
+        let sale = (token_key, buyer_id, amount, now);
+        if self.recent_sales.len() < MAX_RECENT_SALES as u64 {
+            self.recent_sales.push(&sale);
+        } else {
+            self.recent_sales.replace(self.recent_sales_next, &sale);
+        }
+        self.recent_sales_next = (self.recent_sales_next + 1) % MAX_RECENT_SALES as u64;
+    }
+
+    /// Removes the listing `nft_id:token_id` when its owner differs from
+    /// the one returned by `nft_token`.
+    /// Returns whether the listing was removed.
+    #[private]
+    fn resolve_sync_listing(&mut self, nft_id: AccountId, token_id: TokenId) -> bool {
+        let token_key = TokenKey(nft_id, token_id);
+        let token = match self.tokens_for_sale.get(&token_key) {
+            None => return false,
+            Some(token) => token,
+        };
+        let is_stale = match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Failed => false,
             PromiseResult::Successful(value) => {
-                if let Ok(payout) = serde_json::from_slice::<Payout>(&value) {
-                    for (receiver_id, amount) in payout {
-                        Promise::new(receiver_id).transfer(amount.0);
-                    }
-                } else {
-                    unreachable!();
+                match serde_json::from_slice::<Option<serde_json::Value>>(&value) {
+                    Ok(None) => true,
+                    Ok(Some(nft_token)) => nft_token["owner_id"] != token.owner_id.as_str(),
+                    Err(_) => false,
+                }
+            }
+        };
+        if is_stale {
+            self.delist_token(&token_key, token);
+        }
+        is_stale
+    }
+
+    /// Completes the `sale` of `nft_id:token_id`, made by `buy_token` or `batch_buy`,
+    /// when `nft_token` shows the token is still owned by the seller `owner_id` of `sale`,
+    /// *i.e.*, calls `nft_transfer_payout` for `amount` followed by `make_payouts`.
+    /// Otherwise, the listing was stale and the sale is rejected:
+    /// the `deposit` of `sale` is refunded to `buyer_id` and the `listing_fee` to `owner_id`.
+    /// Returns whether the purchase went ahead.
+    #[private]
+    fn resolve_purchase(&mut self, sale: Sale, listing_fee: U128) -> bool {
+        let is_owner = match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Failed => false,
+            PromiseResult::Successful(value) => {
+                match serde_json::from_slice::<Option<serde_json::Value>>(&value) {
+                    Ok(Some(nft_token)) => nft_token["owner_id"] == sale.owner_id.as_str(),
+                    _ => false,
                 }
             }
+        };
+        if !is_owner {
+            self.in_flight_sales = self.in_flight_sales.saturating_sub(sale.amount.0);
+            refund_listing(sale.nft_id, sale.token_id, sale.owner_id, listing_fee);
+            Promise::new(sale.buyer_id).transfer(sale.deposit.0);
+            return false;
+        }
+
+        mg_core::nep171::nft::nft_transfer_payout(
+            sale.buyer_id.clone().try_into().unwrap(),
+            sale.token_id,
+            None,
+            None,
+            Some(sale.amount),
+            &sale.nft_id,
+            0,
+            env::prepaid_gas() - GAS_FOR_RESOLVE_PURCHASE - GAS_FOR_MAKE_PAYOUTS,
+        )
+        .then(self_callback::make_payouts(
+            Sale { creator_id: royalty_creator_id(sale.creator_id, &sale.owner_id), ..sale },
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_MAKE_PAYOUTS,
+        ));
+        true
+    }
+}
+
+/// Contract Source Metadata (NEP-330) v1.0.0
+///
+/// <https://github.com/near/NEPs/blob/master/neps/nep-0330.md>
+#[near_log(skip_args, only_pub)]
+#[near_bindgen]
+impl ContractSourceMetadata for MarketContract {
+    /// Returns the crate version and repository this contract was built from.
+    fn contract_source_metadata(&self) -> SourceMetadata {
+        SourceMetadata {
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            link: Some(env!("CARGO_PKG_REPOSITORY").to_string()),
         }
     }
 }
@@ -252,7 +1143,9 @@ impl SelfCallback for MarketContract {
 #[near_bindgen]
 impl NonFungibleTokenApprovalsReceiver for MarketContract {
     /// Callback method to allow this contract to put a `Token` into the marketplace.
+    /// The attached deposit must cover the listing fee.
     /// The msg must contain the following fields:
+    #[payable]
     fn nft_on_approve(
         &mut self,
         token_id: TokenId,
@@ -260,11 +1153,15 @@ impl NonFungibleTokenApprovalsReceiver for MarketContract {
         approval_id: U64,
         msg: String,
     ) {
+        self.check_listing_fee(1);
+
         match serde_json::from_str::<MarketApproveMsg>(&msg) {
             Ok(approve_msg) => {
                 let nft_contract_id = env::predecessor_account_id();
                 let owner_id = owner_id.to_string();
+                let event = list_event(&nft_contract_id, token_id, &owner_id, &approve_msg);
                 self.add_token(&owner_id, &nft_contract_id, token_id, approve_msg, approval_id);
+                log_event(EVENT_STANDARD, "mg_list", vec![event]);
             }
             Err(err) => {
                 let reason = err.to_string();
@@ -274,33 +1171,88 @@ impl NonFungibleTokenApprovalsReceiver for MarketContract {
     }
 
     /// Callback method to remove this `Token` from the marketplace.
+    /// If the `Token` is not listed, e.g., it was already removed, this method does nothing.
     fn nft_on_revoke(&mut self, token_id: TokenId) {
         let nft_contract_id = env::predecessor_account_id();
         let token_key = TokenKey(nft_contract_id, token_id);
 
         if let Some(token) = self.tokens_for_sale.get(&token_key) {
             assert_eq!(token.nft_contract_id, token_key.0);
-            self.remove_token_id(&token_key, &token.owner_id, &token.gate_id, &token.creator_id);
-        } else {
-            Panics::TokenKeyNotFound { token_key }.panic();
+            self.delist_token(&token_key, token);
         }
     }
 
     /// Callback method to allow this contract to put multiple `Token`s into the marketplace.
+    /// When a `token_id` appears more than once, only its last entry is listed.
+    /// The attached deposit must cover the listing fee of every distinct token.
+    /// A single `mg_list` event is logged with all listed tokens.
+    #[payable]
     fn batch_on_approve(
         &mut self,
         tokens: Vec<(TokenId, MarketApproveMsg)>,
         owner_id: ValidAccountId,
     ) {
+        check_batch_size(tokens.len());
+        let mut seen = HashSet::new();
+        let mut tokens = tokens
+            .into_iter()
+            .rev()
+            .filter(|(token_id, _)| seen.insert(token_id.0))
+            .collect::<Vec<_>>();
+        tokens.reverse();
+
+        self.check_listing_fee(tokens.len());
+
         let nft_contract_id = env::predecessor_account_id();
         let owner_id = owner_id.to_string();
+        let mut events = Vec::with_capacity(tokens.len());
         for (token_id, approve_msg) in tokens {
+            events.push(list_event(&nft_contract_id, token_id, &owner_id, &approve_msg));
             self.add_token(&owner_id, &nft_contract_id, token_id, approve_msg, U64(0));
         }
+        log_event(EVENT_STANDARD, "mg_list", events);
     }
 }
 
 impl MarketContract {
+    /// Panics when the attached deposit does not cover the listing fee of `count` tokens.
+    fn check_listing_fee(&self, count: usize) {
+        let required = self.listing_fee * count as Balance;
+        if env::attached_deposit() < required {
+            Panics::NotEnoughDepositToList { required: required.into() }.panic();
+        }
+    }
+
+    /// Removes the `token` for sale identified by `token_key` and logs the delist event.
+    /// The listing fee is refunded to the token owner.
+    fn delist_token(&mut self, token_key: &TokenKey, token: TokenForSale) {
+        self.remove_token_id(token_key, &token.owner_id, &token.gate_id, &token.creator_id);
+        refund_listing(token.nft_contract_id, token.token_id, token.owner_id, token.listing_fee);
+    }
+
+    fn update_token_price(
+        &mut self,
+        token_key: TokenKey,
+        owner_id: &AccountId,
+        min_price: U128,
+    ) -> Result<(), Panics> {
+        let mut token = match self.tokens_for_sale.get(&token_key) {
+            None => return Err(Panics::TokenKeyNotFound { token_key }),
+            Some(token) => token,
+        };
+        if &token.owner_id != owner_id {
+            return Err(Panics::TokenKeyNotOwnedBy { token_key, owner_id: owner_id.clone() });
+        }
+
+        self.total_listed_value = self.total_listed_value - token.min_price.0 + min_price.0;
+        token.min_price = min_price;
+        self.tokens_for_sale.insert(&token_key, &token);
+        Ok(())
+    }
+
+    /// Lists `token_id` for sale, replacing any previous listing of the same token.
+    /// The listing fee of the replaced listing is refunded to its owner,
+    /// since the new listing has been charged its own fee.
     fn add_token(
         &mut self,
         owner_id: &AccountId,
@@ -310,6 +1262,20 @@ impl MarketContract {
         approval_id: U64,
     ) {
         let token_key = TokenKey(nft_contract_id.clone(), token_id);
+        if let Some(previous) = self.tokens_for_sale.get(&token_key) {
+            self.remove_token_id(
+                &token_key,
+                &previous.owner_id,
+                &previous.gate_id,
+                &previous.creator_id,
+            );
+            if previous.listing_fee.0 > 0 {
+                Promise::new(previous.owner_id).transfer(previous.listing_fee.0);
+            }
+        }
+
+        self.total_listed_value += approve_msg.min_price.0;
+        self.listing_fees_held += self.listing_fee;
         self.tokens_for_sale.insert(
             &token_key,
             &TokenForSale {
@@ -320,6 +1286,8 @@ impl MarketContract {
                 min_price: approve_msg.min_price,
                 gate_id: approve_msg.gate_id.clone().map(|g| g.to_string()),
                 creator_id: approve_msg.creator_id.clone(),
+                listing_fee: self.listing_fee.into(),
+                created_at: env::block_timestamp() / 1_000_000,
             },
         );
 
@@ -354,6 +1322,12 @@ impl MarketContract {
     }
 }
 
+/// Inserts `token_key` into the set of `tokens_map` given by `key`.
+/// The set is created when missing, under the storage prefix returned by `f`.
+///
+/// Every index passes its own `Keys` variant as `f`,
+/// so sets of different indices never share storage,
+/// even when the same string is used as key, *e.g.*, an account ID equal to a gate ID.
 fn insert_token_id_to<T: BorshSerialize + BorshDeserialize, F: FnOnce(CryptoHash) -> Keys>(
     tokens_map: &mut LookupMap<String, UnorderedSet<T>>,
     key: &String,
@@ -372,9 +1346,59 @@ fn get_tokens_by<K: BorshSerialize>(
 ) -> Vec<TokenForSale> {
     match tokens_map.get(&key) {
         None => Vec::new(),
-        Some(tids) => {
-            tids.iter().map(|token_id| ts.get(&token_id).expect("Token not found")).collect()
-        }
+        Some(tids) => tids.iter().map(|token_key| get_token_or_panic(ts, token_key)).collect(),
+    }
+}
+
+/// Returns the `creator_id` whose royalty is to be added to its royalties earned on a sale by `owner_id`.
+/// A creator selling its own token is paid its royalty merged with the sale proceeds,
+/// so `None` is returned in that case.
+fn royalty_creator_id(creator_id: Option<AccountId>, owner_id: &AccountId) -> Option<AccountId> {
+    creator_id.filter(|creator_id| creator_id != owner_id)
+}
+
+/// Refunds the `listing_fee` of the removed listing `nft_id:token_id` to `owner_id`,
+/// and logs the `mg_delist` event.
+fn refund_listing(nft_id: AccountId, token_id: TokenId, owner_id: AccountId, listing_fee: U128) {
+    if listing_fee.0 > 0 {
+        Promise::new(owner_id.clone()).transfer(listing_fee.0);
+    }
+    log_event(EVENT_STANDARD, "mg_delist", vec![DelistEvent { nft_id, token_id, owner_id }]);
+}
+
+/// Builds the `mg_list` event data of `token_id` listed with `approve_msg`.
+fn list_event(
+    nft_id: &AccountId,
+    token_id: TokenId,
+    owner_id: &AccountId,
+    approve_msg: &MarketApproveMsg,
+) -> ListEvent {
+    ListEvent {
+        nft_id: nft_id.clone(),
+        token_id,
+        owner_id: owner_id.clone(),
+        min_price: approve_msg.min_price,
+        gate_id: approve_msg.gate_id.clone().map(|g| g.to_string()),
+        creator_id: approve_msg.creator_id.clone(),
+    }
+}
+
+/// Gets the token for sale with given `token_key`.
+/// Panics otherwise, *e.g.*, when an index refers to a token no longer for sale.
+fn get_token_or_panic(
+    ts: &UnorderedMap<TokenKey, TokenForSale>,
+    token_key: TokenKey,
+) -> TokenForSale {
+    match ts.get(&token_key) {
+        None => Panics::TokenKeyNotFound { token_key }.panic(),
+        Some(token) => token,
+    }
+}
+
+/// Panics when more than `MAX_BATCH_SIZE` tokens are given to a batch method.
+fn check_batch_size(len: usize) {
+    if len > MAX_BATCH_SIZE {
+        Panics::ExceedTokensInBatch { max_batch_size: MAX_BATCH_SIZE }.panic();
     }
 }
 
@@ -3,17 +3,24 @@
 use mg_core::{
     gate::ValidGateId,
     mock_context,
-    mocked_context::{alice, any, bob, charlie, gate_id, nft},
+    mocked_context::{
+        alice, any, bob, charlie, gate_id, mintgate_admin, mintgate_fee_account_id, nft,
+    },
     nep178::NonFungibleTokenApprovalsReceiver,
-    MarketApproveMsg, TokenId,
+    MarketApproveMsg, TokenId, EVENT_JSON,
+};
+use mg_market::{
+    DelistEvent, ListEvent, MarketContract, Panics, PayoutMismatchEvent, Sale, SelfCallback,
+    TokenForSale, TokenKey, DEFAULT_STORAGE_RESERVE_MARGIN, MAX_BATCH_SIZE, MAX_RECENT_SALES,
 };
-use mg_market::{MarketContract, TokenForSale};
 use near_sdk::{
-    json_types::{ValidAccountId, U64},
-    serde_json,
+    env,
+    json_types::{ValidAccountId, U128, U64},
+    serde_json::{self, Value},
+    test_utils::{get_created_receipts, get_logs},
 };
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeSet, HashMap, HashSet},
     convert::TryInto,
     ops::{Deref, DerefMut},
 };
@@ -58,6 +65,7 @@ impl MockedContext<MarketContractChecker> {
                     // t.royalty,
                 )
             };
+            b.retain(|x| x.nft_contract_id != t.nft_contract_id || x.token_id != t.token_id);
             b.push(t);
             let a: BTreeSet<_> = a.into_iter().map(f).collect();
             let b: &Vec<TokenForSale> = b;
@@ -80,6 +88,7 @@ impl MockedContext<MarketContractChecker> {
             ]
         }
 
+        self.update_context();
         let mut b = snapshot(&self.contract, &msg, owner_id.clone());
 
         self.contract.nft_on_approve(
@@ -103,6 +112,8 @@ impl MockedContext<MarketContractChecker> {
                     gate_id: msg.gate_id.clone().map(|g| g.to_string()),
                     creator_id: msg.creator_id.clone(),
                     // royalty: msg.royalty,
+                    listing_fee: self.contract.get_listing_fee(),
+                    created_at: self.context.block_timestamp / 1_000_000,
                 },
             );
         });
@@ -117,12 +128,44 @@ fn approve_msg(price: u128, gate_id: ValidGateId, creator_id: ValidAccountId) ->
     }
 }
 
-fn init_contract() -> MockedContext<MarketContractChecker> {
-    MockedContext::new(|| MarketContractChecker { contract: MarketContract::init() })
+fn last_event() -> Value {
+    let logs = get_logs();
+    let log = logs.iter().rev().find(|log| log.starts_with(EVENT_JSON)).expect("No event logged");
+    serde_json::from_str(&log[EVENT_JSON.len()..]).unwrap()
+}
+
+/// Returns the `(receiver_id, deposit)` of every transfer created by the last call.
+fn transfers() -> Vec<(String, u128)> {
+    get_created_receipts()
+        .into_iter()
+        .filter_map(|receipt| {
+            let receipt = serde_json::to_string(&receipt).unwrap();
+            let receipt: Value = serde_json::from_str(&receipt).unwrap();
+            let deposit = receipt["actions"][0]["Transfer"]["deposit"].as_u64()?;
+            Some((receipt["receiver_id"].as_str().unwrap().to_string(), deposit as u128))
+        })
+        .collect()
+}
+
+fn init_contract(listing_fee: u128) -> MockedContext<MarketContractChecker> {
+    MockedContext::new(|| MarketContractChecker {
+        contract: MarketContract::init(
+            U128(listing_fee),
+            mintgate_fee_account_id(),
+            Some(mintgate_admin()),
+        ),
+    })
 }
 
 fn init() -> MockedContext<MarketContractChecker> {
-    init_contract()
+    init_contract(0)
+}
+
+/// Balance expected from `get_free_balance` when `held` yoctoNEARs are held
+/// as listing fees or sale amounts in flight.
+fn free_balance(held: u128) -> u128 {
+    let storage_reserve = env::storage_usage() as u128 * env::storage_byte_cost();
+    env::account_balance() - storage_reserve - DEFAULT_STORAGE_RESERVE_MARGIN - held
 }
 
 mod initial_state {
@@ -136,6 +179,29 @@ mod initial_state {
             assert_eq!(contract.get_tokens_by_gate_id(gate_id(99)).len(), 0);
             assert_eq!(contract.get_tokens_by_owner_id(any()).len(), 0);
             assert_eq!(contract.get_tokens_by_creator_id(any()).len(), 0);
+            assert!(contract.get_token_for_sale(nft(), 0.into()).is_none());
+            assert_eq!(contract.get_lifetime_fees(), U128(0));
+            assert_eq!(contract.get_recent_sales(None).len(), 0);
+            assert_eq!(contract.get_gate_listed_count(gate_id(1)), U64(0));
+            assert_eq!(contract.get_royalties_earned(any()), U128(0));
+        });
+    }
+}
+
+mod contract_source_metadata {
+
+    use mg_core::nep330::ContractSourceMetadata;
+
+    use super::*;
+
+    #[test]
+    fn contract_source_metadata_reports_crate_version() {
+        init().run_as(any(), |contract| {
+            let metadata = contract.contract_source_metadata();
+            let version = metadata.version.unwrap();
+            assert!(!version.is_empty());
+            assert_eq!(version, env!("CARGO_PKG_VERSION"));
+            assert_eq!(metadata.link.unwrap(), env!("CARGO_PKG_REPOSITORY"));
         });
     }
 }
@@ -197,68 +263,1421 @@ mod nft_on_approve {
     }
 }
 
-mod buy_token {
+mod batch_on_approve {
 
     use super::*;
 
+    const LISTING_FEE: u128 = 1_000;
+
     #[test]
-    #[should_panic(expected = "Token Key `nft:U64(99)` was not found")]
-    fn buy_a_non_existent_token_should_panic() {
-        init().run_as(alice(), |contract| {
-            contract.buy_token(nft(), 99.into());
+    fn batch_on_approve_should_list_and_index_every_token() {
+        init_contract(LISTING_FEE).run_as(nft(), |contract| {
+            let tokens = (1..=5u16)
+                .map(|token_id| {
+                    let msg = approve_msg(token_id as u128 * 10, gate_id(token_id % 2), alice());
+                    (U64(token_id as u64), msg)
+                })
+                .collect::<Vec<_>>();
+            contract.attach_deposit(5 * LISTING_FEE);
+            contract.batch_on_approve(tokens, bob());
+
+            assert_eq!(contract.get_tokens_for_sale().len(), 5);
+            for token_id in 1..=5 {
+                let token = contract.get_token_for_sale(nft(), token_id.into()).unwrap();
+                assert_eq!(token.owner_id, bob().to_string());
+                assert_eq!(token.min_price, U128(token_id as u128 * 10));
+            }
+            assert_eq!(contract.get_tokens_by_owner_id(bob()).len(), 5);
+            assert_eq!(contract.get_tokens_by_creator_id(alice()).len(), 5);
+            assert_eq!(contract.get_tokens_by_gate_id(gate_id(0)).len(), 2);
+            assert_eq!(contract.get_tokens_by_gate_id(gate_id(1)).len(), 3);
+            assert_eq!(contract.get_total_listed_value(), U128(150));
+
+            let event = last_event();
+            assert_eq!(event["event"], "mg_list");
+            let data: Vec<ListEvent> = serde_json::from_value(event["data"].clone()).unwrap();
+            let token_ids = data.iter().map(|e| e.token_id.0).collect::<Vec<_>>();
+            assert_eq!(token_ids, vec![1, 2, 3, 4, 5]);
         });
     }
 
     #[test]
-    #[should_panic(expected = "Buyer cannot buy own token")]
-    fn buy_own_token_should_panic() {
-        let token_id = 5.into();
+    #[should_panic(expected = "At most 60 tokens are allowed in batch")]
+    fn batch_on_approve_too_many_tokens_should_panic() {
+        init_contract(LISTING_FEE).run_as(nft(), |contract| {
+            let tokens = (0..=MAX_BATCH_SIZE as u64)
+                .map(|token_id| (U64(token_id), approve_msg(10, gate_id(1), alice())))
+                .collect::<Vec<_>>();
+            contract.attach_deposit(LISTING_FEE * tokens.len() as u128);
+            contract.batch_on_approve(tokens, bob());
+        });
+    }
+
+    #[test]
+    fn batch_on_approve_with_duplicates_should_list_last_entry_once() {
+        init_contract(LISTING_FEE).run_as(nft(), |contract| {
+            let tokens = vec![
+                (5.into(), approve_msg(10, gate_id(1), alice())),
+                (6.into(), approve_msg(20, gate_id(1), alice())),
+                (5.into(), approve_msg(30, gate_id(1), alice())),
+            ];
+            contract.attach_deposit(2 * LISTING_FEE);
+            contract.batch_on_approve(tokens, bob());
+
+            assert_eq!(contract.get_tokens_for_sale().len(), 2);
+            assert_eq!(contract.get_token_for_sale(nft(), 5.into()).unwrap().min_price, U128(30));
+            assert_eq!(contract.get_tokens_by_gate_id(gate_id(1)).len(), 2);
+            assert_eq!(contract.get_total_listed_value(), U128(50));
+
+            let data: Vec<ListEvent> =
+                serde_json::from_value(last_event()["data"].clone()).unwrap();
+            let token_ids = data.iter().map(|e| e.token_id.0).collect::<Vec<_>>();
+            assert_eq!(token_ids, vec![6, 5]);
+        });
+    }
+}
+
+mod nft_on_revoke {
+
+    use super::*;
+
+    #[test]
+    fn nft_on_revoke_a_non_listed_token_does_nothing() {
+        init().run_as(nft(), |contract| {
+            contract.nft_on_revoke(5.into());
+            assert_eq!(contract.get_tokens_for_sale().len(), 0);
+        });
+    }
+
+    #[test]
+    fn nft_on_revoke_twice_is_a_no_op() {
+        init().run_as(nft(), |contract| {
+            let msg = approve_msg(10, gate_id(1), charlie());
+            contract.nft_on_approve(5.into(), bob(), 0.into(), msg);
+            let msg = approve_msg(10, gate_id(1), charlie());
+            contract.nft_on_approve(6.into(), bob(), 0.into(), msg);
+
+            let events = || get_logs().iter().filter(|log| log.starts_with(EVENT_JSON)).count();
+            contract.nft_on_revoke(5.into());
+            let delist_events = events();
+            contract.contract.nft_on_revoke(5.into());
+
+            assert_eq!(events(), delist_events);
+            assert!(contract.get_token_for_sale(nft(), 5.into()).is_none());
+            assert!(contract.get_token_for_sale(nft(), 6.into()).is_some());
+            assert_eq!(contract.get_tokens_by_owner_id(bob()).len(), 1);
+        });
+    }
+}
+
+mod index_keys {
+
+    use super::*;
+
+    fn token_ids(tokens: Vec<TokenForSale>) -> Vec<(String, u64)> {
+        let mut ids =
+            tokens.into_iter().map(|t| (t.nft_contract_id, t.token_id.0)).collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn indices_stay_separate_when_an_account_id_equals_a_gate_id() {
+        let alice_gate_id: ValidGateId = alice().as_ref().as_str().try_into().unwrap();
         init()
             .run_as(nft(), |contract| {
-                let msg = approve_msg(10, gate_id(1), charlie());
-                contract.nft_on_approve(token_id, bob(), 0.into(), msg);
+                contract.nft_on_approve(
+                    1.into(),
+                    bob(),
+                    0.into(),
+                    approve_msg(10, alice_gate_id.clone(), charlie()),
+                );
+                contract.nft_on_approve(
+                    2.into(),
+                    alice(),
+                    0.into(),
+                    approve_msg(10, gate_id(1), alice()),
+                );
             })
-            .run_as(bob(), |contract| {
-                contract.buy_token(nft(), token_id);
+            .run_as(alice(), |contract| {
+                contract.nft_on_approve(
+                    3.into(),
+                    bob(),
+                    0.into(),
+                    approve_msg(10, gate_id(1), charlie()),
+                );
+            })
+            .run_as(any(), |contract| {
+                let nft_id = nft().to_string();
+                let alice_id = alice().to_string();
+                assert_eq!(
+                    token_ids(contract.get_tokens_by_gate_id(alice_gate_id.clone())),
+                    vec![(nft_id.clone(), 1)]
+                );
+                assert_eq!(
+                    token_ids(contract.get_tokens_by_owner_id(alice())),
+                    vec![(nft_id.clone(), 2)]
+                );
+                assert_eq!(
+                    token_ids(contract.get_tokens_by_creator_id(alice())),
+                    vec![(nft_id.clone(), 2)]
+                );
+                assert_eq!(
+                    token_ids(contract.get_tokens_by_gate_id(gate_id(1))),
+                    vec![(alice_id.clone(), 3), (nft_id.clone(), 2)]
+                );
+            })
+            .run_as(nft(), |contract| {
+                contract.nft_on_revoke(2.into());
+                assert!(contract.get_tokens_by_owner_id(alice()).is_empty());
+                assert!(contract.get_tokens_by_creator_id(alice()).is_empty());
+                assert_eq!(
+                    token_ids(contract.get_tokens_by_gate_id(alice_gate_id.clone())),
+                    vec![(nft().to_string(), 1)]
+                );
+            })
+            .run_as(alice(), |contract| {
+                contract.nft_on_revoke(3.into());
+                assert_eq!(
+                    token_ids(contract.get_tokens_by_gate_id(alice_gate_id.clone())),
+                    vec![(nft().to_string(), 1)]
+                );
+                assert_eq!(token_ids(contract.get_tokens_for_sale()), vec![(nft().to_string(), 1)]);
             });
     }
+}
+
+mod listing_fee {
+
+    use super::*;
+
+    const LISTING_FEE: u128 = 1_000;
 
     #[test]
-    #[should_panic(expected = "Not enough deposit to cover token minimum price")]
-    fn buy_a_token_with_no_deposit_should_panic() {
-        let token_id = 5.into();
+    fn initial_listing_fee() {
+        init_contract(LISTING_FEE).run_as(any(), |contract| {
+            assert_eq!(contract.get_listing_fee(), U128(LISTING_FEE));
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Not enough deposit to cover the listing fee, `U128(1000)` is required"
+    )]
+    fn nft_on_approve_without_listing_fee_should_panic() {
+        init_contract(LISTING_FEE).run_as(nft(), |contract| {
+            let msg = approve_msg(10, gate_id(1), charlie());
+            contract.attach_deposit(LISTING_FEE - 1);
+            contract.nft_on_approve(5.into(), bob(), 0.into(), msg);
+        });
+    }
+
+    #[test]
+    fn nft_on_approve_with_listing_fee() {
+        init_contract(LISTING_FEE).run_as(nft(), |contract| {
+            let msg = approve_msg(10, gate_id(1), charlie());
+            contract.attach_deposit(LISTING_FEE);
+            contract.nft_on_approve(5.into(), bob(), 0.into(), msg);
+
+            let token = contract.get_token_for_sale(nft(), 5.into()).unwrap();
+            assert_eq!(token.listing_fee, U128(LISTING_FEE));
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Not enough deposit to cover the listing fee, `U128(2000)` is required"
+    )]
+    fn batch_on_approve_without_listing_fee_for_every_token_should_panic() {
+        init_contract(LISTING_FEE).run_as(nft(), |contract| {
+            let tokens = vec![
+                (5.into(), approve_msg(10, gate_id(1), charlie())),
+                (6.into(), approve_msg(10, gate_id(1), charlie())),
+            ];
+            contract.attach_deposit(LISTING_FEE);
+            contract.batch_on_approve(tokens, bob());
+        });
+    }
+
+    #[test]
+    fn nft_on_revoke_should_remove_listing_with_listing_fee() {
+        init_contract(LISTING_FEE).run_as(nft(), |contract| {
+            let msg = approve_msg(10, gate_id(1), charlie());
+            contract.attach_deposit(LISTING_FEE);
+            contract.nft_on_approve(5.into(), bob(), 0.into(), msg);
+            contract.attach_deposit(0);
+            contract.nft_on_revoke(5.into());
+
+            assert!(contract.get_token_for_sale(nft(), 5.into()).is_none());
+        });
+    }
+}
+
+mod get_gate_listed_count {
+
+    use super::*;
+
+    #[test]
+    fn get_gate_listed_count_after_listing_and_delisting() {
+        init().run_as(nft(), |contract| {
+            for token_id in 1..=3 {
+                let msg = approve_msg(10, gate_id(1), charlie());
+                contract.nft_on_approve(U64(token_id), bob(), 0.into(), msg);
+            }
+            let msg = approve_msg(10, gate_id(2), charlie());
+            contract.nft_on_approve(U64(4), bob(), 0.into(), msg);
+
+            assert_eq!(contract.get_gate_listed_count(gate_id(1)), U64(3));
+            assert_eq!(contract.get_gate_listed_count(gate_id(2)), U64(1));
+            assert_eq!(contract.get_gate_listed_count(gate_id(3)), U64(0));
+
+            contract.nft_on_revoke(U64(2));
+            assert_eq!(contract.get_gate_listed_count(gate_id(1)), U64(2));
+        });
+    }
+}
+
+mod get_tokens_for_sale {
+
+    use super::*;
+
+    #[test]
+    fn get_tokens_for_sale_should_be_sorted() {
         init()
             .run_as(nft(), |contract| {
-                let msg = approve_msg(1000, gate_id(1), charlie());
-                contract.nft_on_approve(token_id, bob(), 0.into(), msg);
+                for token_id in [7, 3, 11, 1, 5].iter() {
+                    let msg = approve_msg(10, gate_id(1), charlie());
+                    contract.nft_on_approve(U64(*token_id), bob(), 0.into(), msg);
+                }
             })
             .run_as(alice(), |contract| {
-                contract.attach_deposit(700);
-                contract.buy_token(nft(), token_id);
+                for token_id in [4, 2].iter() {
+                    let msg = approve_msg(10, gate_id(1), charlie());
+                    contract.nft_on_approve(U64(*token_id), bob(), 0.into(), msg);
+                }
+            })
+            .run_as(any(), |contract| {
+                let keys = contract
+                    .get_tokens_for_sale()
+                    .iter()
+                    .map(|t| (t.nft_contract_id.clone(), t.token_id.0))
+                    .collect::<Vec<_>>();
+                let mut sorted = keys.clone();
+                sorted.sort();
+                assert_eq!(keys, sorted);
+                assert_eq!(keys[0], (alice().to_string(), 2));
+                assert_eq!(keys[2], (nft().to_string(), 1));
             });
     }
+}
+
+mod token_key {
+
+    use super::*;
+
+    #[test]
+    fn token_keys_in_a_btree_set_should_be_deduped_and_sorted() {
+        let keys: BTreeSet<_> = vec![
+            TokenKey::new(nft().to_string(), U64(3)),
+            TokenKey::new(alice().to_string(), U64(7)),
+            TokenKey::new(nft().to_string(), U64(1)),
+            TokenKey::new(nft().to_string(), U64(3)),
+            TokenKey::new(alice().to_string(), U64(2)),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            keys.into_iter().collect::<Vec<_>>(),
+            vec![
+                TokenKey::new(alice().to_string(), U64(2)),
+                TokenKey::new(alice().to_string(), U64(7)),
+                TokenKey::new(nft().to_string(), U64(1)),
+                TokenKey::new(nft().to_string(), U64(3)),
+            ]
+        );
+    }
 
     #[test]
-    fn buy_a_token() {
-        let token_id = 5.into();
+    fn token_keys_in_a_hash_set_should_be_deduped() {
+        let keys: HashSet<_> = vec![
+            TokenKey::new(nft().to_string(), U64(3)),
+            TokenKey::new(nft().to_string(), U64(3)),
+            TokenKey::new(alice().to_string(), U64(3)),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(keys.len(), 2);
+        assert!(keys.contains(&TokenKey::new(alice().to_string(), U64(3))));
+    }
+}
+
+mod get_floor_listing {
+
+    use super::*;
+
+    #[test]
+    fn get_floor_listing_with_no_listings() {
+        init().run_as(any(), |contract| {
+            assert!(contract.get_floor_listing(gate_id(1)).is_none());
+        });
+    }
+
+    #[test]
+    fn get_floor_listing_should_return_the_cheapest_token() {
         init()
             .run_as(nft(), |contract| {
-                let msg = approve_msg(1000, gate_id(1), charlie());
-                contract.nft_on_approve(token_id, bob(), 0.into(), msg);
+                for (token_id, price) in [(1, 30), (2, 10), (3, 20)].iter() {
+                    let msg = approve_msg(*price, gate_id(1), charlie());
+                    contract.nft_on_approve(U64(*token_id), bob(), 0.into(), msg);
+                }
+                let msg = approve_msg(5, gate_id(2), charlie());
+                contract.nft_on_approve(U64(4), bob(), 0.into(), msg);
             })
-            .run_as(alice(), |contract| {
-                assert_eq!(contract.get_tokens_for_sale().len(), 1);
-                assert_eq!(contract.get_tokens_by_gate_id(gate_id(1)).len(), 1);
-                assert_eq!(contract.get_tokens_by_owner_id(bob()).len(), 1);
-                assert_eq!(contract.get_tokens_by_creator_id(charlie()).len(), 1);
-
-                contract.attach_deposit(1500);
-                contract.buy_token(nft(), token_id);
+            .run_as(any(), |contract| {
+                let token = contract.get_floor_listing(gate_id(1)).unwrap();
+                assert_eq!(token.token_id, U64(2));
+                assert_eq!(token.min_price, U128(10));
+            });
+    }
 
-                assert_eq!(contract.get_tokens_for_sale().len(), 0);
-                assert_eq!(contract.get_tokens_by_gate_id(gate_id(1)).len(), 0);
-                assert_eq!(contract.get_tokens_by_owner_id(bob()).len(), 0);
-                assert_eq!(contract.get_tokens_by_creator_id(charlie()).len(), 0);
+    #[test]
+    fn get_floor_listing_ties_should_return_the_lowest_token_id() {
+        init()
+            .run_as(nft(), |contract| {
+                for token_id in [9, 7, 8].iter() {
+                    let msg = approve_msg(10, gate_id(1), charlie());
+                    contract.nft_on_approve(U64(*token_id), bob(), 0.into(), msg);
+                }
+            })
+            .run_as(any(), |contract| {
+                assert_eq!(contract.get_floor_listing(gate_id(1)).unwrap().token_id, U64(7));
             });
     }
 }
+
+mod get_tokens_by_gate_ids {
+
+    use super::*;
+
+    fn list_tokens(contract: &mut MockedContext<MarketContractChecker>) {
+        for (token_id, gate) in [(1, 1), (2, 1), (3, 2), (4, 3)].iter() {
+            let msg = approve_msg(10, gate_id(*gate), charlie());
+            contract.nft_on_approve(U64(*token_id), bob(), 0.into(), msg);
+        }
+    }
+
+    fn token_ids(tokens: Vec<TokenForSale>) -> Vec<u64> {
+        let mut token_ids = tokens.into_iter().map(|token| token.token_id.0).collect::<Vec<_>>();
+        token_ids.sort_unstable();
+        token_ids
+    }
+
+    #[test]
+    fn get_tokens_by_gate_ids_should_return_tokens_of_all_gates() {
+        init().run_as(nft(), list_tokens).run_as(any(), |contract| {
+            let tokens = contract.get_tokens_by_gate_ids(vec![gate_id(1), gate_id(2)], None, None);
+            assert_eq!(token_ids(tokens), vec![1, 2, 3]);
+        });
+    }
+
+    #[test]
+    fn get_tokens_by_gate_ids_should_skip_repeated_and_unknown_gates() {
+        init().run_as(nft(), list_tokens).run_as(any(), |contract| {
+            let gate_ids = vec![gate_id(2), gate_id(9), gate_id(2)];
+            let tokens = contract.get_tokens_by_gate_ids(gate_ids, None, None);
+            assert_eq!(token_ids(tokens), vec![3]);
+        });
+    }
+
+    #[test]
+    fn get_tokens_by_gate_ids_should_paginate_across_gates() {
+        init().run_as(nft(), list_tokens).run_as(any(), |contract| {
+            let gate_ids = || vec![gate_id(1), gate_id(2), gate_id(3)];
+            let first = contract.get_tokens_by_gate_ids(gate_ids(), None, Some(3));
+            let second = contract.get_tokens_by_gate_ids(gate_ids(), Some(U64(3)), Some(3));
+            assert_eq!(first.len(), 3);
+            assert_eq!(token_ids(second), vec![4]);
+
+            let middle = contract.get_tokens_by_gate_ids(gate_ids(), Some(U64(1)), Some(2));
+            assert_eq!(middle.len(), 2);
+            assert_eq!(middle[1].token_id, U64(3));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "At most 20 gate IDs are allowed to query at once")]
+    fn get_tokens_by_gate_ids_with_too_many_gates_should_panic() {
+        init().run_as(any(), |contract| {
+            contract.get_tokens_by_gate_ids((0..21).map(gate_id).collect(), None, None);
+        });
+    }
+}
+
+mod stale_index {
+
+    use super::*;
+    use near_sdk::{borsh::BorshSerialize, env};
+
+    /// Removes `nft_id:token_id` from the lookup of the contract's `tokens_for_sale`,
+    /// so it is no longer found while the indexes still refer to it.
+    /// The prefix `0` is the Borsh encoding of `Keys::TokensForSale`.
+    fn remove_token_for_sale(nft_id: ValidAccountId, token_id: TokenId) {
+        let mut lookup_key = vec![0, b'i'];
+        lookup_key.extend((nft_id.to_string(), token_id.0).try_to_vec().unwrap());
+        env::storage_remove(&lookup_key);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token Key `nft:U64(1)` was not found")]
+    fn get_tokens_by_owner_id_with_orphaned_token_key_should_panic() {
+        init().run_as(nft(), |contract| {
+            let msg = approve_msg(10, gate_id(1), charlie());
+            contract.nft_on_approve(U64(1), bob(), 0.into(), msg);
+            remove_token_for_sale(nft(), U64(1));
+            contract.get_tokens_by_owner_id(bob());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token Key `nft:U64(1)` was not found")]
+    fn get_tokens_by_gate_ids_with_orphaned_token_key_should_panic() {
+        init().run_as(nft(), |contract| {
+            let msg = approve_msg(10, gate_id(1), charlie());
+            contract.nft_on_approve(U64(1), bob(), 0.into(), msg);
+            remove_token_for_sale(nft(), U64(1));
+            contract.get_tokens_by_gate_ids(vec![gate_id(1)], None, None);
+        });
+    }
+}
+
+mod events {
+
+    use super::*;
+
+    #[test]
+    fn nft_on_approve_should_log_list_event() {
+        init().run_as(nft(), |contract| {
+            contract.nft_on_approve(
+                5.into(),
+                bob(),
+                1.into(),
+                approve_msg(100, gate_id(1), alice()),
+            );
+
+            let event = last_event();
+            assert_eq!(event["standard"], "mg-market");
+            assert_eq!(event["event"], "mg_list");
+
+            let data: Vec<ListEvent> = serde_json::from_value(event["data"].clone()).unwrap();
+            assert_eq!(data.len(), 1);
+            assert_eq!(data[0].nft_id, nft().to_string());
+            assert_eq!(data[0].token_id, U64(5));
+            assert_eq!(data[0].owner_id, bob().to_string());
+            assert_eq!(data[0].min_price.0, 100);
+            assert_eq!(data[0].gate_id, Some(gate_id(1).to_string()));
+            assert_eq!(data[0].creator_id, Some(alice().to_string()));
+        });
+    }
+
+    #[test]
+    fn nft_on_revoke_should_log_delist_event() {
+        init().run_as(nft(), |contract| {
+            contract.nft_on_approve(
+                5.into(),
+                bob(),
+                1.into(),
+                approve_msg(100, gate_id(1), alice()),
+            );
+            contract.contract.nft_on_revoke(5.into());
+
+            let event = last_event();
+            assert_eq!(event["standard"], "mg-market");
+            assert_eq!(event["event"], "mg_delist");
+
+            let data: Vec<DelistEvent> = serde_json::from_value(event["data"].clone()).unwrap();
+            assert_eq!(data.len(), 1);
+            assert_eq!(data[0].nft_id, nft().to_string());
+            assert_eq!(data[0].token_id, U64(5));
+            assert_eq!(data[0].owner_id, bob().to_string());
+        });
+    }
+}
+
+mod get_token_for_sale {
+
+    use super::*;
+
+    #[test]
+    fn get_a_listed_token_for_sale() {
+        init()
+            .run_as(nft(), |contract| {
+                contract.nft_on_approve(
+                    5.into(),
+                    bob(),
+                    1.into(),
+                    approve_msg(1000, gate_id(1), charlie()),
+                );
+            })
+            .run_as(alice(), |contract| {
+                let token = contract.get_token_for_sale(nft(), 5.into()).unwrap();
+                assert_eq!(token.nft_contract_id, nft().to_string());
+                assert_eq!(token.token_id, U64(5));
+                assert_eq!(token.owner_id, bob().to_string());
+                assert_eq!(token.approval_id, U64(1));
+                assert_eq!(token.min_price.0, 1000);
+                assert_eq!(token.gate_id, Some(gate_id(1).to_string()));
+                assert_eq!(token.creator_id, Some(charlie().to_string()));
+
+                assert!(contract.get_token_for_sale(nft(), 6.into()).is_none());
+                assert!(contract.get_token_for_sale(alice(), 5.into()).is_none());
+            });
+    }
+}
+
+mod get_listings_since {
+
+    use super::*;
+
+    fn token_ids(tokens: Vec<TokenForSale>) -> Vec<u64> {
+        let mut token_ids = tokens.into_iter().map(|t| t.token_id.0).collect::<Vec<_>>();
+        token_ids.sort();
+        token_ids
+    }
+
+    #[test]
+    fn get_listings_since_returns_only_newer_listings() {
+        init()
+            .run_as(nft(), |contract| {
+                for token_id in 1..=2 {
+                    let msg = approve_msg(1000, gate_id(1), charlie());
+                    contract.nft_on_approve(token_id.into(), bob(), 1.into(), msg);
+                }
+                contract.context.block_timestamp += 5_000_000;
+            })
+            .run_as(nft(), |contract| {
+                for token_id in 3..=4 {
+                    let msg = approve_msg(1000, gate_id(1), charlie());
+                    contract.nft_on_approve(token_id.into(), bob(), 1.into(), msg);
+                }
+            })
+            .run_as(alice(), |contract| {
+                let old = contract.get_token_for_sale(nft(), 2.into()).unwrap().created_at;
+                let new = contract.get_token_for_sale(nft(), 3.into()).unwrap().created_at;
+                assert!(new > old);
+
+                assert_eq!(token_ids(contract.get_listings_since(0, None, None)), vec![1, 2, 3, 4]);
+                assert_eq!(token_ids(contract.get_listings_since(new, None, None)), vec![3, 4]);
+                assert_eq!(contract.get_listings_since(new + 1, None, None).len(), 0);
+
+                assert_eq!(contract.get_listings_since(0, Some(1.into()), Some(2)).len(), 2);
+                assert_eq!(contract.get_listings_since(0, Some(4.into()), None).len(), 0);
+            });
+    }
+}
+
+mod sync_listing {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Token Key `nft:U64(99)` was not found")]
+    fn sync_a_non_existent_listing_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.sync_listing(nft(), 99.into());
+        });
+    }
+}
+
+mod update_price {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Token Key `nft:U64(99)` was not found")]
+    fn update_price_of_a_non_existent_token_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.update_price(nft(), 99.into(), U128(10));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token Key `nft:U64(5)` is not listed by `alice`")]
+    fn update_price_of_a_non_owned_token_should_panic() {
+        init()
+            .run_as(nft(), |contract| {
+                let msg = approve_msg(10, gate_id(1), charlie());
+                contract.nft_on_approve(5.into(), bob(), 0.into(), msg);
+            })
+            .run_as(alice(), |contract| {
+                contract.update_price(nft(), 5.into(), U128(20));
+            });
+    }
+
+    #[test]
+    fn batch_update_price_of_owned_tokens() {
+        init()
+            .run_as(nft(), |contract| {
+                for token_id in 1..=3 {
+                    let msg = approve_msg(10, gate_id(1), charlie());
+                    contract.nft_on_approve(token_id.into(), bob(), 0.into(), msg);
+                }
+                let msg = approve_msg(10, gate_id(1), charlie());
+                contract.nft_on_approve(4.into(), alice(), 0.into(), msg);
+            })
+            .run_as(bob(), |contract| {
+                let updates =
+                    (1..=4).map(|token_id| (nft(), U64(token_id), U128(token_id as u128 * 100)));
+                let errs = contract.batch_update_price(updates.collect());
+
+                assert_eq!(errs.len(), 1);
+                assert_eq!(errs[0].0, TokenKey::new(nft().to_string(), 4.into()));
+                assert!(matches!(errs[0].1, Panics::TokenKeyNotOwnedBy { .. }));
+
+                for token_id in 1..=3 {
+                    let token = contract.get_token_for_sale(nft(), token_id.into()).unwrap();
+                    assert_eq!(token.min_price, U128(token_id as u128 * 100));
+                }
+                let token = contract.get_token_for_sale(nft(), 4.into()).unwrap();
+                assert_eq!(token.min_price, U128(10));
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "At most 60 tokens are allowed in batch")]
+    fn batch_update_price_of_too_many_tokens_should_panic() {
+        init().run_as(bob(), |contract| {
+            let updates =
+                (0..=MAX_BATCH_SIZE as u64).map(|token_id| (nft(), U64(token_id), U128(10)));
+            contract.batch_update_price(updates.collect());
+        });
+    }
+}
+
+mod buy_token {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Token Key `nft:U64(99)` was not found")]
+    fn buy_a_non_existent_token_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.buy_token(nft(), 99.into());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Buyer cannot buy own token")]
+    fn buy_own_token_should_panic() {
+        let token_id = 5.into();
+        init()
+            .run_as(nft(), |contract| {
+                let msg = approve_msg(10, gate_id(1), charlie());
+                contract.nft_on_approve(token_id, bob(), 0.into(), msg);
+            })
+            .run_as(bob(), |contract| {
+                contract.buy_token(nft(), token_id);
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Not enough gas attached, more than `U64(150000000000000)` is required"
+    )]
+    fn buy_a_token_with_insufficient_gas_should_panic() {
+        let token_id = 5.into();
+        init()
+            .run_as(nft(), |contract| {
+                let msg = approve_msg(1000, gate_id(1), charlie());
+                contract.nft_on_approve(token_id, bob(), 0.into(), msg);
+            })
+            .run_as(alice(), |contract| {
+                contract.attach_deposit(1500);
+                contract.context.prepaid_gas = 100_000_000_000_000;
+                contract.buy_token(nft(), token_id);
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough deposit to cover token minimum price")]
+    fn buy_a_token_with_no_deposit_should_panic() {
+        let token_id = 5.into();
+        init()
+            .run_as(nft(), |contract| {
+                let msg = approve_msg(1000, gate_id(1), charlie());
+                contract.nft_on_approve(token_id, bob(), 0.into(), msg);
+            })
+            .run_as(alice(), |contract| {
+                contract.attach_deposit(700);
+                contract.buy_token(nft(), token_id);
+            });
+    }
+
+    #[test]
+    fn buy_a_token() {
+        let token_id = 5.into();
+        init()
+            .run_as(nft(), |contract| {
+                let msg = approve_msg(1000, gate_id(1), charlie());
+                contract.nft_on_approve(token_id, bob(), 0.into(), msg);
+            })
+            .run_as(alice(), |contract| {
+                assert_eq!(contract.get_tokens_for_sale().len(), 1);
+                assert_eq!(contract.get_tokens_by_gate_id(gate_id(1)).len(), 1);
+                assert_eq!(contract.get_tokens_by_owner_id(bob()).len(), 1);
+                assert_eq!(contract.get_tokens_by_creator_id(charlie()).len(), 1);
+
+                contract.attach_deposit(1500);
+                contract.buy_token(nft(), token_id);
+
+                assert_eq!(contract.get_tokens_for_sale().len(), 0);
+                assert_eq!(contract.get_tokens_by_gate_id(gate_id(1)).len(), 0);
+                assert_eq!(contract.get_tokens_by_owner_id(bob()).len(), 0);
+                assert_eq!(contract.get_tokens_by_creator_id(charlie()).len(), 0);
+            });
+    }
+}
+
+mod buy_token_with_code {
+
+    use super::*;
+    use mg_core::fraction::Fraction;
+
+    const NOW_MS: u64 = 1_000;
+
+    fn init_with_codes() -> MockedContext<MarketContractChecker> {
+        let mut context = init();
+        context
+            .run_as(mintgate_admin(), |contract| {
+                contract.context.block_timestamp = NOW_MS * 1_000_000;
+                contract.admin_set_discount_code(
+                    "PROMO10".to_string(),
+                    "10/100".parse().unwrap(),
+                    NOW_MS + 60_000,
+                );
+                contract.admin_set_discount_code(
+                    "OLD".to_string(),
+                    "10/100".parse().unwrap(),
+                    NOW_MS - 1,
+                );
+            })
+            .run_as(nft(), |contract| {
+                let msg = approve_msg(1000, gate_id(1), charlie());
+                contract.nft_on_approve(5.into(), bob(), 0.into(), msg);
+            });
+        context
+    }
+
+    #[test]
+    #[should_panic(expected = "Operation is allowed only for admin")]
+    fn admin_set_discount_code_from_non_admin_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.admin_set_discount_code("PROMO10".to_string(), "10/100".parse().unwrap(), 0);
+        });
+    }
+
+    #[test]
+    fn admin_set_and_remove_a_discount_code() {
+        init_with_codes().run_as(mintgate_admin(), |contract| {
+            let (discount, expires_at) = contract.get_discount_code("PROMO10".to_string()).unwrap();
+            assert_eq!(discount, "10/100".parse::<Fraction>().unwrap());
+            assert_eq!(expires_at, NOW_MS + 60_000);
+
+            assert!(contract.admin_remove_discount_code("PROMO10".to_string()));
+            assert!(!contract.admin_remove_discount_code("PROMO10".to_string()));
+            assert!(contract.get_discount_code("PROMO10".to_string()).is_none());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Discount code `NOPE` was not found")]
+    fn buy_a_token_with_an_unknown_code_should_panic() {
+        init_with_codes().run_as(alice(), |contract| {
+            contract.attach_deposit(900);
+            contract.buy_token_with_code(nft(), 5.into(), "NOPE".to_string());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Discount code `OLD` has expired")]
+    fn buy_a_token_with_an_expired_code_should_panic() {
+        init_with_codes().run_as(alice(), |contract| {
+            contract.context.block_timestamp = NOW_MS * 1_000_000;
+            contract.attach_deposit(900);
+            contract.buy_token_with_code(nft(), 5.into(), "OLD".to_string());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough deposit to cover token minimum price")]
+    fn buy_a_token_with_a_code_and_not_enough_deposit_should_panic() {
+        init_with_codes().run_as(alice(), |contract| {
+            contract.context.block_timestamp = NOW_MS * 1_000_000;
+            contract.attach_deposit(899);
+            contract.buy_token_with_code(nft(), 5.into(), "PROMO10".to_string());
+        });
+    }
+
+    #[test]
+    fn buy_a_token_with_a_valid_code() {
+        init_with_codes().run_as(alice(), |contract| {
+            contract.context.block_timestamp = NOW_MS * 1_000_000;
+            contract.attach_deposit(900);
+            contract.buy_token_with_code(nft(), 5.into(), "PROMO10".to_string());
+
+            assert_eq!(contract.get_tokens_for_sale().len(), 0);
+            assert_eq!(contract.get_tokens_by_owner_id(bob()).len(), 0);
+        });
+    }
+}
+
+mod total_listed_value {
+
+    use super::*;
+
+    fn check_total_listed_value(contract: &MarketContract, expected: u128) {
+        let rescan = contract.get_tokens_for_sale().iter().map(|t| t.min_price.0).sum::<u128>();
+        assert_eq!(rescan, expected);
+        assert_eq!(contract.get_total_listed_value(), U128(expected));
+    }
+
+    #[test]
+    fn total_listed_value_is_zero_initially() {
+        init().run_as(any(), |contract| {
+            check_total_listed_value(contract, 0);
+        });
+    }
+
+    #[test]
+    fn total_listed_value_follows_listing_changes() {
+        init()
+            .run_as(nft(), |contract| {
+                for (token_id, price) in [(1, 1000), (2, 2000), (3, 3000)].iter() {
+                    let msg = approve_msg(*price, gate_id(1), charlie());
+                    contract.nft_on_approve(U64(*token_id), bob(), 0.into(), msg);
+                }
+                check_total_listed_value(contract, 6000);
+
+                let msg = approve_msg(1500, gate_id(1), charlie());
+                let msg = serde_json::to_string(&msg).unwrap();
+                contract.contract.nft_on_approve(U64(1), bob(), 1.into(), msg);
+                check_total_listed_value(contract, 6500);
+            })
+            .run_as(bob(), |contract| {
+                contract.update_price(nft(), U64(2), U128(500));
+                check_total_listed_value(contract, 5000);
+            })
+            .run_as(nft(), |contract| {
+                contract.nft_on_revoke(U64(3));
+                check_total_listed_value(contract, 2000);
+            })
+            .run_as(alice(), |contract| {
+                contract.attach_deposit(1500);
+                contract.buy_token(nft(), U64(1));
+                check_total_listed_value(contract, 500);
+            });
+    }
+}
+
+mod make_payouts {
+
+    use super::*;
+    use near_sdk::{PromiseResult, VMContext};
+
+    /// Runs `make_payouts` as the contract itself, with `result` as the `nft_transfer_payout` result.
+    fn make_payouts(context: &mut VMContext, contract: &mut MarketContract, result: PromiseResult) {
+        make_payouts_for(context, contract, sale(1), result);
+    }
+
+    /// Sale of `nft:token_id` by `charlie` to `bob` for `100`, paid in full by its deposit.
+    fn sale(token_id: u64) -> Sale {
+        Sale {
+            buyer_id: bob().into(),
+            owner_id: charlie().into(),
+            nft_id: nft().into(),
+            token_id: token_id.into(),
+            amount: U128(100),
+            deposit: U128(100),
+            creator_id: None,
+        }
+    }
+
+    fn make_payouts_for(
+        context: &mut VMContext,
+        contract: &mut MarketContract,
+        sale: Sale,
+        result: PromiseResult,
+    ) {
+        context.predecessor_account_id = context.current_account_id.clone();
+        testing_env!(
+            context.clone(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.make_payouts(sale);
+    }
+
+    fn payout(payout: &[(ValidAccountId, u128)]) -> PromiseResult {
+        let payout = payout
+            .iter()
+            .map(|(account_id, amount)| (account_id.to_string(), U128(*amount)))
+            .collect::<HashMap<_, _>>();
+        PromiseResult::Successful(serde_json::to_vec(&payout).unwrap())
+    }
+
+    #[test]
+    fn make_payouts_should_pay_out_and_record_sale() {
+        init().run_as(any(), |contract| {
+            let result = payout(&[(alice(), 90), (mintgate_fee_account_id(), 10)]);
+            make_payouts(&mut contract.context, &mut contract.contract.contract, result);
+
+            let mut transfers = transfers();
+            transfers.sort();
+            assert_eq!(
+                transfers,
+                vec![(alice().to_string(), 90), (mintgate_fee_account_id().to_string(), 10)]
+            );
+            assert_eq!(contract.get_recent_sales(None).len(), 1);
+        });
+    }
+
+    #[test]
+    fn make_payouts_should_add_fee_account_payout_to_lifetime_fees() {
+        init().run_as(any(), |contract| {
+            let result = payout(&[(alice(), 90), (mintgate_fee_account_id(), 10)]);
+            make_payouts(&mut contract.context, &mut contract.contract.contract, result);
+            assert_eq!(contract.get_lifetime_fees(), U128(10));
+
+            let result = payout(&[(alice(), 95), (charlie(), 5)]);
+            make_payouts(&mut contract.context, &mut contract.contract.contract, result);
+            assert_eq!(contract.get_lifetime_fees(), U128(10));
+
+            let result = payout(&[(alice(), 90), (mintgate_fee_account_id(), 20)]);
+            make_payouts(&mut contract.context, &mut contract.contract.contract, result);
+            assert_eq!(contract.get_lifetime_fees(), U128(10));
+        });
+    }
+
+    #[test]
+    fn make_payouts_should_keep_only_max_recent_sales() {
+        init().run_as(any(), |contract| {
+            let sales = MAX_RECENT_SALES as u64 + 5;
+            for token_id in 0..sales {
+                let result = payout(&[(alice(), 100)]);
+                make_payouts_for(
+                    &mut contract.context,
+                    &mut contract.contract.contract,
+                    sale(token_id),
+                    result,
+                );
+            }
+
+            let token_keys = |sales: Vec<(TokenKey, String, U128, u64)>| {
+                sales.into_iter().map(|(token_key, _, _, _)| token_key).collect::<Vec<_>>()
+            };
+            let expected = |token_ids: Vec<u64>| {
+                token_ids
+                    .into_iter()
+                    .map(|token_id| TokenKey::new(nft().into(), token_id.into()))
+                    .collect::<Vec<_>>()
+            };
+            let recent_sales = token_keys(contract.get_recent_sales(None));
+            assert!(recent_sales == expected((5..sales).rev().collect()));
+            let recent_sales = token_keys(contract.get_recent_sales(Some(3)));
+            assert!(recent_sales == expected(vec![sales - 1, sales - 2, sales - 3]));
+        });
+    }
+
+    #[test]
+    fn make_payouts_with_mismatched_payout_should_pay_the_seller() {
+        init().run_as(any(), |contract| {
+            let result = payout(&[(alice(), 190), (mintgate_fee_account_id(), 10)]);
+            make_payouts(&mut contract.context, &mut contract.contract.contract, result);
+
+            assert_eq!(transfers(), vec![(charlie().to_string(), 100)]);
+            let event = last_event();
+            assert_eq!(event["event"], "mg_payout_mismatch");
+            let data: Vec<PayoutMismatchEvent> =
+                serde_json::from_value(event["data"].clone()).unwrap();
+            assert_eq!(data[0].buyer_id, bob().to_string());
+            assert_eq!(data[0].owner_id, charlie().to_string());
+            assert_eq!(data[0].payout, Some(U128(200)));
+            assert_eq!(data[0].amount, U128(100));
+            assert_eq!(contract.get_recent_sales(None).len(), 0);
+        });
+    }
+
+    #[test]
+    fn make_payouts_with_invalid_payout_should_pay_the_seller() {
+        init().run_as(any(), |contract| {
+            let result = PromiseResult::Successful(b"null".to_vec());
+            make_payouts(&mut contract.context, &mut contract.contract.contract, result);
+
+            assert_eq!(transfers(), vec![(charlie().to_string(), 100)]);
+            assert_eq!(last_event()["data"][0]["payout"], Value::Null);
+        });
+    }
+
+    #[test]
+    fn make_payouts_with_failed_transfer_should_refund_buyer() {
+        init().run_as(any(), |contract| {
+            make_payouts(
+                &mut contract.context,
+                &mut contract.contract.contract,
+                PromiseResult::Failed,
+            );
+
+            assert_eq!(transfers(), vec![(bob().to_string(), 100)]);
+            assert!(get_logs().iter().all(|log| !log.starts_with(EVENT_JSON)));
+        });
+    }
+
+    #[test]
+    fn make_payouts_should_release_the_sale_amount_in_flight() {
+        init()
+            .run_as(nft(), |contract| {
+                let msg = approve_msg(100, gate_id(1), alice());
+                contract.nft_on_approve(1.into(), charlie(), 0.into(), msg);
+            })
+            .run_as(bob(), |contract| {
+                contract.attach_deposit(100);
+                contract.buy_token(nft(), 1.into());
+                assert_eq!(contract.get_free_balance(), U128(free_balance(100)));
+
+                let result = PromiseResult::Failed;
+                make_payouts(&mut contract.context, &mut contract.contract.contract, result);
+                assert_eq!(contract.get_free_balance(), U128(free_balance(0)));
+            });
+    }
+
+    #[test]
+    fn make_payouts_with_failed_discounted_transfer_should_refund_only_the_deposit() {
+        init().run_as(any(), |contract| {
+            let sale = Sale { deposit: U128(90), ..sale(1) };
+            let context = &mut contract.context;
+            make_payouts_for(context, &mut contract.contract.contract, sale, PromiseResult::Failed);
+
+            assert_eq!(transfers(), vec![(bob().to_string(), 90)]);
+        });
+    }
+}
+
+mod admin_withdraw {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Operation is allowed only for admin")]
+    fn admin_withdraw_from_non_admin_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.admin_withdraw(U128(1), alice());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot withdraw")]
+    fn admin_withdraw_more_than_free_balance_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            let amount = contract.get_free_balance().0 + 1;
+            contract.admin_withdraw(U128(amount), alice());
+        });
+    }
+
+    #[test]
+    fn free_balance_excludes_listing_fees_held() {
+        init_contract(100)
+            .run_as(nft(), |contract| {
+                assert_eq!(contract.get_free_balance(), U128(free_balance(0)));
+
+                contract.attach_deposit(100);
+                contract.nft_on_approve(
+                    1.into(),
+                    bob(),
+                    0.into(),
+                    approve_msg(10, gate_id(1), charlie()),
+                );
+                contract.nft_on_approve(
+                    2.into(),
+                    bob(),
+                    0.into(),
+                    approve_msg(10, gate_id(1), charlie()),
+                );
+                contract.attach_deposit(0);
+                assert_eq!(contract.get_free_balance(), U128(free_balance(200)));
+            })
+            .run_as(nft(), |contract| {
+                contract.nft_on_revoke(1.into());
+                assert_eq!(contract.get_free_balance(), U128(free_balance(100)));
+            });
+    }
+
+    #[test]
+    fn relist_should_refund_previous_listing_fee() {
+        init_contract(100)
+            .run_as(nft(), |contract| {
+                contract.attach_deposit(100);
+                contract.nft_on_approve(
+                    1.into(),
+                    bob(),
+                    0.into(),
+                    approve_msg(10, gate_id(1), charlie()),
+                );
+                assert_eq!(transfers(), vec![]);
+                assert_eq!(contract.get_free_balance(), U128(free_balance(100)));
+            })
+            .run_as(nft(), |contract| {
+                contract.attach_deposit(100);
+                contract.nft_on_approve(
+                    1.into(),
+                    bob(),
+                    1.into(),
+                    approve_msg(20, gate_id(1), charlie()),
+                );
+                assert_eq!(transfers(), vec![(bob().to_string(), 100)]);
+                assert_eq!(contract.get_free_balance(), U128(free_balance(100)));
+                assert_eq!(contract.get_tokens_for_sale().len(), 1);
+                assert_eq!(contract.get_total_listed_value(), U128(20));
+            });
+    }
+
+    #[test]
+    fn relist_by_new_owner_should_refund_and_reindex_previous_owner() {
+        init_contract(100)
+            .run_as(nft(), |contract| {
+                contract.attach_deposit(100);
+                contract.nft_on_approve(
+                    1.into(),
+                    bob(),
+                    0.into(),
+                    approve_msg(10, gate_id(1), charlie()),
+                );
+            })
+            .run_as(nft(), |contract| {
+                contract.attach_deposit(100);
+                contract.batch_on_approve(
+                    vec![(1.into(), approve_msg(20, gate_id(2), charlie()))],
+                    alice(),
+                );
+                assert_eq!(transfers(), vec![(bob().to_string(), 100)]);
+                assert_eq!(contract.get_free_balance(), U128(free_balance(100)));
+                assert_eq!(contract.get_tokens_by_owner_id(bob()).len(), 0);
+                assert_eq!(contract.get_tokens_by_owner_id(alice()).len(), 1);
+                assert_eq!(contract.get_tokens_by_gate_id(gate_id(1)).len(), 0);
+                assert_eq!(contract.get_tokens_by_gate_id(gate_id(2)).len(), 1);
+            });
+    }
+
+    #[test]
+    fn admin_withdraw_free_balance() {
+        init().run_as(mintgate_admin(), |contract| {
+            let amount = contract.get_free_balance();
+            contract.admin_withdraw(amount, alice());
+
+            let event = last_event();
+            assert_eq!(event["event"], "mg_withdraw");
+            assert_eq!(event["data"][0]["amount"], amount.0.to_string());
+            assert_eq!(event["data"][0]["receiver_id"], alice().to_string());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "is available, `U128(1)` short")]
+    fn admin_withdraw_into_storage_reserve_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.context.account_balance += 10u128.pow(25);
+            contract.update_context();
+            let amount = contract.get_free_balance().0 + 1;
+            contract.admin_withdraw(U128(amount), alice());
+        });
+    }
+
+    #[test]
+    fn admin_withdraw_keeps_storage_reserve() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.context.account_balance += 10u128.pow(25);
+            contract.update_context();
+            assert_eq!(contract.get_storage_reserve_margin(), U128(DEFAULT_STORAGE_RESERVE_MARGIN));
+            assert_eq!(contract.get_free_balance(), U128(free_balance(0)));
+
+            let amount = contract.get_free_balance().0 - 10u128.pow(24);
+            contract.admin_withdraw(U128(amount), alice());
+            assert_eq!(last_event()["data"][0]["amount"], amount.to_string());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Operation is allowed only for admin")]
+    fn admin_set_storage_reserve_margin_from_non_admin_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.admin_set_storage_reserve_margin(U128(0));
+        });
+    }
+
+    #[test]
+    fn admin_set_storage_reserve_margin() {
+        init().run_as(mintgate_admin(), |contract| {
+            let free_balance = contract.get_free_balance().0;
+            contract.admin_set_storage_reserve_margin(U128(2 * DEFAULT_STORAGE_RESERVE_MARGIN));
+            assert_eq!(
+                contract.get_storage_reserve_margin(),
+                U128(2 * DEFAULT_STORAGE_RESERVE_MARGIN)
+            );
+            assert_eq!(
+                contract.get_free_balance().0,
+                free_balance - DEFAULT_STORAGE_RESERVE_MARGIN
+            );
+        });
+    }
+}
+
+mod admin_prune_orphans {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Operation is allowed only for admin")]
+    fn admin_prune_orphans_from_non_admin_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.admin_prune_orphans(nft(), vec![1.into()]);
+        });
+    }
+
+    #[test]
+    fn admin_prune_orphans_of_burned_tokens() {
+        init()
+            .run_as(nft(), |contract| {
+                for token_id in 1..=3 {
+                    contract.nft_on_approve(
+                        token_id.into(),
+                        bob(),
+                        0.into(),
+                        approve_msg(10, gate_id(1), charlie()),
+                    );
+                }
+            })
+            .run_as(mintgate_admin(), |contract| {
+                // Tokens `1` and `2` were burned, but the market was never notified.
+                let pruned =
+                    contract.admin_prune_orphans(nft(), vec![1.into(), 2.into(), 9.into()]);
+                assert_eq!(pruned, 2);
+                assert_eq!(contract.admin_prune_orphans(nft(), vec![1.into()]), 0);
+
+                let token_ids = |tokens: Vec<TokenForSale>| {
+                    tokens.into_iter().map(|t| t.token_id).collect::<Vec<_>>()
+                };
+                assert_eq!(token_ids(contract.get_tokens_for_sale()), vec![U64(3)]);
+                assert_eq!(token_ids(contract.get_tokens_by_owner_id(bob())), vec![U64(3)]);
+                assert_eq!(token_ids(contract.get_tokens_by_gate_id(gate_id(1))), vec![U64(3)]);
+                assert_eq!(token_ids(contract.get_tokens_by_creator_id(charlie())), vec![U64(3)]);
+                assert_eq!(contract.get_total_listed_value(), U128(10));
+            });
+    }
+}
+
+mod batch_buy {
+
+    use super::*;
+
+    fn list_tokens(contract: &mut MockedContext<MarketContractChecker>) {
+        contract.nft_on_approve(
+            5.into(),
+            bob(),
+            0.into(),
+            approve_msg(1000, gate_id(1), charlie()),
+        );
+        contract.nft_on_approve(
+            6.into(),
+            bob(),
+            0.into(),
+            approve_msg(2000, gate_id(1), charlie()),
+        );
+    }
+
+    #[test]
+    fn batch_buy_a_few_tokens() {
+        init().run_as(nft(), list_tokens).run_as(alice(), |contract| {
+            assert_eq!(contract.get_tokens_for_sale().len(), 2);
+
+            contract.attach_deposit(3500);
+            contract.batch_buy(vec![(nft(), 5.into()), (nft(), 6.into())]);
+
+            assert_eq!(contract.get_tokens_for_sale().len(), 0);
+            assert_eq!(contract.get_tokens_by_gate_id(gate_id(1)).len(), 0);
+            assert_eq!(contract.get_tokens_by_owner_id(bob()).len(), 0);
+            assert_eq!(contract.get_tokens_by_creator_id(charlie()).len(), 0);
+        });
+    }
+
+    #[test]
+    fn batch_buy_should_check_the_owner_of_each_token() {
+        init().run_as(nft(), list_tokens).run_as(alice(), |contract| {
+            contract.attach_deposit(3500);
+            contract.batch_buy(vec![(nft(), 5.into()), (nft(), 6.into())]);
+
+            let calls = get_created_receipts()
+                .into_iter()
+                .filter_map(|receipt| {
+                    let receipt = serde_json::to_string(&receipt).unwrap();
+                    let receipt: Value = serde_json::from_str(&receipt).unwrap();
+                    let method_name = receipt["actions"][0]["FunctionCall"]["method_name"].clone();
+                    method_name.as_str().map(|method_name| method_name.to_string())
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                calls,
+                vec!["nft_token", "resolve_purchase", "nft_token", "resolve_purchase"]
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough deposit to cover token minimum price")]
+    fn batch_buy_with_not_enough_deposit_should_panic() {
+        init().run_as(nft(), list_tokens).run_as(alice(), |contract| {
+            contract.attach_deposit(2500);
+            contract.batch_buy(vec![(nft(), 5.into()), (nft(), 6.into())]);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token Key `nft:U64(5)` was not found")]
+    fn batch_buy_the_same_token_twice_should_panic() {
+        init().run_as(nft(), list_tokens).run_as(alice(), |contract| {
+            contract.attach_deposit(2000);
+            contract.batch_buy(vec![(nft(), 5.into()), (nft(), 5.into())]);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Buyer cannot buy own token")]
+    fn batch_buy_own_token_should_panic() {
+        init().run_as(nft(), list_tokens).run_as(bob(), |contract| {
+            contract.attach_deposit(3000);
+            contract.batch_buy(vec![(nft(), 5.into()), (nft(), 6.into())]);
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Not enough gas attached, more than `U64(280000000000000)` is required"
+    )]
+    fn batch_buy_with_insufficient_gas_should_panic() {
+        init().run_as(nft(), list_tokens).run_as(alice(), |contract| {
+            contract.attach_deposit(3000);
+            contract.context.prepaid_gas = 100_000_000_000_000;
+            contract.batch_buy(vec![(nft(), 5.into()), (nft(), 6.into())]);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "At most 60 tokens are allowed in batch")]
+    fn batch_buy_too_many_tokens_should_panic() {
+        init().run_as(alice(), |contract| {
+            let purchases = (0..=MAX_BATCH_SIZE as u64).map(|token_id| (nft(), U64(token_id)));
+            contract.batch_buy(purchases.collect());
+        });
+    }
+}
@@ -0,0 +1,74 @@
+//! This module implements a misbehaving NFT contract to be used in simulation tests only.
+//! It allows anyone to list any token in a marketplace,
+//! and its `nft_transfer_payout` returns a payout twice as large as the sale balance.
+//! The account listing a token is reported as its owner by `nft_token`.
+//! This way, the marketplace can be tested against buggy or malicious NFT contracts.
+#![deny(warnings)]
+
+use mg_core::{Payout, TokenId};
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+    json_types::{ValidAccountId, U128, U64},
+    near_bindgen,
+    serde_json::{self, json},
+    setup_alloc, AccountId, Promise,
+};
+use std::{collections::HashMap, convert::TryInto};
+
+setup_alloc!();
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+pub struct MockNftContract {
+    owners: HashMap<u64, AccountId>,
+}
+
+#[near_bindgen]
+impl MockNftContract {
+    /// Lists `token_id` in `market_id` on behalf of `predecessor_account_id`.
+    /// No ownership checks are made, the attached deposit is forwarded to the market.
+    #[payable]
+    pub fn list_token(
+        &mut self,
+        token_id: TokenId,
+        market_id: ValidAccountId,
+        msg: String,
+    ) -> Promise {
+        self.owners.insert(token_id.0, env::predecessor_account_id());
+        mg_core::nep178::market::nft_on_approve(
+            token_id,
+            env::predecessor_account_id().try_into().unwrap(),
+            U64(0),
+            msg,
+            market_id.as_ref(),
+            env::attached_deposit(),
+            env::prepaid_gas() / 2,
+        )
+    }
+
+    /// Returns the `token_id` along with the account that listed it as its `owner_id`.
+    /// Or `null` if `token_id` has not been listed.
+    pub fn nft_token(&self, token_id: TokenId) -> Option<serde_json::Value> {
+        self.owners
+            .get(&token_id.0)
+            .map(|owner_id| json!({ "token_id": token_id, "owner_id": owner_id }))
+    }
+
+    /// Returns an inflated payout to `receiver_id`, *i.e.*, twice the given `balance`.
+    #[allow(unused_variables)]
+    pub fn nft_transfer_payout(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        approval_id: Option<U64>,
+        memo: Option<String>,
+        balance: Option<U128>,
+    ) -> Option<Payout> {
+        balance.map(|balance| {
+            let mut payout = HashMap::new();
+            payout.insert(receiver_id.to_string(), U128(balance.0 * 2));
+            payout
+        })
+    }
+}
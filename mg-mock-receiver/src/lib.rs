@@ -0,0 +1,37 @@
+//! This module implements a receiver of `nft_transfer_call` to be used in simulation tests only.
+//! Its behavior is driven by the `msg` given to `nft_on_transfer`:
+//!
+//! - `"return"` asks the NFT contract to return the token back to its previous owner.
+//! - `"keep"` keeps the token.
+//! - Any other `msg` makes `nft_on_transfer` fail.
+#![deny(warnings)]
+
+use mg_core::{nep171::NonFungibleTokenReceiver, TokenId};
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen, setup_alloc, AccountId,
+};
+
+setup_alloc!();
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+pub struct MockReceiverContract {}
+
+#[near_bindgen]
+impl NonFungibleTokenReceiver for MockReceiverContract {
+    #[allow(unused_variables)]
+    fn nft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_id: AccountId,
+        token_id: TokenId,
+        msg: String,
+    ) -> bool {
+        match msg.as_str() {
+            "return" => true,
+            "keep" => false,
+            _ => env::panic(format!("Unexpected msg `{}`", msg).as_bytes()),
+        }
+    }
+}
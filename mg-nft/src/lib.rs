@@ -23,24 +23,32 @@ use mg_core::{
     crypto_hash,
     fraction::Fraction,
     gate::{GateId, ValidGateId},
+    log_event,
     nep171::NonFungibleTokenCore,
     nep177::{NFTContractMetadata, NonFungibleTokenMetadata},
     nep178::NonFungibleTokenApprovalMgmt,
     nep181::NonFungibleTokenEnumeration,
-    Collectible, MarketApproveMsg, Metadata, NftApproveMsg, Payout, Token, TokenApproval, TokenId,
+    nep330::{ContractSourceMetadata, SourceMetadata},
+    supply, Collectible, MarketApproveMsg, Metadata, NftApproveMsg, Payout, Timestamp, Token,
+    TokenApproval, TokenId,
 };
 use near_env::{near_ext, near_log, PanicMessage};
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    collections::{LookupMap, UnorderedMap, UnorderedSet},
+    collections::{LookupMap, UnorderedMap, UnorderedSet, Vector},
     env, ext_contract,
     json_types::{ValidAccountId, U128, U64},
     log, near_bindgen,
     serde::{Deserialize, Serialize},
     serde_json, setup_alloc, AccountId, Balance, BorshStorageKey, CryptoHash, Gas, PanicOnDefault,
-    Promise, PromiseResult,
+    Promise, PromiseResult, StorageUsage,
+};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    convert::TryInto,
+    fmt::Display,
 };
-use std::{cmp::Ordering, collections::HashMap, convert::TryInto, fmt::Display};
 
 setup_alloc!();
 
@@ -53,8 +61,39 @@ pub struct NftContract {
     /// Represents a mapping from `GateId` into `Collectible`.
     collectibles: UnorderedMap<GateId, Collectible>,
     collectibles_by_creator: LookupMap<AccountId, UnorderedSet<GateId>>,
+    /// The `GateId` of every `Collectible` created, in creation order.
+    /// Ids are not removed when a collectible is deleted.
+    collectibles_by_time: Vector<GateId>,
     tokens: UnorderedMap<TokenId, Token>,
+    /// The `TokenId` given to the next claimed `Token`.
+    /// Ids are never reused, even after a `Token` is burned,
+    /// so `minted_tokens` of each `Collectible` are kept sorted.
+    next_token_id: u64,
     tokens_by_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    /// Distinct accounts currently owning at least one `Token`.
+    owners: UnorderedSet<AccountId>,
+    /// Number of `Token`s claimed by each account for a given `GateId`.
+    claims_by_account: LookupMap<(GateId, AccountId), u16>,
+    /// When each account last claimed a `Token` for a given `GateId`, in nanoseconds.
+    /// Only recorded for `Collectible`s with a `claim_cooldown_ns`.
+    last_claim_at: LookupMap<(GateId, AccountId), Timestamp>,
+    /// Accounts allowed to be the `creator_id` of new `Collectible`s.
+    /// When empty, any account is allowed.
+    creator_allowlist: UnorderedSet<AccountId>,
+    /// Accounts allowed to be approved for `Token`s, *e.g.*, marketplaces.
+    /// When empty, any account is allowed.
+    approved_operators: UnorderedSet<AccountId>,
+    /// Per-token `extra` metadata merged into the `extra` inherited from its `Collectible`.
+    extra_overrides: LookupMap<TokenId, String>,
+    /// Token-specific royalties given at `claim_token_to`,
+    /// used by `nft_payout` instead of the `Collectible` royalty.
+    royalty_overrides: LookupMap<TokenId, Fraction>,
+    /// Tokens locked by an approved market, *e.g.*, while in an active auction,
+    /// mapped to the market that locked them.
+    locked_tokens: LookupMap<TokenId, AccountId>,
+    /// Gates whose tokens can be swapped for a token of another gate with `swap_token`,
+    /// mapped to that target gate.
+    swap_targets: LookupMap<GateId, GateId>,
     /// Admin account is only account allowed to make certain calls.
     admin_id: AccountId,
     /// Metadata describing this NFT contract
@@ -68,6 +107,18 @@ pub struct NftContract {
     mintgate_fee: Fraction,
     /// Designated MintGate NEAR account id to receive `mintgate_fee` after a sale.
     mintgate_fee_account_id: AccountId,
+    /// Accounts among which `mintgate_fee` is split, with their share of it.
+    /// Any rounding remainder is paid to `mintgate_fee_account_id`.
+    mintgate_fee_split: Vec<(AccountId, Fraction)>,
+    /// Maximum length in bytes allowed for the `title` of a `Collectible`.
+    max_title_len: u32,
+    /// Maximum length in bytes allowed for the `description` of a `Collectible`.
+    max_description_len: u32,
+    /// Maximum number of tokens allowed in a single batch operation.
+    max_batch_size: u32,
+    /// Whether approved accounts must transfer tokens through `nft_transfer_payout`,
+    /// so royalties are always paid on sales.
+    enforce_royalties: bool,
 }
 
 /// To create a persistent collection on the blockchain, *e.g.*,
@@ -82,6 +133,16 @@ enum Keys {
     Tokens,
     TokensByOwner,
     TokensByOwnerValue { owner_id_hash: CryptoHash },
+    Owners,
+    ClaimsByAccount,
+    ExtraOverrides,
+    RoyaltyOverrides,
+    CreatorAllowlist,
+    LockedTokens,
+    ApprovedOperators,
+    SwapTargets,
+    LastClaimAt,
+    CollectiblesByTime,
 }
 
 /// The error variants thrown by *mg-nft*.
@@ -94,16 +155,28 @@ pub enum Panic {
     RoyaltyMinThanAllowed { royalty: Fraction, gate_id: String },
     #[panic_msg = "Royalty `{}` of `{}` is greater than max"]
     RoyaltyMaxThanAllowed { royalty: Fraction, gate_id: String },
+    #[panic_msg = "Royalty of gate ID `{}` is locked and cannot be changed"]
+    RoyaltyLocked { gate_id: GateId },
+    #[panic_msg = "Tokens of gate ID `{}` cannot be swapped for gate ID `{}`"]
+    SwapNotAllowed { gate_id: GateId, to_gate_id: GateId },
+    #[panic_msg = "The shares of the fee split must add up to 1"]
+    InvalidFeeSplit,
     #[panic_msg = "Royalty `{}` is too large for the given NFT fee `{}`"]
     RoyaltyTooLarge { royalty: Fraction, mintgate_fee: Fraction },
     #[panic_msg = "Gate ID `{}` already exists"]
     GateIdAlreadyExists { gate_id: GateId },
+    #[panic_msg = "Token ID `{:?}` already exists"]
+    TokenIdAlreadyExists { token_id: U64 },
+    #[panic_msg = "Token ID `{:?}` has already been used"]
+    TokenIdAlreadyUsed { token_id: U64 },
     #[panic_msg = "Gate ID `{}` must have a positive supply"]
     ZeroSupplyNotAllowed { gate_id: GateId },
     #[panic_msg = "Invalid argument for gate ID `{}`: {}"]
     InvalidArgument { gate_id: GateId, reason: String },
     #[panic_msg = "Operation is allowed only for admin"]
     AdminRestrictedOperation,
+    #[panic_msg = "The admin `{}` is not allowed to be the `mintgate_fee_account_id` as well"]
+    AdminIsFeeAccount { account_id: AccountId },
     #[panic_msg = "Gate ID `{}` was not found"]
     GateIdNotFound { gate_id: GateId },
     #[panic_msg = "Tokens for gate id `{}` have already been claimed"]
@@ -112,26 +185,68 @@ pub enum Panic {
     GateIdHasTokens { gate_id: GateId },
     #[panic_msg = "Unable to delete gate ID `{}`"]
     NotAuthorized { gate_id: GateId },
+    #[panic_msg = "Account `{}` is not in the creator allowlist"]
+    CreatorNotAllowed { creator_id: AccountId },
+    #[panic_msg = "Account `{}` is not in the operator allowlist"]
+    OperatorNotAllowed { account_id: AccountId },
+    #[panic_msg = "Only the creator of gate ID `{}` is allowed to perform this operation"]
+    CreatorRestrictedOperation { gate_id: GateId },
+    #[panic_msg = "Only the creator of gate ID `{}` or the admin are allowed to perform this operation"]
+    CreatorOrAdminRestrictedOperation { gate_id: GateId },
+    #[panic_msg = "Claiming tokens for gate ID `{}` is paused"]
+    GateNotClaimable { gate_id: GateId },
+    #[panic_msg = "Account `{}` has reached the claim limit for gate ID `{}`"]
+    ClaimLimitReached { account_id: AccountId, gate_id: GateId },
+    #[panic_msg = "Claiming tokens for gate ID `{}` is on cooldown for another `{:?}` nanoseconds"]
+    ClaimCooldownActive { gate_id: GateId, remaining_ns: U64 },
     #[panic_msg = "Token ID `{:?}` was not found"]
     TokenIdNotFound { token_id: U64 },
     #[panic_msg = "Token ID `{:?}` does not belong to account `{}`"]
     TokenIdNotOwnedBy { token_id: U64, owner_id: AccountId },
     #[panic_msg = "At most one approval is allowed per Token"]
     OneApprovalAllowed,
+    #[panic_msg = "Min price must be greater than zero for gate ID `{}`"]
+    ZeroMinPrice { gate_id: GateId },
+    #[panic_msg = "Min price `{:?}` is below the resale floor `{:?}`"]
+    BelowResaleFloor { min_price: U128, min_resale_price: U128 },
     #[panic_msg = "Sender `{}` is not authorized to make transfer"]
     SenderNotAuthToTransfer { sender_id: AccountId },
     #[panic_msg = "The token owner and the receiver should be different"]
     ReceiverIsOwner,
+    #[panic_msg = "Approval of token ID `{:?}` for `{}` has expired"]
+    ApprovalExpired { token_id: U64, account_id: AccountId },
     #[panic_msg = "The approval_id is different from enforce_approval_id"]
     EnforceApprovalFailed,
     #[panic_msg = "The msg argument must contain the minimum price"]
     MsgFormatNotRecognized,
-    #[panic_msg = "Could not find min_price in msg: {}"]
-    MsgFormatMinPriceMissing { reason: String },
+    #[panic_msg = "The msg argument is not valid JSON: {}"]
+    MsgFormatNotJson { reason: String },
+    #[panic_msg = "Invalid field `{}` in msg: {}"]
+    MsgFormatInvalidField { field: String, reason: String },
     #[panic_msg = "Could not revoke approval for `{}`"]
     RevokeApprovalFailed { account_id: AccountId },
-    #[panic_msg = "At most 10 tokens are allowed to approve in batch"]
-    ExceedTokensToBatchApprove,
+    #[panic_msg = "Token ID `{:?}` is not approved for `{}`"]
+    ApprovalNotFound { token_id: U64, account_id: AccountId },
+    #[panic_msg = "Royalties are enforced, `{}` must transfer through `nft_transfer_payout`"]
+    RoyaltiesEnforced { sender_id: AccountId },
+    #[panic_msg = "Token ID `{:?}` is locked by `{}`"]
+    TokenLocked { token_id: U64, account_id: AccountId },
+    #[panic_msg = "Token ID `{:?}` is not locked by `{}`"]
+    TokenNotLockedBy { token_id: U64, account_id: AccountId },
+    #[panic_msg = "At most {} tokens are allowed to approve in batch"]
+    ExceedTokensToBatchApprove { max_batch_size: u32 },
+    #[panic_msg = "At most {} tokens are allowed to revoke in batch"]
+    ExceedTokensToBatchRevoke { max_batch_size: u32 },
+    #[panic_msg = "`max_batch_size` is {} but at most {} tokens fit in the gas of a batch"]
+    MaxBatchSizeTooLarge { max_batch_size: u32, limit: u32 },
+    #[panic_msg = "At most {} gate IDs are allowed to query at once"]
+    ExceedGateIdsToQuery { max_gate_ids: usize },
+    #[panic_msg = "Not enough deposit to cover storage, `{:?}` is required"]
+    NotEnoughDepositToClaim { required: U128 },
+    #[panic_msg = "Not enough deposit to cover storage and mint price, `{:?}` is required"]
+    NotEnoughDepositToMint { required: U128 },
+    #[panic_msg = "Not enough gas attached, more than `{:?}` is required"]
+    InsufficientGas { required: U64 },
     #[panic_msg = "{} error(s) detected, see `panics` fields for a full list of errors"]
     Errors { panics: Panics },
 }
@@ -148,6 +263,153 @@ impl Display for Panics {
     }
 }
 
+/// Data logged in the `mg_batch_approve_result` event once `batch_approve` is resolved.
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct BatchApproveResultEvent {
+    /// The market account the tokens were approved for.
+    pub account_id: AccountId,
+    /// The tokens approved and listed in `account_id`.
+    pub succeeded: Vec<TokenId>,
+    /// The tokens that could not be approved.
+    pub failed: Vec<TokenId>,
+}
+
+/// Approvals forwarded to the `nft_on_approve` or `batch_on_approve` of a market.
+/// They are undone when the market call fails, *e.g.*, when the listing fee is short.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingApprovals {
+    /// The owner of the approved tokens.
+    pub owner_id: AccountId,
+    /// The market account the tokens are approved for.
+    pub account_id: AccountId,
+    /// Each approved token along with its `approval_id`,
+    /// and its previous `min_price` when an existing approval was changed.
+    pub tokens: Vec<(TokenId, U64, Option<U128>)>,
+    /// The deposit forwarded to the market.
+    pub deposit: U128,
+}
+
+/// Data logged in the `nft_burn` event of the `nep171` standard by `burn_token`.
+///
+/// <https://nomicon.io/Standards/NonFungibleToken/Event.html>
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftBurnEvent {
+    /// The owner of the tokens burned.
+    pub owner_id: AccountId,
+    /// The tokens burned.
+    pub token_ids: Vec<TokenId>,
+    /// The approved account that burned the tokens on behalf of `owner_id`, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorized_id: Option<AccountId>,
+}
+
+/// The state of a `Collectible` returned by `export_collectible`,
+/// so it can be recreated in another contract with `import_collectible`.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub struct CollectibleExport {
+    /// The exported `Collectible`.
+    pub collectible: Collectible,
+    /// The tokens minted out of `collectible` that have not been burned, in minting order.
+    pub tokens: Vec<TokenExport>,
+}
+
+/// The state of a `Token` exported along with its `Collectible`.
+/// Approvals are not exported, since listings do not carry over to another contract.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenExport {
+    pub token_id: TokenId,
+    pub owner_id: AccountId,
+    /// When the token was minted, in milliseconds.
+    pub created_at: Timestamp,
+    pub has_been_sold: bool,
+    /// Per-token `extra` metadata, *e.g.*, its serial number, if any.
+    pub extra: Option<String>,
+    /// Royalty given to the token at `claim_token_to`, if any.
+    pub royalty: Option<Fraction>,
+}
+
+/// Optional arguments of `init`, any field left out takes its default.
+#[derive(Serialize, Deserialize, Default)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Debug))]
+#[serde(crate = "near_sdk::serde", default)]
+pub struct InitOptions {
+    /// Splits `mintgate_fee` among several accounts, with their share of it.
+    pub mintgate_fee_split: Option<Vec<(ValidAccountId, Fraction)>>,
+    /// Maximum length in bytes allowed for the `title` of a `Collectible`.
+    pub max_title_len: Option<u32>,
+    /// Maximum length in bytes allowed for the `description` of a `Collectible`.
+    pub max_description_len: Option<u32>,
+    /// Maximum number of tokens given to `batch_approve` and `batch_revoke`.
+    pub max_batch_size: Option<u32>,
+    /// Whether `admin_id` must differ from `mintgate_fee_account_id`.
+    pub require_distinct_admin_fee: bool,
+}
+
+/// Optional arguments of `create_collectible`, any field left out is unset.
+#[derive(Serialize, Deserialize, Default)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Debug))]
+#[serde(crate = "near_sdk::serde", default)]
+pub struct CollectibleOptions {
+    /// Royalty paid on the first resale of each `Token`, instead of `royalty`.
+    pub first_sale_royalty: Option<Fraction>,
+    /// The `royalty` given in basis points, it takes precedence over `royalty`.
+    pub royalty_bps: Option<u16>,
+    /// A small base64 data URL to render galleries without fetching `media`.
+    pub thumbnail: Option<String>,
+    /// Whether the royalties are fixed for good.
+    pub royalty_locked: bool,
+    /// Title given to each `Token`, where `{index}` is replaced by its position.
+    pub title_template: Option<String>,
+    /// Until when (in milliseconds) tokens can be claimed without limit, for open editions.
+    pub open_until: Option<Timestamp>,
+}
+
+/// A `Collectible` along with whether an account can claim from it, as returned by `get_gate_view`.
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct GateView {
+    /// The viewed `Collectible`.
+    pub collectible: Collectible,
+    /// Whether the viewing account can claim a `Token` out of `collectible`.
+    pub eligibility: ClaimEligibility,
+}
+
+/// Indicates whether an account can currently claim a `Token` out of a `Collectible`.
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm"), derive(Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct ClaimEligibility {
+    /// Whether `claim_token` would succeed, *i.e.*, all of the conditions below hold.
+    pub can_claim: bool,
+    /// Whether claiming has not been paused by the creator.
+    pub claimable: bool,
+    /// Whether there are tokens left to claim.
+    pub has_supply: bool,
+    /// Whether the account has not reached `max_claims_per_account`, if any.
+    pub within_claim_limit: bool,
+    /// How many tokens the account can still claim, as in `remaining_claims_for`.
+    pub remaining_claims: u16,
+    /// The errors `claim_token` would fail with, empty when `can_claim`.
+    pub reasons: Vec<Panic>,
+}
+
+/// The `standard` field of the events logged by this contract.
+pub const EVENT_STANDARD: &str = "mg-nft";
+
+/// The `standard` field of the events defined by NEP-171, *e.g.*, `nft_burn`.
+pub const NEP171_EVENT_STANDARD: &str = "nep171";
+
 /// Methods for the NFT contract.
 /// Methods belonging to a NEP Standard are implemented in their own interfaces.
 #[near_log(skip_args, only_pub)]
@@ -161,6 +423,20 @@ impl NftContract {
     /// - `metadata` represents the general information of the contract.
     /// - `min_royalty` and `max_royalty` indicates what must be the max and min royalty respectively when creating a collectible.
     /// - `mintgate_fee` is the percetange to be paid to `mintgate_fee_account_id` for each sale.
+    ///
+    /// The following settings are optionally given in `options`:
+    ///
+    /// - `mintgate_fee_split` splits `mintgate_fee` among several accounts.
+    ///   The shares must add up to `1`. Defaults to `mintgate_fee_account_id` receiving the whole fee.
+    /// - `max_title_len` and `max_description_len` limit the length of collectible `title` and `description`.
+    ///   They default to `DEFAULT_MAX_TITLE_LEN` and `DEFAULT_MAX_DESCRIPTION_LEN` respectively.
+    /// - `max_batch_size` limits the number of tokens given to `batch_approve` and `batch_revoke`.
+    ///   It defaults to `DEFAULT_MAX_BATCH_SIZE` and cannot exceed `MAX_BATCH_SIZE`.
+    /// - `require_distinct_admin_fee` rejects an `admin_id` equal to `mintgate_fee_account_id`,
+    ///   for deployments whose policy requires separating both roles.
+    ///   It defaults to `false`, *i.e.*, the same account is allowed for both.
+    ///
+    /// Note that accounts are only checked to be well-formed, not to exist.
     #[init]
     pub fn init(
         admin_id: ValidAccountId,
@@ -169,7 +445,18 @@ impl NftContract {
         max_royalty: Fraction,
         mintgate_fee: Fraction,
         mintgate_fee_account_id: ValidAccountId,
+        options: Option<InitOptions>,
     ) -> Self {
+        let InitOptions {
+            mintgate_fee_split,
+            max_title_len,
+            max_description_len,
+            max_batch_size,
+            require_distinct_admin_fee,
+        } = options.unwrap_or_default();
+        if require_distinct_admin_fee && admin_id == mintgate_fee_account_id {
+            Panic::AdminIsFeeAccount { account_id: admin_id.into() }.panic();
+        }
         min_royalty.check();
         max_royalty.check();
         mintgate_fee.check();
@@ -178,17 +465,46 @@ impl NftContract {
             Panic::MaxRoyaltyLessThanMinRoyalty { min_royalty, max_royalty }.panic();
         }
 
+        let max_batch_size = max_batch_size.unwrap_or(DEFAULT_MAX_BATCH_SIZE);
+        if max_batch_size > MAX_BATCH_SIZE {
+            Panic::MaxBatchSizeTooLarge { max_batch_size, limit: MAX_BATCH_SIZE }.panic();
+        }
+
+        let mintgate_fee_split = match mintgate_fee_split {
+            None => vec![(mintgate_fee_account_id.to_string(), Fraction { num: 1, den: 1 })],
+            Some(split) => {
+                check_fee_split(&split);
+                split.into_iter().map(|(account_id, share)| (account_id.into(), share)).collect()
+            }
+        };
+
         Self {
             collectibles: UnorderedMap::new(Keys::Collectibles),
             collectibles_by_creator: LookupMap::new(Keys::CollectiblesByCreator),
+            collectibles_by_time: Vector::new(Keys::CollectiblesByTime),
             tokens: UnorderedMap::new(Keys::Tokens),
+            next_token_id: 0,
             tokens_by_owner: LookupMap::new(Keys::TokensByOwner),
+            owners: UnorderedSet::new(Keys::Owners),
+            claims_by_account: LookupMap::new(Keys::ClaimsByAccount),
+            creator_allowlist: UnorderedSet::new(Keys::CreatorAllowlist),
+            approved_operators: UnorderedSet::new(Keys::ApprovedOperators),
+            swap_targets: LookupMap::new(Keys::SwapTargets),
+            last_claim_at: LookupMap::new(Keys::LastClaimAt),
+            extra_overrides: LookupMap::new(Keys::ExtraOverrides),
+            royalty_overrides: LookupMap::new(Keys::RoyaltyOverrides),
+            locked_tokens: LookupMap::new(Keys::LockedTokens),
             admin_id: admin_id.as_ref().to_string(),
             metadata,
             min_royalty,
             max_royalty,
             mintgate_fee,
             mintgate_fee_account_id: mintgate_fee_account_id.to_string(),
+            mintgate_fee_split,
+            max_title_len: max_title_len.unwrap_or(DEFAULT_MAX_TITLE_LEN),
+            max_description_len: max_description_len.unwrap_or(DEFAULT_MAX_DESCRIPTION_LEN),
+            max_batch_size,
+            enforce_royalties: false,
         }
     }
 
@@ -197,11 +513,34 @@ impl NftContract {
     /// The `royalty` indicates the royalty (as percentage) paid to the creator (`predecessor_account_id`).
     /// This royalty is paid when any `Token` is being resold in any marketplace.
     ///
+    /// The remaining optional arguments are given in `options`.
+    /// The `first_sale_royalty` replaces `royalty` for the first resale of each `Token`.
+    /// The `royalty_bps` allows to give `royalty` in basis points instead,
+    /// in which case it takes precedence over `royalty`.
+    ///
     /// The sum of `royalty` and `mintgate_fee` should be less than `1`.
     /// Panics otherwise.
     /// This is to be able to make payouts all participants.
     ///
+    /// The `thumbnail` is a small base64 data URL, *e.g.*, `data:image/png;base64,...`,
+    /// of at most `MAX_THUMBNAIL_LEN` bytes, to render galleries without fetching `media`.
+    ///
+    /// The `title_template`, *e.g.*, `"Parcel #{index}"`, gives each `Token` its own title,
+    /// where `{index}` is replaced by the position of the token in `minted_tokens`.
+    /// When not given, all tokens share `title`.
+    ///
+    /// When `royalty_locked` is `true`, the royalties cannot be changed afterwards,
+    /// *e.g.*, by giving a `royalty_override` in `claim_token_to`.
+    /// This guarantees collectors how any resale is paid out.
+    ///
+    /// When `open_until` (in milliseconds) is given, the collectible is an open edition,
+    /// *i.e.*, tokens can be claimed without limit until then.
+    /// Open editions must be created with a zero `supply`.
+    ///
+    /// When the creator allowlist is set, `creator_id` must be in it.
+    ///
     /// See <https://github.com/epam/mintgate/issues/3>.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_collectible(
         &mut self,
         creator_id: ValidAccountId,
@@ -214,38 +553,40 @@ impl NftContract {
         media_hash: Option<String>,
         reference: Option<String>,
         reference_hash: Option<String>,
+        options: Option<CollectibleOptions>,
     ) {
         let gate_id = gate_id.to_string();
-
-        royalty.check();
-
-        if royalty.cmp(&self.min_royalty) == Ordering::Less {
-            Panic::RoyaltyMinThanAllowed { royalty, gate_id }.panic();
-        }
-        if royalty.cmp(&self.max_royalty) == Ordering::Greater {
-            Panic::RoyaltyMaxThanAllowed { royalty, gate_id }.panic();
-        }
-        let bn = 1_000_000_000_000_000_000_000;
-        if self.mintgate_fee.mult(bn) + royalty.mult(bn) >= bn {
-            Panic::RoyaltyTooLarge { royalty, mintgate_fee: self.mintgate_fee }.panic();
+        let CollectibleOptions {
+            first_sale_royalty,
+            royalty_bps,
+            thumbnail,
+            royalty_locked,
+            title_template,
+            open_until,
+        } = options.unwrap_or_default();
+
+        let royalty = royalty_bps.map_or(royalty, Fraction::from_basis_points);
+        self.check_royalty(royalty, &gate_id);
+        if let Some(first_sale_royalty) = first_sale_royalty {
+            self.check_royalty(first_sale_royalty, &gate_id);
         }
         if self.collectibles.get(&gate_id).is_some() {
             Panic::GateIdAlreadyExists { gate_id }.panic();
         }
-        if supply == 0 {
-            Panic::ZeroSupplyNotAllowed { gate_id }.panic();
-        }
-        if title.len() > 140 {
-            Panic::InvalidArgument { gate_id, reason: "Title exceeds 140 chars".to_string() }
-                .panic();
-        }
-        if description.len() > 1024 {
-            Panic::InvalidArgument {
+        match open_until {
+            None if supply == 0 => Panic::ZeroSupplyNotAllowed { gate_id }.panic(),
+            Some(_) if supply != 0 => Panic::InvalidArgument {
                 gate_id,
-                reason: "`description` exceeds 1024 chars".to_string(),
+                reason: "`open_until` is only allowed with a zero `supply`".to_string(),
             }
-            .panic();
+            .panic(),
+            _ => {}
+        }
+        self.check_title(&title, &gate_id);
+        if let Some(title_template) = &title_template {
+            self.check_title(title_template, &gate_id);
         }
+        self.check_description(&description, &gate_id);
 
         macro_rules! check {
             ($arg:ident) => {{
@@ -266,26 +607,46 @@ impl NftContract {
         check!(media_hash);
         check!(reference);
         check!(reference_hash);
+        if let Some(thumbnail) = &thumbnail {
+            check_thumbnail(thumbnail, &gate_id);
+        }
 
         if env::predecessor_account_id() != self.admin_id {
             Panic::AdminRestrictedOperation.panic();
         }
 
         let creator_id = AccountId::from(creator_id);
+        if !self.is_open_minting() && !self.creator_allowlist.contains(&creator_id) {
+            Panic::CreatorNotAllowed { creator_id }.panic();
+        }
         let now = env::block_timestamp() / 1_000_000;
 
         let collectible = Collectible {
             gate_id,
             creator_id,
             current_supply: supply,
+            open_until,
             minted_tokens: Vec::new(),
             royalty,
+            first_sale_royalty,
+            royalty_locked,
+            min_resale_price: None,
+            allow_zero_price: false,
+            claimable: true,
+            max_claims_per_account: None,
+            claim_cooldown_ns: None,
+            mint_price: None,
+            thumbnail,
+            title_template,
+            serial_numbers: false,
+            minted_count: 0,
+            created_at: now,
             metadata: Metadata {
                 title: Some(title),
                 description: Some(description),
                 media,
                 media_hash,
-                copies: Some(supply),
+                copies: if open_until.is_some() { None } else { Some(supply) },
                 issued_at: Some(now),
                 expires_at: None,
                 starts_at: Some(now),
@@ -295,7 +656,310 @@ impl NftContract {
                 reference_hash,
             },
         };
-        self.collectibles.insert(&collectible.gate_id, &collectible);
+        self.insert_collectible(&collectible);
+        self.collectibles_by_time.push(&collectible.gate_id);
+    }
+
+    /// Creates a new `Collectible` as in `create_collectible`
+    /// and claims its first `Token` for `creator_id` in the same call, *e.g.*, for 1-of-1 drops.
+    /// The remaining optional arguments of `create_collectible` are left unset.
+    ///
+    /// The attached deposit must cover the storage used by the new `Token`.
+    /// Any excess deposit is refunded to the `predecessor_account_id`.
+    /// Only the contract `admin_id` is allowed to call this method.
+    ///
+    /// Returns the `TokenId` of the claimed token.
+    #[payable]
+    pub fn create_and_claim(
+        &mut self,
+        creator_id: ValidAccountId,
+        gate_id: ValidGateId,
+        title: String,
+        description: String,
+        supply: u16,
+        royalty: Fraction,
+    ) -> TokenId {
+        self.create_collectible(
+            creator_id.clone(),
+            gate_id.clone(),
+            title,
+            description,
+            supply,
+            royalty,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        let initial_storage_usage = env::storage_usage();
+        let token_id = self.mint_token(gate_id.to_string(), creator_id.into(), None);
+        self.charge_storage(initial_storage_usage, 0);
+        token_id
+    }
+
+    /// Updates the `title` and/or `description` of the `Collectible` given by `gate_id`.
+    /// Arguments given as `None` are left unchanged.
+    /// Only the `creator_id` of the collectible is allowed to update it.
+    pub fn update_collectible(
+        &mut self,
+        gate_id: ValidGateId,
+        title: Option<String>,
+        description: Option<String>,
+    ) {
+        let gate_id: GateId = From::from(gate_id);
+        match self.collectibles.get(&gate_id) {
+            None => Panic::GateIdNotFound { gate_id }.panic(),
+            Some(mut collectible) => {
+                if env::predecessor_account_id() != collectible.creator_id {
+                    Panic::CreatorRestrictedOperation { gate_id }.panic();
+                }
+
+                if let Some(title) = title {
+                    self.check_title(&title, &gate_id);
+                    collectible.metadata.title = Some(title);
+                }
+                if let Some(description) = description {
+                    self.check_description(&description, &gate_id);
+                    collectible.metadata.description = Some(description);
+                }
+                collectible.metadata.updated_at = Some(env::block_timestamp() / 1_000_000);
+                self.collectibles.insert(&gate_id, &collectible);
+            }
+        }
+    }
+
+    /// Sets both `mintgate_fee` and `mintgate_fee_account_id` at once.
+    /// Updating both together avoids routing fees to a stale account at a new rate.
+    /// The fee split is kept, with the share of the previous `mintgate_fee_account_id` moved to `account_id`.
+    /// Use `set_fee_split` to change the shares.
+    /// The sum of `max_royalty` and the new `fee` must be less than `1`,
+    /// so the payout of any existing `Collectible` can still be computed.
+    /// Only the contract `admin_id` is allowed to call this method.
+    pub fn set_fee_config(&mut self, fee: Fraction, account_id: ValidAccountId) {
+        if env::predecessor_account_id() != self.admin_id {
+            Panic::AdminRestrictedOperation.panic();
+        }
+
+        fee.check();
+        check_royalty_and_fee(self.max_royalty, fee);
+
+        let previous_account_id =
+            std::mem::replace(&mut self.mintgate_fee_account_id, account_id.into());
+        for (split_account_id, _) in self.mintgate_fee_split.iter_mut() {
+            if *split_account_id == previous_account_id {
+                *split_account_id = self.mintgate_fee_account_id.clone();
+            }
+        }
+        self.mintgate_fee = fee;
+    }
+
+    /// Sets the accounts among which `mintgate_fee` is split, with their share of it.
+    /// The shares must add up to `1`, as in `init`.
+    /// Only the contract `admin_id` is allowed to call this method.
+    pub fn set_fee_split(&mut self, split: Vec<(ValidAccountId, Fraction)>) {
+        if env::predecessor_account_id() != self.admin_id {
+            Panic::AdminRestrictedOperation.panic();
+        }
+
+        check_fee_split(&split);
+        self.mintgate_fee_split =
+            split.into_iter().map(|(account_id, share)| (account_id.into(), share)).collect();
+    }
+
+    /// Sets whether royalties are enforced.
+    /// When enforced, approved accounts, *e.g.*, marketplaces,
+    /// can only transfer tokens through `nft_transfer_payout` with a `balance` to pay out.
+    /// Transfers made by the token owner are not affected.
+    /// Royalties are not enforced by default.
+    /// Only the contract `admin_id` is allowed to call this method.
+    pub fn set_enforce_royalties(&mut self, enforce_royalties: bool) {
+        if env::predecessor_account_id() != self.admin_id {
+            Panic::AdminRestrictedOperation.panic();
+        }
+
+        self.enforce_royalties = enforce_royalties;
+    }
+
+    /// Returns whether royalties are enforced, see `set_enforce_royalties`.
+    pub fn is_enforcing_royalties(&self) -> bool {
+        self.enforce_royalties
+    }
+
+    /// Replaces the creator allowlist with `creator_ids`.
+    /// Only accounts in the allowlist can be the `creator_id` of new `Collectible`s.
+    /// An empty `creator_ids` removes the restriction.
+    /// Only the contract `admin_id` is allowed to call this method.
+    pub fn set_creator_allowlist(&mut self, creator_ids: Vec<ValidAccountId>) {
+        if env::predecessor_account_id() != self.admin_id {
+            Panic::AdminRestrictedOperation.panic();
+        }
+
+        self.creator_allowlist.clear();
+        for creator_id in creator_ids {
+            self.creator_allowlist.insert(creator_id.as_ref());
+        }
+    }
+
+    /// Returns whether any account can be the `creator_id` of new `Collectible`s,
+    /// *i.e.*, whether the creator allowlist is empty.
+    pub fn is_open_minting(&self) -> bool {
+        self.creator_allowlist.is_empty()
+    }
+
+    /// Adds `account_id` to the operator allowlist.
+    /// Only accounts in the allowlist can be approved for `Token`s, *e.g.*, marketplaces.
+    /// An empty allowlist allows any account.
+    /// Only the contract `admin_id` is allowed to call this method.
+    pub fn add_approved_operator(&mut self, account_id: ValidAccountId) {
+        if env::predecessor_account_id() != self.admin_id {
+            Panic::AdminRestrictedOperation.panic();
+        }
+
+        self.approved_operators.insert(account_id.as_ref());
+    }
+
+    /// Removes `account_id` from the operator allowlist.
+    /// Existing approvals for `account_id` are kept.
+    /// Returns whether `account_id` was in the allowlist.
+    /// Only the contract `admin_id` is allowed to call this method.
+    pub fn remove_approved_operator(&mut self, account_id: ValidAccountId) -> bool {
+        if env::predecessor_account_id() != self.admin_id {
+            Panic::AdminRestrictedOperation.panic();
+        }
+
+        self.approved_operators.remove(account_id.as_ref())
+    }
+
+    /// Returns the operator allowlist.
+    /// When empty, any account can be approved for `Token`s.
+    pub fn get_approved_operators(&self) -> Vec<AccountId> {
+        self.approved_operators.to_vec()
+    }
+
+    /// Rebuilds the index of `Collectible`s created by `creator_id`
+    /// by scanning all `collectibles` for the ones whose `creator_id` matches.
+    /// This is a repair tool in case the index got out of sync with `collectibles`.
+    /// Its cost grows with the total number of `Collectible`s in the contract.
+    /// Only the contract `admin_id` is allowed to call this method.
+    pub fn admin_rebuild_creator_index(&mut self, creator_id: ValidAccountId) {
+        if env::predecessor_account_id() != self.admin_id {
+            Panic::AdminRestrictedOperation.panic();
+        }
+
+        let creator_id = AccountId::from(creator_id);
+        let mut gids = self.collectibles_by_creator.get(&creator_id).unwrap_or_else(|| {
+            UnorderedSet::new(Keys::CollectiblesByCreatorValue {
+                creator_id_hash: crypto_hash(&creator_id),
+            })
+        });
+        gids.clear();
+        for collectible in self.collectibles.values() {
+            if collectible.creator_id == creator_id {
+                gids.insert(&collectible.gate_id);
+            }
+        }
+
+        self.collectibles_by_creator.insert(&creator_id, &gids);
+    }
+
+    /// Returns the state of the `Collectible` given by `gate_id` along with its tokens,
+    /// to migrate it into another contract with `import_collectible`.
+    /// Only the contract `admin_id` is allowed to call this method.
+    /// Since view calls have no predecessor, it is exposed as a change method.
+    pub fn export_collectible(&mut self, gate_id: ValidGateId) -> CollectibleExport {
+        if env::predecessor_account_id() != self.admin_id {
+            Panic::AdminRestrictedOperation.panic();
+        }
+
+        let collectible = self.get_collectible_or_panic(gate_id.as_ref());
+        let tokens = collectible
+            .minted_tokens
+            .iter()
+            .filter_map(|token_id| self.tokens.get(token_id))
+            .map(|token| TokenExport {
+                token_id: token.token_id,
+                owner_id: token.owner_id,
+                created_at: token.created_at,
+                has_been_sold: token.has_been_sold,
+                extra: self.extra_overrides.get(&token.token_id),
+                royalty: self.royalty_overrides.get(&token.token_id),
+            })
+            .collect();
+        CollectibleExport { collectible, tokens }
+    }
+
+    /// Recreates a `Collectible` exported by `export_collectible` from another contract,
+    /// along with its tokens, keeping their `token_id`s and owners.
+    /// Tokens are imported with no approvals,
+    /// and the number of tokens claimed by each account is not carried over.
+    /// Panics if either the `gate_id` or any `token_id` already exists in this contract,
+    /// or when a `token_id` appears more than once in `export`.
+    /// It also panics if a `token_id` has already been used by this contract, *e.g.*, a burnt token,
+    /// or if a token has a `royalty` while the collectible royalty is locked.
+    /// Every royalty in `export` is checked against the bounds and fee of this contract,
+    /// as in `create_collectible`, since they might differ from those of the exporting contract.
+    /// Only the contract `admin_id` is allowed to call this method.
+    pub fn import_collectible(&mut self, export: CollectibleExport) {
+        if env::predecessor_account_id() != self.admin_id {
+            Panic::AdminRestrictedOperation.panic();
+        }
+
+        let CollectibleExport { mut collectible, tokens } = export;
+        if self.collectibles.get(&collectible.gate_id).is_some() {
+            Panic::GateIdAlreadyExists { gate_id: collectible.gate_id }.panic();
+        }
+        self.check_royalty(collectible.royalty, &collectible.gate_id);
+        if let Some(first_sale_royalty) = collectible.first_sale_royalty {
+            self.check_royalty(first_sale_royalty, &collectible.gate_id);
+        }
+        let mut token_ids = HashSet::new();
+        for token in &tokens {
+            if self.tokens.get(&token.token_id).is_some() || !token_ids.insert(token.token_id.0) {
+                Panic::TokenIdAlreadyExists { token_id: token.token_id }.panic();
+            }
+            if token.token_id.0 < self.next_token_id {
+                Panic::TokenIdAlreadyUsed { token_id: token.token_id }.panic();
+            }
+            if let Some(royalty) = token.royalty {
+                if collectible.royalty_locked {
+                    Panic::RoyaltyLocked { gate_id: collectible.gate_id }.panic();
+                }
+                self.check_royalty(royalty, &collectible.gate_id);
+            }
+        }
+
+        let now = env::block_timestamp() / 1_000_000;
+        collectible.minted_tokens = tokens.iter().map(|token| token.token_id).collect();
+        collectible.minted_tokens.sort_by_key(|token_id| token_id.0);
+        for token in tokens {
+            self.insert_token(&Token {
+                token_id: token.token_id,
+                gate_id: collectible.gate_id.clone(),
+                owner_id: token.owner_id,
+                created_at: token.created_at,
+                modified_at: now,
+                approvals: HashMap::new(),
+                approval_counter: U64::from(0),
+                has_been_sold: token.has_been_sold,
+                metadata: Metadata::default(),
+            });
+            if let Some(extra) = token.extra {
+                self.extra_overrides.insert(&token.token_id, &extra);
+            }
+            if let Some(royalty) = token.royalty {
+                self.royalty_overrides.insert(&token.token_id, &royalty);
+            }
+            self.next_token_id = self.next_token_id.max(token.token_id.0 + 1);
+        }
+        self.insert_collectible(&collectible);
+        self.collectibles_by_time.push(&collectible.gate_id);
+    }
+
+    /// Inserts the given `Collectible` into `collectibles` and `collectibles_by_creator`.
+    fn insert_collectible(&mut self, collectible: &Collectible) {
+        self.collectibles.insert(&collectible.gate_id, collectible);
 
         let mut gids =
             self.collectibles_by_creator.get(&collectible.creator_id).unwrap_or_else(|| {
@@ -308,6 +972,48 @@ impl NftContract {
         self.collectibles_by_creator.insert(&collectible.creator_id, &gids);
     }
 
+    /// Checks the given `title` does not exceed `max_title_len`
+    /// and contains no control characters.
+    fn check_title(&self, title: &str, gate_id: &GateId) {
+        if title.len() > self.max_title_len as usize {
+            Panic::InvalidArgument {
+                gate_id: gate_id.clone(),
+                reason: format!("Title exceeds {} chars", self.max_title_len),
+            }
+            .panic();
+        }
+        check_control_chars("title", title, false, gate_id);
+    }
+
+    /// Checks the given `description` does not exceed `max_description_len`
+    /// and contains no control characters other than newlines.
+    fn check_description(&self, description: &str, gate_id: &GateId) {
+        if description.len() > self.max_description_len as usize {
+            Panic::InvalidArgument {
+                gate_id: gate_id.clone(),
+                reason: format!("`description` exceeds {} chars", self.max_description_len),
+            }
+            .panic();
+        }
+        check_control_chars("description", description, true, gate_id);
+    }
+
+    /// Checks the given `royalty` is a valid `Fraction` within `min_royalty` and `max_royalty`.
+    /// Moreover, the sum of `royalty` and `mintgate_fee` should be less than `1`.
+    fn check_royalty(&self, royalty: Fraction, gate_id: &GateId) {
+        let gate_id = gate_id.clone();
+
+        royalty.check();
+
+        if royalty.cmp(&self.min_royalty) == Ordering::Less {
+            Panic::RoyaltyMinThanAllowed { royalty, gate_id }.panic();
+        }
+        if royalty.cmp(&self.max_royalty) == Ordering::Greater {
+            Panic::RoyaltyMaxThanAllowed { royalty, gate_id }.panic();
+        }
+        check_royalty_and_fee(royalty, self.mintgate_fee);
+    }
+
     /// Returns the `Collectible` with the given `gate_id`.
     /// Panics otherwise.
     ///
@@ -324,6 +1030,91 @@ impl NftContract {
         }
     }
 
+    /// Returns the `Collectible`s with the given `gate_ids`, *e.g.*, to render a gallery in one call.
+    /// There is one entry per gate ID in the same order, `None` when the collectible was not found.
+    /// At most `MAX_GATE_IDS_PER_QUERY` gate IDs are allowed to bound the cost of this method.
+    pub fn get_collectibles_by_gate_ids(
+        &self,
+        gate_ids: Vec<ValidGateId>,
+    ) -> Vec<Option<Collectible>> {
+        if gate_ids.len() > MAX_GATE_IDS_PER_QUERY {
+            Panic::ExceedGateIdsToQuery { max_gate_ids: MAX_GATE_IDS_PER_QUERY }.panic();
+        }
+        gate_ids.into_iter().map(|gate_id| self.collectibles.get(gate_id.as_ref())).collect()
+    }
+
+    /// Returns whether all tokens of the `Collectible` given by `gate_id` have been claimed,
+    /// or for an open edition, whether it is past its `open_until`.
+    /// Panics if `gate_id` is not found.
+    pub fn is_gate_sold_out(&self, gate_id: ValidGateId) -> bool {
+        match self.collectibles.get(gate_id.as_ref()) {
+            None => Panic::GateIdNotFound { gate_id: gate_id.to_string() }.panic(),
+            Some(collectible) => available_supply(&collectible) == 0,
+        }
+    }
+
+    /// Returns how many tokens `account_id` can still claim for the `Collectible` given by `gate_id`.
+    /// That is, the remaining supply bounded by the claims left under `max_claims_per_account`, if any.
+    /// Panics if `gate_id` is not found.
+    pub fn remaining_claims_for(&self, gate_id: ValidGateId, account_id: ValidAccountId) -> u16 {
+        let collectible = self.get_collectible_or_panic(gate_id.as_ref());
+        self.remaining_claims(&collectible, account_id.as_ref())
+    }
+
+    /// Returns the `Collectible` given by `gate_id` along with whether `account_id` can claim from it,
+    /// so drop pages need a single view call.
+    /// Panics if `gate_id` is not found.
+    pub fn get_gate_view(&self, gate_id: ValidGateId, account_id: ValidAccountId) -> GateView {
+        let collectible = self.get_collectible_or_panic(gate_id.as_ref());
+        let account_id = account_id.as_ref();
+        let within_claim_limit = match collectible.max_claims_per_account {
+            None => true,
+            Some(max_claims) => self.get_claims(&collectible.gate_id, account_id) < max_claims,
+        };
+        let reasons = self.claim_errors(&collectible, account_id);
+        let eligibility = ClaimEligibility {
+            can_claim: reasons.is_empty(),
+            claimable: collectible.claimable,
+            has_supply: available_supply(&collectible) > 0,
+            within_claim_limit,
+            remaining_claims: self.remaining_claims(&collectible, account_id),
+            reasons,
+        };
+        GateView { collectible, eligibility }
+    }
+
+    /// Returns whether `account_id` has ever claimed a token from the `Collectible` given by `gate_id`.
+    /// Returns `false` when the collectible was not found.
+    pub fn has_claimed(&self, gate_id: ValidGateId, account_id: ValidAccountId) -> bool {
+        self.get_claims(gate_id.as_ref(), account_id.as_ref()) > 0
+    }
+
+    /// Returns the `thumbnail` of the `Collectible` given by `gate_id`, if any.
+    /// Returns `None` when the collectible was not found.
+    pub fn get_thumbnail(&self, gate_id: ValidGateId) -> Option<String> {
+        self.collectibles.get(gate_id.as_ref()).and_then(|collectible| collectible.thumbnail)
+    }
+
+    /// Returns when the `Collectible` given by `gate_id` was created, in milliseconds.
+    /// Returns `None` when the collectible was not found.
+    pub fn get_collectible_created_at(&self, gate_id: ValidGateId) -> Option<Timestamp> {
+        self.collectibles.get(gate_id.as_ref()).map(|collectible| collectible.created_at)
+    }
+
+    /// Returns the fraction of a resale kept by the token owner
+    /// for the `Collectible` given by `gate_id`,
+    /// *i.e.*, `1 - royalty - mintgate_fee`, reduced to lowest terms.
+    /// The first sale of a token might use `first_sale_royalty` instead, see `nft_payout`.
+    /// Returns `0` if `royalty` and `mintgate_fee` add up to more than `1`.
+    /// Panics if `gate_id` is not found.
+    pub fn owner_share(&self, gate_id: ValidGateId) -> Fraction {
+        let collectible = self.get_collectible_or_panic(gate_id.as_ref());
+        Fraction { num: 1, den: 1 }
+            .checked_sub(&collectible.royalty)
+            .and_then(|share| share.checked_sub(&self.mintgate_fee))
+            .unwrap_or(Fraction { num: 0, den: 1 })
+    }
+
     /// Returns all `Collectible`s created by `creator_id`.
     ///
     /// See <https://github.com/epam/mintgate/issues/15>.
@@ -333,7 +1124,7 @@ impl NftContract {
             Some(list) => list
                 .iter()
                 .map(|gate_id| {
-                    let collectible = self.collectibles.get(&gate_id).expect("Gate Id not found");
+                    let collectible = self.get_collectible_or_panic(&gate_id);
                     assert!(collectible.gate_id == gate_id);
                     assert!(&collectible.creator_id == creator_id.as_ref());
                     collectible
@@ -342,24 +1133,109 @@ impl NftContract {
         }
     }
 
-    /// Deletes the given `Collectible` by `gate_id`.
-    /// The collectible can only be deleted if there are no minted tokens.
-    /// Moreover, only the `creator_id` of the collectible or
-    /// the contract `admin_id` are allowed to delete the collectible.
-    pub fn delete_collectible(&mut self, gate_id: ValidGateId) {
-        let gate_id: GateId = From::from(gate_id);
-        match self.collectibles.get(&gate_id) {
-            None => Panic::GateIdNotFound { gate_id }.panic(),
-            Some(collectible) => {
-                assert!(collectible.gate_id == gate_id);
-
-                if !collectible.minted_tokens.is_empty() {
-                    Panic::GateIdHasTokens { gate_id }.panic();
-                }
+    /// Returns all or paginated `Collectible`s created by `creator_id`,
+    /// each one along with its minted tokens count and its remaining supply.
+    /// The remaining supply of an open edition is `u16::MAX` until its `open_until` has passed,
+    /// as in `remaining_claims_for`.
+    /// Pagination is given by:
+    ///
+    /// - `from_index` the index to start fetching collectibles.
+    /// - `limit` indicates how many collectibles will be at most returned.
+    pub fn get_creator_collectibles_with_stats(
+        &self,
+        creator_id: ValidAccountId,
+        from_index: Option<U64>,
+        limit: Option<u32>,
+    ) -> Vec<(Collectible, U64, U64)> {
+        match self.collectibles_by_creator.get(creator_id.as_ref()) {
+            None => Vec::new(),
+            Some(list) => list
+                .iter()
+                .skip(from_index.map_or(0, |s| s.0) as usize)
+                .take(limit.unwrap_or(u32::MAX) as usize)
+                .map(|gate_id| {
+                    let collectible = self.get_collectible_or_panic(&gate_id);
+                    assert!(collectible.gate_id == gate_id);
+                    assert!(&collectible.creator_id == creator_id.as_ref());
+                    let minted_count = U64(collectible.minted_tokens.len() as u64);
+                    let remaining_supply = U64(available_supply(&collectible) as u64);
+                    (collectible, minted_count, remaining_supply)
+                })
+                .collect(),
+        }
+    }
 
-                let pred_id = env::predecessor_account_id();
+    /// Returns the `Collectible`s whose `royalty` is within `min` and `max`, both inclusive.
+    ///
+    /// There is no index by royalty, so this method scans the collectibles.
+    /// To bound its cost, pagination is applied to the scanned collectibles, not to the result:
+    ///
+    /// - `from_index` the index of the first collectible to scan.
+    /// - `limit` indicates how many collectibles will be at most scanned.
+    ///
+    /// Hence a page might contain less than `limit` collectibles, even if more pages follow.
+    pub fn get_collectibles_by_royalty(
+        &self,
+        min: Fraction,
+        max: Fraction,
+        from_index: Option<U64>,
+        limit: Option<u32>,
+    ) -> Vec<Collectible> {
+        let collectibles = self.collectibles.values_as_vector();
+        let start = from_index.map_or(0, |s| s.0).min(collectibles.len());
+        let end = start.saturating_add(limit.unwrap_or(u32::MAX) as u64).min(collectibles.len());
+        (start..end)
+            .filter_map(|index| collectibles.get(index))
+            .filter(|collectible| {
+                collectible.royalty.cmp(&min) != Ordering::Less
+                    && collectible.royalty.cmp(&max) != Ordering::Greater
+            })
+            .collect()
+    }
+
+    /// Returns the most recently created `Collectible`s, newest first.
+    /// Collectibles deleted since their creation are skipped,
+    /// whereas a gate ID created again after being deleted is listed once per creation.
+    ///
+    /// Pagination is applied to the creation log, not to the result:
+    ///
+    /// - `from_index` the number of most recent entries to skip.
+    /// - `limit` indicates how many entries will be at most scanned.
+    ///
+    /// Hence a page might contain less than `limit` collectibles, even if more pages follow.
+    pub fn get_recent_collectibles(
+        &self,
+        from_index: Option<U64>,
+        limit: Option<u32>,
+    ) -> Vec<Collectible> {
+        let len = self.collectibles_by_time.len();
+        let start = from_index.map_or(0, |s| s.0).min(len);
+        let end = start.saturating_add(limit.unwrap_or(u32::MAX) as u64).min(len);
+        (start..end)
+            .filter_map(|index| self.collectibles_by_time.get(len - 1 - index))
+            .filter_map(|gate_id| self.collectibles.get(&gate_id))
+            .collect()
+    }
+
+    /// Deletes the given `Collectible` by `gate_id`.
+    /// The collectible can only be deleted if there are no minted tokens.
+    /// Moreover, only the `creator_id` of the collectible or
+    /// the contract `admin_id` are allowed to delete the collectible.
+    pub fn delete_collectible(&mut self, gate_id: ValidGateId) {
+        let gate_id: GateId = From::from(gate_id);
+        match self.collectibles.get(&gate_id) {
+            None => Panic::GateIdNotFound { gate_id }.panic(),
+            Some(collectible) => {
+                assert!(collectible.gate_id == gate_id);
+
+                if !collectible.minted_tokens.is_empty() {
+                    Panic::GateIdHasTokens { gate_id }.panic();
+                }
+
+                let pred_id = env::predecessor_account_id();
                 if pred_id == collectible.creator_id || pred_id == self.admin_id {
                     self.collectibles.remove(&gate_id).unwrap();
+                    self.swap_targets.remove(&gate_id);
 
                     let mut cs = self.collectibles_by_creator.get(&collectible.creator_id).unwrap();
                     let removed = cs.remove(&gate_id);
@@ -372,26 +1248,267 @@ impl NftContract {
         }
     }
 
+    /// Sets the minimum price under which tokens of the `Collectible` given by `gate_id`
+    /// cannot be approved for resale.
+    /// Use `None` to remove the floor.
+    /// Only the `creator_id` of the collectible is allowed to set its floor.
+    pub fn set_min_resale_price(&mut self, gate_id: ValidGateId, min_resale_price: Option<U128>) {
+        let gate_id: GateId = From::from(gate_id);
+        match self.collectibles.get(&gate_id) {
+            None => Panic::GateIdNotFound { gate_id }.panic(),
+            Some(mut collectible) => {
+                if env::predecessor_account_id() != collectible.creator_id {
+                    Panic::CreatorRestrictedOperation { gate_id }.panic();
+                }
+
+                collectible.min_resale_price = min_resale_price;
+                self.collectibles.insert(&gate_id, &collectible);
+            }
+        }
+    }
+
+    /// Sets the price (in yoctoNEARs) to claim each token of the `Collectible` given by `gate_id`.
+    /// Use `None` to make claims free again.
+    /// Only the `creator_id` of the collectible is allowed to set its mint price.
+    pub fn set_mint_price(&mut self, gate_id: ValidGateId, mint_price: Option<U128>) {
+        let gate_id: GateId = From::from(gate_id);
+        match self.collectibles.get(&gate_id) {
+            None => Panic::GateIdNotFound { gate_id }.panic(),
+            Some(mut collectible) => {
+                if env::predecessor_account_id() != collectible.creator_id {
+                    Panic::CreatorRestrictedOperation { gate_id }.panic();
+                }
+
+                collectible.mint_price = mint_price;
+                self.collectibles.insert(&gate_id, &collectible);
+            }
+        }
+    }
+
+    /// Sets the maximum number of tokens each account can claim
+    /// for the `Collectible` given by `gate_id`.
+    /// Use `None` to remove the limit.
+    /// Only the `creator_id` of the collectible is allowed to set its limit.
+    pub fn set_max_claims_per_account(&mut self, gate_id: ValidGateId, max_claims: Option<u16>) {
+        let gate_id: GateId = From::from(gate_id);
+        match self.collectibles.get(&gate_id) {
+            None => Panic::GateIdNotFound { gate_id }.panic(),
+            Some(mut collectible) => {
+                if env::predecessor_account_id() != collectible.creator_id {
+                    Panic::CreatorRestrictedOperation { gate_id }.panic();
+                }
+
+                collectible.max_claims_per_account = max_claims;
+                self.collectibles.insert(&gate_id, &collectible);
+            }
+        }
+    }
+
+    /// Sets the minimum time (in nanoseconds) each account must wait between two claims
+    /// for the `Collectible` given by `gate_id`, *e.g.*, to throttle bots on free drops.
+    /// Use `None` to remove the cooldown.
+    /// Claims made while there was no cooldown are not taken into account.
+    /// Only the `creator_id` of the collectible is allowed to set its cooldown.
+    pub fn set_claim_cooldown(&mut self, gate_id: ValidGateId, cooldown_ns: Option<u64>) {
+        let gate_id: GateId = From::from(gate_id);
+        match self.collectibles.get(&gate_id) {
+            None => Panic::GateIdNotFound { gate_id }.panic(),
+            Some(mut collectible) => {
+                if env::predecessor_account_id() != collectible.creator_id {
+                    Panic::CreatorRestrictedOperation { gate_id }.panic();
+                }
+
+                collectible.claim_cooldown_ns = cooldown_ns;
+                self.collectibles.insert(&gate_id, &collectible);
+            }
+        }
+    }
+
+    /// Sets whether `Token`s minted from now on out of the `Collectible` given by `gate_id`
+    /// record their serial number in their `extra` metadata.
+    /// Serial numbers are never reused, even after a `Token` is burned.
+    /// Tokens already minted are not affected.
+    /// Only the `creator_id` of the collectible is allowed to change this setting.
+    pub fn set_serial_numbers(&mut self, gate_id: ValidGateId, serial_numbers: bool) {
+        let gate_id: GateId = From::from(gate_id);
+        match self.collectibles.get(&gate_id) {
+            None => Panic::GateIdNotFound { gate_id }.panic(),
+            Some(mut collectible) => {
+                if env::predecessor_account_id() != collectible.creator_id {
+                    Panic::CreatorRestrictedOperation { gate_id }.panic();
+                }
+
+                collectible.serial_numbers = serial_numbers;
+                self.collectibles.insert(&gate_id, &collectible);
+            }
+        }
+    }
+
+    /// Sets whether tokens of the `Collectible` given by `gate_id`
+    /// can be approved with a zero `min_price`, i.e., given away for free.
+    /// Only the `creator_id` of the collectible is allowed to change this setting.
+    pub fn set_allow_zero_price(&mut self, gate_id: ValidGateId, allow_zero_price: bool) {
+        let gate_id: GateId = From::from(gate_id);
+        match self.collectibles.get(&gate_id) {
+            None => Panic::GateIdNotFound { gate_id }.panic(),
+            Some(mut collectible) => {
+                if env::predecessor_account_id() != collectible.creator_id {
+                    Panic::CreatorRestrictedOperation { gate_id }.panic();
+                }
+
+                collectible.allow_zero_price = allow_zero_price;
+                self.collectibles.insert(&gate_id, &collectible);
+            }
+        }
+    }
+
+    /// Pauses or resumes claiming tokens of the `Collectible` given by `gate_id`.
+    /// Only the `creator_id` of the collectible or the contract `admin_id` are allowed to call it.
+    pub fn set_gate_claimable(&mut self, gate_id: ValidGateId, claimable: bool) {
+        let gate_id: GateId = From::from(gate_id);
+        match self.collectibles.get(&gate_id) {
+            None => Panic::GateIdNotFound { gate_id }.panic(),
+            Some(mut collectible) => {
+                let pred_id = env::predecessor_account_id();
+                if pred_id != collectible.creator_id && pred_id != self.admin_id {
+                    Panic::CreatorOrAdminRestrictedOperation { gate_id }.panic();
+                }
+
+                collectible.claimable = claimable;
+                self.collectibles.insert(&gate_id, &collectible);
+            }
+        }
+    }
+
+    /// Returns whether `account_id` is allowed to delete the `Collectible` given by `gate_id`.
+    /// That is, the collectible exists, has no minted tokens,
+    /// and `account_id` is either its `creator_id` or the contract `admin_id`.
+    /// Returns `false` for an unknown `gate_id`.
+    pub fn can_delete_collectible(&self, gate_id: ValidGateId, account_id: ValidAccountId) -> bool {
+        match self.collectibles.get(gate_id.as_ref()) {
+            None => false,
+            Some(collectible) => {
+                collectible.minted_tokens.is_empty()
+                    && (account_id.as_ref() == &collectible.creator_id
+                        || account_id.as_ref() == &self.admin_id)
+            }
+        }
+    }
+
+    /// Returns whether `account_id` can list the `Token` given by `token_id` for sale.
+    /// That is, the token exists, is owned by `account_id`,
+    /// and has no approval yet, since at most one approval is allowed per token.
+    /// Returns `false` for an unknown `token_id`.
+    pub fn can_list_token(&self, token_id: TokenId, account_id: ValidAccountId) -> bool {
+        match self.tokens.get(&token_id) {
+            None => false,
+            Some(token) => account_id.as_ref() == &token.owner_id && token.approvals.is_empty(),
+        }
+    }
+
     /// Claims a `Token` for the `Collectible` indicated by `gate_id`.
     /// The claim is on behalf the `predecessor_account_id`.
     /// Returns a `TokenId` that represents this claim.
     /// If the given `gate_id` has exhausted its supply, this call will panic.
     ///
+    /// The attached deposit must cover the storage used by the new `Token`.
+    /// When the collectible has a `mint_price`, the deposit must cover it as well.
+    /// The `mint_price` is paid to the `creator_id`, minus the `mintgate_fee`
+    /// which is split as in `nft_payout`.
+    /// Any excess deposit is refunded to the `predecessor_account_id`.
+    ///
+    /// The claim is checked to be eligible, *i.e.*, claimable, not sold out, within the
+    /// `max_claims_per_account` limit and past the `claim_cooldown_ns`, before the deposit is charged.
+    /// A rejected claim panics, so the whole attached deposit is refunded.
+    ///
     /// See <https://github.com/epam/mintgate/issues/6>.
+    #[payable]
     pub fn claim_token(&mut self, gate_id: ValidGateId) -> TokenId {
+        let initial_storage_usage = env::storage_usage();
         let gate_id = gate_id.to_string();
+        let token_id = self.mint_token(gate_id.clone(), env::predecessor_account_id(), None);
+        self.charge_claim(initial_storage_usage, &gate_id);
+        token_id
+    }
 
+    /// Claims a `Token` as in `claim_token`,
+    /// but returns the minted `Token` along with its metadata instead of only its `TokenId`.
+    #[payable]
+    pub fn claim_token_full(&mut self, gate_id: ValidGateId) -> Token {
+        let token_id = self.claim_token(gate_id);
+        self.get_token(token_id).unwrap()
+    }
+
+    /// Claims a `Token` for the `Collectible` indicated by `gate_id` on behalf of `receiver_id`,
+    /// *e.g.*, to deliver a commissioned work.
+    /// Only the `creator_id` of the collectible is allowed to call this method.
+    ///
+    /// When `royalty_override` is given, it is used by `nft_payout` for this token
+    /// instead of the collectible `royalty` and `first_sale_royalty`.
+    /// It must satisfy the same bounds as the collectible `royalty`,
+    /// and it is not allowed when the collectible royalty is locked.
+    ///
+    /// The attached deposit must cover the storage used by the new `Token`.
+    /// Any excess deposit is refunded to the `predecessor_account_id`.
+    #[payable]
+    pub fn claim_token_to(
+        &mut self,
+        gate_id: ValidGateId,
+        receiver_id: ValidAccountId,
+        royalty_override: Option<Fraction>,
+    ) -> TokenId {
+        let initial_storage_usage = env::storage_usage();
+        let gate_id = gate_id.to_string();
+
+        match self.collectibles.get(&gate_id) {
+            None => Panic::GateIdNotFound { gate_id }.panic(),
+            Some(collectible) => {
+                if env::predecessor_account_id() != collectible.creator_id {
+                    Panic::CreatorRestrictedOperation { gate_id }.panic();
+                }
+            }
+        }
+
+        let token_id = self.mint_token(gate_id, receiver_id.into(), royalty_override);
+        self.charge_storage(initial_storage_usage, 0);
+        token_id
+    }
+
+    /// Mints a new `Token` out of the `Collectible` given by `gate_id` and gives it to `owner_id`.
+    /// When given, `royalty_override` is used by `nft_payout` for the new token,
+    /// unless the collectible royalty is locked.
+    /// Every mint goes through here, so `royalty_locked` holds for all of them.
+    fn mint_token(
+        &mut self,
+        gate_id: GateId,
+        owner_id: AccountId,
+        royalty_override: Option<Fraction>,
+    ) -> TokenId {
         match self.collectibles.get(&gate_id) {
             None => Panic::GateIdNotFound { gate_id }.panic(),
             Some(mut collectible) => {
-                if collectible.current_supply == 0 {
-                    Panic::GateIdExhausted { gate_id }.panic()
+                if let Some(royalty) = royalty_override {
+                    if collectible.royalty_locked {
+                        Panic::RoyaltyLocked { gate_id }.panic();
+                    }
+                    self.check_royalty(royalty, &gate_id);
+                }
+                if let Some(err) = self.claim_errors(&collectible, &owner_id).into_iter().next() {
+                    err.panic();
+                }
+
+                let claims = self.get_claims(&gate_id, &owner_id);
+                self.claims_by_account
+                    .insert(&(gate_id.clone(), owner_id.clone()), &claims.saturating_add(1));
+                if collectible.claim_cooldown_ns.is_some() {
+                    self.last_claim_at
+                        .insert(&(gate_id.clone(), owner_id.clone()), &env::block_timestamp());
                 }
 
-                let owner_id = env::predecessor_account_id();
                 let now = env::block_timestamp() / 1_000_000;
 
-                let token_id = self.tokens.len();
+                let token_id = self.next_token_id;
+                self.next_token_id += 1;
                 let token = Token {
                     token_id: U64::from(token_id),
                     gate_id: gate_id.clone(),
@@ -400,50 +1517,140 @@ impl NftContract {
                     modified_at: now,
                     approvals: HashMap::new(),
                     approval_counter: U64::from(0),
+                    has_been_sold: false,
                     metadata: Metadata::default(),
                 };
                 self.insert_token(&token);
+                if let Some(royalty) = royalty_override {
+                    self.royalty_overrides.insert(&U64(token_id), &royalty);
+                }
 
-                collectible.current_supply = collectible.current_supply - 1;
+                if collectible.open_until.is_none() {
+                    collectible.current_supply = supply::checked_sub(collectible.current_supply, 1);
+                }
                 collectible.minted_tokens.push(U64(token_id));
+                collectible.minted_count += 1;
                 self.collectibles.insert(&gate_id, &collectible);
 
+                if collectible.serial_numbers {
+                    let serial = serde_json::json!({ "serial": collectible.minted_count });
+                    self.extra_overrides.insert(&U64(token_id), &serial.to_string());
+                }
+
                 U64::from(token_id)
             }
         }
     }
 
+    /// Splits `fee_amount` among the `mintgate_fee_split` accounts.
+    /// Any rounding remainder goes to `mintgate_fee_account_id`.
+    fn split_fee(&self, fee_amount: Balance) -> Vec<(AccountId, Balance)> {
+        let mut entries = Vec::new();
+        let mut fee_remainder = fee_amount;
+        for (account_id, share) in &self.mintgate_fee_split {
+            let amount = share.mult(fee_amount);
+            fee_remainder -= amount;
+            entries.push((account_id.clone(), amount));
+        }
+        if fee_remainder > 0 {
+            entries.push((self.mintgate_fee_account_id.clone(), fee_remainder));
+        }
+        entries
+    }
+
+    /// Charges the storage used since `initial_storage_usage` plus the `mint_price`
+    /// of the `Collectible` given by `gate_id`, as in `charge_storage`.
+    /// The `mint_price` is paid to the `creator_id`, minus the `mintgate_fee`.
+    fn charge_claim(&self, initial_storage_usage: StorageUsage, gate_id: &GateId) {
+        let collectible = self.get_collectible_or_panic(gate_id);
+        let mint_price = collectible.mint_price.map_or(0, |mint_price| mint_price.0);
+        self.charge_storage(initial_storage_usage, mint_price);
+        if mint_price > 0 {
+            let fee_amount = self.mintgate_fee.mult(mint_price);
+            let mut entries = vec![(collectible.creator_id, mint_price - fee_amount)];
+            entries.extend(self.split_fee(fee_amount));
+            for (account_id, amount) in entries {
+                if amount > 0 {
+                    Promise::new(account_id).transfer(amount);
+                }
+            }
+        }
+    }
+
+    /// Ensures the attached deposit covers the storage used since `initial_storage_usage`,
+    /// plus the `mint_price`, if any.
+    /// Refunds the remaining deposit to the `predecessor_account_id`.
+    fn charge_storage(&self, initial_storage_usage: StorageUsage, mint_price: Balance) {
+        let storage_usage = env::storage_usage().saturating_sub(initial_storage_usage);
+        let required = Balance::from(storage_usage) * env::storage_byte_cost() + mint_price;
+        let deposit = env::attached_deposit();
+        if deposit < required {
+            if mint_price > 0 {
+                Panic::NotEnoughDepositToMint { required: required.into() }.panic();
+            }
+            Panic::NotEnoughDepositToClaim { required: required.into() }.panic();
+        }
+
+        let refund = deposit - required;
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+    }
+
     /// Burns (deletes) the `Token` identifed by `token_id`.
-    /// Only the `owner_id` can burn the token.
+    /// Only the `owner_id` or an approved account can burn the token,
+    /// as in `nft_transfer`.
     ///
     /// After succefully delete the token,
     /// a cross-contract call  is made to `nft_on_revoke` for each approval
     /// to delist from their marketplaces.
+    ///
+    /// Logs an `nft_burn` event, see `NftBurnEvent`,
+    /// whose `authorized_id` is set when the token is burned by an approved account.
     pub fn burn_token(&mut self, token_id: TokenId) {
         let token = self.get_token_or_panic(token_id);
-        let gate_id = token.gate_id;
+        let gate_id = token.gate_id.clone();
 
         match self.collectibles.get(&gate_id) {
             None => Panic::GateIdNotFound { gate_id }.panic(),
             Some(mut collectible) => {
-                let owner_id = env::predecessor_account_id();
-                self.delete_token_from(token_id, &owner_id);
+                let sender_id = env::predecessor_account_id();
+                check_owner_or_approved(
+                    &token,
+                    &sender_id,
+                    Panic::TokenIdNotOwnedBy { token_id, owner_id: sender_id.clone() },
+                );
+                if let Err(err) = self.check_not_locked(&token, &sender_id) {
+                    err.panic();
+                }
+                self.delete_token_from(token_id, &token.owner_id);
+                self.extra_overrides.remove(&token_id);
+                self.locked_tokens.remove(&token_id);
+                self.royalty_overrides.remove(&token_id);
 
                 if let Some(copies) = collectible.metadata.copies {
-                    collectible.metadata.copies = Some(copies - 1);
+                    collectible.metadata.copies = Some(supply::checked_sub(copies, 1));
                 }
 
-                let mut i = 0;
-                for tid in &collectible.minted_tokens {
-                    if tid == &token_id {
-                        collectible.minted_tokens.remove(i);
-                        break;
-                    }
-
-                    i += 1;
+                if let Ok(i) =
+                    collectible.minted_tokens.binary_search_by_key(&token_id.0, |tid| tid.0)
+                {
+                    collectible.minted_tokens.remove(i);
                 }
                 self.collectibles.insert(&gate_id, &collectible);
 
+                let authorized_id =
+                    if sender_id == token.owner_id { None } else { Some(sender_id) };
+                log_event(
+                    NEP171_EVENT_STANDARD,
+                    "nft_burn",
+                    vec![NftBurnEvent {
+                        owner_id: token.owner_id.clone(),
+                        token_ids: vec![token_id],
+                        authorized_id,
+                    }],
+                );
+
                 for (market_id, _) in &token.approvals {
                     mg_core::nep178::market::nft_on_revoke(
                         token_id,
@@ -456,6 +1663,66 @@ impl NftContract {
         }
     }
 
+    /// Allows holders of the `Collectible` given by `gate_id` to swap their tokens
+    /// for a token of `to_gate_id` with `swap_token`, *e.g.*, to upgrade them.
+    /// Use `None` to disallow swapping tokens of `gate_id`.
+    /// Only the `creator_id` of both collectibles or the contract `admin_id` are allowed to call it.
+    pub fn set_swap_target(&mut self, gate_id: ValidGateId, to_gate_id: Option<ValidGateId>) {
+        let pred_id = env::predecessor_account_id();
+        let collectible = self.get_collectible_or_panic(gate_id.as_ref());
+        if pred_id != collectible.creator_id && pred_id != self.admin_id {
+            Panic::CreatorOrAdminRestrictedOperation { gate_id: collectible.gate_id }.panic();
+        }
+
+        match to_gate_id {
+            None => {
+                self.swap_targets.remove(&collectible.gate_id);
+            }
+            Some(to_gate_id) => {
+                let to_collectible = self.get_collectible_or_panic(to_gate_id.as_ref());
+                if pred_id != to_collectible.creator_id && pred_id != self.admin_id {
+                    Panic::CreatorOrAdminRestrictedOperation { gate_id: to_collectible.gate_id }
+                        .panic();
+                }
+                self.swap_targets.insert(&collectible.gate_id, &to_collectible.gate_id);
+            }
+        }
+    }
+
+    /// Returns the gate whose tokens can be claimed by swapping a token of `gate_id`, if any.
+    pub fn get_swap_target(&self, gate_id: ValidGateId) -> Option<GateId> {
+        self.swap_targets.get(gate_id.as_ref())
+    }
+
+    /// Burns the `Token` given by `token_id` and claims a `Token` of `to_gate_id` in its place,
+    /// all in a single call, so the burn is reverted if the claim fails.
+    /// Only the `owner_id` of the token can swap it,
+    /// and `to_gate_id` must be the swap target of its gate, see `set_swap_target`.
+    ///
+    /// The burn is made as in `burn_token` and the claim as in `claim_token`,
+    /// *i.e.*, `to_gate_id` must be eligible to claim from and the attached deposit
+    /// must cover the storage used by the new `Token` and the `mint_price`, if any.
+    ///
+    /// Returns the `TokenId` of the claimed token.
+    #[payable]
+    pub fn swap_token(&mut self, token_id: TokenId, to_gate_id: ValidGateId) -> TokenId {
+        let initial_storage_usage = env::storage_usage();
+        let owner_id = env::predecessor_account_id();
+        let token = self.get_token_or_panic(token_id);
+        if token.owner_id != owner_id {
+            Panic::TokenIdNotOwnedBy { token_id, owner_id }.panic();
+        }
+        let to_gate_id = to_gate_id.to_string();
+        if self.swap_targets.get(&token.gate_id).as_ref() != Some(&to_gate_id) {
+            Panic::SwapNotAllowed { gate_id: token.gate_id, to_gate_id }.panic();
+        }
+
+        self.burn_token(token_id);
+        let new_token_id = self.mint_token(to_gate_id.clone(), owner_id, None);
+        self.charge_claim(initial_storage_usage, &to_gate_id);
+        new_token_id
+    }
+
     /// Returns all `Token`s owned by `owner_id`.
     pub fn get_tokens_by_owner(&self, owner_id: ValidAccountId) -> Vec<Token> {
         match self.tokens_by_owner.get(owner_id.as_ref()) {
@@ -463,7 +1730,7 @@ impl NftContract {
             Some(list) => list
                 .iter()
                 .map(|token_id| {
-                    let token = self.get_token(token_id).expect("Token not found");
+                    let token = self.get_token_or_panic(token_id);
                     assert!(token.token_id == token_id);
                     assert!(&token.owner_id == owner_id.as_ref());
                     token
@@ -487,7 +1754,7 @@ impl NftContract {
             Some(list) => list
                 .iter()
                 .map(|token_id| {
-                    let token = self.get_token(token_id).expect("Token not found");
+                    let token = self.get_token_or_panic(token_id);
                     assert!(token.token_id == token_id);
                     assert!(&token.owner_id == owner_id.as_ref());
                     token
@@ -497,99 +1764,667 @@ impl NftContract {
         }
     }
 
+    /// Returns all or paginated tokens owned by `account_id`,
+    /// each one along with whether it is listed, *i.e.*, whether it has any approval.
+    /// Since listing a token does not transfer it, listed tokens are returned only once.
+    /// Pagination is given by:
+    ///
+    /// - `from_index` the index to start fetching tokens.
+    /// - `limit` indicates how many tokens will be at most returned.
+    pub fn get_user_portfolio(
+        &self,
+        account_id: ValidAccountId,
+        from_index: Option<U64>,
+        limit: Option<u32>,
+    ) -> Vec<(Token, bool)> {
+        match self.tokens_by_owner.get(account_id.as_ref()) {
+            None => Vec::new(),
+            Some(list) => list
+                .iter()
+                .skip(from_index.map_or(0, |s| s.0) as usize)
+                .take(limit.unwrap_or(u32::MAX) as usize)
+                .map(|token_id| {
+                    let token = self.get_token_or_panic(token_id);
+                    let listed = !token.approvals.is_empty();
+                    (token, listed)
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns all or paginated tokens minted for the `Collectible` given by `gate_id`,
+    /// together with their current `owner_id`, in minting order.
+    /// Burned tokens are skipped.
+    /// Panics if `gate_id` is not found.
+    /// Pagination is given by:
+    ///
+    /// - `from_index` the index to start fetching holders.
+    /// - `limit` indicates how many holders will be at most returned.
+    pub fn get_gate_holders(
+        &self,
+        gate_id: ValidGateId,
+        from_index: Option<U64>,
+        limit: Option<u32>,
+    ) -> Vec<(TokenId, AccountId)> {
+        match self.collectibles.get(gate_id.as_ref()) {
+            None => Panic::GateIdNotFound { gate_id: gate_id.to_string() }.panic(),
+            Some(collectible) => collectible
+                .minted_tokens
+                .iter()
+                .filter_map(|token_id| {
+                    self.tokens.get(token_id).map(|token| (*token_id, token.owner_id))
+                })
+                .skip(from_index.map_or(0, |s| s.0) as usize)
+                .take(limit.unwrap_or(u32::MAX) as usize)
+                .collect(),
+        }
+    }
+
+    /// Returns the number of distinct accounts currently owning a `Token`
+    /// of the `Collectible` given by `gate_id`.
+    /// Panics if `gate_id` is not found.
+    ///
+    /// Note that this method reads every token in `minted_tokens`,
+    /// so its cost grows with the number of tokens minted for the collectible.
+    pub fn get_gate_holder_count(&self, gate_id: ValidGateId) -> U64 {
+        match self.collectibles.get(gate_id.as_ref()) {
+            None => Panic::GateIdNotFound { gate_id: gate_id.to_string() }.panic(),
+            Some(collectible) => {
+                let holders: HashSet<AccountId> = collectible
+                    .minted_tokens
+                    .iter()
+                    .filter_map(|token_id| self.tokens.get(token_id).map(|token| token.owner_id))
+                    .collect();
+                U64(holders.len() as u64)
+            }
+        }
+    }
+
+    /// Returns the `TokenId` that will be given to the next minted `Token`,
+    /// without reserving it.
+    /// Note that another claim may take this id before the caller's own claim.
+    pub fn peek_next_token_id(&self) -> U64 {
+        U64(self.next_token_id)
+    }
+
+    /// Returns the number of `Token`s ever minted by this contract, including burned ones.
+    /// Use `nft_total_supply` to get the number of live `Token`s instead.
+    pub fn nft_total_minted(&self) -> U64 {
+        U64(self.next_token_id)
+    }
+
+    /// Returns all or paginated accounts currently owning at least one `Token`.
+    /// Pagination is given by:
+    ///
+    /// - `from_index` the index to start fetching owners.
+    /// - `limit` indicates how many owners will be at most returned.
+    pub fn get_owners(&self, from_index: Option<U64>, limit: Option<u32>) -> Vec<AccountId> {
+        self.owners
+            .iter()
+            .skip(from_index.map_or(0, |s| s.0) as usize)
+            .take(limit.unwrap_or(u32::MAX) as usize)
+            .collect()
+    }
+
+    /// Returns the markets where the `Token` identified by `token_id` is listed,
+    /// together with the `min_price` approved for each one.
+    /// Listings are derived from the token approvals, sorted by market account id.
+    /// Note that currently at most one approval is allowed per `Token`.
+    pub fn nft_token_listings(&self, token_id: TokenId) -> Vec<(AccountId, U128)> {
+        let token = self.get_token_or_panic(token_id);
+        let mut listings: Vec<(AccountId, U128)> = token
+            .approvals
+            .into_iter()
+            .map(|(market_id, approval)| (market_id, approval.min_price))
+            .collect();
+        listings.sort_by(|(a, _), (b, _)| a.cmp(b));
+        listings
+    }
+
+    /// Returns the `min_price` approved for `market_id` to sell the `Token` identified by `token_id`,
+    /// or `None` if `market_id` is not approved.
+    /// Unlike `nft_token`, it does not fetch the collectible metadata.
+    /// Panics if `token_id` is not found.
+    pub fn nft_approval_price(&self, token_id: TokenId, market_id: ValidAccountId) -> Option<U128> {
+        match self.tokens.get(&token_id) {
+            None => Panic::TokenIdNotFound { token_id }.panic(),
+            Some(token) => {
+                token.approvals.get(market_id.as_ref()).map(|approval| approval.min_price)
+            }
+        }
+    }
+
+    /// Returns the `approval_id` of `market_id` for the `Token` identified by `token_id`,
+    /// or `None` if `market_id` is not approved.
+    /// This is the value to give as `enforce_approval_id` to `nft_transfer`,
+    /// which checks it against the approval of its `receiver_id`.
+    /// Panics if `token_id` is not found.
+    pub fn nft_enforce_approval_id(
+        &self,
+        token_id: TokenId,
+        market_id: ValidAccountId,
+    ) -> Option<U64> {
+        match self.tokens.get(&token_id) {
+            None => Panic::TokenIdNotFound { token_id }.panic(),
+            Some(token) => {
+                token.approvals.get(market_id.as_ref()).map(|approval| approval.approval_id)
+            }
+        }
+    }
+
+    /// Returns the tokens approved for `market_id`, together with their approved `min_price`,
+    /// *e.g.*, for a market to reconcile its listings against the actual approvals.
+    ///
+    /// There is no index by market, so this method scans the tokens.
+    /// To bound its cost, pagination is applied to the scanned tokens, not to the result:
+    ///
+    /// - `from_index` the index of the first token to scan.
+    /// - `limit` indicates how many tokens will be at most scanned.
+    ///
+    /// Hence a page might contain less than `limit` tokens, even if more pages follow.
+    pub fn nft_tokens_approved_for(
+        &self,
+        market_id: ValidAccountId,
+        from_index: Option<U64>,
+        limit: Option<u32>,
+    ) -> Vec<(TokenId, U128)> {
+        let tokens = self.tokens.values_as_vector();
+        let start = from_index.map_or(0, |s| s.0).min(tokens.len());
+        let end = start.saturating_add(limit.unwrap_or(u32::MAX) as u64).min(tokens.len());
+        (start..end)
+            .filter_map(|index| tokens.get(index))
+            .filter_map(|token| {
+                token
+                    .approvals
+                    .get(market_id.as_ref())
+                    .map(|approval| (token.token_id, approval.min_price))
+            })
+            .collect()
+    }
+
     // pub fn get_token_by_id(&self, token_id: TokenId) -> Option<Token> {
     //     self.get_token(token_id)
     // }
 
+    /// Checks that `min_price` is allowed for the `Collectible` given by `gate_id`.
+    /// That is, it is not zero unless the collectible allows it,
+    /// and it is not below the collectible's resale floor.
+    fn check_min_price(&self, gate_id: &GateId, min_price: U128) -> Result<(), Panic> {
+        match self.collectibles.get(gate_id) {
+            None => Err(Panic::GateIdNotFound { gate_id: gate_id.clone() }),
+            Some(Collectible { allow_zero_price: false, .. }) if min_price.0 == 0 => {
+                Err(Panic::ZeroMinPrice { gate_id: gate_id.clone() })
+            }
+            Some(Collectible { min_resale_price: Some(min_resale_price), .. })
+                if min_price.0 < min_resale_price.0 =>
+            {
+                Err(Panic::BelowResaleFloor { min_price, min_resale_price })
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Returns how many tokens `account_id` has claimed for `gate_id`.
+    fn get_claims(&self, gate_id: &GateId, account_id: &AccountId) -> u16 {
+        self.claims_by_account.get(&(gate_id.clone(), account_id.clone())).unwrap_or(0)
+    }
+
+    /// Returns the remaining supply of `collectible`, see `available_supply`,
+    /// bounded by the claims `account_id` has left under `max_claims_per_account`, if any.
+    fn remaining_claims(&self, collectible: &Collectible, account_id: &AccountId) -> u16 {
+        let supply = available_supply(collectible);
+        match collectible.max_claims_per_account {
+            None => supply,
+            Some(max_claims) => {
+                let claims = self.get_claims(&collectible.gate_id, account_id);
+                supply.min(max_claims.saturating_sub(claims))
+            }
+        }
+    }
+
+    /// Returns the errors claiming a token out of `collectible` for `account_id` would fail with,
+    /// in the order they are checked.
+    fn claim_errors(&self, collectible: &Collectible, account_id: &AccountId) -> Vec<Panic> {
+        let gate_id = &collectible.gate_id;
+        let mut errs = Vec::new();
+        if !collectible.claimable {
+            errs.push(Panic::GateNotClaimable { gate_id: gate_id.clone() });
+        }
+        if available_supply(collectible) == 0 {
+            errs.push(Panic::GateIdExhausted { gate_id: gate_id.clone() });
+        }
+        if let Some(max_claims) = collectible.max_claims_per_account {
+            if self.get_claims(gate_id, account_id) >= max_claims {
+                errs.push(Panic::ClaimLimitReached {
+                    account_id: account_id.clone(),
+                    gate_id: gate_id.clone(),
+                });
+            }
+        }
+        if let Some(cooldown_ns) = collectible.claim_cooldown_ns {
+            if let Some(last_claim_at) =
+                self.last_claim_at.get(&(gate_id.clone(), account_id.clone()))
+            {
+                let ends_at = last_claim_at.saturating_add(cooldown_ns);
+                let now = env::block_timestamp();
+                if now < ends_at {
+                    errs.push(Panic::ClaimCooldownActive {
+                        gate_id: gate_id.clone(),
+                        remaining_ns: U64(ends_at - now),
+                    });
+                }
+            }
+        }
+        errs
+    }
+
     /// Returns the token given by `token_id`.
     /// Otherwise returns `None`.
     fn get_token(&self, token_id: TokenId) -> Option<Token> {
         match self.tokens.get(&token_id) {
             None => None,
-            Some(mut token) => {
+            Some(token) => {
                 assert!(token.token_id == token_id);
-                let collectible = self.collectibles.get(&token.gate_id).expect("Gate id not found");
-                token.metadata = collectible.metadata;
-                Some(token)
+                Some(self.hydrate_token(token))
+            }
+        }
+    }
+
+    /// Fills in the `metadata` of the stored `token` from its `Collectible`,
+    /// along with its templated title and its `extra` overrides.
+    /// Every method returning `Token`s must hydrate them through this method.
+    fn hydrate_token(&self, mut token: Token) -> Token {
+        let collectible = self.get_collectible_or_panic(&token.gate_id);
+        let title = token_title(&collectible, token.token_id);
+        token.metadata = collectible.metadata;
+        token.metadata.title = title;
+        if let Some(overrides) = self.extra_overrides.get(&token.token_id) {
+            token.metadata.extra = Some(merge_extra(token.metadata.extra, overrides));
+        }
+        token
+    }
+
+    /// Gets the `Token` with given `token_id`.
+    /// Panics otherwise.
+    fn get_token_or_panic(&self, token_id: TokenId) -> Token {
+        match self.get_token(token_id) {
+            None => Panic::TokenIdNotFound { token_id }.panic(),
+            Some(token) => token,
+        }
+    }
+
+    /// Gets the `Collectible` with given `gate_id`.
+    /// Panics otherwise.
+    fn get_collectible_or_panic(&self, gate_id: &GateId) -> Collectible {
+        match self.collectibles.get(gate_id) {
+            None => Panic::GateIdNotFound { gate_id: gate_id.clone() }.panic(),
+            Some(collectible) => collectible,
+        }
+    }
+
+    /// Inserts the given `Token` into `tokens` and `tokens_by_owner`,
+    /// and registers its owner in `owners`.
+    fn insert_token(&mut self, token: &Token) {
+        self.tokens.insert(&token.token_id, token);
+
+        let mut tids = self.tokens_by_owner.get(&token.owner_id).unwrap_or_else(|| {
+            UnorderedSet::new(Keys::TokensByOwnerValue {
+                owner_id_hash: crypto_hash(&token.owner_id),
+            })
+        });
+        tids.insert(&token.token_id);
+
+        self.tokens_by_owner.insert(&token.owner_id, &tids);
+        self.owners.insert(&token.owner_id);
+    }
+
+    /// Internal method to delete the corgi with `id` owned by `owner`.
+    /// Panics if `owner` does not own the corgi with `id`.
+    fn delete_token_from(&mut self, token_id: TokenId, owner_id: &AccountId) {
+        match self.tokens_by_owner.get(&owner_id) {
+            None => Panic::TokenIdNotOwnedBy { token_id, owner_id: owner_id.clone() }.panic(),
+            Some(mut list) => {
+                if !list.remove(&token_id) {
+                    Panic::TokenIdNotOwnedBy { token_id, owner_id: owner_id.clone() }.panic();
+                }
+                self.tokens_by_owner.insert(&owner_id, &list);
+                if list.is_empty() {
+                    self.owners.remove(owner_id);
+                }
+
+                let was_removed = self.tokens.remove(&token_id);
+                assert!(was_removed.is_some());
+            }
+        }
+    }
+
+    /// Transfers the token `token_id` from its owner to `receiver_id` on behalf of `sender_id`.
+    /// When royalties are enforced, an approved `sender_id` must have `payout_requested`.
+    /// The approvals of the token are cleared.
+    /// Returns the token as it was before the transfer.
+    fn transfer_token(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        enforce_approval_id: Option<U64>,
+        memo: Option<String>,
+        payout_requested: bool,
+    ) -> Token {
+        let mut token = self.get_token_or_panic(token_id);
+        check_owner_or_approved(
+            &token,
+            sender_id,
+            Panic::SenderNotAuthToTransfer { sender_id: sender_id.clone() },
+        );
+        if self.enforce_royalties && !payout_requested && sender_id != &token.owner_id {
+            Panic::RoyaltiesEnforced { sender_id: sender_id.clone() }.panic();
+        }
+        if let Err(err) = self.check_not_locked(&token, sender_id) {
+            err.panic();
+        }
+
+        if &token.owner_id == receiver_id.as_ref() {
+            Panic::ReceiverIsOwner.panic();
+        }
+
+        if let Some(enforce_approval_id) = enforce_approval_id {
+            let TokenApproval { approval_id, .. } = token
+                .approvals
+                .get(receiver_id.as_ref())
+                .expect("Receiver not an approver of this token.");
+            if approval_id != &enforce_approval_id {
+                Panic::EnforceApprovalFailed.panic();
+            }
+        }
+
+        if let Some(memo) = memo {
+            if memo.len() > MAX_MEMO_LEN {
+                Panic::InvalidArgument {
+                    gate_id: token.gate_id,
+                    reason: format!("`memo` exceeds {} bytes", MAX_MEMO_LEN),
+                }
+                .panic();
+            }
+            log!("Memo: {}", memo);
+        }
+
+        self.delete_token_from(token_id, &token.owner_id);
+        self.locked_tokens.remove(&token_id);
+
+        let previous_owner_id = token.owner_id.clone();
+        let previous_approvals = std::mem::take(&mut token.approvals);
+        token.owner_id = receiver_id.as_ref().to_string();
+        token.modified_at = env::block_timestamp() / 1_000_000;
+        self.insert_token(&token);
+
+        token.owner_id = previous_owner_id;
+        token.approvals = previous_approvals;
+        token
+    }
+
+    /// Approves a batch of tokens, similar to `nft_approve`.
+    /// Each approval contains the `TokenId` to approve and the minimum price to sell the token for.
+    /// `account_id` indicates the market account contract where list these tokens.
+    /// When the operator allowlist is not empty, `account_id` must be in it.
+    /// The attached deposit is forwarded to the market to cover its listing fee.
+    /// When the market rejects the batch, the approvals are undone and the deposit refunded,
+    /// see `resolve_batch_approve`.
+    /// The prepaid gas must cover `MIN_GAS_FOR_BATCH_APPROVE` plus `GAS_FOR_BATCH_APPROVE_ITEM` per token.
+    #[payable]
+    pub fn batch_approve(
+        &mut self,
+        tokens: Vec<(TokenId, U128)>,
+        account_id: ValidAccountId,
+    ) -> Promise {
+        if tokens.len() > self.max_batch_size as usize {
+            Panic::ExceedTokensToBatchApprove { max_batch_size: self.max_batch_size }.panic();
+        }
+        check_prepaid_gas(
+            MIN_GAS_FOR_BATCH_APPROVE + GAS_FOR_BATCH_APPROVE_ITEM * tokens.len() as Gas,
+        );
+        if let Err(err) = self.check_operator(account_id.as_ref()) {
+            err.panic();
+        }
+
+        let owner_id = env::predecessor_account_id();
+        let mut approved = Vec::new();
+        let mut oks = Vec::new();
+        let mut errs = Vec::new();
+        for (token_id, min_price) in tokens {
+            match self.approve_token(token_id, &owner_id, account_id.to_string(), min_price) {
+                Ok((approval_id, msg)) => {
+                    approved.push((token_id, approval_id, None));
+                    oks.push((token_id, msg));
+                }
+                Err(err) => errs.push((token_id, err)),
+            }
+        }
+        let pending = PendingApprovals {
+            owner_id: owner_id.clone(),
+            account_id: account_id.to_string(),
+            tokens: approved,
+            deposit: env::attached_deposit().into(),
+        };
+        let gas = GAS_FOR_BATCH_ON_APPROVE + GAS_FOR_BATCH_ON_APPROVE_ITEM * oks.len() as Gas;
+        mg_core::nep178::market::batch_on_approve(
+            oks,
+            owner_id.try_into().unwrap(),
+            account_id.as_ref(),
+            env::attached_deposit(),
+            gas,
+        )
+        .then(self_callback::resolve_batch_approve(
+            pending,
+            errs,
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_BATCH_APPROVE,
+        ))
+    }
+
+    /// Changes the `min_price` of the existing approval of `token_id` for `account_id`,
+    /// keeping its `approval_id` and `expires_at`.
+    /// `nft_on_approve` is called on `account_id` with the new price,
+    /// so the market updates the listing without a revoke round-trip.
+    /// The attached deposit is forwarded to the market to cover its listing fee.
+    /// When the market rejects the new price, the previous `min_price` is restored
+    /// and the deposit refunded, see `resolve_approve`.
+    #[payable]
+    pub fn nft_reapprove(
+        &mut self,
+        token_id: TokenId,
+        account_id: ValidAccountId,
+        new_min_price: U128,
+    ) -> Promise {
+        check_prepaid_gas(MIN_GAS_FOR_NFT_APPROVE);
+
+        let owner_id = env::predecessor_account_id();
+        let mut token = self.get_token_or_panic(token_id);
+        if owner_id != token.owner_id {
+            Panic::TokenIdNotOwnedBy { token_id, owner_id }.panic();
+        }
+        if let Err(err) = self.check_min_price(&token.gate_id, new_min_price) {
+            err.panic();
+        }
+        let (approval_id, previous_min_price) = match token.approvals.get_mut(account_id.as_ref()) {
+            None => {
+                Panic::ApprovalNotFound { token_id, account_id: account_id.to_string() }.panic()
+            }
+            Some(approval) => {
+                let previous_min_price = std::mem::replace(&mut approval.min_price, new_min_price);
+                (approval.approval_id, previous_min_price)
+            }
+        };
+        token.modified_at = env::block_timestamp() / 1_000_000;
+        self.tokens.insert(&token_id, &token);
+
+        let collectible = self.get_collectible_or_panic(&token.gate_id);
+        let market_msg = MarketApproveMsg {
+            min_price: new_min_price,
+            gate_id: Some(token.gate_id.try_into().unwrap()),
+            creator_id: Some(collectible.creator_id),
+        };
+        let pending = PendingApprovals {
+            owner_id: owner_id.clone(),
+            account_id: account_id.to_string(),
+            tokens: vec![(token_id, approval_id, Some(previous_min_price))],
+            deposit: env::attached_deposit().into(),
+        };
+        mg_core::nep178::market::nft_on_approve(
+            token_id,
+            owner_id.try_into().unwrap(),
+            approval_id,
+            serde_json::to_string(&market_msg).unwrap(),
+            account_id.as_ref(),
+            env::attached_deposit(),
+            env::prepaid_gas() / 2,
+        )
+        .then(self_callback::resolve_approve(
+            pending,
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_APPROVE,
+        ))
+    }
+
+    /// Locks `token_id` on behalf of the calling market, *e.g.*, when an auction for it starts.
+    /// Only an account holding a non-expired approval for the token can lock it.
+    ///
+    /// While locked, the owner cannot transfer, burn nor revoke the approval of the token,
+    /// so the token cannot be taken out from under the bidders.
+    /// The locking market can still transfer it through the sale path, *e.g.*, `nft_transfer_payout`,
+    /// which releases the lock.
+    /// The lock is also released when the approval of the locking market expires.
+    pub fn lock_token(&mut self, token_id: TokenId) {
+        let account_id = env::predecessor_account_id();
+        let token = self.get_token_or_panic(token_id);
+        match token.approvals.get(&account_id) {
+            None => Panic::ApprovalNotFound { token_id, account_id }.panic(),
+            Some(approval) if is_expired(approval) => {
+                Panic::ApprovalExpired { token_id, account_id }.panic()
+            }
+            Some(_) => {
+                self.locked_tokens.insert(&token_id, &account_id);
+            }
+        }
+    }
+
+    /// Releases the lock on `token_id`, *e.g.*, when its auction is cancelled.
+    /// Only the market that locked the token can unlock it.
+    pub fn unlock_token(&mut self, token_id: TokenId) {
+        let account_id = env::predecessor_account_id();
+        let token = self.get_token_or_panic(token_id);
+        if self.get_lock(&token).as_ref() != Some(&account_id) {
+            Panic::TokenNotLockedBy { token_id, account_id }.panic();
+        }
+        self.locked_tokens.remove(&token_id);
+    }
+
+    /// Returns the market that has locked `token_id`, if any.
+    /// Returns `None` when the token is not locked or does not exist.
+    pub fn get_token_lock(&self, token_id: TokenId) -> Option<AccountId> {
+        self.tokens.get(&token_id).and_then(|token| self.get_lock(&token))
+    }
+
+    /// Revokes `account_id` from a batch of tokens, similar to `nft_revoke`.
+    /// `nft_on_revoke` is called on `account_id` for every revoked token to delist it.
+    /// Tokens that cannot be revoked are reported back as a `Panic::Errors`,
+    /// once the remaining tokens have been revoked.
+    /// The prepaid gas must cover `MIN_GAS_FOR_BATCH_REVOKE` plus `GAS_FOR_BATCH_REVOKE_ITEM` per token.
+    pub fn batch_revoke(&mut self, tokens: Vec<TokenId>, account_id: ValidAccountId) {
+        if tokens.len() > self.max_batch_size as usize {
+            Panic::ExceedTokensToBatchRevoke { max_batch_size: self.max_batch_size }.panic();
+        }
+        check_prepaid_gas(
+            MIN_GAS_FOR_BATCH_REVOKE + GAS_FOR_BATCH_REVOKE_ITEM * tokens.len() as Gas,
+        );
+
+        let owner_id = env::predecessor_account_id();
+        let mut promise: Option<Promise> = None;
+        let mut errs = Vec::new();
+        for token_id in tokens {
+            match self.revoke_token(token_id, &owner_id, account_id.as_ref()) {
+                Ok(()) => {
+                    let on_revoke = mg_core::nep178::market::nft_on_revoke(
+                        token_id,
+                        account_id.as_ref(),
+                        NO_DEPOSIT,
+                        GAS_FOR_NFT_ON_REVOKE,
+                    );
+                    promise = Some(match promise {
+                        None => on_revoke,
+                        Some(promise) => promise.and(on_revoke),
+                    });
+                }
+                Err(err) => errs.push((token_id, err)),
             }
         }
-    }
 
-    /// Gets the `Token` with given `token_id`.
-    /// Panics otherwise.
-    fn get_token_or_panic(&self, token_id: TokenId) -> Token {
-        match self.get_token(token_id) {
-            None => Panic::TokenIdNotFound { token_id }.panic(),
-            Some(token) => token,
+        match promise {
+            None => {
+                if !errs.is_empty() {
+                    Panic::Errors { panics: Panics(errs) }.panic();
+                }
+            }
+            Some(promise) => {
+                promise.then(self_callback::resolve_batch_revoke(
+                    errs,
+                    &env::current_account_id(),
+                    NO_DEPOSIT,
+                    GAS_FOR_RESOLVE_BATCH_REVOKE,
+                ));
+            }
         }
     }
 
-    /// Inserts the given `Token` into `tokens` and `tokens_by_owner`.
-    fn insert_token(&mut self, token: &Token) {
-        self.tokens.insert(&token.token_id, token);
-
-        let mut tids = self.tokens_by_owner.get(&token.owner_id).unwrap_or_else(|| {
-            UnorderedSet::new(Keys::TokensByOwnerValue {
-                owner_id_hash: crypto_hash(&token.owner_id),
-            })
-        });
-        tids.insert(&token.token_id);
+    fn revoke_token(
+        &mut self,
+        token_id: TokenId,
+        owner_id: &AccountId,
+        account_id: &AccountId,
+    ) -> Result<(), Panic> {
+        let mut token = match self.tokens.get(&token_id) {
+            None => return Err(Panic::TokenIdNotFound { token_id }),
+            Some(token) => token,
+        };
 
-        self.tokens_by_owner.insert(&token.owner_id, &tids);
+        if owner_id != &token.owner_id {
+            return Err(Panic::TokenIdNotOwnedBy { token_id, owner_id: owner_id.clone() });
+        }
+        self.check_not_locked(&token, owner_id)?;
+        if token.approvals.remove(account_id).is_none() {
+            return Err(Panic::RevokeApprovalFailed { account_id: account_id.clone() });
+        }
+        token.modified_at = env::block_timestamp() / 1_000_000;
+        self.tokens.insert(&token_id, &token);
+        self.locked_tokens.remove(&token_id);
+        Ok(())
     }
 
-    /// Internal method to delete the corgi with `id` owned by `owner`.
-    /// Panics if `owner` does not own the corgi with `id`.
-    fn delete_token_from(&mut self, token_id: TokenId, owner_id: &AccountId) {
-        match self.tokens_by_owner.get(&owner_id) {
-            None => Panic::TokenIdNotOwnedBy { token_id, owner_id: owner_id.clone() }.panic(),
-            Some(mut list) => {
-                if !list.remove(&token_id) {
-                    Panic::TokenIdNotOwnedBy { token_id, owner_id: owner_id.clone() }.panic();
-                }
-                self.tokens_by_owner.insert(&owner_id, &list);
-
-                let was_removed = self.tokens.remove(&token_id);
-                assert!(was_removed.is_some());
-            }
-        }
+    /// Returns the market that has locked `token`, as long as its approval has not expired.
+    fn get_lock(&self, token: &Token) -> Option<AccountId> {
+        self.locked_tokens.get(&token.token_id).filter(|account_id| {
+            matches!(token.approvals.get(account_id), Some(approval) if !is_expired(approval))
+        })
     }
 
-    /// Approves a batch of tokens, similar to `nft_approve`.
-    /// Each approval contains the `TokenId` to approve and the minimum price to sell the token for.
-    /// `account_id` indicates the market account contract where list these tokens.
-    pub fn batch_approve(
-        &mut self,
-        tokens: Vec<(TokenId, U128)>,
-        account_id: ValidAccountId,
-    ) -> Promise {
-        if tokens.len() > 10 {
-            Panic::ExceedTokensToBatchApprove.panic();
+    /// Checks `account_id` is in the operator allowlist, unless it is empty.
+    fn check_operator(&self, account_id: &AccountId) -> Result<(), Panic> {
+        if !self.approved_operators.is_empty() && !self.approved_operators.contains(account_id) {
+            return Err(Panic::OperatorNotAllowed { account_id: account_id.clone() });
         }
+        Ok(())
+    }
 
-        let owner_id = env::predecessor_account_id();
-        let mut oks = Vec::new();
-        let mut errs = Vec::new();
-        for (token_id, min_price) in tokens {
-            match self.approve_token(token_id, &owner_id, account_id.to_string(), min_price) {
-                Ok(msg) => oks.push((token_id, msg)),
-                Err(err) => errs.push((token_id, err)),
+    /// Checks `token` is not locked by an account other than `sender_id`.
+    fn check_not_locked(&self, token: &Token, sender_id: &AccountId) -> Result<(), Panic> {
+        match self.get_lock(token) {
+            Some(account_id) if &account_id != sender_id => {
+                Err(Panic::TokenLocked { token_id: token.token_id, account_id })
             }
+            _ => Ok(()),
         }
-        mg_core::nep178::market::batch_on_approve(
-            oks,
-            owner_id.try_into().unwrap(),
-            account_id.as_ref(),
-            NO_DEPOSIT,
-            // env::prepaid_gas() / 2,
-            GAS_FOR_ROYALTIES,
-        )
-        .then(self_callback::resolve_batch_approve(
-            errs,
-            &env::current_account_id(),
-            NO_DEPOSIT,
-            GAS_FOR_ROYALTIES,
-        ))
     }
 
     fn approve_token(
@@ -598,7 +2433,7 @@ impl NftContract {
         owner_id: &AccountId,
         account_id: AccountId,
         min_price: U128,
-    ) -> Result<MarketApproveMsg, Panic> {
+    ) -> Result<(U64, MarketApproveMsg), Panic> {
         let mut token = match self.tokens.get(&token_id) {
             None => return Err(Panic::TokenIdNotFound { token_id }),
             Some(token) => token,
@@ -610,20 +2445,55 @@ impl NftContract {
         if token.approvals.len() > 0 {
             return Err(Panic::OneApprovalAllowed);
         }
+        self.check_min_price(&token.gate_id, min_price)?;
 
         token.approval_counter.0 = token.approval_counter.0 + 1;
-        token
-            .approvals
-            .insert(account_id, TokenApproval { approval_id: token.approval_counter, min_price });
+        token.approvals.insert(
+            account_id,
+            TokenApproval { approval_id: token.approval_counter, min_price, expires_at: None },
+        );
+        token.modified_at = env::block_timestamp() / 1_000_000;
         self.tokens.insert(&token_id, &token);
 
         match self.collectibles.get(&token.gate_id) {
             None => Err(Panic::GateIdNotFound { gate_id: token.gate_id }),
-            Some(collectible) => Ok(MarketApproveMsg {
-                min_price,
-                gate_id: Some(token.gate_id.try_into().unwrap()),
-                creator_id: Some(collectible.creator_id),
-            }),
+            Some(collectible) => Ok((
+                token.approval_counter,
+                MarketApproveMsg {
+                    min_price,
+                    gate_id: Some(token.gate_id.try_into().unwrap()),
+                    creator_id: Some(collectible.creator_id),
+                },
+            )),
+        }
+    }
+
+    /// Undoes the `pending` approvals rejected by their market,
+    /// and refunds the deposit forwarded to the market back to the owner.
+    /// A new approval is removed, while a changed approval gets its previous `min_price` back.
+    /// Approvals replaced in the meantime, *i.e.*, with another `approval_id`, are left untouched.
+    fn undo_approvals(&mut self, pending: PendingApprovals) {
+        let PendingApprovals { owner_id, account_id, tokens, deposit } = pending;
+        for (token_id, approval_id, previous_min_price) in tokens {
+            let mut token = match self.tokens.get(&token_id) {
+                None => continue,
+                Some(token) => token,
+            };
+            let approval = match token.approvals.get_mut(&account_id) {
+                Some(approval) if approval.approval_id == approval_id => approval,
+                _ => continue,
+            };
+            match previous_min_price {
+                Some(min_price) => approval.min_price = min_price,
+                None => {
+                    token.approvals.remove(&account_id);
+                }
+            }
+            token.modified_at = env::block_timestamp() / 1_000_000;
+            self.tokens.insert(&token_id, &token);
+        }
+        if deposit.0 > 0 {
+            Promise::new(owner_id).transfer(deposit.0);
         }
     }
 }
@@ -637,6 +2507,8 @@ impl NftContract {
 #[near_bindgen]
 impl NonFungibleTokenCore for NftContract {
     /// Transfer the token `token_id` to the `receiver_id` account.
+    /// When royalties are enforced, only the owner can use this method,
+    /// see `set_enforce_royalties`.
     ///
     /// See <https://github.com/epam/mintgate/issues/18>.
     fn nft_transfer(
@@ -647,36 +2519,7 @@ impl NonFungibleTokenCore for NftContract {
         memo: Option<String>,
     ) {
         let sender_id = env::predecessor_account_id();
-        let mut token = self.get_token_or_panic(token_id);
-
-        if sender_id != token.owner_id && token.approvals.get(&sender_id).is_none() {
-            Panic::SenderNotAuthToTransfer { sender_id }.panic();
-        }
-
-        if &token.owner_id == receiver_id.as_ref() {
-            Panic::ReceiverIsOwner.panic();
-        }
-
-        if let Some(enforce_approval_id) = enforce_approval_id {
-            let TokenApproval { approval_id, min_price: _ } = token
-                .approvals
-                .get(receiver_id.as_ref())
-                .expect("Receiver not an approver of this token.");
-            if approval_id != &enforce_approval_id {
-                Panic::EnforceApprovalFailed.panic();
-            }
-        }
-
-        if let Some(memo) = memo {
-            log!("Memo: {}", memo);
-        }
-
-        self.delete_token_from(token_id, &token.owner_id);
-
-        token.owner_id = receiver_id.as_ref().to_string();
-        token.modified_at = env::block_timestamp() / 1_000_000;
-        token.approvals.clear();
-        self.insert_token(&token);
+        self.transfer_token(&sender_id, receiver_id, token_id, enforce_approval_id, memo, false);
     }
 
     /// Query whom to be paid out for a given `token_id`, derived from some `balance`.
@@ -693,6 +2536,16 @@ impl NonFungibleTokenCore for NftContract {
     ///
     /// for any `token_id` claimed from `gate_id`.
     ///
+    /// If the collectible has a `first_sale_royalty` and the token has not been sold yet,
+    /// `first_sale_royalty` is used instead of `royalty`.
+    /// A royalty override given to the token at `claim_token_to` takes precedence over both.
+    ///
+    /// When the royalty amount is zero, the creator is left out of the payout,
+    /// so no zero-value transfer is issued for it.
+    ///
+    /// An account appearing more than once, *e.g.*, an owner who is also the creator
+    /// or a recipient of the fee split, gets a single entry with the combined amount.
+    ///
     /// This is part of an ongoing (yet not settled) NEP spec:
     /// <https://github.com/thor314/NEPs/blob/patch-5/specs/Standards/NonFungibleToken/payouts.md>
     fn nft_payout(&self, token_id: TokenId, balance: U128) -> Payout {
@@ -700,14 +2553,21 @@ impl NonFungibleTokenCore for NftContract {
         match self.collectibles.get(&token.gate_id) {
             None => Panic::GateIdNotFound { gate_id: token.gate_id }.panic(),
             Some(collectible) => {
-                let royalty_amount = collectible.royalty.mult(balance.0);
+                let royalty = match self.royalty_overrides.get(&token_id) {
+                    Some(royalty_override) => royalty_override,
+                    None => match collectible.first_sale_royalty {
+                        Some(first_sale_royalty) if !token.has_been_sold => first_sale_royalty,
+                        _ => collectible.royalty,
+                    },
+                };
+                let royalty_amount = royalty.mult(balance.0);
                 let fee_amount = self.mintgate_fee.mult(balance.0);
                 let owner_amount = balance.0 - royalty_amount - fee_amount;
-                let entries = vec![
-                    (collectible.creator_id, royalty_amount),
-                    (self.mintgate_fee_account_id.clone(), fee_amount),
-                    (token.owner_id, owner_amount),
-                ];
+                let mut entries = vec![(token.owner_id, owner_amount)];
+                entries.extend(self.split_fee(fee_amount));
+                if royalty_amount > 0 {
+                    entries.push((collectible.creator_id, royalty_amount));
+                }
 
                 let mut payout = HashMap::new();
                 for (account_id, amount) in entries {
@@ -721,6 +2581,8 @@ impl NonFungibleTokenCore for NftContract {
     /// Attempts to transfer the token.
     /// Afterwards returns the payout data.
     /// Effectively it is calling `nft_transfer` followed by `nft_payout`.
+    /// The token is then marked as sold.
+    /// When royalties are enforced, approved accounts must give a `balance` to pay out.
     ///
     /// This is part of an ongoing (yet not settled) NEP spec:
     /// <https://github.com/thor314/NEPs/blob/patch-5/specs/Standards/NonFungibleToken/payouts.md>
@@ -733,7 +2595,13 @@ impl NonFungibleTokenCore for NftContract {
         balance: Option<U128>,
     ) -> Option<Payout> {
         let payout = balance.map(|balance| self.nft_payout(token_id, balance));
-        self.nft_transfer(receiver_id, token_id, approval_id, memo);
+        let sender_id = env::predecessor_account_id();
+        self.transfer_token(&sender_id, receiver_id, token_id, approval_id, memo, payout.is_some());
+
+        let mut token = self.tokens.get(&token_id).unwrap();
+        token.has_been_sold = true;
+        self.tokens.insert(&token_id, &token);
+
         payout
     }
 
@@ -744,6 +2612,50 @@ impl NonFungibleTokenCore for NftContract {
     fn nft_token(&self, token_id: TokenId) -> Option<Token> {
         self.get_token(token_id)
     }
+
+    /// Transfers the token `token_id` to `receiver_id` as in `nft_transfer`,
+    /// and then calls `receiver_id::nft_on_transfer`.
+    /// The token is returned back to its previous owner, along with its approvals,
+    /// when `nft_on_transfer` returns `true` or fails.
+    fn nft_transfer_call(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        approval_id: Option<U64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> Promise {
+        check_prepaid_gas(GAS_FOR_NFT_TRANSFER_CALL);
+
+        let sender_id = env::predecessor_account_id();
+        let token = self.transfer_token(
+            &sender_id,
+            receiver_id.clone(),
+            token_id,
+            approval_id,
+            memo,
+            false,
+        );
+
+        mg_core::nep171::receiver::nft_on_transfer(
+            sender_id,
+            token.owner_id.clone(),
+            token_id,
+            msg,
+            receiver_id.as_ref(),
+            NO_DEPOSIT,
+            env::prepaid_gas() - GAS_FOR_NFT_TRANSFER_CALL,
+        )
+        .then(self_callback::nft_resolve_transfer(
+            token.owner_id,
+            receiver_id.to_string(),
+            token_id,
+            Some(token.approvals),
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_TRANSFER,
+        ))
+    }
 }
 
 /// Non-Fungible Token Metadata (NEP-177) v1.0.0
@@ -758,6 +2670,21 @@ impl NonFungibleTokenMetadata for NftContract {
     }
 }
 
+/// Contract Source Metadata (NEP-330) v1.0.0
+///
+/// <https://github.com/near/NEPs/blob/master/neps/nep-0330.md>
+#[near_log(skip_args, only_pub)]
+#[near_bindgen]
+impl ContractSourceMetadata for NftContract {
+    /// Returns the crate version and repository this contract was built from.
+    fn contract_source_metadata(&self) -> SourceMetadata {
+        SourceMetadata {
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            link: Some(env!("CARGO_PKG_REPOSITORY").to_string()),
+        }
+    }
+}
+
 /// Non-Fungible Token Approval Management (NEP-178) v1.0.0
 ///
 /// <https://nomicon.io/Standards/NonFungibleToken/ApprovalManagement.html>
@@ -768,17 +2695,28 @@ impl NonFungibleTokenApprovalMgmt for NftContract {
     /// The `msg` argument allows the caller to pass into additional information.
     /// A contract implementing the `nft_on_approve` methods must be
     /// deployed into `account_id`.
+    /// The attached deposit is forwarded to `nft_on_approve`, e.g., to pay a listing fee.
+    /// When `nft_on_approve` fails, *e.g.*, the listing fee is short,
+    /// the approval is removed and the deposit refunded, see `resolve_approve`.
+    /// When `msg` contains `expires_at`, `account_id` cannot transfer the token after that time.
+    /// When the operator allowlist is not empty, `account_id` must be in it.
+    ///
+    /// Only the token owner is allowed to approve,
+    /// an already approved account cannot approve further accounts.
+    #[payable]
     fn nft_approve(
         &mut self,
         token_id: TokenId,
         account_id: ValidAccountId,
         msg: Option<String>,
     ) -> Promise {
-        let min_price = {
+        check_prepaid_gas(MIN_GAS_FOR_NFT_APPROVE);
+
+        let (min_price, expires_at) = {
             if let Some(msg) = msg.clone() {
-                match serde_json::from_str::<NftApproveMsg>(&msg) {
-                    Ok(approve_msg) => approve_msg.min_price,
-                    Err(err) => Panic::MsgFormatMinPriceMissing { reason: err.to_string() }.panic(),
+                match parse_approve_msg(&msg) {
+                    Ok(approve_msg) => (approve_msg.min_price, approve_msg.expires_at),
+                    Err(err) => err.panic(),
                 }
             } else {
                 Panic::MsgFormatNotRecognized.panic();
@@ -790,15 +2728,22 @@ impl NonFungibleTokenApprovalMgmt for NftContract {
         if &owner_id != &token.owner_id {
             Panic::TokenIdNotOwnedBy { token_id, owner_id }.panic();
         }
+        if let Err(err) = self.check_operator(account_id.as_ref()) {
+            err.panic();
+        }
         if token.approvals.len() > 0 {
             Panic::OneApprovalAllowed.panic();
         }
+        if let Err(err) = self.check_min_price(&token.gate_id, min_price) {
+            err.panic();
+        }
 
         token.approval_counter.0 = token.approval_counter.0 + 1;
         token.approvals.insert(
             account_id.clone().into(),
-            TokenApproval { approval_id: token.approval_counter, min_price },
+            TokenApproval { approval_id: token.approval_counter, min_price, expires_at },
         );
+        token.modified_at = env::block_timestamp() / 1_000_000;
         self.tokens.insert(&token_id, &token);
 
         match self.collectibles.get(&token.gate_id) {
@@ -809,15 +2754,27 @@ impl NonFungibleTokenApprovalMgmt for NftContract {
                     gate_id: Some(token.gate_id.try_into().unwrap()),
                     creator_id: Some(collectible.creator_id),
                 };
+                let pending = PendingApprovals {
+                    owner_id: owner_id.clone(),
+                    account_id: account_id.to_string(),
+                    tokens: vec![(token_id, token.approval_counter, None)],
+                    deposit: env::attached_deposit().into(),
+                };
                 mg_core::nep178::market::nft_on_approve(
                     token_id,
                     owner_id.try_into().unwrap(),
                     U64::from(token.approval_counter),
                     serde_json::to_string(&market_msg).unwrap(),
                     account_id.as_ref(),
-                    0,
+                    env::attached_deposit(),
                     env::prepaid_gas() / 2,
                 )
+                .then(self_callback::resolve_approve(
+                    pending,
+                    &env::current_account_id(),
+                    NO_DEPOSIT,
+                    GAS_FOR_RESOLVE_APPROVE,
+                ))
             }
         }
     }
@@ -829,10 +2786,15 @@ impl NonFungibleTokenApprovalMgmt for NftContract {
         if &owner_id != &token.owner_id {
             Panic::TokenIdNotOwnedBy { token_id, owner_id }.panic();
         }
+        if let Err(err) = self.check_not_locked(&token, &owner_id) {
+            err.panic();
+        }
         if token.approvals.remove(account_id.as_ref()).is_none() {
             Panic::RevokeApprovalFailed { account_id: account_id.to_string() }.panic();
         }
+        token.modified_at = env::block_timestamp() / 1_000_000;
         self.tokens.insert(&token_id, &token);
+        self.locked_tokens.remove(&token_id);
         mg_core::nep178::market::nft_on_revoke(
             token_id,
             account_id.as_ref(),
@@ -848,12 +2810,17 @@ impl NonFungibleTokenApprovalMgmt for NftContract {
         if &owner_id != &token.owner_id {
             Panic::TokenIdNotOwnedBy { token_id, owner_id }.panic();
         }
+        if let Err(err) = self.check_not_locked(&token, &owner_id) {
+            err.panic();
+        }
         for (nft_id, _) in &token.approvals {
             mg_core::nep178::market::nft_on_revoke(token_id, nft_id, 0, env::prepaid_gas() / 2);
         }
 
         token.approvals.clear();
+        token.modified_at = env::block_timestamp() / 1_000_000;
         self.tokens.insert(&token_id, &token);
+        self.locked_tokens.remove(&token_id);
     }
 }
 
@@ -864,6 +2831,7 @@ impl NonFungibleTokenApprovalMgmt for NftContract {
 #[near_bindgen]
 impl NonFungibleTokenEnumeration for NftContract {
     /// Returns the total token supply.
+    /// Burned tokens are not counted, see `nft_total_minted`.
     fn nft_total_supply(&self) -> U64 {
         U64::from(self.tokens.len())
     }
@@ -877,10 +2845,8 @@ impl NonFungibleTokenEnumeration for NftContract {
         let mut i = from_index.map_or(0, |s| s.0);
         let mut result = Vec::new();
         while result.len() < limit.unwrap_or(u32::MAX) as usize {
-            if let Some(mut token) = self.tokens.values_as_vector().get(i) {
-                let collectible = self.collectibles.get(&token.gate_id).expect("Gate id not found");
-                token.metadata = collectible.metadata;
-                result.push(token);
+            if let Some(token) = self.tokens.values_as_vector().get(i) {
+                result.push(self.hydrate_token(token));
                 i += 1
             } else {
                 break;
@@ -916,7 +2882,7 @@ impl NonFungibleTokenEnumeration for NftContract {
                 let mut result = Vec::new();
                 while result.len() < limit.unwrap_or(u32::MAX) as usize {
                     if let Some(token_id) = list.as_vector().get(i) {
-                        let token = self.get_token(token_id).expect("Token not found");
+                        let token = self.get_token_or_panic(token_id);
                         assert!(token.token_id == token_id);
                         assert!(&token.owner_id == account_id.as_ref());
                         result.push(token);
@@ -945,28 +2911,370 @@ impl NonFungibleTokenEnumeration for NftContract {
     }
 }
 
-const GAS_FOR_ROYALTIES: Gas = 120_000_000_000_000;
 const NO_DEPOSIT: Balance = 0;
 
+/// Maximum length (in bytes) of the `memo` given to transfer methods.
+pub const MAX_MEMO_LEN: usize = 256;
+
+/// Default for `max_title_len` when not given at `init`.
+pub const DEFAULT_MAX_TITLE_LEN: u32 = 140;
+
+/// Default for `max_description_len` when not given at `init`.
+pub const DEFAULT_MAX_DESCRIPTION_LEN: u32 = 1024;
+
+/// Default for `max_batch_size` when not given at `init`.
+pub const DEFAULT_MAX_BATCH_SIZE: u32 = 50;
+
+/// Maximum length (in bytes) of the `thumbnail` of a `Collectible`.
+pub const MAX_THUMBNAIL_LEN: usize = 16 * 1024;
+
+/// Maximum number of gate IDs given to `get_collectibles_by_gate_ids`.
+pub const MAX_GATE_IDS_PER_QUERY: usize = 20;
+
+/// Gas given to the `nft_resolve_transfer` callback.
+const GAS_FOR_RESOLVE_TRANSFER: Gas = 10_000_000_000_000;
+
+/// Gas kept by `nft_transfer_call` for itself and its `nft_resolve_transfer` callback,
+/// the remaining prepaid gas is forwarded to `nft_on_transfer`.
+const GAS_FOR_NFT_TRANSFER_CALL: Gas = 25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER;
+
+/// Gas given to each `nft_on_revoke` call made by `batch_revoke`.
+const GAS_FOR_NFT_ON_REVOKE: Gas = 4_000_000_000_000;
+
+/// Gas given to the `resolve_batch_revoke` callback.
+const GAS_FOR_RESOLVE_BATCH_REVOKE: Gas = 10_000_000_000_000;
+
+/// Minimum prepaid gas for `nft_approve`.
+/// Half of the prepaid gas is forwarded to the market's `nft_on_approve`,
+/// and `GAS_FOR_RESOLVE_APPROVE` to the `resolve_approve` callback.
+pub const MIN_GAS_FOR_NFT_APPROVE: Gas = 40_000_000_000_000;
+
+/// Gas given to the `resolve_approve` callback.
+const GAS_FOR_RESOLVE_APPROVE: Gas = 10_000_000_000_000;
+
+/// Gas given to the market's `batch_on_approve`, besides `GAS_FOR_BATCH_ON_APPROVE_ITEM` per token.
+const GAS_FOR_BATCH_ON_APPROVE: Gas = 20_000_000_000_000;
+
+/// Gas given to the market's `batch_on_approve` to list each token.
+const GAS_FOR_BATCH_ON_APPROVE_ITEM: Gas = 3_000_000_000_000;
+
+/// Gas given to the `resolve_batch_approve` callback.
+const GAS_FOR_RESOLVE_BATCH_APPROVE: Gas = 10_000_000_000_000;
+
+/// Minimum prepaid gas for `batch_approve`, besides `GAS_FOR_BATCH_APPROVE_ITEM` per token.
+/// It covers `batch_approve` itself, `batch_on_approve` and `resolve_batch_approve`.
+pub const MIN_GAS_FOR_BATCH_APPROVE: Gas =
+    20_000_000_000_000 + GAS_FOR_BATCH_ON_APPROVE + GAS_FOR_RESOLVE_BATCH_APPROVE;
+
+/// Prepaid gas required by `batch_approve` for each token,
+/// to approve it here and to list it in the market.
+pub const GAS_FOR_BATCH_APPROVE_ITEM: Gas = 1_000_000_000_000 + GAS_FOR_BATCH_ON_APPROVE_ITEM;
+
+/// Minimum prepaid gas for `batch_revoke`, besides `GAS_FOR_BATCH_REVOKE_ITEM` per token.
+pub const MIN_GAS_FOR_BATCH_REVOKE: Gas = 10_000_000_000_000 + GAS_FOR_RESOLVE_BATCH_REVOKE;
+
+/// Prepaid gas required by `batch_revoke` for each token,
+/// to revoke it here and to delist it through `nft_on_revoke`.
+pub const GAS_FOR_BATCH_REVOKE_ITEM: Gas = 1_000_000_000_000 + GAS_FOR_NFT_ON_REVOKE;
+
+/// Maximum gas that can be prepaid to a single transaction.
+const MAX_PREPAID_GAS: Gas = 300_000_000_000_000;
+
+/// Upper bound for `max_batch_size`,
+/// *i.e.*, the most tokens whose `batch_approve` and `batch_revoke` fit in `MAX_PREPAID_GAS`.
+pub const MAX_BATCH_SIZE: u32 = {
+    let approve = (MAX_PREPAID_GAS - 1 - MIN_GAS_FOR_BATCH_APPROVE) / GAS_FOR_BATCH_APPROVE_ITEM;
+    let revoke = (MAX_PREPAID_GAS - 1 - MIN_GAS_FOR_BATCH_REVOKE) / GAS_FOR_BATCH_REVOKE_ITEM;
+    (if approve < revoke { approve } else { revoke }) as u32
+};
+
+// Checks at compile time that the default batch size is allowed,
+// and that the largest batches still fit in a single transaction.
+const _: () = assert!(DEFAULT_MAX_BATCH_SIZE <= MAX_BATCH_SIZE);
+const _: () = assert!(
+    MIN_GAS_FOR_BATCH_APPROVE + GAS_FOR_BATCH_APPROVE_ITEM * (MAX_BATCH_SIZE as Gas)
+        < MAX_PREPAID_GAS
+);
+const _: () = assert!(
+    MIN_GAS_FOR_BATCH_REVOKE + GAS_FOR_BATCH_REVOKE_ITEM * (MAX_BATCH_SIZE as Gas)
+        < MAX_PREPAID_GAS
+);
+
+/// Merges the per-token `overrides` into the `extra` metadata inherited from its `Collectible`.
+/// When both are JSON objects, the keys in `overrides` take precedence.
+/// Otherwise, `overrides` replaces `extra`.
+fn merge_extra(extra: Option<String>, overrides: String) -> String {
+    let extra = extra.and_then(|extra| serde_json::from_str::<serde_json::Value>(&extra).ok());
+    match (extra, serde_json::from_str::<serde_json::Value>(&overrides)) {
+        (Some(serde_json::Value::Object(mut extra)), Ok(serde_json::Value::Object(overrides))) => {
+            extra.extend(overrides);
+            serde_json::Value::Object(extra).to_string()
+        }
+        _ => overrides,
+    }
+}
+
+/// Checks the given `value` of argument `name` contains no control characters,
+/// e.g., null bytes, which break rendering and logs downstream.
+/// Newlines are accepted only when `allow_newline` is set.
+fn check_control_chars(name: &str, value: &str, allow_newline: bool, gate_id: &GateId) {
+    if value.chars().any(|c| c.is_control() && !(allow_newline && c == '\n')) {
+        Panic::InvalidArgument {
+            gate_id: gate_id.clone(),
+            reason: format!("`{}` contains control characters", name),
+        }
+        .panic();
+    }
+}
+
+/// Checks `sender_id` is either the owner of `token` or an account approved for it,
+/// whose approval has not expired.
+/// Panics with `not_authorized` when `sender_id` is neither.
+fn check_owner_or_approved(token: &Token, sender_id: &AccountId, not_authorized: Panic) {
+    if sender_id != &token.owner_id {
+        match token.approvals.get(sender_id) {
+            None => not_authorized.panic(),
+            Some(approval) if is_expired(approval) => {
+                Panic::ApprovalExpired { token_id: token.token_id, account_id: sender_id.clone() }
+                    .panic()
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Parses the `msg` argument of `nft_approve`.
+/// Input that is not JSON at all is reported apart from a JSON `msg`
+/// whose `min_price` or `expires_at` is missing or has the wrong type.
+fn parse_approve_msg(msg: &str) -> Result<NftApproveMsg, Panic> {
+    let value = serde_json::from_str::<serde_json::Value>(msg)
+        .map_err(|err| Panic::MsgFormatNotJson { reason: err.to_string() })?;
+    let invalid_field = |field: &str, reason: String| Panic::MsgFormatInvalidField {
+        field: field.to_string(),
+        reason,
+    };
+
+    let min_price = match value.get("min_price") {
+        None => {
+            return Err(invalid_field(
+                "min_price",
+                "field is missing, expected a string of yoctoNEARs".to_string(),
+            ))
+        }
+        Some(min_price) => serde_json::from_value::<U128>(min_price.clone()).map_err(|_| {
+            invalid_field(
+                "min_price",
+                format!("expected a string of yoctoNEARs, found `{}`", min_price),
+            )
+        })?,
+    };
+    let expires_at = match value.get("expires_at") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(expires_at) => {
+            Some(serde_json::from_value::<Timestamp>(expires_at.clone()).map_err(|_| {
+                invalid_field(
+                    "expires_at",
+                    format!("expected a timestamp in milliseconds, found `{}`", expires_at),
+                )
+            })?)
+        }
+    };
+
+    Ok(NftApproveMsg { min_price, expires_at })
+}
+
+/// Returns the title of `token_id` out of `collectible`.
+/// That is, its `title_template` with `{index}` replaced by the position of the token,
+/// or the collectible title when there is no template.
+fn token_title(collectible: &Collectible, token_id: TokenId) -> Option<String> {
+    match &collectible.title_template {
+        None => collectible.metadata.title.clone(),
+        Some(template) => {
+            match collectible.minted_tokens.binary_search_by_key(&token_id.0, |tid| tid.0) {
+                Ok(i) => Some(template.replace("{index}", &(i + 1).to_string())),
+                Err(_) => collectible.metadata.title.clone(),
+            }
+        }
+    }
+}
+
+/// Returns how many tokens can still be claimed out of `collectible`.
+/// Open editions are not limited by supply,
+/// so `u16::MAX` is returned for them until their `open_until` has passed.
+fn available_supply(collectible: &Collectible) -> u16 {
+    match collectible.open_until {
+        None => collectible.current_supply,
+        Some(open_until) if env::block_timestamp() / 1_000_000 > open_until => 0,
+        Some(_) => u16::MAX,
+    }
+}
+
+/// Returns whether `approval` has an `expires_at` already in the past.
+fn is_expired(approval: &TokenApproval) -> bool {
+    match approval.expires_at {
+        Some(expires_at) => env::block_timestamp() / 1_000_000 > expires_at,
+        None => false,
+    }
+}
+
+/// Checks the given `thumbnail` is a base64 data URL not longer than `MAX_THUMBNAIL_LEN`.
+fn check_thumbnail(thumbnail: &str, gate_id: &GateId) {
+    let reason = if thumbnail.len() > MAX_THUMBNAIL_LEN {
+        format!("`thumbnail` exceeds {} bytes", MAX_THUMBNAIL_LEN)
+    } else if !thumbnail.starts_with("data:") || !thumbnail.contains(";base64,") {
+        "`thumbnail` must be a base64 data URL".to_string()
+    } else {
+        return;
+    };
+    Panic::InvalidArgument { gate_id: gate_id.clone(), reason }.panic();
+}
+
+/// Checks the sum of `royalty` and `mintgate_fee` is less than `1`,
+/// *i.e.*, a sale still leaves some amount to the seller.
+fn check_royalty_and_fee(royalty: Fraction, mintgate_fee: Fraction) {
+    let bn = 1_000_000_000_000_000_000_000;
+    if mintgate_fee.mult(bn) + royalty.mult(bn) >= bn {
+        Panic::RoyaltyTooLarge { royalty, mintgate_fee }.panic();
+    }
+}
+
+/// Checks every share of the fee `split` is a valid `Fraction` and that they add up exactly to `1`.
+/// The sum is computed as a reduced fraction, an overflow is reported as an invalid split as well.
+fn check_fee_split(split: &[(ValidAccountId, Fraction)]) {
+    let sum: Option<Fraction> = split
+        .iter()
+        .map(|(_, share)| {
+            share.check();
+            share
+        })
+        .sum();
+    match sum {
+        Some(Fraction { num, den }) if num == den => {}
+        _ => Panic::InvalidFeeSplit.panic(),
+    }
+}
+
+/// Panics with `InsufficientGas` when the prepaid gas does not exceed `required`.
+fn check_prepaid_gas(required: Gas) {
+    if env::prepaid_gas() <= required {
+        Panic::InsufficientGas { required: required.into() }.panic();
+    }
+}
+
+/// Callbacks this contract attaches to its own cross-contract calls.
 #[near_ext]
 #[ext_contract(self_callback)]
-trait SelfCallback {
-    fn resolve_batch_approve(&mut self, errs: Vec<(TokenId, Panic)>);
+pub trait SelfCallback {
+    fn resolve_approve(&mut self, pending: PendingApprovals) -> bool;
+    fn resolve_batch_approve(&mut self, pending: PendingApprovals, errs: Vec<(TokenId, Panic)>);
+    fn resolve_batch_revoke(&mut self, errs: Vec<(TokenId, Panic)>);
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: Option<HashMap<AccountId, TokenApproval>>,
+    ) -> bool;
 }
 
 #[near_log(skip_args, only_pub)]
 #[near_bindgen]
 impl SelfCallback for NftContract {
+    /// Resolves the `nft_on_approve` call made by `nft_approve` or `nft_reapprove`.
+    /// When `nft_on_approve` failed, *e.g.*, because the listing fee was short,
+    /// the `pending` approval is undone and its deposit refunded to the owner.
+    /// Returns whether the market accepted the approval.
+    #[private]
+    fn resolve_approve(&mut self, pending: PendingApprovals) -> bool {
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Failed => {
+                self.undo_approvals(pending);
+                false
+            }
+            PromiseResult::Successful(_) => true,
+        }
+    }
+
+    /// Resolves the `batch_on_approve` call made by `batch_approve`.
+    /// Logs an `mg_batch_approve_result` event with the succeeded and failed tokens,
+    /// so clients get the outcome of every token even on partial success.
+    /// The tokens that could not be approved are then reported as a `Panic::Errors`.
+    /// When `batch_on_approve` failed, *e.g.*, because the listing fee was short,
+    /// every token is reported as failed, the `pending` approvals are undone
+    /// and their deposit refunded to the owner.
     #[private]
-    fn resolve_batch_approve(&mut self, errs: Vec<(TokenId, Panic)>) {
+    fn resolve_batch_approve(&mut self, pending: PendingApprovals, errs: Vec<(TokenId, Panic)>) {
+        let account_id = pending.account_id.clone();
+        let mut failed = errs.iter().map(|(token_id, _)| *token_id).collect::<Vec<_>>();
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
-            PromiseResult::Failed => unreachable!(),
+            PromiseResult::Failed => {
+                failed.extend(pending.tokens.iter().map(|(token_id, _, _)| *token_id));
+                log_event(
+                    EVENT_STANDARD,
+                    "mg_batch_approve_result",
+                    vec![BatchApproveResultEvent { account_id, succeeded: Vec::new(), failed }],
+                );
+                self.undo_approvals(pending);
+            }
             PromiseResult::Successful(_) => {
+                let succeeded = pending.tokens.iter().map(|(token_id, _, _)| *token_id).collect();
+                log_event(
+                    EVENT_STANDARD,
+                    "mg_batch_approve_result",
+                    vec![BatchApproveResultEvent { account_id, succeeded, failed }],
+                );
                 if !errs.is_empty() {
                     Panic::Errors { panics: Panics(errs) }.panic()
                 }
             }
         }
     }
+
+    /// Resolves the `nft_on_revoke` calls made by `batch_revoke`.
+    /// The tokens are revoked regardless of how the market handled them,
+    /// so only the tokens that could not be revoked are reported.
+    #[private]
+    fn resolve_batch_revoke(&mut self, errs: Vec<(TokenId, Panic)>) {
+        if !errs.is_empty() {
+            Panic::Errors { panics: Panics(errs) }.panic()
+        }
+    }
+
+    /// Resolves the `nft_on_transfer` call made by `nft_transfer_call`.
+    /// When `nft_on_transfer` returns `true` or fails,
+    /// the token is returned back to `previous_owner_id` and its `approved_account_ids` restored,
+    /// unless `receiver_id` does not own the token anymore.
+    /// Returns whether the token was finally transferred to `receiver_id`.
+    #[private]
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: Option<HashMap<AccountId, TokenApproval>>,
+    ) -> bool {
+        let must_revert = match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Failed => true,
+            PromiseResult::Successful(value) => serde_json::from_slice(&value).unwrap_or(true),
+        };
+        if !must_revert {
+            return true;
+        }
+
+        let mut token = match self.tokens.get(&token_id) {
+            Some(token) if token.owner_id == receiver_id => token,
+            _ => return true,
+        };
+
+        self.delete_token_from(token_id, &receiver_id);
+
+        token.owner_id = previous_owner_id;
+        token.modified_at = env::block_timestamp() / 1_000_000;
+        token.approvals = approved_account_ids.unwrap_or_default();
+        self.insert_token(&token);
+        false
+    }
 }
@@ -1,6 +1,7 @@
 #![deny(warnings)]
 
 use mg_core::{
+    fraction::Fraction,
     gate::{GateId, ValidGateId},
     mock_context,
     mocked_context::{
@@ -11,12 +12,17 @@ use mg_core::{
     nep177::NonFungibleTokenMetadata,
     nep178::NonFungibleTokenApprovalMgmt,
     nep181::NonFungibleTokenEnumeration,
-    NftApproveMsg, TokenApproval, TokenId,
+    NftApproveMsg, TokenApproval, TokenId, EVENT_JSON,
+};
+use mg_nft::{
+    BatchApproveResultEvent, CollectibleOptions, InitOptions, NftBurnEvent, NftContract,
+    PendingApprovals, SelfCallback, MAX_BATCH_SIZE,
 };
-use mg_nft::NftContract;
 use near_sdk::{
     json_types::{ValidAccountId, U128, U64},
     serde_json,
+    test_utils::get_created_receipts,
+    AccountId, PromiseResult, VMContext,
 };
 use std::{
     convert::TryInto,
@@ -67,6 +73,7 @@ impl MockedContext<NftContractChecker> {
             Some("123".to_string()),
             Some("ref".to_string()),
             Some("456".to_string()),
+            None,
         );
 
         let collectible = self.contract.get_collectible_by_gate_id(gate_id.clone()).unwrap();
@@ -109,7 +116,10 @@ impl MockedContext<NftContractChecker> {
         let total_supply = self.contract.nft_total_supply().0;
         let supply_for_owner = self.contract.nft_supply_for_owner(self.pred_id()).0;
 
+        self.attach_deposit(CLAIM_DEPOSIT);
+        self.update_context();
         let token_id = self.contract.claim_token(gate_id.clone());
+        self.attach_deposit(0);
 
         assert_eq!(self.contract.nft_total_supply(), U64(total_supply + 1));
         assert_eq!(self.contract.nft_supply_for_owner(self.pred_id()), U64(supply_for_owner + 1));
@@ -166,13 +176,74 @@ impl MockedContext<NftContractChecker> {
         token_id
     }
 
+    fn claim_token_to(
+        &mut self,
+        gate_id: ValidGateId,
+        receiver_id: ValidAccountId,
+        royalty_override: Option<&str>,
+    ) -> TokenId {
+        let total_supply = self.contract.nft_total_supply().0;
+        let supply_for_receiver = self.contract.nft_supply_for_owner(receiver_id.clone()).0;
+
+        self.attach_deposit(CLAIM_DEPOSIT);
+        self.update_context();
+        let token_id = self.contract.claim_token_to(
+            gate_id.clone(),
+            receiver_id.clone(),
+            royalty_override.map(|royalty| royalty.parse().unwrap()),
+        );
+        self.attach_deposit(0);
+
+        assert_eq!(self.contract.nft_total_supply(), U64(total_supply + 1));
+        assert_eq!(
+            self.contract.nft_supply_for_owner(receiver_id.clone()),
+            U64(supply_for_receiver + 1)
+        );
+
+        let token = self.nft_token(token_id).unwrap();
+        assert_eq!(&token.gate_id, gate_id.as_ref());
+        assert_eq!(token.owner_id, receiver_id.to_string());
+
+        self.claimed_tokens.insert(0, token_id);
+        token_id
+    }
+
     pub fn last_claimed_token(&self) -> TokenId {
         *self.claimed_tokens.get(0).unwrap()
     }
 }
 
+/// Deposit attached when claiming tokens, enough to cover storage.
+const CLAIM_DEPOSIT: u128 = 100_000_000_000_000_000_000_000;
+
 fn approve_msg(price: u128) -> Option<String> {
-    serde_json::to_string(&NftApproveMsg { min_price: price.into() }).ok()
+    serde_json::to_string(&NftApproveMsg { min_price: price.into(), expires_at: None }).ok()
+}
+
+/// Sets `result` as the result of the market call, and the contract itself as predecessor,
+/// so its callbacks can be run.
+fn set_promise_result(context: &mut VMContext, result: PromiseResult) {
+    context.predecessor_account_id = context.current_account_id.clone();
+    testing_env!(
+        context.clone(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        vec![result]
+    );
+}
+
+/// Returns the `(receiver_id, deposit)` of every transfer created by the last call.
+fn transfers() -> Vec<(String, u128)> {
+    get_created_receipts()
+        .into_iter()
+        .filter_map(|receipt| {
+            let receipt = serde_json::to_string(&receipt).unwrap();
+            let receipt: serde_json::Value = serde_json::from_str(&receipt).unwrap();
+            let deposit = receipt["actions"][0]["Transfer"]["deposit"].as_u64()?;
+            Some((receipt["receiver_id"].as_str().unwrap().to_string(), deposit as u128))
+        })
+        .collect()
 }
 
 fn init_contract(
@@ -188,6 +259,22 @@ fn init_contract(
             max_royalty.parse().unwrap(),
             "25/1000".parse().unwrap(),
             mintgate_fee_account_id(),
+            None,
+        ),
+        claimed_tokens: Vec::new(),
+    })
+}
+
+fn init_with_options(options: InitOptions) -> MockedContext<NftContractChecker> {
+    MockedContext::new(|| NftContractChecker {
+        contract: NftContract::init(
+            mintgate_admin(),
+            metadata(base_uri()),
+            "5/100".parse().unwrap(),
+            "30/100".parse().unwrap(),
+            "25/1000".parse().unwrap(),
+            mintgate_fee_account_id(),
+            Some(options),
         ),
         claimed_tokens: Vec::new(),
     })
@@ -259,11 +346,51 @@ mod initial_state {
             assert_eq!(contract.get_collectible_by_gate_id(gate_id(0)), None);
             assert_eq!(contract.nft_token(0.into()), None);
             assert_eq!(contract.nft_total_supply(), U64(0));
+            assert_eq!(contract.nft_total_minted(), U64(0));
             assert_eq!(contract.nft_supply_for_owner(any()), U64(0));
             assert_eq!(contract.nft_tokens(None, None).len(), 0);
             assert_eq!(contract.nft_tokens_for_owner(any(), None, None).len(), 0);
         });
     }
+
+    #[test]
+    #[should_panic(expected = "The shares of the fee split must add up to 1")]
+    fn init_state_with_fee_split_not_adding_up_to_one_should_panic() {
+        init_with_options(InitOptions {
+            mintgate_fee_split: Some(vec![
+                (mintgate_fee_account_id(), "7/10".parse().unwrap()),
+                (charlie(), "2/10".parse().unwrap()),
+            ]),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "The shares of the fee split must add up to 1")]
+    fn init_state_with_empty_fee_split_should_panic() {
+        init_with_options(InitOptions {
+            mintgate_fee_split: Some(Vec::new()),
+            ..Default::default()
+        });
+    }
+}
+
+mod contract_source_metadata {
+
+    use mg_core::nep330::ContractSourceMetadata;
+
+    use super::*;
+
+    #[test]
+    fn contract_source_metadata_reports_crate_version() {
+        init().run_as(any(), |contract| {
+            let metadata = contract.contract_source_metadata();
+            let version = metadata.version.unwrap();
+            assert!(!version.is_empty());
+            assert_eq!(version, env!("CARGO_PKG_VERSION"));
+            assert_eq!(metadata.link.unwrap(), env!("CARGO_PKG_REPOSITORY"));
+        });
+    }
 }
 
 mod create_collectible {
@@ -351,6 +478,7 @@ mod create_collectible {
                 None,
                 None,
                 None,
+                None,
             );
         });
     }
@@ -372,6 +500,7 @@ mod create_collectible {
                 None,
                 None,
                 None,
+                None,
             );
         });
     }
@@ -393,6 +522,7 @@ mod create_collectible {
                 None,
                 None,
                 None,
+                None,
             );
         });
     }
@@ -414,6 +544,7 @@ mod create_collectible {
                 Some(String::from_utf8(vec![b'X'; 1025]).unwrap()),
                 None,
                 None,
+                None,
             );
         });
     }
@@ -435,6 +566,7 @@ mod create_collectible {
                 None,
                 Some(String::from_utf8(vec![b'X'; 1025]).unwrap()),
                 None,
+                None,
             );
         });
     }
@@ -456,6 +588,7 @@ mod create_collectible {
                 None,
                 None,
                 Some(String::from_utf8(vec![b'X'; 1025]).unwrap()),
+                None,
             );
         });
     }
@@ -475,6 +608,7 @@ mod create_collectible {
                 None,
                 None,
                 None,
+                None,
             );
         });
     }
@@ -486,6 +620,47 @@ mod create_collectible {
         });
     }
 
+    #[test]
+    fn create_a_collectible_with_royalty_bps() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.contract.create_collectible(
+                alice(),
+                gate_id(1),
+                "title".to_string(),
+                "desc".to_string(),
+                10,
+                "1/100".parse().unwrap(),
+                None,
+                None,
+                None,
+                None,
+                Some(CollectibleOptions { royalty_bps: Some(1_250), ..Default::default() }),
+            );
+            let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+            assert_eq!(collectible.royalty, "125/1000".parse().unwrap());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Basis points must be less or equal to 10000, but was 10001")]
+    fn create_a_collectible_with_invalid_royalty_bps_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.contract.create_collectible(
+                alice(),
+                gate_id(1),
+                "title".to_string(),
+                "desc".to_string(),
+                10,
+                "1/100".parse().unwrap(),
+                None,
+                None,
+                None,
+                None,
+                Some(CollectibleOptions { royalty_bps: Some(10_001), ..Default::default() }),
+            );
+        });
+    }
+
     #[test]
     fn create_a_few_collectibles() {
         init()
@@ -511,556 +686,4672 @@ mod create_collectible {
             contract.create_test_collectible(alice(), gate_id(1), 20);
         });
     }
-}
 
-mod delete_collectible {
+    #[test]
+    #[should_panic(
+        expected = "Invalid argument for gate ID `GPZkspuVGaZxwWoP6bJoWU`: Title exceeds 10 chars"
+    )]
+    fn create_a_collectible_with_title_over_configured_limit_should_panic() {
+        init_with_options(InitOptions { max_title_len: Some(10), ..Default::default() }).run_as(
+            mintgate_admin(),
+            |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            },
+        );
+    }
 
-    use super::*;
+    #[test]
+    #[should_panic(
+        expected = "Invalid argument for gate ID `GPZkspuVGaZxwWoP6bJoWU`: `description` exceeds 12 chars"
+    )]
+    fn create_a_collectible_with_description_over_configured_limit_should_panic() {
+        init_with_options(InitOptions { max_description_len: Some(12), ..Default::default() })
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            });
+    }
 
     #[test]
-    #[should_panic(expected = "Gate ID `Nekq22i3rvzDe7c51Yc8hU` was not found")]
-    fn delete_a_non_existent_collectible_should_panic() {
-        init().run_as(alice(), |contract| {
-            contract.delete_collectible(gate_id(0));
+    fn create_a_collectible_within_configured_limits() {
+        init_with_options(InitOptions {
+            max_title_len: Some(14),
+            max_description_len: Some(15),
+            ..Default::default()
+        })
+        .run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
         });
     }
 
     #[test]
-    #[should_panic(expected = "Gate ID `GPZkspuVGaZxwWoP6bJoWU` has already some claimed tokens")]
-    fn delete_a_claimed_gate_id_should_panic() {
+    #[should_panic(
+        expected = "Invalid argument for gate ID `GPZkspuVGaZxwWoP6bJoWU`: `title` contains control characters"
+    )]
+    fn create_a_collectible_with_null_byte_in_title_should_panic() {
         init().run_as(mintgate_admin(), |contract| {
-            contract.create_test_collectible(alice(), gate_id(1), 10);
-            contract.claim_token(gate_id(1));
-            contract.delete_collectible(gate_id(1));
+            contract.contract.create_collectible(
+                alice(),
+                gate_id(1),
+                "My\0collectible".to_string(),
+                "desc".to_string(),
+                10,
+                "5/100".parse().unwrap(),
+                None,
+                None,
+                None,
+                None,
+                None,
+            );
         });
     }
 
     #[test]
-    #[should_panic(expected = "Unable to delete gate ID `GPZkspuVGaZxwWoP6bJoWU`")]
-    fn delete_a_collectible_from_non_creator_should_panic() {
-        init()
-            .run_as(mintgate_admin(), |contract| {
-                contract.create_test_collectible(alice(), gate_id(1), 10);
-            })
-            .run_as(bob(), |contract| {
-                contract.delete_collectible(gate_id(1));
-            });
+    #[should_panic(
+        expected = "Invalid argument for gate ID `GPZkspuVGaZxwWoP6bJoWU`: `title` contains control characters"
+    )]
+    fn create_a_collectible_with_newline_in_title_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.contract.create_collectible(
+                alice(),
+                gate_id(1),
+                "My\ncollectible".to_string(),
+                "desc".to_string(),
+                10,
+                "5/100".parse().unwrap(),
+                None,
+                None,
+                None,
+                None,
+                None,
+            );
+        });
     }
 
     #[test]
-    fn delete_a_collectible_from_creator() {
+    #[should_panic(
+        expected = "Invalid argument for gate ID `GPZkspuVGaZxwWoP6bJoWU`: `description` contains control characters"
+    )]
+    fn create_a_collectible_with_escape_in_description_should_panic() {
         init().run_as(mintgate_admin(), |contract| {
-            contract.create_test_collectible(alice(), gate_id(1), 10);
-            assert!(contract.get_collectible_by_gate_id(gate_id(1)).is_some());
-
-            contract.delete_collectible(gate_id(1));
-            assert!(contract.get_collectible_by_gate_id(gate_id(1)).is_none());
-            assert!(contract.get_collectibles_by_creator(alice()).is_empty());
+            contract.contract.create_collectible(
+                alice(),
+                gate_id(1),
+                "title".to_string(),
+                "red \u{1b}[31m text".to_string(),
+                10,
+                "5/100".parse().unwrap(),
+                None,
+                None,
+                None,
+                None,
+                None,
+            );
         });
     }
 
     #[test]
-    fn delete_a_collectible_from_admin() {
-        init()
-            .run_as(mintgate_admin(), |contract| {
-                contract.create_test_collectible(alice(), gate_id(1), 10);
-                assert!(contract.get_collectible_by_gate_id(gate_id(1)).is_some());
-            })
-            .run_as(mintgate_admin(), |contract| {
-                contract.delete_collectible(gate_id(1));
-                assert!(contract.get_collectible_by_gate_id(gate_id(1)).is_none());
-                assert!(contract.get_collectibles_by_creator(alice()).is_empty());
-            });
+    fn create_a_collectible_with_unicode_title_and_multiline_description() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.contract.create_collectible(
+                alice(),
+                gate_id(1),
+                "Café ☕ コレクション".to_string(),
+                "First line\nSecond line".to_string(),
+                10,
+                "5/100".parse().unwrap(),
+                None,
+                None,
+                None,
+                None,
+                None,
+            );
+
+            let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+            assert_eq!(collectible.metadata.title, Some("Café ☕ コレクション".to_string()));
+        });
     }
 }
 
-mod claim_token {
+mod create_and_claim {
 
     use super::*;
 
     #[test]
-    fn claim_a_token() {
-        init()
-            .run_as(mintgate_admin(), |contract| {
-                contract.create_test_collectible(alice(), gate_id(1), 10);
-                assert_eq!(contract.get_collectibles_by_creator(alice()).len(), 1);
-            })
-            .run_as(bob(), |contract| {
-                let uri = format!("{}{}", base_uri().unwrap(), gate_id(1));
-
-                for _i in 0..3 {
-                    let token_id = contract.claim_token(gate_id(1));
-                    assert_eq!(contract.nft_token_uri(token_id).unwrap(), uri);
-                }
-
-                let tokens = contract.get_tokens_by_owner(bob());
-                assert_eq!(tokens.len(), 3);
+    fn create_and_claim_a_single_edition() {
+        init().attach_deposit(CLAIM_DEPOSIT).run_as(mintgate_admin(), |contract| {
+            let token_id = contract.create_and_claim(
+                alice(),
+                gate_id(1),
+                "One of one".to_string(),
+                "desc".to_string(),
+                1,
+                "5/100".parse().unwrap(),
+            );
 
-                let c = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
-                assert_eq!(c.current_supply, 7);
-            });
-    }
+            let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+            assert_eq!(collectible.creator_id, alice().to_string());
+            assert_eq!(collectible.current_supply, 0);
+            assert_eq!(collectible.minted_tokens, vec![token_id]);
+            assert!(contract.is_gate_sold_out(gate_id(1)));
 
-    #[test]
-    fn claim_a_few_tokens() {
-        init()
-            .run_as(mintgate_admin(), |contract| {
-                contract.create_test_collectible(alice(), gate_id(1), 10);
-                assert_eq!(contract.get_collectibles_by_creator(alice()).len(), 1);
-            })
-            .run_as(mintgate_admin(), |contract| {
-                contract.create_test_collectible(bob(), gate_id(2), 15);
-                assert_eq!(contract.get_collectibles_by_creator(bob()).len(), 1);
-                contract.claim_token(gate_id(2));
-            });
+            let token = contract.nft_token(token_id).unwrap();
+            assert_eq!(token.owner_id, alice().to_string());
+            assert_eq!(token.gate_id, gate_id(1).to_string());
+            assert_eq!(contract.nft_supply_for_owner(alice()), U64(1));
+            assert_eq!(contract.nft_supply_for_owner(mintgate_admin()), U64(0));
+        });
     }
 
     #[test]
-    #[should_panic(expected = "Gate ID `Nekq22i3rvzDe7c51Yc8hU` was not found")]
-    fn claim_a_token_of_non_existent_gate_id_should_panic() {
-        init().run_as(alice(), |contract| {
-            contract.claim_token(gate_id(0));
+    #[should_panic(expected = "Not enough deposit to cover storage")]
+    fn create_and_claim_without_deposit_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_and_claim(
+                alice(),
+                gate_id(1),
+                "One of one".to_string(),
+                "desc".to_string(),
+                1,
+                "5/100".parse().unwrap(),
+            );
         });
     }
 
     #[test]
-    #[should_panic(
-        expected = "Tokens for gate id `GPZkspuVGaZxwWoP6bJoWU` have already been claimed"
-    )]
-    fn claim_a_token_with_no_supply_should_panic() {
-        init()
-            .run_as(mintgate_admin(), |contract| {
-                contract.create_test_collectible(alice(), gate_id(1), 1);
-                contract.claim_token(gate_id(1));
-            })
-            .run_as(bob(), |contract| {
-                contract.claim_token(gate_id(1));
-            });
+    #[should_panic(expected = "Operation is allowed only for admin")]
+    fn create_and_claim_by_non_admin_should_panic() {
+        init().attach_deposit(CLAIM_DEPOSIT).run_as(alice(), |contract| {
+            contract.create_and_claim(
+                alice(),
+                gate_id(1),
+                "One of one".to_string(),
+                "desc".to_string(),
+                1,
+                "5/100".parse().unwrap(),
+            );
+        });
     }
+}
+
+mod collectible_created_at {
+
+    use super::*;
 
     #[test]
-    fn claim_and_get_a_few_tokens() {
+    fn get_created_at_of_a_non_existent_collectible() {
+        init().run_as(any(), |contract| {
+            assert_eq!(contract.get_collectible_created_at(gate_id(1)), None);
+        });
+    }
+
+    #[test]
+    fn created_at_is_set_at_creation() {
         init()
             .run_as(mintgate_admin(), |contract| {
-                contract.create_test_collectible(alice(), gate_id(1), 100);
-            })
-            .run_as(alice(), |contract| {
-                assert_eq!(contract.get_collectibles_by_creator(alice()).len(), 1);
-
-                assert_eq!(contract.nft_tokens(None, Some(0)).len(), 0);
-                assert_eq!(contract.nft_tokens(None, Some(10)).len(), 0);
-                assert_eq!(contract.nft_tokens(Some(50.into()), None).len(), 0);
-
-                for _i in 0..20 {
-                    contract.claim_token(gate_id(1));
-                }
+                contract.context.block_timestamp = 42_000_000;
             })
             .run_as(mintgate_admin(), |contract| {
-                contract.create_test_collectible(bob(), gate_id(2), 15);
-            })
-            .run_as(bob(), |contract| {
-                assert_eq!(contract.get_collectibles_by_creator(bob()).len(), 1);
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                let created_at = contract.context.block_timestamp / 1_000_000;
+                assert_eq!(contract.get_collectible_created_at(gate_id(1)), Some(created_at));
 
-                for _i in 0..10 {
-                    contract.claim_token(gate_id(2));
-                }
+                let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+                assert_eq!(collectible.created_at, created_at);
+            })
+            .run_as(alice(), |contract| {
+                contract.context.block_timestamp += 5_000_000;
+                contract.claim_token(gate_id(1));
+                let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+                assert_eq!(collectible.created_at, 42);
+            });
+    }
+}
 
-                assert_eq!(contract.nft_total_supply(), U64(30));
-                assert_eq!(contract.nft_supply_for_owner(alice()), U64(20));
+mod owner_share {
 
-                assert_eq!(contract.nft_tokens(None, None).len(), 30);
-                assert_eq!(contract.nft_tokens_for_owner(alice(), None, None).len(), 20);
+    use super::*;
 
-                assert_eq!(contract.nft_tokens(None, Some(10)).len(), 10);
-                assert_eq!(contract.nft_tokens_for_owner(alice(), None, Some(10)).len(), 10);
+    #[test]
+    fn owner_share_of_a_collectible() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_royalty_collectible(alice(), gate_id(1), 10, "30/100");
+            let share = contract.owner_share(gate_id(1));
+            assert_eq!((share.num, share.den), (27, 40));
+        });
+    }
 
-                assert_eq!(contract.nft_tokens(Some(25.into()), Some(10)).len(), 5);
-                assert_eq!(
-                    contract.nft_tokens_for_owner(alice(), Some(15.into()), Some(10)).len(),
-                    5
-                );
-            });
+    #[test]
+    #[should_panic(expected = "Gate ID `GPZkspuVGaZxwWoP6bJoWU` was not found")]
+    fn owner_share_of_a_non_existent_collectible_should_panic() {
+        init().run_as(any(), |contract| {
+            contract.owner_share(gate_id(1));
+        });
     }
 }
 
-mod nft_token_uri {
+mod creator_allowlist {
 
     use super::*;
 
     #[test]
-    fn get_token_uri_with_base_uri() {
-        init_contract("5/100", "30/100", metadata(base_uri())).run_as(
-            mintgate_admin(),
-            |contract| {
-                assert_eq!(contract.nft_token_uri(U64(0)), None);
-
-                contract.create_test_collectible(alice(), gate_id(1), 10);
-                let uri = format!("{}{}", base_uri().unwrap(), gate_id(1));
-                let token_id = contract.claim_token(gate_id(1));
-                assert_eq!(contract.nft_token_uri(token_id).unwrap(), uri);
-            },
-        );
+    fn minting_is_open_by_default() {
+        init().run_as(any(), |contract| {
+            assert!(contract.is_open_minting());
+        });
     }
 
     #[test]
-    fn get_token_uri_with_no_slash_base_uri() {
-        init_contract("5/100", "30/100", metadata(Some("https://mintgate/t".to_string()))).run_as(
-            mintgate_admin(),
-            |contract| {
-                assert_eq!(contract.nft_token_uri(U64(0)), None);
-
-                contract.create_test_collectible(alice(), gate_id(1), 10);
-                let uri = format!("{}/{}", "https://mintgate/t", gate_id(1));
-                let token_id = contract.claim_token(gate_id(1));
-                assert_eq!(contract.nft_token_uri(token_id).unwrap(), uri);
-            },
-        );
+    #[should_panic(expected = "Operation is allowed only for admin")]
+    fn set_creator_allowlist_from_non_admin_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.set_creator_allowlist(vec![alice()]);
+        });
     }
 
     #[test]
-    fn get_token_uri_with_none_base_uri() {
-        init_contract("5/100", "30/100", metadata(None)).run_as(mintgate_admin(), |contract| {
-            assert_eq!(contract.nft_token_uri(U64(0)), None);
+    #[should_panic(expected = "Account `bob` is not in the creator allowlist")]
+    fn create_a_collectible_for_a_non_allowed_creator_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.set_creator_allowlist(vec![alice()]);
+            contract.create_test_collectible(bob(), gate_id(1), 10);
+        });
+    }
 
+    #[test]
+    fn set_creator_allowlist() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.set_creator_allowlist(vec![alice(), charlie()]);
+            assert!(!contract.is_open_minting());
             contract.create_test_collectible(alice(), gate_id(1), 10);
-            let token_id = contract.claim_token(gate_id(1));
-            assert_eq!(contract.nft_token_uri(token_id), None);
+            contract.create_test_collectible(charlie(), gate_id(2), 10);
+
+            contract.set_creator_allowlist(vec![]);
+            assert!(contract.is_open_minting());
+            contract.create_test_collectible(bob(), gate_id(3), 10);
         });
     }
 }
-mod burn_token {
+
+mod approved_operators {
 
     use super::*;
 
+    fn init_with_token() -> MockedContext<NftContractChecker> {
+        let mut context = init();
+        context
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+            });
+        context
+    }
+
     #[test]
-    #[should_panic(expected = "Token ID `U64(0)` was not found")]
-    fn burn_a_non_existent_token_should_panic() {
-        init().run_as(alice(), |contract| {
-            contract.burn_token(0.into());
+    fn any_operator_is_allowed_by_default() {
+        init_with_token().run_as(bob(), |contract| {
+            assert!(contract.get_approved_operators().is_empty());
+            let token_id = contract.last_claimed_token();
+            contract.nft_approve(token_id, market(), approve_msg(10));
+            assert!(contract
+                .nft_token(token_id)
+                .unwrap()
+                .approvals
+                .contains_key(market().as_ref()));
         });
     }
 
     #[test]
-    fn burn_a_few_tokens() {
-        init().run_as(mintgate_admin(), |contract| {
-            contract.create_test_collectible(alice(), gate_id(1), 10);
-            let token_id = contract.claim_token(gate_id(1));
-            contract.burn_token(token_id);
-            let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
-            assert_eq!(collectible.metadata.copies.unwrap(), 9);
+    #[should_panic(expected = "Operation is allowed only for admin")]
+    fn add_approved_operator_from_non_admin_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.add_approved_operator(market());
+        });
+    }
 
-            let token_id = contract.claim_token(gate_id(1));
-            contract.nft_approve(token_id, bob(), approve_msg(10));
-            contract.burn_token(token_id);
-            let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
-            assert_eq!(collectible.metadata.copies.unwrap(), 8);
+    #[test]
+    #[should_panic(expected = "Operation is allowed only for admin")]
+    fn remove_approved_operator_from_non_admin_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.remove_approved_operator(market());
         });
     }
 
     #[test]
-    #[should_panic(expected = "Token ID `U64(0)` does not belong to account `bob`")]
-    fn transfer_a_non_approved_token_should_panic() {
-        init()
+    fn approve_an_allowed_operator() {
+        init_with_token()
             .run_as(mintgate_admin(), |contract| {
-                contract.create_test_collectible(alice(), gate_id(1), 10);
-                contract.claim_token(gate_id(1));
+                contract.add_approved_operator(market());
+                assert_eq!(contract.get_approved_operators(), vec![market().to_string()]);
             })
             .run_as(bob(), |contract| {
                 let token_id = contract.last_claimed_token();
-                contract.burn_token(token_id);
+                contract.nft_approve(token_id, market(), approve_msg(10));
             });
     }
-}
-
-mod nft_transfer {
-
-    use super::*;
 
     #[test]
-    fn transfer_a_token() {
-        init()
+    #[should_panic(expected = "Account `charlie` is not in the operator allowlist")]
+    fn approve_a_non_allowed_operator_should_panic() {
+        init_with_token()
             .run_as(mintgate_admin(), |contract| {
-                contract.create_test_collectible(alice(), gate_id(1), 10);
+                contract.add_approved_operator(market());
             })
             .run_as(bob(), |contract| {
-                let token_id = contract.claim_token(gate_id(1));
-                contract.nft_transfer(charlie(), token_id, None, None);
-
-                let ts = contract.get_tokens_by_owner(charlie());
-                assert_eq!(ts.len(), 1);
+                let token_id = contract.last_claimed_token();
+                contract.nft_approve(token_id, charlie(), approve_msg(10));
             });
     }
 
     #[test]
-    #[should_panic(expected = "Token ID `U64(99)` was not found")]
-    fn transfer_a_non_existent_token_should_panic() {
-        init().run_as(alice(), |contract| {
-            contract.nft_transfer(charlie(), 99.into(), None, None);
-        });
+    #[should_panic(expected = "Account `charlie` is not in the operator allowlist")]
+    fn batch_approve_a_non_allowed_operator_should_panic() {
+        init_with_token()
+            .run_as(mintgate_admin(), |contract| {
+                contract.add_approved_operator(market());
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.batch_approve(vec![(token_id, U128(10))], charlie());
+            });
     }
 
     #[test]
-    #[should_panic(expected = "Sender `bob` is not authorized to make transfer")]
-    fn transfer_a_non_approved_token_should_panic() {
-        init()
+    fn removing_the_last_operator_opens_the_allowlist() {
+        init_with_token()
             .run_as(mintgate_admin(), |contract| {
-                contract.create_test_collectible(alice(), gate_id(1), 10);
-                contract.claim_token(gate_id(1));
+                contract.add_approved_operator(market());
+                assert!(contract.remove_approved_operator(market()));
+                assert!(!contract.remove_approved_operator(market()));
             })
             .run_as(bob(), |contract| {
                 let token_id = contract.last_claimed_token();
-                contract.nft_transfer(charlie(), token_id, None, None);
+                contract.nft_approve(token_id, charlie(), approve_msg(10));
             });
     }
 }
 
-mod nft_approve {
+mod thumbnail {
 
     use super::*;
 
-    #[test]
-    #[should_panic(expected = "The msg argument must contain the minimum price")]
-    fn nft_approve_with_no_msg_should_panic() {
-        init().run_as(alice(), |contract| {
-            contract.nft_approve(0.into(), bob(), None);
-        });
+    fn create_with_thumbnail(contract: &mut NftContract, gate_id: ValidGateId, thumbnail: String) {
+        contract.create_collectible(
+            alice(),
+            gate_id,
+            "title".to_string(),
+            "desc".to_string(),
+            10,
+            "5/100".parse().unwrap(),
+            None,
+            None,
+            None,
+            None,
+            Some(CollectibleOptions { thumbnail: Some(thumbnail), ..Default::default() }),
+        );
+    }
+
+    fn data_url(len: usize) -> String {
+        let prefix = "data:image/png;base64,";
+        format!("{}{}", prefix, "A".repeat(len - prefix.len()))
     }
 
     #[test]
-    #[should_panic(expected = "Could not find min_price in msg: ")]
-    fn nft_approve_with_invalid_msg_should_panic() {
-        init().run_as(alice(), |contract| {
-            contract.nft_approve(0.into(), bob(), Some("".to_string()));
+    fn get_thumbnail_of_a_non_existent_collectible() {
+        init().run_as(any(), |contract| {
+            assert_eq!(contract.get_thumbnail(gate_id(1)), None);
         });
     }
 
     #[test]
-    #[should_panic(expected = "Token ID `U64(99)` was not found")]
-    fn nft_approve_a_non_existent_token_should_panic() {
-        init().run_as(alice(), |contract| {
-            contract.nft_approve(99.into(), bob(), approve_msg(10));
+    fn create_a_collectible_without_thumbnail() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            assert_eq!(contract.get_thumbnail(gate_id(1)), None);
         });
     }
 
     #[test]
-    fn nft_approve_a_token() {
+    fn create_a_collectible_with_a_thumbnail() {
         init().run_as(mintgate_admin(), |contract| {
-            contract.create_test_collectible(alice(), gate_id(1), 10);
-            let token_id = contract.claim_token(gate_id(1));
-            contract.nft_approve(token_id, bob(), approve_msg(10));
+            let thumbnail = "data:image/png;base64,iVBORw0KGgo=".to_string();
+            create_with_thumbnail(contract, gate_id(1), thumbnail.clone());
+            assert_eq!(contract.get_thumbnail(gate_id(1)), Some(thumbnail.clone()));
+            assert_eq!(
+                contract.get_collectible_by_gate_id(gate_id(1)).unwrap().thumbnail,
+                Some(thumbnail)
+            );
 
-            let token = contract.nft_token(token_id).unwrap();
-            assert_eq!(token.approval_counter, U64(1));
-            assert_eq!(token.approvals.len(), 1);
-            assert_eq!(token.approvals[bob().as_ref()], TokenApproval::new(1, U128(10)));
+            create_with_thumbnail(contract, gate_id(2), data_url(16 * 1024));
+            assert_eq!(contract.get_thumbnail(gate_id(2)), Some(data_url(16 * 1024)));
         });
     }
 
     #[test]
-    #[should_panic(expected = "At most one approval is allowed per Token")]
-    fn nft_approve_a_token_twice_should_panic() {
+    #[should_panic(
+        expected = "Invalid argument for gate ID `GPZkspuVGaZxwWoP6bJoWU`: `thumbnail` exceeds 16384 bytes"
+    )]
+    fn create_a_collectible_with_a_too_large_thumbnail_should_panic() {
         init().run_as(mintgate_admin(), |contract| {
-            contract.create_test_collectible(alice(), gate_id(1), 10);
-            let token_id = contract.claim_token(gate_id(1));
-            contract.nft_approve(token_id, bob(), approve_msg(10));
-            contract.nft_approve(token_id, charlie(), approve_msg(15));
+            create_with_thumbnail(contract, gate_id(1), data_url(20 * 1024));
         });
     }
 
     #[test]
-    fn nft_approve_before_transfer_a_token() {
-        init()
-            .run_as(mintgate_admin(), |contract| {
-                contract.create_test_collectible(alice(), gate_id(1), 10);
-            })
-            .run_as(bob(), |contract| {
-                let token_id = contract.claim_token(gate_id(1));
-                contract.nft_approve(token_id, market(), approve_msg(10));
-
-                let token = contract.nft_token(token_id).unwrap();
-                assert_eq!(token.approval_counter, U64(1));
-                assert_eq!(token.approvals.len(), 1);
-                assert_eq!(token.approvals[market().as_ref()], TokenApproval::new(1, U128(10)));
-            })
-            .run_as(market(), |contract| {
-                let token_id = contract.last_claimed_token();
-                contract.nft_transfer(charlie(), token_id, None, None);
-
-                let token = contract.nft_token(token_id).unwrap();
-                assert_eq!(token.approval_counter, U64(1));
-                assert_eq!(token.approvals.len(), 0);
-            });
+    #[should_panic(
+        expected = "Invalid argument for gate ID `GPZkspuVGaZxwWoP6bJoWU`: `thumbnail` must be a base64 data URL"
+    )]
+    fn create_a_collectible_with_a_non_data_url_thumbnail_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            create_with_thumbnail(contract, gate_id(1), "https://example.com/t.png".to_string());
+        });
     }
 }
 
-mod nft_revoke_all {
+mod update_collectible {
+
     use super::*;
 
     #[test]
-    #[should_panic(expected = "Token ID `U64(99)` was not found")]
-    fn nft_revoke_all_for_non_existent_token_should_panic() {
-        init().run_as(bob(), |contract| {
-            contract.nft_revoke_all(99.into());
+    #[should_panic(expected = "Gate ID `GPZkspuVGaZxwWoP6bJoWU` was not found")]
+    fn update_a_non_existent_collectible_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.update_collectible(gate_id(1), Some("title".to_string()), None);
         });
     }
 
     #[test]
-    #[should_panic(expected = "Token ID `U64(0)` does not belong to account `bob")]
-    fn nft_revoke_all_for_non_owned_token_should_panic() {
+    #[should_panic(
+        expected = "Only the creator of gate ID `GPZkspuVGaZxwWoP6bJoWU` is allowed to perform this operation"
+    )]
+    fn update_a_collectible_by_non_creator_should_panic() {
         init()
             .run_as(mintgate_admin(), |contract| {
                 contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.update_collectible(gate_id(1), Some("title".to_string()), None);
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid argument for gate ID `GPZkspuVGaZxwWoP6bJoWU`: Title exceeds 14 chars"
+    )]
+    fn update_a_collectible_with_title_over_configured_limit_should_panic() {
+        init_with_options(InitOptions { max_title_len: Some(14), ..Default::default() })
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.update_collectible(gate_id(1), Some("X".repeat(15)), None);
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid argument for gate ID `GPZkspuVGaZxwWoP6bJoWU`: `description` exceeds 20 chars"
+    )]
+    fn update_a_collectible_with_description_over_configured_limit_should_panic() {
+        init_with_options(InitOptions { max_description_len: Some(20), ..Default::default() })
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.update_collectible(gate_id(1), None, Some("X".repeat(21)));
+            });
+    }
+
+    #[test]
+    fn update_a_collectible_title_and_description() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.update_collectible(gate_id(1), Some("New title".to_string()), None);
+                let metadata = contract.get_collectible_by_gate_id(gate_id(1)).unwrap().metadata;
+                assert_eq!(metadata.title, Some("New title".to_string()));
+                assert_eq!(metadata.description, Some("NFT description".to_string()));
+                assert!(metadata.updated_at.is_some());
+
+                contract.update_collectible(gate_id(1), None, Some("New desc".to_string()));
+                let metadata = contract.get_collectible_by_gate_id(gate_id(1)).unwrap().metadata;
+                assert_eq!(metadata.title, Some("New title".to_string()));
+                assert_eq!(metadata.description, Some("New desc".to_string()));
+            });
+    }
+}
+
+mod get_creator_collectibles_with_stats {
+
+    use super::*;
+
+    #[test]
+    fn get_creator_collectibles_with_stats_for_unknown_creator() {
+        init().run_as(alice(), |contract| {
+            assert_eq!(contract.get_creator_collectibles_with_stats(bob(), None, None).len(), 0);
+        });
+    }
+
+    #[test]
+    fn get_creator_collectibles_with_stats_after_claiming() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                contract.create_test_collectible(alice(), gate_id(2), 5);
+                contract.create_test_collectible(bob(), gate_id(3), 5);
+            })
+            .run_as(charlie(), |contract| {
+                for _ in 0..3 {
+                    contract.claim_token(gate_id(1));
+                }
+                contract.claim_token(gate_id(2));
+                contract.claim_token(gate_id(3));
+
+                let stats = contract
+                    .get_creator_collectibles_with_stats(alice(), None, None)
+                    .into_iter()
+                    .map(|(c, minted, remaining)| (c.gate_id, minted.0, remaining.0))
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    stats,
+                    vec![(gate_id(1).to_string(), 3, 7), (gate_id(2).to_string(), 1, 4)]
+                );
+            });
+    }
+
+    #[test]
+    fn get_creator_collectibles_with_stats_paginated() {
+        init().run_as(mintgate_admin(), |contract| {
+            for i in 1..=5 {
+                contract.create_test_collectible(alice(), gate_id(i), 10);
+            }
+
+            let page = |from_index: u64, limit: u32| {
+                contract
+                    .get_creator_collectibles_with_stats(
+                        alice(),
+                        Some(from_index.into()),
+                        Some(limit),
+                    )
+                    .into_iter()
+                    .map(|(c, _, _)| c.gate_id)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(page(0, 2), vec![gate_id(1).to_string(), gate_id(2).to_string()]);
+            assert_eq!(page(4, 2), vec![gate_id(5).to_string()]);
+            assert_eq!(page(5, 2), Vec::<String>::new());
+        });
+    }
+}
+
+mod get_collectibles_by_royalty {
+
+    use super::*;
+
+    fn gate_ids_by_royalty(
+        contract: &NftContract,
+        min: &str,
+        max: &str,
+        from_index: Option<U64>,
+        limit: Option<u32>,
+    ) -> Vec<GateId> {
+        contract
+            .get_collectibles_by_royalty(
+                min.parse().unwrap(),
+                max.parse().unwrap(),
+                from_index,
+                limit,
+            )
+            .into_iter()
+            .map(|collectible| collectible.gate_id)
+            .collect()
+    }
+
+    #[test]
+    fn get_collectibles_by_royalty_with_no_collectibles() {
+        init().run_as(alice(), |contract| {
+            assert!(gate_ids_by_royalty(contract, "0/1", "1/1", None, None).is_empty());
+        });
+    }
+
+    #[test]
+    fn get_collectibles_by_royalty_within_range() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_royalty_collectible(alice(), gate_id(1), 10, "5/100");
+            contract.create_royalty_collectible(alice(), gate_id(2), 10, "15/100");
+            contract.create_royalty_collectible(bob(), gate_id(3), 10, "30/100");
+
+            assert_eq!(
+                gate_ids_by_royalty(contract, "10/100", "20/100", None, None),
+                vec![gate_id(2).to_string()]
+            );
+            assert_eq!(
+                gate_ids_by_royalty(contract, "5/100", "15/100", None, None),
+                vec![gate_id(1).to_string(), gate_id(2).to_string()]
+            );
+            assert!(gate_ids_by_royalty(contract, "16/100", "29/100", None, None).is_empty());
+        });
+    }
+
+    #[test]
+    fn get_collectibles_by_royalty_paginated() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_royalty_collectible(alice(), gate_id(1), 10, "5/100");
+            contract.create_royalty_collectible(alice(), gate_id(2), 10, "15/100");
+            contract.create_royalty_collectible(bob(), gate_id(3), 10, "30/100");
+
+            assert!(gate_ids_by_royalty(contract, "10/100", "20/100", None, Some(1)).is_empty());
+            assert_eq!(
+                gate_ids_by_royalty(contract, "10/100", "20/100", Some(U64(1)), Some(1)),
+                vec![gate_id(2).to_string()]
+            );
+            assert!(
+                gate_ids_by_royalty(contract, "10/100", "20/100", Some(U64(2)), None).is_empty()
+            );
+            assert!(gate_ids_by_royalty(contract, "0/1", "1/1", Some(U64(9)), None).is_empty());
+        });
+    }
+}
+
+mod get_collectibles_by_gate_ids {
+
+    use super::*;
+
+    #[test]
+    fn get_collectibles_by_gate_ids_with_missing_gates() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            contract.create_test_collectible(bob(), gate_id(3), 10);
+
+            let collectibles = contract.get_collectibles_by_gate_ids(vec![
+                gate_id(3),
+                gate_id(2),
+                gate_id(1),
+                gate_id(3),
+                gate_id(4),
+            ]);
+            let gate_ids = collectibles
+                .iter()
+                .map(|collectible| collectible.as_ref().map(|c| c.gate_id.clone()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                gate_ids,
+                vec![
+                    Some(gate_id(3).to_string()),
+                    None,
+                    Some(gate_id(1).to_string()),
+                    Some(gate_id(3).to_string()),
+                    None,
+                ]
+            );
+            assert_eq!(collectibles[0].as_ref().unwrap().creator_id, bob().to_string());
+            assert_eq!(contract.get_collectibles_by_gate_ids(vec![]).len(), 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "At most 20 gate IDs are allowed to query at once")]
+    fn get_collectibles_by_too_many_gate_ids_should_panic() {
+        init().run_as(any(), |contract| {
+            contract.get_collectibles_by_gate_ids((0..21).map(gate_id).collect());
+        });
+    }
+}
+
+mod get_recent_collectibles {
+
+    use super::*;
+
+    fn recent_gate_ids(
+        contract: &NftContract,
+        from_index: Option<U64>,
+        limit: Option<u32>,
+    ) -> Vec<String> {
+        contract
+            .get_recent_collectibles(from_index, limit)
+            .into_iter()
+            .map(|collectible| collectible.gate_id)
+            .collect()
+    }
+
+    #[test]
+    fn get_recent_collectibles_newest_first() {
+        init().run_as(mintgate_admin(), |contract| {
+            assert_eq!(recent_gate_ids(contract, None, None), Vec::<String>::new());
+
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            contract.create_test_collectible(bob(), gate_id(2), 10);
+            contract.create_test_collectible(alice(), gate_id(3), 10);
+
+            assert_eq!(
+                recent_gate_ids(contract, None, None),
+                vec![gate_id(3).to_string(), gate_id(2).to_string(), gate_id(1).to_string()]
+            );
+            assert_eq!(
+                recent_gate_ids(contract, None, Some(2)),
+                vec![gate_id(3).to_string(), gate_id(2).to_string()]
+            );
+            assert_eq!(
+                recent_gate_ids(contract, Some(2.into()), None),
+                vec![gate_id(1).to_string()]
+            );
+            assert_eq!(recent_gate_ids(contract, Some(3.into()), None), Vec::<String>::new());
+        });
+    }
+
+    #[test]
+    fn get_recent_collectibles_skips_deleted() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            contract.create_test_collectible(alice(), gate_id(2), 10);
+            contract.create_test_collectible(alice(), gate_id(3), 10);
+
+            contract.delete_collectible(gate_id(2));
+
+            assert_eq!(
+                recent_gate_ids(contract, None, None),
+                vec![gate_id(3).to_string(), gate_id(1).to_string()]
+            );
+            assert_eq!(recent_gate_ids(contract, Some(1.into()), Some(1)), Vec::<String>::new());
+        });
+    }
+}
+
+mod delete_collectible {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Gate ID `Nekq22i3rvzDe7c51Yc8hU` was not found")]
+    fn delete_a_non_existent_collectible_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.delete_collectible(gate_id(0));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Gate ID `GPZkspuVGaZxwWoP6bJoWU` has already some claimed tokens")]
+    fn delete_a_claimed_gate_id_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            contract.claim_token(gate_id(1));
+            contract.delete_collectible(gate_id(1));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Unable to delete gate ID `GPZkspuVGaZxwWoP6bJoWU`")]
+    fn delete_a_collectible_from_non_creator_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.delete_collectible(gate_id(1));
+            });
+    }
+
+    #[test]
+    fn delete_a_collectible_from_creator() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            assert!(contract.get_collectible_by_gate_id(gate_id(1)).is_some());
+
+            contract.delete_collectible(gate_id(1));
+            assert!(contract.get_collectible_by_gate_id(gate_id(1)).is_none());
+            assert!(contract.get_collectibles_by_creator(alice()).is_empty());
+        });
+    }
+
+    #[test]
+    fn delete_a_collectible_from_admin() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                assert!(contract.get_collectible_by_gate_id(gate_id(1)).is_some());
+            })
+            .run_as(mintgate_admin(), |contract| {
+                contract.delete_collectible(gate_id(1));
+                assert!(contract.get_collectible_by_gate_id(gate_id(1)).is_none());
+                assert!(contract.get_collectibles_by_creator(alice()).is_empty());
+            });
+    }
+}
+
+mod can_delete_collectible {
+
+    use super::*;
+
+    #[test]
+    fn can_delete_a_non_existent_collectible() {
+        init().run_as(alice(), |contract| {
+            assert!(!contract.can_delete_collectible(gate_id(1), alice()));
+            assert!(!contract.can_delete_collectible(gate_id(1), mintgate_admin()));
+        });
+    }
+
+    #[test]
+    fn creator_and_admin_can_delete_a_collectible() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            assert!(contract.can_delete_collectible(gate_id(1), alice()));
+            assert!(contract.can_delete_collectible(gate_id(1), mintgate_admin()));
+        });
+    }
+
+    #[test]
+    fn stranger_cannot_delete_a_collectible() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            assert!(!contract.can_delete_collectible(gate_id(1), bob()));
+        });
+    }
+
+    #[test]
+    fn cannot_delete_a_collectible_with_tokens() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+                assert!(!contract.can_delete_collectible(gate_id(1), alice()));
+                assert!(!contract.can_delete_collectible(gate_id(1), mintgate_admin()));
+            });
+    }
+}
+
+mod admin_rebuild_creator_index {
+
+    use super::*;
+    use near_sdk::collections::{LookupMap, UnorderedSet};
+
+    /// Opens the contract's `collectibles_by_creator` index directly from storage.
+    /// The prefix `1` is the Borsh encoding of `Keys::CollectiblesByCreator`.
+    fn creator_index() -> LookupMap<AccountId, UnorderedSet<GateId>> {
+        LookupMap::new(vec![1u8])
+    }
+
+    fn gate_ids_by_creator(contract: &NftContract, creator_id: ValidAccountId) -> Vec<GateId> {
+        let mut gate_ids = contract
+            .get_collectibles_by_creator(creator_id)
+            .into_iter()
+            .map(|collectible| collectible.gate_id)
+            .collect::<Vec<_>>();
+        gate_ids.sort();
+        gate_ids
+    }
+
+    #[test]
+    #[should_panic(expected = "Operation is allowed only for admin")]
+    fn rebuild_from_non_admin_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.admin_rebuild_creator_index(alice());
+        });
+    }
+
+    #[test]
+    fn rebuild_a_corrupted_index() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                contract.create_test_collectible(alice(), gate_id(2), 10);
+                contract.create_test_collectible(bob(), gate_id(3), 10);
+
+                let mut index = creator_index();
+                let mut gids = index.get(&alice().to_string()).unwrap();
+                gids.remove(&gate_id(1).to_string());
+                gids.insert(&gate_id(3).to_string());
+                index.insert(&alice().to_string(), &gids);
+            })
+            .run_as(mintgate_admin(), |contract| {
+                contract.admin_rebuild_creator_index(alice());
+
+                let mut expected = vec![gate_id(1).to_string(), gate_id(2).to_string()];
+                expected.sort();
+                assert_eq!(gate_ids_by_creator(contract, alice()), expected);
+                assert_eq!(gate_ids_by_creator(contract, bob()), vec![gate_id(3).to_string()]);
+            });
+    }
+
+    #[test]
+    fn rebuild_a_missing_index() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                let mut index = creator_index();
+                index.remove(&alice().to_string()).unwrap().clear();
+                assert!(contract.get_collectibles_by_creator(alice()).is_empty());
+            })
+            .run_as(mintgate_admin(), |contract| {
+                contract.admin_rebuild_creator_index(alice());
+                assert_eq!(gate_ids_by_creator(contract, alice()), vec![gate_id(1).to_string()]);
+            });
+    }
+}
+
+mod export_collectible {
+
+    use super::*;
+    use mg_nft::CollectibleExport;
+
+    /// Exports `gate_id(1)` with two tokens, one owned by `bob` and one by `charlie`.
+    /// Tokens record their serial numbers.
+    fn export_from_source() -> CollectibleExport {
+        let mut export = None;
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_serial_numbers(gate_id(1), true);
+            })
+            .attach_deposit(CLAIM_DEPOSIT)
+            .run_as(bob(), |contract| {
+                contract.contract.claim_token(gate_id(1));
+                let token_id = contract.contract.claim_token(gate_id(1));
+                contract.attach_deposit(1);
+                contract.nft_transfer(charlie(), token_id, None, None);
+            })
+            .run_as(mintgate_admin(), |contract| {
+                export = Some(contract.export_collectible(gate_id(1)));
+            });
+
+        // Drops the storage of the source contract,
+        // so the next contract starts empty as if deployed on another account.
+        near_sdk::env::take_blockchain_interface();
+        export.unwrap()
+    }
+
+    #[test]
+    fn export_a_collectible_with_its_tokens() {
+        let export = export_from_source();
+        assert_eq!(export.collectible.gate_id, gate_id(1).to_string());
+        let owners = export.tokens.iter().map(|token| token.owner_id.clone()).collect::<Vec<_>>();
+        assert_eq!(owners, vec![bob().to_string(), charlie().to_string()]);
+    }
+
+    #[test]
+    fn import_an_exported_collectible_into_another_contract() {
+        let export = export_from_source();
+        let collectible = serde_json::to_value(&export.collectible).unwrap();
+        init().run_as(mintgate_admin(), |contract| {
+            contract.import_collectible(export);
+
+            let imported = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+            assert_eq!(serde_json::to_value(&imported).unwrap(), collectible);
+            assert_eq!(contract.nft_token(U64(0)).unwrap().owner_id, bob().to_string());
+            assert_eq!(contract.nft_token(U64(1)).unwrap().owner_id, charlie().to_string());
+            assert_eq!(contract.get_tokens_by_owner(bob()).len(), 1);
+            assert_eq!(contract.get_collectibles_by_creator(alice()).len(), 1);
+            assert_eq!(contract.nft_total_supply(), U64(2));
+            assert_eq!(contract.peek_next_token_id(), U64(2));
+            let recent = contract.get_recent_collectibles(None, None);
+            assert_eq!(recent.len(), 1);
+            assert_eq!(recent[0].gate_id, gate_id(1).to_string());
+
+            let extra = contract.nft_token(U64(1)).unwrap().metadata.extra.unwrap();
+            assert_eq!(serde_json::from_str::<serde_json::Value>(&extra).unwrap()["serial"], 2);
+        });
+    }
+
+    #[test]
+    fn export_an_imported_collectible_round_trips() {
+        let export = export_from_source();
+        let expected = serde_json::to_string(&export).unwrap();
+        init().run_as(mintgate_admin(), |contract| {
+            contract.import_collectible(export);
+            let export = contract.export_collectible(gate_id(1));
+            assert_eq!(serde_json::to_string(&export).unwrap(), expected);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Gate ID `GPZkspuVGaZxwWoP6bJoWU` already exists")]
+    fn import_an_existing_gate_id_should_panic() {
+        let export = export_from_source();
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            contract.import_collectible(export);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` already exists")]
+    fn import_an_existing_token_id_should_panic() {
+        let export = export_from_source();
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(2), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(2));
+            })
+            .run_as(mintgate_admin(), |contract| {
+                contract.import_collectible(export);
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` already exists")]
+    fn import_a_duplicated_token_id_should_panic() {
+        let mut export = export_from_source();
+        export.tokens[1].token_id = U64(0);
+        init().run_as(mintgate_admin(), |contract| {
+            contract.import_collectible(export);
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Royalty of gate ID `GPZkspuVGaZxwWoP6bJoWU` is locked and cannot be changed"
+    )]
+    fn import_a_locked_collectible_with_a_royalty_override_should_panic() {
+        let mut export = export_from_source();
+        export.collectible.royalty_locked = true;
+        export.tokens[1].royalty = Some("25/100".parse().unwrap());
+        init().run_as(mintgate_admin(), |contract| {
+            contract.import_collectible(export);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalty `31/100` of `GPZkspuVGaZxwWoP6bJoWU` is greater than max")]
+    fn import_a_collectible_with_royalty_over_max_should_panic() {
+        let mut export = export_from_source();
+        export.collectible.royalty = "31/100".parse().unwrap();
+        init().run_as(mintgate_admin(), |contract| {
+            contract.import_collectible(export);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalty `1/100` of `GPZkspuVGaZxwWoP6bJoWU` is less than min")]
+    fn import_a_token_with_royalty_below_min_should_panic() {
+        let mut export = export_from_source();
+        export.tokens[1].royalty = Some("1/100".parse().unwrap());
+        init().run_as(mintgate_admin(), |contract| {
+            contract.import_collectible(export);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` has already been used")]
+    fn import_a_burnt_token_id_should_panic() {
+        let export = export_from_source();
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(2), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(2));
+                contract.burn_token(token_id);
+            })
+            .run_as(mintgate_admin(), |contract| {
+                contract.import_collectible(export);
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Operation is allowed only for admin")]
+    fn export_by_non_admin_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.export_collectible(gate_id(1));
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Operation is allowed only for admin")]
+    fn import_by_non_admin_should_panic() {
+        let export = export_from_source();
+        init().run_as(alice(), |contract| {
+            contract.import_collectible(export);
+        });
+    }
+}
+
+mod stale_index {
+
+    use super::*;
+    use near_sdk::{borsh::BorshSerialize, env};
+
+    /// Removes `key` from the lookup of the `UnorderedMap` stored under `prefix`,
+    /// so the map no longer finds it while the indexes still refer to it.
+    fn remove_from_map<K: BorshSerialize>(prefix: u8, key: &K) {
+        let mut lookup_key = vec![prefix, b'i'];
+        lookup_key.extend(key.try_to_vec().unwrap());
+        env::storage_remove(&lookup_key);
+    }
+
+    /// The prefix `0` is the Borsh encoding of `Keys::Collectibles`.
+    fn remove_collectible(gate_id: ValidGateId) {
+        remove_from_map(0, gate_id.as_ref());
+    }
+
+    /// The prefix `3` is the Borsh encoding of `Keys::Tokens`.
+    fn remove_token(token_id: TokenId) {
+        remove_from_map(3, &token_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Gate ID `GPZkspuVGaZxwWoP6bJoWU` was not found")]
+    fn get_collectibles_by_creator_with_orphaned_gate_id_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            remove_collectible(gate_id(1));
+            contract.get_collectibles_by_creator(alice());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Gate ID `GPZkspuVGaZxwWoP6bJoWU` was not found")]
+    fn nft_tokens_with_orphaned_gate_id_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+                remove_collectible(gate_id(1));
+                contract.nft_tokens(None, None);
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` was not found")]
+    fn get_tokens_by_owner_with_orphaned_token_id_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                remove_token(token_id);
+                contract.get_tokens_by_owner(bob());
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` was not found")]
+    fn nft_tokens_for_owner_with_orphaned_token_id_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                remove_token(token_id);
+                contract.nft_tokens_for_owner(bob(), None, None);
+            });
+    }
+}
+
+mod claim_token {
+
+    use super::*;
+
+    #[test]
+    fn claim_a_token() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                assert_eq!(contract.get_collectibles_by_creator(alice()).len(), 1);
+            })
+            .run_as(bob(), |contract| {
+                let uri = format!("{}{}", base_uri().unwrap(), gate_id(1));
+
+                for _i in 0..3 {
+                    let token_id = contract.claim_token(gate_id(1));
+                    assert_eq!(contract.nft_token_uri(token_id).unwrap(), uri);
+                }
+
+                let tokens = contract.get_tokens_by_owner(bob());
+                assert_eq!(tokens.len(), 3);
+
+                let c = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+                assert_eq!(c.current_supply, 7);
+            });
+    }
+
+    #[test]
+    fn peek_next_token_id_before_claim() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                assert_eq!(contract.peek_next_token_id(), U64(0));
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.burn_token(token_id);
+
+                let next_token_id = contract.peek_next_token_id();
+                assert_eq!(contract.peek_next_token_id(), next_token_id);
+                assert_eq!(contract.claim_token(gate_id(1)), next_token_id);
+                assert_eq!(contract.peek_next_token_id(), U64(next_token_id.0 + 1));
+            });
+    }
+
+    #[test]
+    fn claim_a_full_token() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .attach_deposit(CLAIM_DEPOSIT)
+            .run_as(bob(), |contract| {
+                let token = contract.claim_token_full(gate_id(1));
+                assert_eq!(token.owner_id, bob().to_string());
+                assert_eq!(token.gate_id, gate_id(1).to_string());
+                assert_eq!(token.approvals.len(), 0);
+                assert_eq!(token.metadata.extra, None);
+
+                let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+                assert_eq!(token.metadata, collectible.metadata);
+                assert_eq!(contract.nft_token(token.token_id), Some(token));
+            });
+    }
+
+    #[test]
+    fn claim_tokens_with_distinct_serials() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_serial_numbers(gate_id(1), true);
+            })
+            .attach_deposit(CLAIM_DEPOSIT)
+            .run_as(bob(), |contract| {
+                let token_id1 = contract.contract.claim_token(gate_id(1));
+                let token_id2 = contract.contract.claim_token(gate_id(1));
+                contract.burn_token(token_id2);
+                let token_id3 = contract.contract.claim_token(gate_id(1));
+
+                let extra = |token_id| contract.nft_token(token_id).unwrap().metadata.extra;
+                assert_eq!(extra(token_id1), Some(r#"{"serial":1}"#.to_string()));
+                assert_eq!(extra(token_id3), Some(r#"{"serial":3}"#.to_string()));
+
+                let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+                assert_eq!(collectible.metadata.extra, None);
+                assert_eq!(collectible.minted_count, 3);
+            });
+    }
+
+    #[test]
+    fn nft_tokens_should_include_serials() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_serial_numbers(gate_id(1), true);
+            })
+            .attach_deposit(CLAIM_DEPOSIT)
+            .run_as(bob(), |contract| {
+                let token_id = contract.contract.claim_token(gate_id(1));
+                let tokens = contract.nft_tokens(None, None);
+                assert_eq!(tokens.len(), 1);
+                assert_eq!(Some(&tokens[0]), contract.nft_token(token_id).as_ref());
+                assert_eq!(tokens[0].metadata.extra, Some(r#"{"serial":1}"#.to_string()));
+            });
+    }
+
+    #[test]
+    fn claim_tokens_without_serials_by_default() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                assert_eq!(contract.nft_token(token_id).unwrap().metadata.extra, None);
+                assert_eq!(
+                    contract.get_collectible_by_gate_id(gate_id(1)).unwrap().minted_count,
+                    1
+                );
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the creator of gate ID `GPZkspuVGaZxwWoP6bJoWU` is allowed")]
+    fn set_serial_numbers_by_non_creator_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.set_serial_numbers(gate_id(1), true);
+            });
+    }
+
+    #[test]
+    fn claim_a_few_tokens() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                assert_eq!(contract.get_collectibles_by_creator(alice()).len(), 1);
+            })
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(bob(), gate_id(2), 15);
+                assert_eq!(contract.get_collectibles_by_creator(bob()).len(), 1);
+                contract.claim_token(gate_id(2));
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough deposit to cover storage")]
+    fn claim_a_token_without_deposit_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.contract.claim_token(gate_id(1));
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Gate ID `Nekq22i3rvzDe7c51Yc8hU` was not found")]
+    fn claim_a_token_of_non_existent_gate_id_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.claim_token(gate_id(0));
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Tokens for gate id `GPZkspuVGaZxwWoP6bJoWU` have already been claimed"
+    )]
+    fn claim_a_token_with_no_supply_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 1);
+                contract.claim_token(gate_id(1));
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+            });
+    }
+
+    #[test]
+    fn claim_a_token_with_max_supply() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), u16::MAX);
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+
+                let c = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+                assert_eq!(c.current_supply, u16::MAX - 1);
+            });
+    }
+
+    #[test]
+    fn claim_the_last_token() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 1);
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+
+                let c = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+                assert_eq!(c.current_supply, 0);
+            });
+    }
+
+    #[test]
+    fn claim_and_get_a_few_tokens() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 100);
+            })
+            .run_as(alice(), |contract| {
+                assert_eq!(contract.get_collectibles_by_creator(alice()).len(), 1);
+
+                assert_eq!(contract.nft_tokens(None, Some(0)).len(), 0);
+                assert_eq!(contract.nft_tokens(None, Some(10)).len(), 0);
+                assert_eq!(contract.nft_tokens(Some(50.into()), None).len(), 0);
+
+                for _i in 0..20 {
+                    contract.claim_token(gate_id(1));
+                }
+            })
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(bob(), gate_id(2), 15);
+            })
+            .run_as(bob(), |contract| {
+                assert_eq!(contract.get_collectibles_by_creator(bob()).len(), 1);
+
+                for _i in 0..10 {
+                    contract.claim_token(gate_id(2));
+                }
+
+                assert_eq!(contract.nft_total_supply(), U64(30));
+                assert_eq!(contract.nft_supply_for_owner(alice()), U64(20));
+
+                assert_eq!(contract.nft_tokens(None, None).len(), 30);
+                assert_eq!(contract.nft_tokens_for_owner(alice(), None, None).len(), 20);
+
+                assert_eq!(contract.nft_tokens(None, Some(10)).len(), 10);
+                assert_eq!(contract.nft_tokens_for_owner(alice(), None, Some(10)).len(), 10);
+
+                assert_eq!(contract.nft_tokens(Some(25.into()), Some(10)).len(), 5);
+                assert_eq!(
+                    contract.nft_tokens_for_owner(alice(), Some(15.into()), Some(10)).len(),
+                    5
+                );
+            });
+    }
+}
+
+mod claim_token_to {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Gate ID `Nekq22i3rvzDe7c51Yc8hU` was not found")]
+    fn claim_a_non_existent_gate_id_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.claim_token_to(gate_id(0), bob(), None);
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Only the creator of gate ID `GPZkspuVGaZxwWoP6bJoWU` is allowed to perform this operation"
+    )]
+    fn claim_from_non_creator_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token_to(gate_id(1), bob(), None);
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalty `31/100` of `GPZkspuVGaZxwWoP6bJoWU` is greater than max")]
+    fn claim_with_a_royalty_override_above_max_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.claim_token_to(gate_id(1), bob(), Some("31/100"));
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalty `1/100` of `GPZkspuVGaZxwWoP6bJoWU` is less than min")]
+    fn claim_with_a_royalty_override_below_min_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.claim_token_to(gate_id(1), bob(), Some("1/100"));
+            });
+    }
+
+    #[test]
+    fn claim_a_token_to_another_account() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.claim_token_to(gate_id(1), bob(), None);
+                contract.claim_token_to(gate_id(1), bob(), Some("20/100"));
+                assert_eq!(contract.nft_supply_for_owner(alice()), U64(0));
+
+                let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+                assert_eq!(collectible.current_supply, 8);
+                assert_eq!(collectible.minted_tokens.len(), 2);
+            });
+    }
+}
+
+mod gate_claimable {
+
+    use super::*;
+
+    #[test]
+    fn pause_and_resume_claims() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                assert!(contract.get_collectible_by_gate_id(gate_id(1)).unwrap().claimable);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_gate_claimable(gate_id(1), false);
+                assert!(!contract.get_collectible_by_gate_id(gate_id(1)).unwrap().claimable);
+            })
+            .run_as(mintgate_admin(), |contract| {
+                contract.set_gate_claimable(gate_id(1), true);
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+                assert_eq!(contract.get_tokens_by_owner(bob()).len(), 1);
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Claiming tokens for gate ID `GPZkspuVGaZxwWoP6bJoWU` is paused")]
+    fn claim_token_when_paused_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                contract.set_gate_claimable(gate_id(1), false);
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Only the creator of gate ID `GPZkspuVGaZxwWoP6bJoWU` or the admin are allowed"
+    )]
+    fn set_gate_claimable_by_other_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.set_gate_claimable(gate_id(1), false);
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Gate ID `GPZkspuVGaZxwWoP6bJoWU` was not found")]
+    fn set_gate_claimable_of_non_existent_gate_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.set_gate_claimable(gate_id(1), false);
+        });
+    }
+}
+
+mod has_claimed {
+
+    use super::*;
+
+    #[test]
+    fn has_claimed_only_for_claimer() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                assert!(!contract.has_claimed(gate_id(1), bob()));
+                contract.claim_token(gate_id(1));
+                assert!(contract.has_claimed(gate_id(1), bob()));
+                assert!(!contract.has_claimed(gate_id(1), charlie()));
+            });
+    }
+
+    #[test]
+    fn has_claimed_after_transfer() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_transfer(charlie(), token_id, None, None);
+                assert!(contract.has_claimed(gate_id(1), bob()));
+                assert!(!contract.has_claimed(gate_id(1), charlie()));
+            });
+    }
+
+    #[test]
+    fn has_claimed_for_non_existent_gate() {
+        init().run_as(bob(), |contract| {
+            assert!(!contract.has_claimed(gate_id(1), bob()));
+        });
+    }
+}
+
+mod remaining_claims_for {
+
+    use super::*;
+
+    #[test]
+    fn remaining_claims_with_no_limit() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                assert_eq!(contract.remaining_claims_for(gate_id(1), bob()), 10);
+                contract.claim_token(gate_id(1));
+                contract.claim_token(gate_id(1));
+                assert_eq!(contract.remaining_claims_for(gate_id(1), bob()), 8);
+                assert_eq!(contract.remaining_claims_for(gate_id(1), charlie()), 8);
+            });
+    }
+
+    #[test]
+    fn remaining_claims_with_partial_limit() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_max_claims_per_account(gate_id(1), Some(3));
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+                assert_eq!(contract.remaining_claims_for(gate_id(1), bob()), 2);
+                assert_eq!(contract.remaining_claims_for(gate_id(1), charlie()), 3);
+            });
+    }
+
+    #[test]
+    fn remaining_claims_bounded_by_supply() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 2);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_max_claims_per_account(gate_id(1), Some(5));
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+                assert_eq!(contract.remaining_claims_for(gate_id(1), bob()), 1);
+                assert_eq!(contract.remaining_claims_for(gate_id(1), charlie()), 1);
+            });
+    }
+
+    #[test]
+    fn remaining_claims_with_limit_reached() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_max_claims_per_account(gate_id(1), Some(2));
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.claim_token(gate_id(1));
+                assert_eq!(contract.remaining_claims_for(gate_id(1), bob()), 0);
+
+                contract.nft_transfer(charlie(), token_id, None, None);
+                assert_eq!(contract.remaining_claims_for(gate_id(1), bob()), 0);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_max_claims_per_account(gate_id(1), Some(1));
+                assert_eq!(contract.remaining_claims_for(gate_id(1), bob()), 0);
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Account `bob` has reached the claim limit for gate ID `GPZkspuVGaZxwWoP6bJoWU`"
+    )]
+    fn claim_token_over_limit_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_max_claims_per_account(gate_id(1), Some(1));
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+                contract.claim_token(gate_id(1));
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the creator of gate ID `GPZkspuVGaZxwWoP6bJoWU`")]
+    fn set_max_claims_per_account_by_non_creator_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.set_max_claims_per_account(gate_id(1), Some(1));
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Gate ID `GPZkspuVGaZxwWoP6bJoWU` was not found")]
+    fn remaining_claims_for_non_existent_gate_should_panic() {
+        init().run_as(bob(), |contract| {
+            contract.remaining_claims_for(gate_id(1), bob());
+        });
+    }
+}
+
+mod is_gate_sold_out {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Gate ID `GPZkspuVGaZxwWoP6bJoWU` was not found")]
+    fn is_gate_sold_out_for_non_existent_gate_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.is_gate_sold_out(gate_id(1));
+        });
+    }
+
+    #[test]
+    fn is_gate_sold_out_after_claiming_all_supply() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 3);
+            })
+            .run_as(bob(), |contract| {
+                for _ in 0..3 {
+                    assert!(!contract.is_gate_sold_out(gate_id(1)));
+                    contract.claim_token(gate_id(1));
+                }
+                assert!(contract.is_gate_sold_out(gate_id(1)));
+            });
+    }
+}
+
+mod nft_token_uri {
+
+    use super::*;
+
+    #[test]
+    fn get_token_uri_with_base_uri() {
+        init_contract("5/100", "30/100", metadata(base_uri())).run_as(
+            mintgate_admin(),
+            |contract| {
+                assert_eq!(contract.nft_token_uri(U64(0)), None);
+
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                let uri = format!("{}{}", base_uri().unwrap(), gate_id(1));
+                let token_id = contract.claim_token(gate_id(1));
+                assert_eq!(contract.nft_token_uri(token_id).unwrap(), uri);
+            },
+        );
+    }
+
+    #[test]
+    fn get_token_uri_with_no_slash_base_uri() {
+        init_contract("5/100", "30/100", metadata(Some("https://mintgate/t".to_string()))).run_as(
+            mintgate_admin(),
+            |contract| {
+                assert_eq!(contract.nft_token_uri(U64(0)), None);
+
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                let uri = format!("{}/{}", "https://mintgate/t", gate_id(1));
+                let token_id = contract.claim_token(gate_id(1));
+                assert_eq!(contract.nft_token_uri(token_id).unwrap(), uri);
+            },
+        );
+    }
+
+    #[test]
+    fn get_token_uri_with_none_base_uri() {
+        init_contract("5/100", "30/100", metadata(None)).run_as(mintgate_admin(), |contract| {
+            assert_eq!(contract.nft_token_uri(U64(0)), None);
+
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            let token_id = contract.claim_token(gate_id(1));
+            assert_eq!(contract.nft_token_uri(token_id), None);
+        });
+    }
+}
+mod burn_token {
+
+    use super::*;
+
+    /// Returns the data of the last `nft_burn` event logged.
+    fn last_burn_event() -> Vec<NftBurnEvent> {
+        let logs = near_sdk::test_utils::get_logs();
+        let log =
+            logs.iter().rev().find(|log| log.starts_with(EVENT_JSON)).expect("No event logged");
+        let event: serde_json::Value = serde_json::from_str(&log[EVENT_JSON.len()..]).unwrap();
+        assert_eq!(event["standard"], "nep171");
+        assert_eq!(event["version"], "1.0.0");
+        assert_eq!(event["event"], "nft_burn");
+        serde_json::from_value(event["data"].clone()).unwrap()
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` was not found")]
+    fn burn_a_non_existent_token_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.burn_token(0.into());
+        });
+    }
+
+    #[test]
+    fn burn_a_token_keeps_the_total_minted() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.claim_token(gate_id(1));
+                contract.claim_token(gate_id(1));
+                contract.burn_token(token_id);
+
+                let event = last_burn_event();
+                assert_eq!(event.len(), 1);
+                assert_eq!(event[0].owner_id, bob().to_string());
+                assert_eq!(event[0].token_ids, vec![token_id]);
+                assert_eq!(event[0].authorized_id, None);
+                let logs = near_sdk::test_utils::get_logs();
+                assert!(!logs.last().unwrap().contains("authorized_id"));
+
+                assert_eq!(contract.nft_total_minted(), U64(3));
+                assert_eq!(contract.nft_total_supply(), U64(2));
+            });
+    }
+
+    #[test]
+    fn burn_a_few_tokens() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            let token_id = contract.claim_token(gate_id(1));
+            contract.burn_token(token_id);
+            let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+            assert_eq!(collectible.metadata.copies.unwrap(), 9);
+
+            let token_id = contract.claim_token(gate_id(1));
+            contract.nft_approve(token_id, bob(), approve_msg(10));
+            contract.burn_token(token_id);
+            let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+            assert_eq!(collectible.metadata.copies.unwrap(), 8);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` does not belong to account `bob`")]
+    fn transfer_a_non_approved_token_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                contract.claim_token(gate_id(1));
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.burn_token(token_id);
+            });
+    }
+
+    #[test]
+    fn burn_a_token_by_approved_account() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, market(), approve_msg(10));
+            })
+            .run_as(market(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.burn_token(token_id);
+
+                let event = last_burn_event();
+                assert_eq!(event.len(), 1);
+                assert_eq!(event[0].owner_id, bob().to_string());
+                assert_eq!(event[0].token_ids, vec![token_id]);
+                assert_eq!(event[0].authorized_id, Some(market().to_string()));
+
+                assert!(contract.nft_token(token_id).is_none());
+                assert_eq!(contract.nft_supply_for_owner(bob()), U64(1));
+                assert_eq!(contract.nft_total_supply(), U64(1));
+                let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+                assert_eq!(collectible.metadata.copies, Some(9));
+                assert_eq!(collectible.current_supply, 8);
+                assert_eq!(collectible.minted_tokens, vec![U64(0)]);
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Approval of token ID `U64(0)` for `market` has expired")]
+    fn burn_a_token_by_approved_account_after_expiration_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let expires_at = contract.context.block_timestamp / 1_000_000 + 5;
+                let msg = NftApproveMsg { min_price: 10.into(), expires_at: Some(expires_at) };
+                contract.nft_approve(token_id, market(), serde_json::to_string(&msg).ok());
+                contract.context.block_timestamp += 10_000_000;
+            })
+            .run_as(market(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.burn_token(token_id);
+            });
+    }
+
+    #[test]
+    fn burn_a_token_out_of_many_claimed() {
+        let mut context = init();
+        context.run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 1000);
+        });
+        context.attach_deposit(CLAIM_DEPOSIT);
+        for _ in 0..300 {
+            context.run_as(mintgate_admin(), |contract| {
+                contract.contract.claim_token(gate_id(1));
+            });
+        }
+        context.attach_deposit(0);
+
+        context
+            .run_as(mintgate_admin(), |contract| {
+                contract.burn_token(U64(150));
+                let minted_tokens =
+                    contract.get_collectible_by_gate_id(gate_id(1)).unwrap().minted_tokens;
+                assert_eq!(minted_tokens.len(), 299);
+                assert!(!minted_tokens.contains(&U64(150)));
+            })
+            .attach_deposit(CLAIM_DEPOSIT)
+            .run_as(mintgate_admin(), |contract| {
+                let token_id = contract.contract.claim_token(gate_id(1));
+                contract.attach_deposit(0);
+                assert_eq!(token_id, U64(300));
+                assert_eq!(contract.nft_token(U64(299)).unwrap().token_id, U64(299));
+
+                let minted_tokens =
+                    contract.get_collectible_by_gate_id(gate_id(1)).unwrap().minted_tokens;
+                assert_eq!(minted_tokens.len(), 300);
+                assert!(minted_tokens.windows(2).all(|w| w[0].0 < w[1].0));
+            })
+            .run_as(mintgate_admin(), |contract| {
+                contract.burn_token(U64(0));
+                contract.burn_token(U64(300));
+                let minted_tokens =
+                    contract.get_collectible_by_gate_id(gate_id(1)).unwrap().minted_tokens;
+                assert_eq!(minted_tokens.len(), 298);
+                assert_eq!(minted_tokens.first(), Some(&U64(1)));
+                assert_eq!(minted_tokens.last(), Some(&U64(299)));
+            });
+    }
+}
+
+mod nft_transfer {
+
+    use super::*;
+
+    #[test]
+    fn transfer_a_token() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_transfer(charlie(), token_id, None, None);
+
+                let ts = contract.get_tokens_by_owner(charlie());
+                assert_eq!(ts.len(), 1);
+            });
+    }
+
+    #[test]
+    fn transfer_a_token_with_memo() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let memo = "X".repeat(mg_nft::MAX_MEMO_LEN);
+                contract.nft_transfer(charlie(), token_id, None, Some(memo.clone()));
+
+                assert!(near_sdk::test_utils::get_logs().contains(&format!("Memo: {}", memo)));
+                assert_eq!(contract.get_tokens_by_owner(charlie()).len(), 1);
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid argument for gate ID `GPZkspuVGaZxwWoP6bJoWU`: `memo` exceeds 256 bytes"
+    )]
+    fn transfer_a_token_with_long_memo_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let memo = "X".repeat(mg_nft::MAX_MEMO_LEN + 1);
+                contract.nft_transfer(charlie(), token_id, None, Some(memo));
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid argument for gate ID `GPZkspuVGaZxwWoP6bJoWU`: `memo` exceeds 256 bytes"
+    )]
+    fn transfer_payout_a_token_with_long_memo_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let memo = "X".repeat(mg_nft::MAX_MEMO_LEN + 1);
+                contract.nft_transfer_payout(charlie(), token_id, None, Some(memo), None);
+            });
+    }
+
+    #[test]
+    fn transfer_a_token_by_approved_account_before_expiration() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let expires_at = contract.context.block_timestamp / 1_000_000 + 5;
+                let msg = NftApproveMsg { min_price: 10.into(), expires_at: Some(expires_at) };
+                contract.nft_approve(token_id, market(), serde_json::to_string(&msg).ok());
+            })
+            .run_as(market(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.nft_transfer(charlie(), token_id, None, None);
+                assert_eq!(contract.get_tokens_by_owner(charlie()).len(), 1);
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Approval of token ID `U64(0)` for `market` has expired")]
+    fn transfer_a_token_by_approved_account_after_expiration_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let expires_at = contract.context.block_timestamp / 1_000_000 + 5;
+                let msg = NftApproveMsg { min_price: 10.into(), expires_at: Some(expires_at) };
+                contract.nft_approve(token_id, market(), serde_json::to_string(&msg).ok());
+                contract.context.block_timestamp += 10_000_000;
+            })
+            .run_as(market(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.nft_transfer(charlie(), token_id, None, None);
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(99)` was not found")]
+    fn transfer_a_non_existent_token_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.nft_transfer(charlie(), 99.into(), None, None);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Sender `bob` is not authorized to make transfer")]
+    fn transfer_a_non_approved_token_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                contract.claim_token(gate_id(1));
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.nft_transfer(charlie(), token_id, None, None);
+            });
+    }
+}
+
+mod nft_transfer_call {
+
+    use super::*;
+
+    #[test]
+    fn transfer_call_a_token() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_transfer_call(charlie(), token_id, None, None, "msg".to_string());
+
+                assert!(contract.get_tokens_by_owner(bob()).is_empty());
+                assert_eq!(contract.get_tokens_by_owner(charlie()).len(), 1);
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Sender `charlie` is not authorized to make transfer")]
+    fn transfer_call_a_non_approved_token_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                contract.claim_token(gate_id(1));
+            })
+            .run_as(charlie(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.nft_transfer_call(bob(), token_id, None, None, "msg".to_string());
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Not enough gas attached, more than `U64(35000000000000)` is required"
+    )]
+    fn transfer_call_with_insufficient_gas_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.context.prepaid_gas = 20_000_000_000_000;
+                contract.nft_transfer_call(charlie(), token_id, None, None, "msg".to_string());
+            });
+    }
+}
+
+mod get_user_portfolio {
+    use super::*;
+
+    #[test]
+    fn get_user_portfolio_with_no_tokens() {
+        init().run_as(any(), |contract| {
+            assert!(contract.get_user_portfolio(bob(), None, None).is_empty());
+        });
+    }
+
+    #[test]
+    fn get_user_portfolio_should_flag_listed_tokens() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let listed_id = contract.claim_token(gate_id(1));
+                let owned_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(listed_id, market(), approve_msg(10));
+
+                let mut portfolio = contract
+                    .get_user_portfolio(bob(), None, None)
+                    .into_iter()
+                    .map(|(token, listed)| (token.token_id, listed))
+                    .collect::<Vec<_>>();
+                portfolio.sort_by_key(|(token_id, _)| token_id.0);
+                assert_eq!(portfolio, vec![(listed_id, true), (owned_id, false)]);
+            });
+    }
+
+    #[test]
+    fn get_user_portfolio_with_pagination() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                for _ in 0..3 {
+                    contract.claim_token(gate_id(1));
+                }
+                assert_eq!(contract.get_user_portfolio(bob(), None, Some(2)).len(), 2);
+                assert_eq!(contract.get_user_portfolio(bob(), Some(U64(2)), Some(2)).len(), 1);
+            });
+    }
+}
+
+mod get_owners {
+
+    use super::*;
+
+    fn sorted_owners(contract: &NftContractChecker) -> Vec<AccountId> {
+        let mut owners = contract.get_owners(None, None);
+        owners.sort();
+        owners
+    }
+
+    #[test]
+    fn get_owners_with_no_tokens() {
+        init().run_as(alice(), |contract| {
+            assert!(contract.get_owners(None, None).is_empty());
+        });
+    }
+
+    #[test]
+    fn get_owners_follows_current_holders() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(charlie(), |contract| {
+                contract.claim_token(gate_id(1));
+            })
+            .run_as(bob(), |contract| {
+                let token_id1 = contract.claim_token(gate_id(1));
+                let token_id2 = contract.claim_token(gate_id(1));
+                assert_eq!(sorted_owners(contract), vec![bob().to_string(), charlie().to_string()]);
+
+                contract.nft_transfer(alice(), token_id1, None, None);
+                assert_eq!(
+                    sorted_owners(contract),
+                    vec![alice().to_string(), bob().to_string(), charlie().to_string()]
+                );
+
+                contract.nft_transfer(charlie(), token_id2, None, None);
+                assert_eq!(
+                    sorted_owners(contract),
+                    vec![alice().to_string(), charlie().to_string()]
+                );
+            })
+            .run_as(alice(), |contract| {
+                let token_id = contract.get_tokens_by_owner(alice())[0].token_id;
+                contract.burn_token(token_id);
+                assert_eq!(sorted_owners(contract), vec![charlie().to_string()]);
+            });
+    }
+
+    #[test]
+    fn get_owners_paginated() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.claim_token(gate_id(1));
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+            })
+            .run_as(charlie(), |contract| {
+                contract.claim_token(gate_id(1));
+
+                let all = contract.get_owners(None, None);
+                assert_eq!(all.len(), 3);
+                assert_eq!(contract.get_owners(None, Some(2)), all[..2].to_vec());
+                assert_eq!(contract.get_owners(Some(1.into()), Some(1)), all[1..2].to_vec());
+                assert!(contract.get_owners(Some(3.into()), None).is_empty());
+            });
+    }
+}
+
+mod get_gate_holders {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Gate ID `GPZkspuVGaZxwWoP6bJoWU` was not found")]
+    fn get_gate_holders_of_non_existent_gate_id_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.get_gate_holders(gate_id(1), None, None);
+        });
+    }
+
+    #[test]
+    fn get_gate_holders_with_two_owners() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                contract.create_test_collectible(alice(), gate_id(2), 10);
+                assert!(contract.get_gate_holders(gate_id(1), None, None).is_empty());
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+                contract.claim_token(gate_id(2));
+            })
+            .run_as(charlie(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.claim_token(gate_id(1));
+                contract.burn_token(token_id);
+            })
+            .run_as(alice(), |contract| {
+                let holders = contract.get_gate_holders(gate_id(1), None, None);
+                assert_eq!(
+                    holders,
+                    vec![(U64(0), bob().to_string()), (U64(3), charlie().to_string())]
+                );
+                assert_eq!(
+                    contract.get_gate_holders(gate_id(1), Some(U64(1)), Some(1)),
+                    vec![(U64(3), charlie().to_string())]
+                );
+                assert_eq!(
+                    contract.get_gate_holders(gate_id(2), None, None),
+                    vec![(U64(1), bob().to_string())]
+                );
+            });
+    }
+}
+
+mod get_gate_holder_count {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Gate ID `GPZkspuVGaZxwWoP6bJoWU` was not found")]
+    fn get_gate_holder_count_of_non_existent_gate_id_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.get_gate_holder_count(gate_id(1));
+        });
+    }
+
+    #[test]
+    fn get_gate_holder_count_after_transfers() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                assert_eq!(contract.get_gate_holder_count(gate_id(1)), U64(0));
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+                contract.claim_token(gate_id(1));
+            })
+            .run_as(charlie(), |contract| {
+                contract.claim_token(gate_id(1));
+                assert_eq!(contract.get_gate_holder_count(gate_id(1)), U64(2));
+
+                let token_id = contract.last_claimed_token();
+                contract.nft_transfer(bob(), token_id, None, None);
+                assert_eq!(contract.get_gate_holder_count(gate_id(1)), U64(1));
+            });
+    }
+}
+
+mod nft_approve {
+
+    use super::*;
+
+    #[test]
+    fn nft_approve_with_short_listing_fee_should_undo_the_approval() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.attach_deposit(5);
+                contract.nft_approve(token_id, market(), approve_msg(10));
+
+                let pending = PendingApprovals {
+                    owner_id: alice().into(),
+                    account_id: market().into(),
+                    tokens: vec![(token_id, U64(1), None)],
+                    deposit: U128(5),
+                };
+                set_promise_result(&mut contract.context, PromiseResult::Failed);
+                assert!(!contract.contract.contract.resolve_approve(pending));
+
+                assert_eq!(transfers(), vec![(alice().to_string(), 5)]);
+                assert!(contract.nft_token(token_id).unwrap().approvals.is_empty());
+            })
+            .run_as(alice(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.attach_deposit(0);
+                contract.nft_approve(token_id, market(), approve_msg(10));
+                let token = contract.nft_token(token_id).unwrap();
+                assert_eq!(token.approvals[market().as_ref()], TokenApproval::new(2, U128(10)));
+            });
+    }
+
+    #[test]
+    fn nft_approve_accepted_by_the_market_should_keep_the_approval() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            let token_id = contract.claim_token(gate_id(1));
+            contract.nft_approve(token_id, market(), approve_msg(10));
+
+            let pending = PendingApprovals {
+                owner_id: mintgate_admin().into(),
+                account_id: market().into(),
+                tokens: vec![(token_id, U64(1), None)],
+                deposit: U128(0),
+            };
+            set_promise_result(&mut contract.context, PromiseResult::Successful(Vec::new()));
+            assert!(contract.contract.contract.resolve_approve(pending));
+
+            assert_eq!(transfers(), vec![]);
+            let token = contract.nft_token(token_id).unwrap();
+            assert_eq!(token.approvals[market().as_ref()], TokenApproval::new(1, U128(10)));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "The msg argument must contain the minimum price")]
+    fn nft_approve_with_no_msg_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.nft_approve(0.into(), bob(), None);
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The msg argument is not valid JSON: EOF while parsing a value at line 1 column 0"
+    )]
+    fn nft_approve_with_empty_msg_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.nft_approve(0.into(), bob(), Some("".to_string()));
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid field `min_price` in msg: field is missing, expected a string of yoctoNEARs"
+    )]
+    fn nft_approve_with_no_min_price_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.nft_approve(0.into(), bob(), Some("{}".to_string()));
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid field `min_price` in msg: expected a string of yoctoNEARs, found `"
+    )]
+    fn nft_approve_with_negative_min_price_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.nft_approve(0.into(), bob(), Some(r#"{"min_price":"-1"}"#.to_string()));
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid field `min_price` in msg: expected a string of yoctoNEARs, found `"
+    )]
+    fn nft_approve_with_non_numeric_min_price_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.nft_approve(0.into(), bob(), Some(r#"{"min_price":"ten"}"#.to_string()));
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid field `min_price` in msg: expected a string of yoctoNEARs, found `10`"
+    )]
+    fn nft_approve_with_numeric_min_price_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.nft_approve(0.into(), bob(), Some(r#"{"min_price":10}"#.to_string()));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(99)` was not found")]
+    fn nft_approve_a_non_existent_token_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.nft_approve(99.into(), bob(), approve_msg(10));
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Not enough gas attached, more than `U64(40000000000000)` is required"
+    )]
+    fn nft_approve_with_insufficient_gas_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            let token_id = contract.claim_token(gate_id(1));
+            contract.context.prepaid_gas = 20_000_000_000_000;
+            contract.nft_approve(token_id, bob(), approve_msg(10));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` does not belong to account `bob`")]
+    fn nft_approve_by_an_approved_account_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, bob(), approve_msg(10));
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.nft_approve(token_id, charlie(), approve_msg(10));
+            });
+    }
+
+    #[test]
+    fn nft_approve_a_token() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            let token_id = contract.claim_token(gate_id(1));
+            contract.nft_approve(token_id, bob(), approve_msg(10));
+
+            let token = contract.nft_token(token_id).unwrap();
+            assert_eq!(token.approval_counter, U64(1));
+            assert_eq!(token.approvals.len(), 1);
+            assert_eq!(token.approvals[bob().as_ref()], TokenApproval::new(1, U128(10)));
+        });
+    }
+
+    #[test]
+    fn nft_approve_a_token_should_update_modified_at() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+                contract.context.block_timestamp += 5_000_000;
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.nft_approve(token_id, market(), approve_msg(10));
+
+                let token = contract.nft_token(token_id).unwrap();
+                assert!(token.modified_at > token.created_at);
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "At most one approval is allowed per Token")]
+    fn nft_approve_a_token_twice_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            let token_id = contract.claim_token(gate_id(1));
+            contract.nft_approve(token_id, bob(), approve_msg(10));
+            contract.nft_approve(token_id, charlie(), approve_msg(15));
+        });
+    }
+
+    #[test]
+    fn nft_approve_before_transfer_a_token() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, market(), approve_msg(10));
+
+                let token = contract.nft_token(token_id).unwrap();
+                assert_eq!(token.approval_counter, U64(1));
+                assert_eq!(token.approvals.len(), 1);
+                assert_eq!(token.approvals[market().as_ref()], TokenApproval::new(1, U128(10)));
+            })
+            .run_as(market(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.nft_transfer(charlie(), token_id, None, None);
+
+                let token = contract.nft_token(token_id).unwrap();
+                assert_eq!(token.approval_counter, U64(1));
+                assert_eq!(token.approvals.len(), 0);
+            });
+    }
+}
+
+mod nft_reapprove {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` is not approved for `market`")]
+    fn nft_reapprove_a_token_not_approved_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            let token_id = contract.claim_token(gate_id(1));
+            contract.nft_reapprove(token_id, market(), U128(15));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` does not belong to account `bob`")]
+    fn nft_reapprove_by_non_owner_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, market(), approve_msg(10));
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.nft_reapprove(token_id, market(), U128(15));
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Min price must be greater than zero for gate ID `GPZkspuVGaZxwWoP6bJoWU`"
+    )]
+    fn nft_reapprove_with_zero_min_price_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            let token_id = contract.claim_token(gate_id(1));
+            contract.nft_approve(token_id, market(), approve_msg(10));
+            contract.nft_reapprove(token_id, market(), U128(0));
+        });
+    }
+
+    #[test]
+    fn nft_reapprove_a_token() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            let token_id = contract.claim_token(gate_id(1));
+            contract.nft_approve(token_id, market(), approve_msg(10));
+            contract.nft_reapprove(token_id, market(), U128(15));
+
+            let token = contract.nft_token(token_id).unwrap();
+            assert_eq!(token.approval_counter, U64(1));
+            assert_eq!(token.approvals.len(), 1);
+            assert_eq!(token.approvals[market().as_ref()], TokenApproval::new(1, U128(15)));
+        });
+    }
+
+    #[test]
+    fn nft_reapprove_with_short_listing_fee_should_restore_the_min_price() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            let token_id = contract.claim_token(gate_id(1));
+            contract.nft_approve(token_id, market(), approve_msg(10));
+            contract.attach_deposit(5);
+            contract.nft_reapprove(token_id, market(), U128(15));
+
+            let pending = PendingApprovals {
+                owner_id: mintgate_admin().into(),
+                account_id: market().into(),
+                tokens: vec![(token_id, U64(1), Some(U128(10)))],
+                deposit: U128(5),
+            };
+            set_promise_result(&mut contract.context, PromiseResult::Failed);
+            assert!(!contract.contract.contract.resolve_approve(pending));
+
+            assert_eq!(transfers(), vec![(mintgate_admin().to_string(), 5)]);
+            let token = contract.nft_token(token_id).unwrap();
+            assert_eq!(token.approvals[market().as_ref()], TokenApproval::new(1, U128(10)));
+        });
+    }
+}
+
+mod lock_token {
+    use super::*;
+
+    fn init_locked() -> MockedContext<NftContractChecker> {
+        let mut context = init();
+        context
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let expires_at = contract.context.block_timestamp / 1_000_000 + 5;
+                let msg = NftApproveMsg { min_price: 10.into(), expires_at: Some(expires_at) };
+                contract.nft_approve(token_id, market(), serde_json::to_string(&msg).ok());
+            })
+            .run_as(market(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.lock_token(token_id);
+                assert_eq!(contract.get_token_lock(token_id), Some(market().to_string()));
+            });
+        context
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` is locked by `market`")]
+    fn owner_cannot_transfer_a_locked_token() {
+        init_locked().run_as(bob(), |contract| {
+            let token_id = contract.last_claimed_token();
+            contract.nft_transfer(charlie(), token_id, None, None);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` is locked by `market`")]
+    fn owner_cannot_revoke_a_locked_token() {
+        init_locked().run_as(bob(), |contract| {
+            let token_id = contract.last_claimed_token();
+            contract.nft_revoke(token_id, market());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` is locked by `market`")]
+    fn owner_cannot_burn_a_locked_token() {
+        init_locked().run_as(bob(), |contract| {
+            let token_id = contract.last_claimed_token();
+            contract.burn_token(token_id);
+        });
+    }
+
+    #[test]
+    fn locking_market_can_transfer_a_locked_token() {
+        init_locked().run_as(market(), |contract| {
+            let token_id = contract.last_claimed_token();
+            contract.nft_transfer_payout(charlie(), token_id, None, None, Some(U128(10)));
+
+            assert_eq!(contract.nft_token(token_id).unwrap().owner_id, charlie().to_string());
+            assert_eq!(contract.get_token_lock(token_id), None);
+        });
+    }
+
+    #[test]
+    fn owner_can_transfer_an_unlocked_token() {
+        init_locked()
+            .run_as(market(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.unlock_token(token_id);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.last_claimed_token();
+                assert_eq!(contract.get_token_lock(token_id), None);
+                contract.nft_transfer(charlie(), token_id, None, None);
+            });
+    }
+
+    #[test]
+    fn owner_can_transfer_a_token_once_the_locking_approval_expires() {
+        init_locked().run_as(bob(), |contract| {
+            contract.context.block_timestamp += 10_000_000;
+            contract.update_context();
+            let token_id = contract.last_claimed_token();
+            assert_eq!(contract.get_token_lock(token_id), None);
+            contract.nft_transfer(charlie(), token_id, None, None);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` is not approved for `charlie`")]
+    fn lock_a_non_approved_token_should_panic() {
+        init_locked().run_as(charlie(), |contract| {
+            let token_id = contract.last_claimed_token();
+            contract.lock_token(token_id);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` is not locked by `bob`")]
+    fn unlock_by_other_than_locking_market_should_panic() {
+        init_locked().run_as(bob(), |contract| {
+            let token_id = contract.last_claimed_token();
+            contract.unlock_token(token_id);
+        });
+    }
+}
+
+mod can_list_token {
+
+    use super::*;
+
+    #[test]
+    fn can_list_a_non_existent_token() {
+        init().run_as(alice(), |contract| {
+            assert!(!contract.can_list_token(U64(99), alice()));
+        });
+    }
+
+    #[test]
+    fn owner_can_list_a_token() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                assert!(contract.can_list_token(token_id, bob()));
+            });
+    }
+
+    #[test]
+    fn non_owner_cannot_list_a_token() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                assert!(!contract.can_list_token(token_id, alice()));
+                assert!(!contract.can_list_token(token_id, mintgate_admin()));
+            });
+    }
+
+    #[test]
+    fn cannot_list_an_already_approved_token() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, market(), approve_msg(10));
+                assert!(!contract.can_list_token(token_id, bob()));
+
+                contract.nft_revoke(token_id, market());
+                assert!(contract.can_list_token(token_id, bob()));
+            });
+    }
+}
+
+mod nft_approval_price {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(99)` was not found")]
+    fn nft_approval_price_for_non_existent_token_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.nft_approval_price(99.into(), market());
+        });
+    }
+
+    #[test]
+    fn nft_approval_price_should_follow_approvals() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                assert_eq!(contract.nft_approval_price(token_id, market()), None);
+
+                contract.nft_approve(token_id, market(), approve_msg(10));
+                assert_eq!(contract.nft_approval_price(token_id, market()), Some(U128(10)));
+                assert_eq!(contract.nft_approval_price(token_id, charlie()), None);
+
+                contract.nft_revoke(token_id, market());
+                assert_eq!(contract.nft_approval_price(token_id, market()), None);
+            });
+    }
+}
+
+mod nft_enforce_approval_id {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(99)` was not found")]
+    fn nft_enforce_approval_id_for_non_existent_token_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.nft_enforce_approval_id(99.into(), market());
+        });
+    }
+
+    #[test]
+    fn nft_transfer_with_the_enforce_approval_id() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+                let token_id = contract.claim_token(gate_id(1));
+                assert_eq!(contract.nft_enforce_approval_id(token_id, market()), None);
+
+                contract.nft_approve(token_id, market(), approve_msg(10));
+                let approval_id = contract.nft_enforce_approval_id(token_id, market()).unwrap();
+                assert_eq!(
+                    approval_id,
+                    contract.nft_token(token_id).unwrap().approvals[market().as_ref()].approval_id
+                );
+                assert_eq!(contract.nft_enforce_approval_id(token_id, charlie()), None);
+
+                contract.nft_transfer(market(), token_id, Some(approval_id), None);
+                assert_eq!(contract.nft_token(token_id).unwrap().owner_id, market().to_string());
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "The approval_id is different from enforce_approval_id")]
+    fn nft_transfer_with_another_enforce_approval_id_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, market(), approve_msg(10));
+                let approval_id = contract.nft_enforce_approval_id(token_id, market()).unwrap();
+
+                contract.nft_transfer(market(), token_id, Some(U64(approval_id.0 + 1)), None);
+            });
+    }
+}
+
+mod nft_tokens_approved_for {
+
+    use super::*;
+
+    #[test]
+    fn nft_tokens_approved_for_a_market() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                assert!(contract.nft_tokens_approved_for(market(), None, None).is_empty());
+            })
+            .run_as(bob(), |contract| {
+                for price in 1..=4 {
+                    let token_id = contract.claim_token(gate_id(1));
+                    if price != 2 {
+                        contract.nft_approve(token_id, market(), approve_msg(price * 10));
+                    }
+                }
+            })
+            .run_as(charlie(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, bob(), approve_msg(50));
+            })
+            .run_as(any(), |contract| {
+                let approved = vec![(U64(0), U128(10)), (U64(2), U128(30)), (U64(3), U128(40))];
+                assert_eq!(contract.nft_tokens_approved_for(market(), None, None), approved);
+                assert_eq!(
+                    contract.nft_tokens_approved_for(bob(), None, None),
+                    vec![(U64(4), U128(50))]
+                );
+                assert_eq!(
+                    contract.nft_tokens_approved_for(market(), Some(U64(1)), Some(2)),
+                    vec![(U64(2), U128(30))]
+                );
+            });
+    }
+}
+
+mod nft_token_listings {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(99)` was not found")]
+    fn nft_token_listings_for_non_existent_token_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.nft_token_listings(99.into());
+        });
+    }
+
+    #[test]
+    fn nft_token_listings_for_a_non_approved_token() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            let token_id = contract.claim_token(gate_id(1));
+            assert_eq!(contract.nft_token_listings(token_id), vec![]);
+        });
+    }
+
+    #[test]
+    fn nft_token_listings_should_follow_approvals() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, market(), approve_msg(10));
+                assert_eq!(
+                    contract.nft_token_listings(token_id),
+                    vec![(market().to_string(), U128(10))]
+                );
+
+                contract.nft_revoke(token_id, market());
+                assert_eq!(contract.nft_token_listings(token_id), vec![]);
+
+                contract.nft_approve(token_id, charlie(), approve_msg(15));
+                assert_eq!(
+                    contract.nft_token_listings(token_id),
+                    vec![(charlie().to_string(), U128(15))]
+                );
+            });
+    }
+}
+
+mod min_resale_price {
+
+    use super::*;
+
+    const NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+
+    #[test]
+    #[should_panic(expected = "Only the creator of gate ID")]
+    fn set_min_resale_price_by_non_creator_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.set_min_resale_price(gate_id(1), Some(U128(5 * NEAR)));
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Min price `U128(3000000000000000000000000)` is below the resale floor `U128(5000000000000000000000000)`"
+    )]
+    fn nft_approve_below_min_resale_price_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_min_resale_price(gate_id(1), Some(U128(5 * NEAR)));
+                let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+                assert_eq!(collectible.min_resale_price, Some(U128(5 * NEAR)));
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, market(), approve_msg(3 * NEAR));
+            });
+    }
+
+    #[test]
+    fn nft_approve_at_min_resale_price() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_min_resale_price(gate_id(1), Some(U128(5 * NEAR)));
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, market(), approve_msg(5 * NEAR));
+
+                let token = contract.nft_token(token_id).unwrap();
+                assert_eq!(token.approvals[market().as_ref()].min_price, U128(5 * NEAR));
+            });
+    }
+
+    #[test]
+    fn nft_approve_below_removed_min_resale_price() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_min_resale_price(gate_id(1), Some(U128(5 * NEAR)));
+                contract.set_min_resale_price(gate_id(1), None);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, market(), approve_msg(3 * NEAR));
+                assert_eq!(contract.nft_token(token_id).unwrap().approvals.len(), 1);
+            });
+    }
+}
+
+mod mint_price {
+
+    use super::*;
+
+    const NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+
+    #[test]
+    #[should_panic(expected = "Only the creator of gate ID")]
+    fn set_mint_price_by_non_creator_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.set_mint_price(gate_id(1), Some(U128(NEAR)));
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough deposit to cover storage and mint price")]
+    fn claim_a_paid_token_without_enough_deposit_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_mint_price(gate_id(1), Some(U128(NEAR)));
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+            });
+    }
+
+    #[test]
+    fn claim_a_paid_token() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_mint_price(gate_id(1), Some(U128(NEAR)));
+                let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+                assert_eq!(collectible.mint_price, Some(U128(NEAR)));
+            })
+            .run_as(bob(), |contract| {
+                contract.attach_deposit(CLAIM_DEPOSIT + NEAR);
+                contract.update_context();
+                let token_id = contract.contract.claim_token(gate_id(1));
+                contract.attach_deposit(0);
+                assert_eq!(contract.nft_token(token_id).unwrap().owner_id, bob().to_string());
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Tokens for gate id `GPZkspuVGaZxwWoP6bJoWU` have already been claimed"
+    )]
+    fn claim_a_sold_out_paid_token_should_panic_before_charging() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 1);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_mint_price(gate_id(1), Some(U128(NEAR)));
+            })
+            .run_as(bob(), |contract| {
+                contract.attach_deposit(CLAIM_DEPOSIT + NEAR);
+                contract.update_context();
+                contract.contract.claim_token(gate_id(1));
+            })
+            .run_as(charlie(), |contract| {
+                contract.attach_deposit(0);
+                contract.update_context();
+                contract.contract.claim_token(gate_id(1));
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Account `bob` has reached the claim limit for gate ID")]
+    fn claim_a_paid_token_over_the_limit_should_panic_before_charging() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_mint_price(gate_id(1), Some(U128(NEAR)));
+                contract.set_max_claims_per_account(gate_id(1), Some(1));
+            })
+            .run_as(bob(), |contract| {
+                contract.attach_deposit(CLAIM_DEPOSIT + NEAR);
+                contract.update_context();
+                contract.contract.claim_token(gate_id(1));
+            })
+            .run_as(bob(), |contract| {
+                contract.attach_deposit(0);
+                contract.update_context();
+                contract.contract.claim_token(gate_id(1));
+            });
+    }
+
+    #[test]
+    fn claim_a_free_token_after_removing_mint_price() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_mint_price(gate_id(1), Some(U128(NEAR)));
+                contract.set_mint_price(gate_id(1), None);
+            })
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+            });
+    }
+}
+
+mod zero_min_price {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(
+        expected = "Min price must be greater than zero for gate ID `GPZkspuVGaZxwWoP6bJoWU`"
+    )]
+    fn nft_approve_with_zero_min_price_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            let token_id = contract.claim_token(gate_id(1));
+            contract.nft_approve(token_id, market(), approve_msg(0));
+        });
+    }
+
+    #[test]
+    fn nft_approve_with_positive_min_price() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(1), 10);
+            let token_id = contract.claim_token(gate_id(1));
+            contract.nft_approve(token_id, market(), approve_msg(1));
+            assert_eq!(contract.nft_token(token_id).unwrap().approvals.len(), 1);
+        });
+    }
+
+    #[test]
+    fn nft_approve_with_zero_min_price_when_allowed() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_allow_zero_price(gate_id(1), true);
+                assert!(contract.get_collectible_by_gate_id(gate_id(1)).unwrap().allow_zero_price);
+
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, market(), approve_msg(0));
+                assert_eq!(contract.nft_token(token_id).unwrap().approvals.len(), 1);
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the creator of gate ID `GPZkspuVGaZxwWoP6bJoWU`")]
+    fn set_allow_zero_price_by_non_creator_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                contract.set_allow_zero_price(gate_id(1), true);
+            });
+    }
+}
+
+mod nft_revoke_all {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(99)` was not found")]
+    fn nft_revoke_all_for_non_existent_token_should_panic() {
+        init().run_as(bob(), |contract| {
+            contract.nft_revoke_all(99.into());
+        });
+    }
+
+    #[test]
+    fn nft_revoke_all_should_update_modified_at() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, market(), approve_msg(10));
+                contract.context.block_timestamp += 5_000_000;
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.last_claimed_token();
+                let modified_at = contract.nft_token(token_id).unwrap().modified_at;
+                contract.nft_revoke_all(token_id);
+
+                let token = contract.nft_token(token_id).unwrap();
+                assert_eq!(token.approvals.len(), 0);
+                assert!(token.modified_at > modified_at);
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(0)` does not belong to account `bob")]
+    fn nft_revoke_all_for_non_owned_token_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                contract.claim_token(gate_id(1));
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.last_claimed_token();
+                contract.nft_revoke_all(token_id);
+            });
+    }
+}
+
+mod batch_approve {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "At most 2 tokens are allowed to approve in batch")]
+    fn batch_approve_over_max_batch_size_should_panic() {
+        init_with_options(InitOptions { max_batch_size: Some(2), ..Default::default() })
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let tokens = (0..3)
+                    .map(|_| (contract.claim_token(gate_id(1)), U128(10)))
+                    .collect::<Vec<_>>();
+                contract.batch_approve(tokens, market());
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Not enough gas attached, more than `U64(70000000000000)` is required"
+    )]
+    fn batch_approve_with_insufficient_gas_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let tokens = (0..5)
+                    .map(|_| (contract.claim_token(gate_id(1)), U128(10)))
+                    .collect::<Vec<_>>();
+                contract.context.prepaid_gas = 60_000_000_000_000;
+                contract.batch_approve(tokens, market());
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "`max_batch_size` is 56 but at most 55 tokens fit in the gas of a batch"
+    )]
+    fn init_with_max_batch_size_over_gas_limit_should_panic() {
+        init_with_options(InitOptions {
+            max_batch_size: Some(MAX_BATCH_SIZE + 1),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn init_with_max_batch_size_up_to_gas_limit() {
+        init_with_options(InitOptions {
+            max_batch_size: Some(MAX_BATCH_SIZE),
+            ..Default::default()
+        })
+        .run_as(bob(), |contract| {
+            contract.batch_revoke(Vec::new(), market());
+        });
+    }
+
+    #[test]
+    fn batch_approve_with_short_listing_fee_should_undo_the_approvals() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let tokens = (0..2)
+                    .map(|_| (contract.claim_token(gate_id(1)), U128(10)))
+                    .collect::<Vec<_>>();
+                let token_ids = tokens.iter().map(|(token_id, _)| *token_id).collect::<Vec<_>>();
+                contract.attach_deposit(5);
+                contract.batch_approve(tokens, market());
+
+                let pending = PendingApprovals {
+                    owner_id: bob().into(),
+                    account_id: market().into(),
+                    tokens: token_ids.iter().map(|token_id| (*token_id, U64(1), None)).collect(),
+                    deposit: U128(5),
+                };
+                set_promise_result(&mut contract.context, PromiseResult::Failed);
+                contract.contract.contract.resolve_batch_approve(pending, Vec::new());
+
+                assert_eq!(transfers(), vec![(bob().to_string(), 5)]);
+                for token_id in &token_ids {
+                    assert!(contract.nft_token(*token_id).unwrap().approvals.is_empty());
+                }
+                let logs = near_sdk::test_utils::get_logs();
+                let log = logs.iter().rev().find(|log| log.starts_with(EVENT_JSON)).unwrap();
+                let event: serde_json::Value =
+                    serde_json::from_str(&log[EVENT_JSON.len()..]).unwrap();
+                assert_eq!(event["event"], "mg_batch_approve_result");
+                let data: Vec<BatchApproveResultEvent> =
+                    serde_json::from_value(event["data"].clone()).unwrap();
+                assert!(data[0].succeeded.is_empty());
+                assert_eq!(data[0].failed, token_ids);
+            });
+    }
+}
+
+mod batch_revoke {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "At most 50 tokens are allowed to revoke in batch")]
+    fn batch_revoke_too_many_tokens_should_panic() {
+        init().run_as(bob(), |contract| {
+            contract.batch_revoke((0..51).map(U64).collect(), market());
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Not enough gas attached, more than `U64(45000000000000)` is required"
+    )]
+    fn batch_revoke_with_insufficient_gas_should_panic() {
+        init().run_as(bob(), |contract| {
+            contract.context.prepaid_gas = 40_000_000_000_000;
+            contract.batch_revoke((0..5).map(U64).collect(), market());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "At most 2 tokens are allowed to revoke in batch")]
+    fn batch_revoke_over_max_batch_size_should_panic() {
+        init_with_options(InitOptions { max_batch_size: Some(2), ..Default::default() })
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let tokens = (0..3).map(|_| contract.claim_token(gate_id(1))).collect::<Vec<_>>();
+                for token_id in &tokens {
+                    contract.nft_approve(*token_id, market(), approve_msg(10));
+                }
+                contract.batch_revoke(tokens, market());
+            });
+    }
+
+    #[test]
+    fn batch_revoke_up_to_max_batch_size() {
+        init_with_options(InitOptions { max_batch_size: Some(2), ..Default::default() })
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let tokens = (0..2).map(|_| contract.claim_token(gate_id(1))).collect::<Vec<_>>();
+                for token_id in &tokens {
+                    contract.nft_approve(*token_id, market(), approve_msg(10));
+                }
+                contract.batch_revoke(tokens.clone(), market());
+                for token_id in tokens {
+                    assert_eq!(contract.nft_token(token_id).unwrap().approvals.len(), 0);
+                }
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "2 error(s) detected, see `panics` fields for a full list of errors")]
+    fn batch_revoke_only_failing_tokens_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.batch_revoke(vec![token_id, U64(99)], market());
+            });
+    }
+
+    #[test]
+    fn batch_revoke_a_few_tokens() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                for _ in 0..3 {
+                    let token_id = contract.claim_token(gate_id(1));
+                    contract.nft_approve(token_id, market(), approve_msg(10));
+                }
+                contract.context.block_timestamp += 5_000_000;
+            })
+            .run_as(bob(), |contract| {
+                let tokens = contract.claimed_tokens.clone();
+                let modified_at = contract.nft_token(tokens[0]).unwrap().modified_at;
+                contract.batch_revoke(tokens.clone(), market());
+
+                for token_id in tokens {
+                    let token = contract.nft_token(token_id).unwrap();
+                    assert_eq!(token.approvals.len(), 0);
+                    assert!(token.modified_at > modified_at);
+                }
+            });
+    }
+
+    #[test]
+    fn batch_revoke_keeps_other_tokens_revoked_on_errors() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, market(), approve_msg(10));
+                let not_approved_id = contract.claim_token(gate_id(1));
+
+                contract.batch_revoke(vec![token_id, not_approved_id, U64(99)], market());
+                assert_eq!(contract.nft_token(token_id).unwrap().approvals.len(), 0);
+            });
+    }
+}
+
+mod nft_payout {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Token ID `U64(99)` was not found")]
+    fn nft_payout_non_existent_token_id_should_panic() {
+        init().run_as(bob(), |contract| {
+            contract.nft_payout(99.into(), 0.into());
+        });
+    }
+
+    #[test]
+    fn nft_get_payout_no_royalty() {
+        init_contract("0/10", "30/100", metadata(base_uri()))
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_royalty_collectible(alice(), gate_id(1), 10, "0/1");
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let payout = contract.nft_payout(token_id, 2000.into());
+                assert_eq!(payout.len(), 2);
+                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
+                assert!(!payout.contains_key(alice().as_ref()));
+                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1950);
+                assert_eq!(payout.values().map(|amount| amount.0).sum::<u128>(), 2000);
+            });
+    }
+
+    #[test]
+    fn nft_get_payout() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_royalty_collectible(alice(), gate_id(1), 10, "15/100");
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let payout = contract.nft_payout(token_id, 2000.into());
+                assert_eq!(payout.len(), 3);
+                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
+                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 300);
+                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1650);
+            });
+    }
+
+    #[test]
+    fn nft_get_payout_with_royalty_override() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_royalty_collectible(alice(), gate_id(1), 10, "15/100");
+            })
+            .run_as(alice(), |contract| {
+                let default_token_id = contract.claim_token_to(gate_id(1), bob(), None);
+                let override_token_id = contract.claim_token_to(gate_id(1), bob(), Some("25/100"));
+
+                let payout = contract.nft_payout(default_token_id, 2000.into());
+                assert_eq!(payout.len(), 3);
+                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
+                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 300);
+                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1650);
+
+                let payout = contract.nft_payout(override_token_id, 2000.into());
+                assert_eq!(payout.len(), 3);
+                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
+                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 500);
+                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1450);
+            });
+    }
+
+    #[test]
+    fn nft_get_payout_with_fee_split() {
+        init_with_options(InitOptions {
+            mintgate_fee_split: Some(vec![
+                (mintgate_fee_account_id(), "7/10".parse().unwrap()),
+                (charlie(), "3/10".parse().unwrap()),
+            ]),
+            ..Default::default()
+        })
+        .run_as(mintgate_admin(), |contract| {
+            contract.create_royalty_collectible(alice(), gate_id(1), 10, "15/100");
+        })
+        .run_as(bob(), |contract| {
+            let token_id = contract.claim_token(gate_id(1));
+            let payout = contract.nft_payout(token_id, 2000.into());
+            assert_eq!(payout.len(), 4);
+            assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 35);
+            assert_eq!(payout.get(charlie().as_ref()).unwrap().0, 15);
+            assert_eq!(payout.get(alice().as_ref()).unwrap().0, 300);
+            assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1650);
+        });
+    }
+
+    #[test]
+    fn nft_get_payout_with_fee_split_remainder() {
+        init_with_options(InitOptions {
+            mintgate_fee_split: Some(vec![
+                (alice(), "1/3".parse().unwrap()),
+                (charlie(), "2/3".parse().unwrap()),
+            ]),
+            ..Default::default()
+        })
+        .run_as(mintgate_admin(), |contract| {
+            contract.create_royalty_collectible(alice(), gate_id(1), 10, "15/100");
+        })
+        .run_as(bob(), |contract| {
+            let token_id = contract.claim_token(gate_id(1));
+            let payout = contract.nft_payout(token_id, 2000.into());
+            assert_eq!(payout.len(), 4);
+            assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 1);
+            assert_eq!(payout.get(charlie().as_ref()).unwrap().0, 33);
+            assert_eq!(payout.get(alice().as_ref()).unwrap().0, 300 + 16);
+            assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1650);
+            assert_eq!(payout.values().map(|amount| amount.0).sum::<u128>(), 2000);
+        });
+    }
+
+    #[test]
+    fn nft_get_payout_with_fee_split_to_owner_and_creator() {
+        init_with_options(InitOptions {
+            mintgate_fee_split: Some(vec![
+                (alice(), "1/2".parse().unwrap()),
+                (charlie(), "1/2".parse().unwrap()),
+            ]),
+            ..Default::default()
+        })
+        .run_as(mintgate_admin(), |contract| {
+            contract.create_royalty_collectible(alice(), gate_id(1), 10, "15/100");
+        })
+        .run_as(alice(), |contract| {
+            let token_id = contract.claim_token(gate_id(1));
+            let payout = contract.nft_payout(token_id, 2000.into());
+            assert_eq!(payout.len(), 2);
+            assert_eq!(payout.get(charlie().as_ref()).unwrap().0, 25);
+            assert_eq!(payout.get(alice().as_ref()).unwrap().0, 1650 + 300 + 25);
+            assert_eq!(payout.values().map(|amount| amount.0).sum::<u128>(), 2000);
+        });
+    }
+
+    #[test]
+    fn nft_get_example_payout() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_royalty_collectible(alice(), gate_id(1), 10, "30/100");
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let payout = contract.nft_payout(token_id, 5_000_000.into());
+                assert_eq!(payout.len(), 3);
+                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 125_000);
+                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 1_500_000);
+                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 3_375_000);
+            });
+    }
+
+    #[test]
+    fn nft_get_payout_periodic_royalty_fraction() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_royalty_collectible(alice(), gate_id(1), 10, "1/6");
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let payout = contract.nft_payout(token_id, 2000.into());
+                assert_eq!(payout.len(), 3);
+                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
+                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 333);
+                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1617);
+            });
+    }
+
+    #[test]
+    fn nft_get_payout_infinite_royalty_fraction() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_royalty_collectible(alice(), gate_id(1), 10, "1/7");
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let payout = contract.nft_payout(token_id, 2000.into());
+                assert_eq!(payout.len(), 3);
+                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
+                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 285);
+                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1665);
+            });
+    }
+
+    #[test]
+    fn nft_get_payout_when_creator_and_owner_are_the_same() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_royalty_collectible(bob(), gate_id(1), 10, "1/7");
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let payout = contract.nft_payout(token_id, 2000.into());
+                assert_eq!(payout.len(), 2);
+                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
+                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1950);
+            });
+    }
+
+    #[test]
+    fn nft_get_payout_when_creator_and_owner_are_the_same_with_no_royalty() {
+        init_contract("0/1", "1/1", metadata(base_uri()))
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_royalty_collectible(bob(), gate_id(1), 10, "0/7");
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let payout = contract.nft_payout(token_id, 2000.into());
+                assert_eq!(payout.len(), 2);
+                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
+                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1950);
+            });
+    }
+}
+
+mod set_fee_config {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Operation is allowed only for admin")]
+    fn set_fee_config_by_no_admin_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.set_fee_config("5/100".parse().unwrap(), alice());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "The fraction must be less or equal to 1")]
+    fn set_fee_config_with_invalid_fee_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.set_fee_config("3/2".parse().unwrap(), charlie());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalty `30/100` is too large for the given NFT fee `70/100`")]
+    fn set_fee_config_with_fee_too_large_for_max_royalty_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.set_fee_config("70/100".parse().unwrap(), charlie());
+        });
+    }
+
+    #[test]
+    fn set_fee_config_with_fee_within_max_royalty_limit() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_royalty_collectible(alice(), gate_id(1), 10, "30/100");
+                contract.set_fee_config("69/100".parse().unwrap(), charlie());
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let payout = contract.nft_payout(token_id, 100.into());
+                assert_eq!(payout.get(charlie().as_ref()).unwrap().0, 69);
+                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 30);
+                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1);
+            });
+    }
+
+    #[test]
+    fn set_fee_config_should_update_payout() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_royalty_collectible(alice(), gate_id(1), 10, "15/100");
+                contract.set_fee_config("5/100".parse().unwrap(), charlie());
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let payout = contract.nft_payout(token_id, 2000.into());
+                assert_eq!(payout.len(), 3);
+                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()), None);
+                assert_eq!(payout.get(charlie().as_ref()).unwrap().0, 100);
+                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 300);
+                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1600);
+            });
+    }
+
+    #[test]
+    fn set_fee_config_should_keep_fee_split() {
+        init_with_options(InitOptions {
+            mintgate_fee_split: Some(vec![
+                (mintgate_fee_account_id(), "7/10".parse().unwrap()),
+                (charlie(), "3/10".parse().unwrap()),
+            ]),
+            ..Default::default()
+        })
+        .run_as(mintgate_admin(), |contract| {
+            contract.create_royalty_collectible(alice(), gate_id(1), 10, "15/100");
+            contract.set_fee_config("5/100".parse().unwrap(), mintgate_fee_account_id());
+        })
+        .run_as(bob(), |contract| {
+            let token_id = contract.claim_token(gate_id(1));
+            let payout = contract.nft_payout(token_id, 2000.into());
+            assert_eq!(payout.len(), 4);
+            assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 70);
+            assert_eq!(payout.get(charlie().as_ref()).unwrap().0, 30);
+        });
+    }
+
+    #[test]
+    fn set_fee_config_should_move_fee_split_share_to_new_account() {
+        init_with_options(InitOptions {
+            mintgate_fee_split: Some(vec![
+                (mintgate_fee_account_id(), "7/10".parse().unwrap()),
+                (charlie(), "3/10".parse().unwrap()),
+            ]),
+            ..Default::default()
+        })
+        .run_as(mintgate_admin(), |contract| {
+            contract.create_royalty_collectible(alice(), gate_id(1), 10, "15/100");
+            contract.set_fee_config("5/100".parse().unwrap(), any());
+        })
+        .run_as(bob(), |contract| {
+            let token_id = contract.claim_token(gate_id(1));
+            let payout = contract.nft_payout(token_id, 2000.into());
+            assert_eq!(payout.len(), 4);
+            assert_eq!(payout.get(mintgate_fee_account_id().as_ref()), None);
+            assert_eq!(payout.get(any().as_ref()).unwrap().0, 70);
+            assert_eq!(payout.get(charlie().as_ref()).unwrap().0, 30);
+        });
+    }
+}
+
+mod set_fee_split {
+
+    use super::*;
+
+    fn split(split: Vec<(ValidAccountId, &str)>) -> Vec<(ValidAccountId, Fraction)> {
+        split.into_iter().map(|(account_id, share)| (account_id, share.parse().unwrap())).collect()
+    }
+
+    #[test]
+    #[should_panic(expected = "Operation is allowed only for admin")]
+    fn set_fee_split_by_no_admin_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.set_fee_split(split(vec![(alice(), "1/1")]));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "The shares of the fee split must add up to 1")]
+    fn set_fee_split_not_adding_up_to_one_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.set_fee_split(split(vec![
+                (mintgate_fee_account_id(), "7/10"),
+                (charlie(), "2/10"),
+            ]));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "The shares of the fee split must add up to 1")]
+    fn set_empty_fee_split_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.set_fee_split(Vec::new());
+        });
+    }
+
+    #[test]
+    fn set_fee_split_should_update_payout() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_royalty_collectible(alice(), gate_id(1), 10, "15/100");
+                contract.set_fee_split(split(vec![
+                    (mintgate_fee_account_id(), "1/2"),
+                    (charlie(), "1/2"),
+                ]));
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let payout = contract.nft_payout(token_id, 2000.into());
+                assert_eq!(payout.len(), 4);
+                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 25);
+                assert_eq!(payout.get(charlie().as_ref()).unwrap().0, 25);
+            });
+    }
+}
+
+mod nft_transfer_payout {
+
+    use super::*;
+
+    #[test]
+    fn nft_get_transfer_payout() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_royalty_collectible(alice(), gate_id(1), 10, "15/100");
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                let payout = contract
+                    .nft_transfer_payout(charlie(), token_id, None, None, Some(2000.into()))
+                    .unwrap();
+                assert_eq!(payout.len(), 3);
+                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
+                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 300);
+                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1650);
+            });
+    }
+}
+
+mod enforce_royalties {
+
+    use super::*;
+
+    fn init_approved(enforce_royalties: bool) -> MockedContext<NftContractChecker> {
+        let mut context = init();
+        context
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                contract.set_enforce_royalties(enforce_royalties);
+                assert_eq!(contract.is_enforcing_royalties(), enforce_royalties);
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                contract.nft_approve(token_id, market(), approve_msg(10));
+            });
+        context
+    }
+
+    #[test]
+    fn royalties_are_not_enforced_by_default() {
+        init().run_as(any(), |contract| {
+            assert!(!contract.is_enforcing_royalties());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Operation is allowed only for admin")]
+    fn set_enforce_royalties_by_non_admin_should_panic() {
+        init().run_as(alice(), |contract| {
+            contract.set_enforce_royalties(true);
+        });
+    }
+
+    #[test]
+    fn approved_account_can_transfer_when_not_enforced() {
+        init_approved(false).run_as(market(), |contract| {
+            let token_id = contract.last_claimed_token();
+            contract.nft_transfer(charlie(), token_id, None, None);
+            assert_eq!(contract.nft_token(token_id).unwrap().owner_id, charlie().to_string());
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Royalties are enforced, `market` must transfer through `nft_transfer_payout`"
+    )]
+    fn approved_account_cannot_transfer_when_enforced() {
+        init_approved(true).run_as(market(), |contract| {
+            let token_id = contract.last_claimed_token();
+            contract.nft_transfer(charlie(), token_id, None, None);
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Royalties are enforced, `market` must transfer through `nft_transfer_payout`"
+    )]
+    fn approved_account_cannot_transfer_payout_with_no_balance_when_enforced() {
+        init_approved(true).run_as(market(), |contract| {
+            let token_id = contract.last_claimed_token();
+            contract.nft_transfer_payout(charlie(), token_id, None, None, None);
+        });
+    }
+
+    #[test]
+    fn approved_account_can_transfer_payout_when_enforced() {
+        init_approved(true).run_as(market(), |contract| {
+            let token_id = contract.last_claimed_token();
+            let payout =
+                contract.nft_transfer_payout(charlie(), token_id, None, None, Some(U128(1000)));
+            assert!(payout.is_some());
+            assert_eq!(contract.nft_token(token_id).unwrap().owner_id, charlie().to_string());
+        });
+    }
+
+    #[test]
+    fn owner_can_transfer_when_enforced() {
+        init_approved(true).run_as(bob(), |contract| {
+            let token_id = contract.last_claimed_token();
+            contract.nft_transfer(charlie(), token_id, None, None);
+            assert_eq!(contract.nft_token(token_id).unwrap().owner_id, charlie().to_string());
+        });
+    }
+}
+
+mod first_sale_royalty {
+
+    use super::*;
+
+    fn create_first_sale_royalty_collectible(
+        contract: &mut MockedContext<NftContractChecker>,
+        royalty: &str,
+        first_sale_royalty: &str,
+    ) {
+        contract.contract.create_collectible(
+            alice(),
+            gate_id(1),
+            "title".to_string(),
+            "desc".to_string(),
+            10,
+            royalty.parse().unwrap(),
+            None,
+            None,
+            None,
+            None,
+            Some(CollectibleOptions {
+                first_sale_royalty: Some(first_sale_royalty.parse().unwrap()),
+                ..Default::default()
+            }),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalty `5/10` of `GPZkspuVGaZxwWoP6bJoWU` is greater than max")]
+    fn create_a_collectible_with_greater_than_max_first_sale_royalty_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            create_first_sale_royalty_collectible(contract, "15/100", "5/10");
+        });
+    }
+
+    #[test]
+    fn nft_get_first_and_second_sale_payout() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                create_first_sale_royalty_collectible(contract, "15/100", "25/100");
+                let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+                assert_eq!(collectible.first_sale_royalty, Some("25/100".parse().unwrap()));
+            })
+            .run_as(bob(), |contract| {
+                let token_id = contract.claim_token(gate_id(1));
+                assert!(!contract.nft_token(token_id).unwrap().has_been_sold);
+
+                let payout = contract
+                    .nft_transfer_payout(charlie(), token_id, None, None, Some(2000.into()))
+                    .unwrap();
+                assert_eq!(payout.len(), 3);
+                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
+                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 500);
+                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1450);
+                assert!(contract.nft_token(token_id).unwrap().has_been_sold);
+            })
+            .run_as(charlie(), |contract| {
+                let token_id = contract.last_claimed_token();
+                let payout = contract
+                    .nft_transfer_payout(bob(), token_id, None, None, Some(2000.into()))
+                    .unwrap();
+                assert_eq!(payout.len(), 3);
+                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
+                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 300);
+                assert_eq!(payout.get(charlie().as_ref()).unwrap().0, 1650);
+            });
+    }
+}
+
+mod royalty_locked {
+
+    use super::*;
+
+    fn create_locked_collectible(contract: &mut NftContract, royalty_locked: bool) {
+        contract.create_collectible(
+            alice(),
+            gate_id(1),
+            "title".to_string(),
+            "desc".to_string(),
+            10,
+            "15/100".parse().unwrap(),
+            None,
+            None,
+            None,
+            None,
+            Some(CollectibleOptions { royalty_locked, ..Default::default() }),
+        );
+    }
+
+    #[test]
+    fn create_a_collectible_with_royalty_locked() {
+        init().run_as(mintgate_admin(), |contract| {
+            contract.create_test_collectible(alice(), gate_id(2), 10);
+            assert!(!contract.get_collectible_by_gate_id(gate_id(2)).unwrap().royalty_locked);
+
+            create_locked_collectible(contract, true);
+            assert!(contract.get_collectible_by_gate_id(gate_id(1)).unwrap().royalty_locked);
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Royalty of gate ID `GPZkspuVGaZxwWoP6bJoWU` is locked and cannot be changed"
+    )]
+    fn claim_with_a_royalty_override_from_a_locked_collectible_should_panic() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                create_locked_collectible(contract, true);
+            })
+            .run_as(alice(), |contract| {
+                contract.claim_token_to(gate_id(1), bob(), Some("25/100"));
+            });
+    }
+
+    #[test]
+    fn claim_without_a_royalty_override_from_a_locked_collectible() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                create_locked_collectible(contract, true);
+            })
+            .run_as(alice(), |contract| {
+                let token_id = contract.claim_token_to(gate_id(1), bob(), None);
+                let payout = contract.nft_payout(token_id, 2000.into());
+                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 300);
+            });
+    }
+
+    #[test]
+    fn claim_with_a_royalty_override_from_an_unlocked_collectible() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                create_locked_collectible(contract, false);
+            })
+            .run_as(alice(), |contract| {
+                let token_id = contract.claim_token_to(gate_id(1), bob(), Some("25/100"));
+                let payout = contract.nft_payout(token_id, 2000.into());
+                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 500);
+            });
+    }
+}
+
+mod get_gate_view {
+
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Gate ID `GPZkspuVGaZxwWoP6bJoWU` was not found")]
+    fn get_gate_view_of_a_non_existent_gate_should_panic() {
+        init().run_as(bob(), |contract| {
+            contract.get_gate_view(gate_id(1), bob());
+        });
+    }
+
+    #[test]
+    fn get_gate_view_until_claim_limit_is_reached() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_max_claims_per_account(gate_id(1), Some(1));
+            })
+            .run_as(bob(), |contract| {
+                let view = contract.get_gate_view(gate_id(1), bob());
+                assert_eq!(&view.collectible.gate_id, gate_id(1).as_ref());
+                assert!(view.eligibility.can_claim);
+                assert!(view.eligibility.claimable);
+                assert!(view.eligibility.has_supply);
+                assert!(view.eligibility.within_claim_limit);
+                assert_eq!(view.eligibility.remaining_claims, 1);
+                assert!(view.eligibility.reasons.is_empty());
+
                 contract.claim_token(gate_id(1));
+
+                let view = contract.get_gate_view(gate_id(1), bob());
+                assert_eq!(view.collectible.current_supply, 9);
+                assert!(!view.eligibility.can_claim);
+                assert!(view.eligibility.claimable);
+                assert!(view.eligibility.has_supply);
+                assert!(!view.eligibility.within_claim_limit);
+                assert_eq!(view.eligibility.remaining_claims, 0);
+                assert_eq!(
+                    serde_json::to_value(&view.eligibility.reasons).unwrap(),
+                    serde_json::json!([{
+                        "err": "ClaimLimitReached",
+                        "account_id": "bob",
+                        "gate_id": "GPZkspuVGaZxwWoP6bJoWU",
+                    }])
+                );
+
+                assert!(contract.get_gate_view(gate_id(1), charlie()).eligibility.can_claim);
+            });
+    }
+
+    #[test]
+    fn get_gate_view_of_a_paused_and_sold_out_gate() {
+        init()
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 1);
             })
             .run_as(bob(), |contract| {
-                let token_id = contract.last_claimed_token();
-                contract.nft_revoke_all(token_id);
+                contract.claim_token(gate_id(1));
+            })
+            .run_as(alice(), |contract| {
+                contract.set_gate_claimable(gate_id(1), false);
+
+                let eligibility = contract.get_gate_view(gate_id(1), charlie()).eligibility;
+                assert!(!eligibility.can_claim);
+                assert!(!eligibility.claimable);
+                assert!(!eligibility.has_supply);
+                assert!(eligibility.within_claim_limit);
+                assert_eq!(eligibility.remaining_claims, 0);
+                assert_eq!(eligibility.reasons.len(), 2);
             });
     }
 }
 
-mod nft_payout {
+mod swap_token {
 
     use super::*;
 
-    #[test]
-    #[should_panic(expected = "Token ID `U64(99)` was not found")]
-    fn nft_payout_non_existent_token_id_should_panic() {
-        init().run_as(bob(), |contract| {
-            contract.nft_payout(99.into(), 0.into());
-        });
+    fn init_with_swap_target() -> MockedContext<NftContractChecker> {
+        let mut context = init();
+        context
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                contract.create_test_collectible(alice(), gate_id(2), 1);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_swap_target(gate_id(1), Some(gate_id(2)));
+                assert_eq!(contract.get_swap_target(gate_id(1)), Some(gate_id(2).to_string()));
+                assert_eq!(contract.get_swap_target(gate_id(2)), None);
+            });
+        context
+    }
+
+    fn swap_token(
+        contract: &mut MockedContext<NftContractChecker>,
+        token_id: TokenId,
+        to_gate_id: ValidGateId,
+    ) {
+        contract.attach_deposit(CLAIM_DEPOSIT);
+        contract.update_context();
+        contract.swap_token(token_id, to_gate_id);
     }
 
     #[test]
-    fn nft_get_payout_no_royalty() {
-        init_contract("0/10", "30/100", metadata(base_uri()))
+    #[should_panic(
+        expected = "Only the creator of gate ID `GPZkspuVGaZxwWoP6bJoWU` or the admin are allowed to perform this operation"
+    )]
+    fn set_swap_target_from_non_creator_should_panic() {
+        init()
             .run_as(mintgate_admin(), |contract| {
-                contract.create_royalty_collectible(alice(), gate_id(1), 10, "0/1");
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                contract.create_test_collectible(bob(), gate_id(2), 10);
             })
             .run_as(bob(), |contract| {
-                let token_id = contract.claim_token(gate_id(1));
-                let payout = contract.nft_payout(token_id, 2000.into());
-                assert_eq!(payout.len(), 3);
-                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
-                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 0);
-                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1950);
+                contract.set_swap_target(gate_id(1), Some(gate_id(2)));
             });
     }
 
     #[test]
-    fn nft_get_payout() {
+    #[should_panic(
+        expected = "Only the creator of gate ID `Nekq22i3rvzDe7c51Yc8hU` or the admin are allowed to perform this operation"
+    )]
+    fn set_swap_target_to_a_gate_of_another_creator_should_panic() {
         init()
             .run_as(mintgate_admin(), |contract| {
-                contract.create_royalty_collectible(alice(), gate_id(1), 10, "15/100");
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+                contract.create_test_collectible(bob(), gate_id(0), 10);
             })
-            .run_as(bob(), |contract| {
-                let token_id = contract.claim_token(gate_id(1));
-                let payout = contract.nft_payout(token_id, 2000.into());
-                assert_eq!(payout.len(), 3);
-                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
-                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 300);
-                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1650);
+            .run_as(alice(), |contract| {
+                contract.set_swap_target(gate_id(1), Some(gate_id(0)));
             });
     }
 
     #[test]
-    fn nft_get_example_payout() {
-        init()
+    fn swap_a_token() {
+        init_with_swap_target().run_as(bob(), |contract| {
+            let token_id = contract.claim_token(gate_id(1));
+            assert_eq!(contract.nft_supply_for_owner(bob()), U64(1));
+
+            swap_token(contract, token_id, gate_id(2));
+
+            assert_eq!(contract.nft_token(token_id), None);
+            assert_eq!(contract.nft_supply_for_owner(bob()), U64(1));
+            assert_eq!(contract.nft_total_supply(), U64(1));
+            let tokens = contract.get_tokens_by_owner(bob());
+            assert_eq!(tokens.len(), 1);
+            assert_eq!(&tokens[0].gate_id, gate_id(2).as_ref());
+
+            let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+            assert_eq!(collectible.minted_tokens.len(), 0);
+            let collectible = contract.get_collectible_by_gate_id(gate_id(2)).unwrap();
+            assert_eq!(collectible.current_supply, 0);
+            assert_eq!(collectible.minted_tokens, vec![tokens[0].token_id]);
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Tokens of gate ID `GPZkspuVGaZxwWoP6bJoWU` cannot be swapped for gate ID `Nekq22i3rvzDe7c51Yc8hU`"
+    )]
+    fn swap_a_token_for_a_non_target_gate_should_panic() {
+        init_with_swap_target()
             .run_as(mintgate_admin(), |contract| {
-                contract.create_royalty_collectible(alice(), gate_id(1), 10, "30/100");
+                contract.create_test_collectible(alice(), gate_id(0), 10);
             })
             .run_as(bob(), |contract| {
                 let token_id = contract.claim_token(gate_id(1));
-                let payout = contract.nft_payout(token_id, 5_000_000.into());
-                assert_eq!(payout.len(), 3);
-                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 125_000);
-                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 1_500_000);
-                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 3_375_000);
+                swap_token(contract, token_id, gate_id(0));
             });
     }
 
     #[test]
-    fn nft_get_payout_periodic_royalty_fraction() {
+    #[should_panic(expected = "Token ID `U64(0)` does not belong to account `charlie`")]
+    fn swap_a_token_from_non_owner_should_panic() {
+        init_with_swap_target()
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+            })
+            .run_as(charlie(), |contract| {
+                swap_token(contract, 0.into(), gate_id(2));
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Tokens for gate id `Tj6wXP9tHBR2sGBEBkUyho` have already been claimed"
+    )]
+    fn swap_a_token_for_an_exhausted_gate_should_panic() {
+        init_with_swap_target().run_as(bob(), |contract| {
+            let token_id = contract.claim_token(gate_id(1));
+            let other_token_id = contract.claim_token(gate_id(1));
+            swap_token(contract, token_id, gate_id(2));
+            swap_token(contract, other_token_id, gate_id(2));
+        });
+    }
+
+    #[test]
+    fn unset_swap_target() {
+        init_with_swap_target().run_as(alice(), |contract| {
+            contract.set_swap_target(gate_id(1), None);
+            assert_eq!(contract.get_swap_target(gate_id(1)), None);
+        });
+    }
+}
+
+mod title_template {
+
+    use super::*;
+
+    fn create_templated_collectible(contract: &mut NftContract, title_template: Option<String>) {
+        contract.create_collectible(
+            alice(),
+            gate_id(1),
+            "Parcel".to_string(),
+            "desc".to_string(),
+            10,
+            "15/100".parse().unwrap(),
+            None,
+            None,
+            None,
+            None,
+            Some(CollectibleOptions { title_template, ..Default::default() }),
+        );
+    }
+
+    fn title(contract: &NftContract, token_id: TokenId) -> Option<String> {
+        contract.nft_token(token_id).unwrap().metadata.title
+    }
+
+    #[test]
+    fn claim_tokens_with_a_title_template() {
         init()
             .run_as(mintgate_admin(), |contract| {
-                contract.create_royalty_collectible(alice(), gate_id(1), 10, "1/6");
+                create_templated_collectible(contract, Some("Parcel #{index}".to_string()));
             })
             .run_as(bob(), |contract| {
-                let token_id = contract.claim_token(gate_id(1));
-                let payout = contract.nft_payout(token_id, 2000.into());
-                assert_eq!(payout.len(), 3);
-                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
-                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 333);
-                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1617);
+                contract.attach_deposit(CLAIM_DEPOSIT);
+                contract.update_context();
+                let token_ids =
+                    (0..3).map(|_| contract.contract.claim_token(gate_id(1))).collect::<Vec<_>>();
+                for (i, token_id) in token_ids.into_iter().enumerate() {
+                    assert_eq!(title(contract, token_id), Some(format!("Parcel #{}", i + 1)));
+                }
+
+                let titles = contract
+                    .nft_tokens(None, None)
+                    .into_iter()
+                    .map(|token| token.metadata.title.unwrap())
+                    .collect::<Vec<_>>();
+                assert_eq!(titles, vec!["Parcel #1", "Parcel #2", "Parcel #3"]);
+                assert_eq!(
+                    contract.get_collectible_by_gate_id(gate_id(1)).unwrap().metadata.title,
+                    Some("Parcel".to_string())
+                );
             });
     }
 
     #[test]
-    fn nft_get_payout_infinite_royalty_fraction() {
+    fn claim_tokens_without_a_title_template() {
         init()
             .run_as(mintgate_admin(), |contract| {
-                contract.create_royalty_collectible(alice(), gate_id(1), 10, "1/7");
+                create_templated_collectible(contract, None);
             })
             .run_as(bob(), |contract| {
                 let token_id = contract.claim_token(gate_id(1));
-                let payout = contract.nft_payout(token_id, 2000.into());
-                assert_eq!(payout.len(), 3);
-                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
-                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 285);
-                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1665);
+                assert_eq!(title(contract, token_id), Some("Parcel".to_string()));
             });
     }
 
     #[test]
-    fn nft_get_payout_when_creator_and_owner_are_the_same() {
-        init()
+    #[should_panic(expected = "Title exceeds")]
+    fn create_a_collectible_with_a_long_title_template_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            create_templated_collectible(contract, Some("#{index}".repeat(1000)));
+        });
+    }
+}
+
+mod claim_cooldown {
+
+    use super::*;
+
+    const COOLDOWN_NS: u64 = 60_000_000_000;
+
+    fn init_with_cooldown() -> MockedContext<NftContractChecker> {
+        let mut context = init();
+        context
             .run_as(mintgate_admin(), |contract| {
-                contract.create_royalty_collectible(bob(), gate_id(1), 10, "1/7");
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            })
+            .run_as(alice(), |contract| {
+                contract.set_claim_cooldown(gate_id(1), Some(COOLDOWN_NS));
+                assert_eq!(
+                    contract.get_collectible_by_gate_id(gate_id(1)).unwrap().claim_cooldown_ns,
+                    Some(COOLDOWN_NS)
+                );
+            });
+        context
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Claiming tokens for gate ID `GPZkspuVGaZxwWoP6bJoWU` is on cooldown for another `U64("
+    )]
+    fn claim_twice_within_the_cooldown_should_panic() {
+        init_with_cooldown().run_as(bob(), |contract| {
+            contract.claim_token(gate_id(1));
+            contract.claim_token(gate_id(1));
+        });
+    }
+
+    #[test]
+    fn claim_again_after_the_cooldown() {
+        init_with_cooldown()
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+                contract.context.block_timestamp += COOLDOWN_NS / 2;
             })
             .run_as(bob(), |contract| {
-                let token_id = contract.claim_token(gate_id(1));
-                let payout = contract.nft_payout(token_id, 2000.into());
-                assert_eq!(payout.len(), 2);
-                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
-                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1950);
+                let reasons = contract.get_gate_view(gate_id(1), bob()).eligibility.reasons;
+                let reasons = serde_json::to_value(&reasons).unwrap();
+                assert_eq!(reasons.as_array().unwrap().len(), 1);
+                assert_eq!(reasons[0]["err"], "ClaimCooldownActive");
+                let remaining_ns: u64 =
+                    reasons[0]["remaining_ns"].as_str().unwrap().parse().unwrap();
+                assert!(remaining_ns > 0 && remaining_ns <= COOLDOWN_NS / 2);
+                assert!(contract.get_gate_view(gate_id(1), charlie()).eligibility.can_claim);
+
+                contract.context.block_timestamp += COOLDOWN_NS / 2;
+            })
+            .run_as(bob(), |contract| {
+                assert!(contract.get_gate_view(gate_id(1), bob()).eligibility.can_claim);
+                contract.claim_token(gate_id(1));
+                assert_eq!(contract.nft_supply_for_owner(bob()), U64(2));
             });
     }
 
     #[test]
-    fn nft_get_payout_when_creator_and_owner_are_the_same_with_no_royalty() {
-        init_contract("0/1", "1/1", metadata(base_uri()))
-            .run_as(mintgate_admin(), |contract| {
-                contract.create_royalty_collectible(bob(), gate_id(1), 10, "0/7");
+    fn claim_twice_without_a_cooldown() {
+        init_with_cooldown()
+            .run_as(alice(), |contract| {
+                contract.set_claim_cooldown(gate_id(1), None);
             })
             .run_as(bob(), |contract| {
-                let token_id = contract.claim_token(gate_id(1));
-                let payout = contract.nft_payout(token_id, 2000.into());
-                assert_eq!(payout.len(), 2);
-                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
-                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1950);
+                contract.claim_token(gate_id(1));
+                contract.claim_token(gate_id(1));
+                assert_eq!(contract.nft_supply_for_owner(bob()), U64(2));
             });
     }
+
+    #[test]
+    #[should_panic(
+        expected = "Only the creator of gate ID `GPZkspuVGaZxwWoP6bJoWU` is allowed to perform this operation"
+    )]
+    fn set_claim_cooldown_by_non_creator_should_panic() {
+        init_with_cooldown().run_as(bob(), |contract| {
+            contract.set_claim_cooldown(gate_id(1), None);
+        });
+    }
 }
 
-mod nft_transfer_payout {
+mod open_edition {
 
     use super::*;
 
+    /// How long (in milliseconds) the open edition is open after its creation.
+    const OPEN_FOR_MS: u64 = 60_000;
+
+    fn create_open_edition(contract: &mut NftContract, supply: u16, open_until: Option<u64>) {
+        contract.create_collectible(
+            alice(),
+            gate_id(1),
+            "Open edition".to_string(),
+            "desc".to_string(),
+            supply,
+            "5/100".parse().unwrap(),
+            None,
+            None,
+            None,
+            None,
+            Some(CollectibleOptions { open_until, ..Default::default() }),
+        );
+    }
+
+    fn init_open_edition() -> MockedContext<NftContractChecker> {
+        let mut context = init();
+        context.run_as(mintgate_admin(), |contract| {
+            let now = contract.context.block_timestamp / 1_000_000;
+            create_open_edition(contract, 0, Some(now + OPEN_FOR_MS));
+        });
+        context
+    }
+
     #[test]
-    fn nft_get_transfer_payout() {
-        init()
-            .run_as(mintgate_admin(), |contract| {
-                contract.create_royalty_collectible(alice(), gate_id(1), 10, "15/100");
+    fn claim_many_tokens_from_an_open_edition() {
+        init_open_edition().run_as(bob(), |contract| {
+            for _ in 0..50 {
+                contract.claim_token(gate_id(1));
+            }
+
+            let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+            assert_eq!(collectible.current_supply, 0);
+            assert_eq!(collectible.minted_tokens.len(), 50);
+            assert_eq!(collectible.metadata.copies, None);
+            assert!(!contract.is_gate_sold_out(gate_id(1)));
+            assert_eq!(contract.remaining_claims_for(gate_id(1), bob()), u16::MAX);
+            assert_eq!(contract.nft_supply_for_owner(bob()), U64(50));
+        });
+    }
+
+    #[test]
+    fn get_creator_collectibles_with_stats_of_an_open_edition() {
+        let stats = |contract: &NftContract| {
+            let stats = contract.get_creator_collectibles_with_stats(alice(), None, None);
+            stats
+                .into_iter()
+                .map(|(_, minted, remaining)| (minted.0, remaining.0))
+                .collect::<Vec<_>>()
+        };
+        init_open_edition()
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+                assert_eq!(stats(contract), vec![(1, u16::MAX as u64)]);
+                contract.context.block_timestamp += (OPEN_FOR_MS + 1) * 1_000_000;
             })
             .run_as(bob(), |contract| {
-                let token_id = contract.claim_token(gate_id(1));
-                let payout = contract
-                    .nft_transfer_payout(charlie(), token_id, None, None, Some(2000.into()))
-                    .unwrap();
-                assert_eq!(payout.len(), 3);
-                assert_eq!(payout.get(mintgate_fee_account_id().as_ref()).unwrap().0, 50);
-                assert_eq!(payout.get(alice().as_ref()).unwrap().0, 300);
-                assert_eq!(payout.get(bob().as_ref()).unwrap().0, 1650);
+                assert_eq!(stats(contract), vec![(1, 0)]);
+            });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Tokens for gate id `GPZkspuVGaZxwWoP6bJoWU` have already been claimed"
+    )]
+    fn claim_from_an_open_edition_after_the_deadline_should_panic() {
+        init_open_edition()
+            .run_as(bob(), |contract| {
+                contract.claim_token(gate_id(1));
+                contract.claim_token(gate_id(1));
+                contract.context.block_timestamp += (OPEN_FOR_MS + 1) * 1_000_000;
+            })
+            .run_as(bob(), |contract| {
+                assert!(contract.is_gate_sold_out(gate_id(1)));
+                assert_eq!(contract.remaining_claims_for(gate_id(1), bob()), 0);
+                let collectible = contract.get_collectible_by_gate_id(gate_id(1)).unwrap();
+                assert_eq!(collectible.minted_tokens.len(), 2);
+
+                contract.claim_token(gate_id(1));
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "Gate ID `GPZkspuVGaZxwWoP6bJoWU` must have a positive supply")]
+    fn create_a_zero_supply_collectible_without_open_until_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            create_open_edition(contract, 0, None);
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid argument for gate ID `GPZkspuVGaZxwWoP6bJoWU`: `open_until` is only allowed with a zero `supply`"
+    )]
+    fn create_an_open_edition_with_supply_should_panic() {
+        init().run_as(mintgate_admin(), |contract| {
+            let now = contract.context.block_timestamp / 1_000_000;
+            create_open_edition(contract, 10, Some(now + OPEN_FOR_MS));
+        });
+    }
+}
+
+mod require_distinct_admin_fee {
+
+    use super::*;
+
+    fn init_with_admin_as_fee_account(options: InitOptions) -> MockedContext<NftContractChecker> {
+        MockedContext::new(|| NftContractChecker {
+            contract: NftContract::init(
+                mintgate_admin(),
+                metadata(base_uri()),
+                "5/100".parse().unwrap(),
+                "30/100".parse().unwrap(),
+                "25/1000".parse().unwrap(),
+                mintgate_admin(),
+                Some(options),
+            ),
+            claimed_tokens: Vec::new(),
+        })
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The admin `admin` is not allowed to be the `mintgate_fee_account_id` as well"
+    )]
+    fn init_with_admin_as_fee_account_should_panic() {
+        init_with_admin_as_fee_account(InitOptions {
+            require_distinct_admin_fee: true,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn init_with_admin_as_fee_account_by_default() {
+        init_with_admin_as_fee_account(InitOptions::default()).run_as(
+            mintgate_admin(),
+            |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
+            },
+        );
+    }
+
+    #[test]
+    fn init_with_distinct_admin_and_fee_account() {
+        init_with_options(InitOptions { require_distinct_admin_fee: true, ..Default::default() })
+            .run_as(mintgate_admin(), |contract| {
+                contract.create_test_collectible(alice(), gate_id(1), 10);
             });
     }
 }
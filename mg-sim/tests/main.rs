@@ -1,10 +1,14 @@
-use mg_core::{gate::ValidGateId, mocked_context::gate_id, MarketApproveMsg, TokenId};
-use mg_nft::Panic;
+use mg_core::{
+    gate::ValidGateId, mocked_context::gate_id, MarketApproveMsg, NftApproveMsg, Token, TokenId,
+    EVENT_JSON,
+};
+use mg_market::{PayoutMismatchEvent, TokenKey};
+use mg_nft::{BatchApproveResultEvent, Panic};
 use near_sdk::{
     json_types::{ValidAccountId, U128, U64},
-    serde_json,
+    serde_json, AccountId,
 };
-use near_sdk_sim::to_yocto;
+use near_sdk_sim::{call, to_yocto, view, ContractAccount, ExecutionResult, UserAccount};
 
 mod sim;
 use sim::*;
@@ -71,6 +75,69 @@ fn create_and_claim_collectibles() {
     }
 }
 
+#[test]
+fn claim_tokens_with_deposit() {
+    let Sim { nft, alice, admin, .. } = &init(0, "1/1000", "30/100", "25/1000");
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+
+    let alice_balance = alice.balance();
+    claim_token_with_deposit(nft, alice, 1, to_yocto("5")).unwrap();
+    alice.check_amount(alice_balance);
+
+    let required = match claim_token_with_deposit(nft, alice, 1, 1) {
+        Ok(_) => panic!("Claim with insufficient deposit should fail"),
+        Err(msg) => match serde_json::from_str::<Panic>(&msg) {
+            Ok(Panic::NotEnoughDepositToClaim { required }) => required,
+            _ => panic!("Unexpected error: {}", msg),
+        },
+    };
+    assert!(required.0 > 1);
+
+    claim_token_with_deposit(nft, alice, 1, required.0).unwrap();
+    assert_eq!(get_collectible_by_gate_id(nft, gate_id(1)).current_supply, 8);
+}
+
+#[test]
+fn claim_a_paid_collectible() {
+    let Sim { nft, mintgate, alice, bob, admin, .. } = &init(0, "1/1000", "30/100", "25/1000");
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    set_mint_price(nft, alice, 1, "4").unwrap();
+
+    match claim_token(nft, bob, 1) {
+        Ok(_) => panic!("Claim without the mint price should fail"),
+        Err(msg) => match serde_json::from_str::<Panic>(&msg) {
+            Ok(Panic::NotEnoughDepositToMint { required }) => assert!(required.0 > to_yocto("4")),
+            _ => panic!("Unexpected error: {}", msg),
+        },
+    }
+
+    let bob_balance = bob.balance();
+    let alice_balance = alice.balance();
+    let mintgate_balance = mintgate.balance();
+    claim_token_with_deposit(nft, bob, 1, to_yocto("5")).unwrap();
+    bob.check_amount(bob_balance - to_yocto("4"));
+    alice.check_amount(alice_balance + to_yocto("4") - to_yocto("0.1"));
+    mintgate.check_amount(mintgate_balance + to_yocto("0.1"));
+}
+
+#[test]
+fn claim_a_sold_out_paid_collectible_refunds_the_deposit() {
+    let Sim { nft, alice, bob, charlie, admin, .. } = &init(0, "1/1000", "30/100", "25/1000");
+
+    create_collectible(nft, admin, alice, gate_id(1), 1, "10/100").unwrap();
+    set_mint_price(nft, alice, 1, "4").unwrap();
+    claim_token_with_deposit(nft, bob, 1, to_yocto("5")).unwrap();
+
+    let alice_balance = alice.balance();
+    let charlie_balance = charlie.balance();
+    claim_token_with_deposit(nft, charlie, 1, to_yocto("5"))
+        .failure(Panic::GateIdExhausted { gate_id: gate_id(1).to_string() }.msg());
+    charlie.check_amount(charlie_balance);
+    alice.check_amount(alice_balance);
+}
+
 #[test]
 fn nft_approve_and_revoke_tokens() {
     let Sim { nft, markets, fake_market, alice, bob, charlie, admin, .. } =
@@ -130,10 +197,31 @@ fn nft_approve_and_revoke_tokens() {
     )
     .unwrap();
 
-    nft_approve(nft, &fake_market, alice, token_id, "1")
-        .failure("cannot find contract code for account".to_string());
+    let msg = serde_json::to_string(&NftApproveMsg { min_price: U128(1), expires_at: None });
+    tx(call!(alice, nft.nft_approve(token_id, fake_market.valid_account_id(), msg.ok()))).unwrap();
+    assert_eq!(token_owner_and_approvals(nft, token_id), (alice.account_id(), Vec::new()));
     nft_revoke(nft, &fake_market, alice, token_id)
-        .failure("cannot find contract code for account".to_string());
+        .failure(Panic::RevokeApprovalFailed { account_id: fake_market.account_id() }.msg());
+}
+
+#[test]
+fn reprice_a_listing_with_nft_reapprove() {
+    let Sim { nft, markets, alice, admin, .. } = &init(1, "1/1000", "30/100", "25/1000");
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let token_id = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, &markets[0], alice, token_id, "3").unwrap();
+    nft_reapprove(nft, &markets[0], alice, token_id, "5").unwrap();
+
+    let tokens = get_tokens_for_sale(&markets[0]);
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].token_id, token_id);
+    assert_eq!(tokens[0].min_price, U128(to_yocto("5")));
+
+    let token: Token = view!(nft.nft_token(token_id)).unwrap_json::<Option<Token>>().unwrap();
+    let approval = &token.approvals[&markets[0].account_id()];
+    assert_eq!(approval.min_price, U128(to_yocto("5")));
+    assert_eq!(approval.approval_id, U64(1));
 }
 
 #[test]
@@ -143,10 +231,12 @@ fn batch_approve_a_few_tokens() {
     let users = [alice, bob, charlie];
 
     let mut tokens = Vec::new();
-    for u in 1..=11 {
+    for u in 1..=mg_nft::DEFAULT_MAX_BATCH_SIZE as u64 + 1 {
         tokens.push((u.into(), U128(u as u128 * 1000)));
     }
-    batch_approve(nft, &markets[0], alice, tokens).failure(Panic::ExceedTokensToBatchApprove.msg());
+    batch_approve(nft, &markets[0], alice, tokens).failure(
+        Panic::ExceedTokensToBatchApprove { max_batch_size: mg_nft::DEFAULT_MAX_BATCH_SIZE }.msg(),
+    );
 
     let n = 3;
     for u in 1..=(users.len() * n) {
@@ -187,6 +277,64 @@ fn batch_approve_a_few_tokens() {
     ));
 }
 
+#[test]
+fn batch_approve_logs_the_result_of_each_token() {
+    let Sim { nft, markets, alice, bob, admin, .. } = &init(1, "1/1000", "30/100", "25/1000");
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let mut tokens = Vec::new();
+    for _ in 0..3 {
+        tokens.push((claim_token(nft, alice, 1).unwrap(), U128(to_yocto("1"))));
+    }
+    let bobs_token_id = claim_token(nft, bob, 1).unwrap();
+    tokens.push((bobs_token_id, U128(to_yocto("1"))));
+
+    let result = call!(alice, nft.batch_approve(tokens.clone(), markets[0].valid_account_id()));
+    let events = result
+        .promise_results()
+        .into_iter()
+        .flatten()
+        .flat_map(|outcome| outcome.logs().clone())
+        .filter_map(|log| log.strip_prefix(EVENT_JSON).map(|event| event.to_string()))
+        .map(|event| serde_json::from_str::<serde_json::Value>(&event).unwrap())
+        .filter(|event| event["event"] == "mg_batch_approve_result")
+        .collect::<Vec<_>>();
+    assert_eq!(events.len(), 1);
+
+    let result: BatchApproveResultEvent =
+        serde_json::from_value(events[0]["data"][0].clone()).unwrap();
+    assert_eq!(result.account_id, markets[0].account_id());
+    assert_eq!(
+        result.succeeded,
+        tokens[..3].iter().map(|(token_id, _)| *token_id).collect::<Vec<_>>()
+    );
+    assert_eq!(result.failed, vec![bobs_token_id]);
+}
+
+#[test]
+fn batch_revoke_a_few_tokens() {
+    let Sim { nft, markets, alice, admin, .. } = &init(1, "1/1000", "30/100", "25/1000");
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let mut tokens = Vec::new();
+    for u in 1..=3 {
+        let token_id = claim_token(nft, alice, 1).unwrap();
+        tokens.push((token_id, U128(u * 1000)));
+    }
+    batch_approve(nft, &markets[0], alice, tokens.clone()).unwrap();
+    assert_eq!(get_tokens_for_sale(&markets[0]).len(), 3);
+
+    let tokens = tokens.into_iter().map(|(token_id, _)| token_id).collect::<Vec<TokenId>>();
+    batch_revoke(nft, &markets[0], alice, tokens.clone()).unwrap();
+    assert!(get_tokens_for_sale(&markets[0]).is_empty());
+    assert!(get_tokens_by_owner_id(&markets[0], alice.valid_account_id()).is_empty());
+
+    batch_revoke(nft, &markets[0], alice, tokens.clone()).failure(format!(
+        "{} error(s) detected, see `panics` fields for a full list of errors",
+        tokens.len()
+    ));
+}
+
 #[test]
 fn buy_a_few_tokens() {
     let Sim { nft, mintgate, markets, alice, bob, admin, .. } =
@@ -220,6 +368,530 @@ fn buy_a_few_tokens() {
     mintgate.check_amount(mintgate_balance + to_yocto("0.175"));
 }
 
+#[test]
+fn buy_a_token_with_a_discount_code() {
+    let Sim { root, nft, mintgate, markets, alice, bob, admin, .. } =
+        &init(1, "1/1000", "30/100", "25/1000");
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let token_id = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, &markets[0], alice, token_id, "3").unwrap();
+
+    let now = block_timestamp(root);
+    for (code, expires_at) in [("PROMO10", now + 60_000), ("OLD", now - 1)].iter() {
+        tx(call!(
+            root,
+            markets[0].admin_set_discount_code(
+                code.to_string(),
+                "10/100".parse().unwrap(),
+                *expires_at
+            )
+        ))
+        .unwrap();
+    }
+
+    tx(call!(
+        bob,
+        markets[0].buy_token_with_code(nft.valid_account_id(), token_id, "OLD".to_string()),
+        deposit = to_yocto("2.7")
+    ))
+    .failure(mg_market::Panics::DiscountCodeExpired { code: "OLD".to_string() }.msg());
+
+    let bob_balance = bob.balance();
+    let alice_balance = alice.balance();
+    let mintgate_balance = mintgate.balance();
+    tx(call!(
+        bob,
+        markets[0].buy_token_with_code(nft.valid_account_id(), token_id, "PROMO10".to_string()),
+        deposit = to_yocto("2.7")
+    ))
+    .unwrap();
+    bob.check_amount(bob_balance - to_yocto("2.7"));
+    alice.check_amount(alice_balance + to_yocto("3") - to_yocto("0.075"));
+    mintgate.check_amount(mintgate_balance + to_yocto("0.075"));
+    assert_eq!(get_tokens_by_owner(nft, bob).len(), 1);
+}
+
+#[test]
+fn lifetime_fees_after_a_few_sales() {
+    let Sim { nft, markets, alice, bob, admin, .. } = &init(1, "1/1000", "30/100", "25/1000");
+    let market = &markets[0];
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let lifetime_fees = || view!(market.get_lifetime_fees()).unwrap_json::<U128>().0;
+    assert_eq!(lifetime_fees(), 0);
+
+    let token_id = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, market, alice, token_id, "3").unwrap();
+    buy_token(market, nft, bob, token_id, "3").unwrap();
+    assert_eq!(lifetime_fees(), to_yocto("0.075"));
+
+    let token_id = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, market, alice, token_id, "5").unwrap();
+    buy_token(market, nft, bob, token_id, "7").unwrap();
+    assert_eq!(lifetime_fees(), to_yocto("0.075") + to_yocto("0.175"));
+}
+
+#[test]
+fn purchase_history_after_a_few_sales() {
+    let Sim { nft, markets, alice, bob, admin, .. } = &init(1, "1/1000", "30/100", "25/1000");
+    let market = &markets[0];
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let purchases = |user: &UserAccount| {
+        view!(market.get_purchases(user.valid_account_id(), None, None))
+            .unwrap_json::<Vec<(TokenKey, U128, u64)>>()
+    };
+    assert_eq!(purchases(bob).len(), 0);
+
+    let token_id1 = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, market, alice, token_id1, "3").unwrap();
+    buy_token(market, nft, bob, token_id1, "3").unwrap();
+
+    let token_id2 = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, market, alice, token_id2, "5").unwrap();
+    buy_token(market, nft, bob, token_id2, "7").unwrap();
+
+    let history = purchases(bob);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].0, TokenKey::new(nft.account_id(), token_id1));
+    assert_eq!(history[0].1, U128(to_yocto("3")));
+    assert_eq!(history[1].0, TokenKey::new(nft.account_id(), token_id2));
+    assert_eq!(history[1].1, U128(to_yocto("7")));
+
+    let page = view!(market.get_purchases(bob.valid_account_id(), Some(U64(1)), Some(1)))
+        .unwrap_json::<Vec<(TokenKey, U128, u64)>>();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].0, TokenKey::new(nft.account_id(), token_id2));
+
+    assert_eq!(purchases(alice).len(), 0);
+}
+
+#[test]
+fn royalties_earned_after_a_few_sales() {
+    let Sim { nft, markets, alice, bob, charlie, admin, .. } =
+        &init(1, "1/1000", "30/100", "25/1000");
+    let market = &markets[0];
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let royalties_earned = |user: &UserAccount| {
+        view!(market.get_royalties_earned(user.valid_account_id())).unwrap_json::<U128>().0
+    };
+    assert_eq!(royalties_earned(alice), 0);
+
+    let token_id1 = claim_token(nft, bob, 1).unwrap();
+    nft_approve(nft, market, bob, token_id1, "3").unwrap();
+    buy_token(market, nft, charlie, token_id1, "3").unwrap();
+    assert_eq!(royalties_earned(alice), to_yocto("0.3"));
+
+    let token_id2 = claim_token(nft, bob, 1).unwrap();
+    nft_approve(nft, market, bob, token_id2, "5").unwrap();
+    buy_token(market, nft, charlie, token_id2, "5").unwrap();
+    assert_eq!(royalties_earned(alice), to_yocto("0.3") + to_yocto("0.5"));
+
+    let token_id3 = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, market, alice, token_id3, "7").unwrap();
+    buy_token(market, nft, charlie, token_id3, "7").unwrap();
+    assert_eq!(royalties_earned(alice), to_yocto("0.3") + to_yocto("0.5"));
+    assert_eq!(royalties_earned(bob), 0);
+}
+
+#[test]
+fn recent_sales_after_a_few_sales() {
+    let Sim { nft, markets, alice, bob, charlie, admin, .. } =
+        &init(1, "1/1000", "30/100", "25/1000");
+    let market = &markets[0];
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let recent_sales = |limit: Option<u32>| {
+        view!(market.get_recent_sales(limit)).unwrap_json::<Vec<(TokenKey, AccountId, U128, u64)>>()
+    };
+    assert_eq!(recent_sales(None).len(), 0);
+
+    let token_id1 = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, market, alice, token_id1, "3").unwrap();
+    buy_token(market, nft, bob, token_id1, "3").unwrap();
+
+    let token_id2 = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, market, alice, token_id2, "5").unwrap();
+    buy_token(market, nft, charlie, token_id2, "7").unwrap();
+
+    let sales = recent_sales(None);
+    assert_eq!(sales.len(), 2);
+    assert_eq!(sales[0].0, TokenKey::new(nft.account_id(), token_id2));
+    assert_eq!(sales[0].1, charlie.account_id());
+    assert_eq!(sales[0].2, U128(to_yocto("7")));
+    assert_eq!(sales[1].0, TokenKey::new(nft.account_id(), token_id1));
+    assert_eq!(sales[1].1, bob.account_id());
+    assert_eq!(sales[1].2, U128(to_yocto("3")));
+    assert!(sales[0].3 >= sales[1].3);
+
+    let sales = recent_sales(Some(1));
+    assert_eq!(sales.len(), 1);
+    assert_eq!(sales[0].0, TokenKey::new(nft.account_id(), token_id2));
+}
+
+#[test]
+fn batch_buy_a_few_tokens() {
+    let Sim { nft, mintgate, markets, alice, bob, admin, .. } =
+        &init(1, "1/1000", "30/100", "25/1000");
+    let market = &markets[0];
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let token_id1 = claim_token(nft, alice, 1).unwrap();
+    let token_id2 = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, market, alice, token_id1, "3").unwrap();
+    nft_approve(nft, market, alice, token_id2, "7").unwrap();
+
+    let bob_balance = bob.balance();
+    let alice_balance = alice.balance();
+    let mintgate_balance = mintgate.balance();
+    let result = tx(call!(
+        bob,
+        market.batch_buy(vec![
+            (nft.valid_account_id(), token_id1),
+            (nft.valid_account_id(), token_id2)
+        ]),
+        deposit = to_yocto("11")
+    ))
+    .unwrap();
+    assert!(result.promise_errors().is_empty());
+
+    let tokens = get_tokens_by_owner(nft, bob);
+    assert_eq!(tokens.len(), 2);
+    assert!(get_tokens_for_sale(market).is_empty());
+
+    bob.check_amount(bob_balance - to_yocto("10"));
+    alice.check_amount(alice_balance + to_yocto("10") - to_yocto("0.25"));
+    mintgate.check_amount(mintgate_balance + to_yocto("0.25"));
+}
+
+#[test]
+fn batch_buy_refunds_failed_tokens() {
+    let Sim { nft, markets, alice, bob, charlie, admin, .. } =
+        &init(1, "1/1000", "30/100", "25/1000");
+    let market = &markets[0];
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let token_id1 = claim_token(nft, alice, 1).unwrap();
+    let token_id2 = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, market, alice, token_id1, "3").unwrap();
+    nft_approve(nft, market, alice, token_id2, "7").unwrap();
+
+    tx(call!(alice, nft.nft_transfer(charlie.valid_account_id(), token_id2, None, None))).unwrap();
+
+    let bob_balance = bob.balance();
+    let result = tx(call!(
+        bob,
+        market.batch_buy(vec![
+            (nft.valid_account_id(), token_id1),
+            (nft.valid_account_id(), token_id2)
+        ]),
+        deposit = to_yocto("10")
+    ))
+    .unwrap();
+    assert!(result.promise_errors().is_empty());
+
+    let tokens = get_tokens_by_owner(nft, bob);
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].token_id, token_id1);
+    let token = view!(nft.nft_token(token_id2)).unwrap_json::<Option<Token>>().unwrap();
+    assert_eq!(token.owner_id, charlie.account_id());
+    bob.check_amount(bob_balance - to_yocto("3"));
+}
+
+#[test]
+fn approve_and_buy_tokens_with_insufficient_gas() {
+    let Sim { nft, markets, alice, bob, admin, .. } = &init(1, "1/1000", "30/100", "25/1000");
+    let market = &markets[0];
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let token_id = claim_token(nft, alice, 1).unwrap();
+    let msg =
+        serde_json::to_string(&NftApproveMsg { min_price: to_yocto("3").into(), expires_at: None })
+            .ok();
+
+    tx(call!(
+        alice,
+        nft.nft_approve(token_id, market.valid_account_id(), msg),
+        gas = mg_nft::MIN_GAS_FOR_NFT_APPROVE
+    ))
+    .failure(Panic::InsufficientGas { required: mg_nft::MIN_GAS_FOR_NFT_APPROVE.into() }.msg());
+
+    tx(call!(
+        alice,
+        nft.batch_approve(vec![(token_id, to_yocto("3").into())], market.valid_account_id()),
+        gas = mg_nft::MIN_GAS_FOR_BATCH_APPROVE
+    ))
+    .failure(
+        Panic::InsufficientGas {
+            required: (mg_nft::MIN_GAS_FOR_BATCH_APPROVE + mg_nft::GAS_FOR_BATCH_APPROVE_ITEM)
+                .into(),
+        }
+        .msg(),
+    );
+
+    nft_approve(nft, market, alice, token_id, "3").unwrap();
+
+    tx(call!(
+        bob,
+        market.buy_token(nft.valid_account_id(), token_id),
+        to_yocto("3"),
+        mg_market::MIN_GAS_FOR_BUY_TOKEN
+    ))
+    .failure(
+        mg_market::Panics::InsufficientGas { required: mg_market::MIN_GAS_FOR_BUY_TOKEN.into() }
+            .msg(),
+    );
+}
+
+#[test]
+fn sync_listing_after_transfer_off_market() {
+    let Sim { nft, markets, alice, bob, charlie, admin, .. } =
+        &init(1, "1/1000", "30/100", "25/1000");
+    let market = &markets[0];
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let token_id = claim_token(nft, alice, 1).unwrap();
+    let other_token_id = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, market, alice, token_id, "3").unwrap();
+    nft_approve(nft, market, alice, other_token_id, "3").unwrap();
+
+    tx(call!(alice, nft.nft_transfer(charlie.valid_account_id(), token_id, None, None))).unwrap();
+    assert_eq!(get_tokens_for_sale(market).len(), 2);
+
+    tx(call!(bob, market.sync_listing(nft.valid_account_id(), other_token_id))).unwrap();
+    assert_eq!(get_tokens_for_sale(market).len(), 2);
+
+    tx(call!(bob, market.sync_listing(nft.valid_account_id(), token_id))).unwrap();
+    let tokens = get_tokens_for_sale(market);
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].token_id, other_token_id);
+
+    tx(call!(bob, market.sync_listing(nft.valid_account_id(), token_id))).failure(
+        mg_market::Panics::TokenKeyNotFound {
+            token_key: TokenKey::new(nft.account_id(), token_id),
+        }
+        .msg(),
+    );
+}
+
+#[test]
+fn buy_token_after_transfer_off_market() {
+    let Sim { nft, markets, alice, bob, charlie, admin, .. } =
+        &init(1, "1/1000", "30/100", "25/1000");
+    let market = &markets[0];
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let token_id = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, market, alice, token_id, "3").unwrap();
+
+    tx(call!(alice, nft.nft_transfer(charlie.valid_account_id(), token_id, None, None))).unwrap();
+    assert_eq!(get_tokens_for_sale(market).len(), 1);
+
+    let alice_balance = alice.balance();
+    let bob_balance = bob.balance();
+
+    let result =
+        tx(call!(bob, market.buy_token(nft.valid_account_id(), token_id), deposit = to_yocto("3")))
+            .unwrap();
+    assert!(result.promise_errors().is_empty());
+
+    assert_eq!(get_tokens_for_sale(market).len(), 0);
+    let token = view!(nft.nft_token(token_id)).unwrap_json::<Option<Token>>().unwrap();
+    assert_eq!(token.owner_id, charlie.account_id());
+    alice.check_amount(alice_balance);
+    bob.check_amount(bob_balance);
+}
+
+#[test]
+fn buy_token_with_inflated_payout() {
+    let Sim { root, markets, alice, bob, .. } = &init(1, "1/1000", "30/100", "25/1000");
+    let market = &markets[0];
+    let mock_nft = deploy_mock_nft(root);
+
+    let token_id = U64(0);
+    let msg = serde_json::to_string(&MarketApproveMsg {
+        min_price: to_yocto("3").into(),
+        gate_id: None,
+        creator_id: None,
+    })
+    .unwrap();
+    tx(call!(alice, mock_nft.list_token(token_id, market.valid_account_id(), msg))).unwrap();
+    assert_eq!(get_tokens_for_sale(market).len(), 1);
+
+    let market_balance = market.user_account.balance();
+    let alice_balance = alice.balance();
+    let bob_balance = bob.balance();
+
+    let result = tx(call!(
+        bob,
+        market.buy_token(mock_nft.valid_account_id(), token_id),
+        deposit = to_yocto("3")
+    ))
+    .unwrap();
+    assert!(result.promise_errors().is_empty());
+    let events = result
+        .promise_results()
+        .into_iter()
+        .flatten()
+        .flat_map(|outcome| outcome.logs().clone())
+        .filter_map(|log| log.strip_prefix(EVENT_JSON).map(|event| event.to_string()))
+        .map(|event| serde_json::from_str::<serde_json::Value>(&event).unwrap())
+        .filter(|event| event["event"] == "mg_payout_mismatch")
+        .collect::<Vec<_>>();
+    assert_eq!(events.len(), 1);
+
+    let event: PayoutMismatchEvent = serde_json::from_value(events[0]["data"][0].clone()).unwrap();
+    assert_eq!(event.buyer_id, bob.account_id());
+    assert_eq!(event.owner_id, alice.account_id());
+    assert_eq!(event.payout, Some(U128(to_yocto("6"))));
+    assert_eq!(event.amount, U128(to_yocto("3")));
+
+    assert!(market.user_account.balance() >= market_balance);
+    alice.check_amount(alice_balance + to_yocto("3"));
+    bob.check_amount(bob_balance - to_yocto("3"));
+}
+
+#[test]
+fn enforce_royalties_rejects_market_transfers_without_payout() {
+    let Sim { nft, markets, alice, bob, charlie, admin, .. } =
+        &init(1, "1/1000", "30/100", "25/1000");
+    let market = &markets[0];
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let token_id = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, market, alice, token_id, "3").unwrap();
+
+    tx(call!(admin, nft.set_enforce_royalties(true))).unwrap();
+
+    tx(call!(
+        market.user_account,
+        nft.nft_transfer(charlie.valid_account_id(), token_id, None, None)
+    ))
+    .failure(Panic::RoyaltiesEnforced { sender_id: market.account_id() }.msg());
+
+    buy_token(market, nft, bob, token_id, "3").unwrap();
+    let token = view!(nft.nft_token(token_id)).unwrap_json::<Option<Token>>().unwrap();
+    assert_eq!(token.owner_id, bob.account_id());
+}
+
+#[test]
+fn admin_withdraw_stranded_balance() {
+    let Sim { root, markets, alice, .. } = &init(1, "1/1000", "30/100", "25/1000");
+    let market = &markets[0];
+
+    root.transfer(market.account_id(), to_yocto("5"));
+
+    tx(call!(alice, market.admin_withdraw(U128(to_yocto("5")), alice.valid_account_id())))
+        .failure(mg_market::Panics::AdminRestrictedOperation.msg());
+
+    let alice_balance = alice.balance();
+    tx(call!(root, market.admin_withdraw(U128(to_yocto("5")), alice.valid_account_id()))).unwrap();
+    alice.check_amount(alice_balance + to_yocto("5"));
+}
+
+#[test]
+fn buy_token_after_approval_expired() {
+    let Sim { nft, markets, alice, bob, admin, .. } = &init(1, "1/1000", "30/100", "25/1000");
+    let market = &markets[0];
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let token_id = claim_token(nft, alice, 1).unwrap();
+    let msg = serde_json::to_string(&NftApproveMsg {
+        min_price: to_yocto("3").into(),
+        expires_at: Some(block_timestamp(alice) + 10_000),
+    })
+    .ok();
+    tx(call!(alice, nft.nft_approve(token_id, market.valid_account_id(), msg))).unwrap();
+    assert_eq!(get_tokens_for_sale(market).len(), 1);
+
+    advance_time(alice, 20);
+
+    let result =
+        tx(call!(bob, market.buy_token(nft.valid_account_id(), token_id), deposit = to_yocto("3")))
+            .unwrap();
+    let msg = Panic::ApprovalExpired { token_id, account_id: market.account_id() }.msg();
+    assert!(result
+        .promise_errors()
+        .into_iter()
+        .filter_map(|err| tx(err.unwrap()).err())
+        .any(|err| err.contains(&msg)));
+
+    let token = view!(nft.nft_token(token_id)).unwrap_json::<Option<Token>>().unwrap();
+    assert_eq!(token.owner_id, alice.account_id());
+}
+
+fn nft_transfer_call(
+    nft: &ContractAccount<NftContract>,
+    receiver: &ContractAccount<MockReceiverContract>,
+    user: &UserAccount,
+    token_id: TokenId,
+    msg: &str,
+) -> ExecutionResult {
+    tx(call!(
+        user,
+        nft.nft_transfer_call(receiver.valid_account_id(), token_id, None, None, msg.to_string())
+    ))
+    .unwrap()
+}
+
+fn token_owner_and_approvals(
+    nft: &ContractAccount<NftContract>,
+    token_id: TokenId,
+) -> (String, Vec<String>) {
+    let token: Token = view!(nft.nft_token(token_id)).unwrap_json::<Option<Token>>().unwrap();
+    (token.owner_id, token.approvals.into_iter().map(|(market_id, _)| market_id).collect())
+}
+
+#[test]
+fn nft_transfer_call_returned_by_receiver() {
+    let Sim { root, nft, markets, alice, admin, .. } = &init(1, "1/1000", "30/100", "25/1000");
+    let receiver = deploy_mock_receiver(root);
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let token_id = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, &markets[0], alice, token_id, "3").unwrap();
+
+    let result = nft_transfer_call(nft, &receiver, alice, token_id, "return");
+    assert!(result.promise_errors().is_empty());
+    assert_eq!(
+        token_owner_and_approvals(nft, token_id),
+        (alice.account_id(), vec![markets[0].account_id()])
+    );
+}
+
+#[test]
+fn nft_transfer_call_kept_by_receiver() {
+    let Sim { root, nft, markets, alice, admin, .. } = &init(1, "1/1000", "30/100", "25/1000");
+    let receiver = deploy_mock_receiver(root);
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let token_id = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, &markets[0], alice, token_id, "3").unwrap();
+
+    let result = nft_transfer_call(nft, &receiver, alice, token_id, "keep");
+    assert!(result.promise_errors().is_empty());
+    assert_eq!(token_owner_and_approvals(nft, token_id), (receiver.account_id(), vec![]));
+}
+
+#[test]
+fn nft_transfer_call_failed_in_receiver() {
+    let Sim { root, nft, markets, alice, admin, .. } = &init(1, "1/1000", "30/100", "25/1000");
+    let receiver = deploy_mock_receiver(root);
+
+    create_collectible(nft, admin, alice, gate_id(1), 10, "10/100").unwrap();
+    let token_id = claim_token(nft, alice, 1).unwrap();
+    nft_approve(nft, &markets[0], alice, token_id, "3").unwrap();
+
+    let result = nft_transfer_call(nft, &receiver, alice, token_id, "fail");
+    assert_eq!(result.promise_errors().len(), 1);
+    assert_eq!(
+        token_owner_and_approvals(nft, token_id),
+        (alice.account_id(), vec![markets[0].account_id()])
+    );
+}
+
 fn approve_msg(price: u128, gate_id: ValidGateId, creator_id: ValidAccountId) -> String {
     serde_json::to_string(&MarketApproveMsg {
         min_price: price.into(),
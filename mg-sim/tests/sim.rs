@@ -1,6 +1,8 @@
 near_sdk_sim::lazy_static_include::lazy_static_include_bytes! {
     NFT_WASM_BYTES => "../target/wasm32-unknown-unknown/release/mg_nft.wasm",
     MARKET_WASM_BYTES => "../target/wasm32-unknown-unknown/release/mg_market.wasm",
+    MOCK_NFT_WASM_BYTES => "../target/wasm32-unknown-unknown/release/mg_mock_nft.wasm",
+    MOCK_RECEIVER_WASM_BYTES => "../target/wasm32-unknown-unknown/release/mg_mock_receiver.wasm",
 }
 
 use ansi_term::{Colour, Style};
@@ -23,8 +25,13 @@ use near_sdk_sim::{transaction::ExecutionOutcome, view, DEFAULT_GAS, STORAGE_AMO
 use std::{convert::TryInto, fmt::Debug};
 
 pub use mg_market::MarketContractContract as MarketContract;
+pub use mg_mock_nft::MockNftContractContract as MockNftContract;
+pub use mg_mock_receiver::MockReceiverContractContract as MockReceiverContract;
 pub use mg_nft::NftContractContract as NftContract;
 
+/// Deposit (in NEARs) attached when claiming tokens, enough to cover storage.
+pub const CLAIM_DEPOSIT: &str = "0.1";
+
 const NFT_ID: &str = "nft";
 const MARKET_ID: &str = "market";
 
@@ -92,7 +99,8 @@ pub fn init(n: usize, min_royalty: &str, max_royalty: &str, mintgate_fee: &str)
             min_royalty.parse().unwrap(),
             max_royalty.parse().unwrap(),
             mintgate_fee.parse().unwrap(),
-            mintgate.valid_account_id()
+            mintgate.valid_account_id(),
+            None
         )
     );
 
@@ -107,7 +115,7 @@ pub fn init(n: usize, min_royalty: &str, max_royalty: &str, mintgate_fee: &str)
             signer_account: root,
             deposit: near_sdk_sim::STORAGE_AMOUNT * 10,
             gas: near_sdk_sim::DEFAULT_GAS,
-            init_method: init()
+            init_method: init(U128(0), mintgate.valid_account_id(), None)
         );
         mids.push(mid);
         markets.push(market);
@@ -121,6 +129,28 @@ pub fn init(n: usize, min_royalty: &str, max_royalty: &str, mintgate_fee: &str)
     Sim { root, nft, markets, fake_market, mids, mintgate, admin, alice, bob, charlie }
 }
 
+/// Deploys the misbehaving NFT contract, see `mg_mock_nft`.
+pub fn deploy_mock_nft(root: &UserAccount) -> ContractAccount<MockNftContract> {
+    deploy!(
+        contract: MockNftContract,
+        contract_id: "mock_nft",
+        bytes: &MOCK_NFT_WASM_BYTES,
+        signer_account: root,
+        deposit: STORAGE_AMOUNT * 10
+    )
+}
+
+/// Deploys the `nft_transfer_call` receiver contract, see `mg_mock_receiver`.
+pub fn deploy_mock_receiver(root: &UserAccount) -> ContractAccount<MockReceiverContract> {
+    deploy!(
+        contract: MockReceiverContract,
+        contract_id: "mock_receiver",
+        bytes: &MOCK_RECEIVER_WASM_BYTES,
+        signer_account: root,
+        deposit: STORAGE_AMOUNT * 10
+    )
+}
+
 fn metadata() -> mg_core::nep177::NFTContractMetadata {
     mg_core::nep177::NFTContractMetadata {
         spec: "mg-nft-1.0.0".to_string(),
@@ -164,6 +194,7 @@ pub fn create_collectible(
             None,
             None,
             None,
+            None,
             None
         ),
         0,
@@ -192,14 +223,46 @@ pub fn get_collectible_by_gate_id(
     result.unwrap()
 }
 
+pub fn set_mint_price(
+    nft: &ContractAccount<NftContract>,
+    creator: &UserAccount,
+    gate_key: u16,
+    mint_price: &str,
+) -> Result<(), String> {
+    let gate_id = gate_id(gate_key);
+    println!(
+        "[{}] `{}` setting mint price for `{}` to `{}`",
+        nft.account_id(),
+        creator.account_id,
+        gate_id,
+        mint_price
+    );
+    tx(call!(creator, nft.set_mint_price(gate_id, Some(U128(to_yocto(mint_price)))))).map(|_| ())
+}
+
 pub fn claim_token(
     nft: &ContractAccount<NftContract>,
     user: &UserAccount,
     gate_key: u16,
+) -> Result<TokenId, String> {
+    claim_token_with_deposit(nft, user, gate_key, to_yocto(CLAIM_DEPOSIT))
+}
+
+pub fn claim_token_with_deposit(
+    nft: &ContractAccount<NftContract>,
+    user: &UserAccount,
+    gate_key: u16,
+    deposit: Balance,
 ) -> Result<TokenId, String> {
     let gate_id = gate_id(gate_key);
-    println!("[{}] `{}` claiming token for `{}`", nft.account_id(), user.account_id, gate_id,);
-    match tx(call!(user, nft.claim_token(gate_id))) {
+    println!(
+        "[{}] `{}` claiming token for `{}` with deposit `{}`",
+        nft.account_id(),
+        user.account_id,
+        gate_id,
+        deposit
+    );
+    match tx(call!(user, nft.claim_token(gate_id), deposit = deposit)) {
         Ok(x) => {
             let result: Option<TokenId> = x.unwrap_json();
             Ok(result.unwrap())
@@ -256,7 +319,7 @@ pub fn nft_approve(
     );
 
     fn approve_msg(price: u128) -> Option<String> {
-        serde_json::to_string(&NftApproveMsg { min_price: price.into() }).ok()
+        serde_json::to_string(&NftApproveMsg { min_price: price.into(), expires_at: None }).ok()
     }
 
     match tx(call!(
@@ -275,6 +338,25 @@ pub fn nft_approve(
     }
 }
 
+pub fn nft_reapprove(
+    nft: &ContractAccount<NftContract>,
+    market: &ContractAccount<MarketContract>,
+    user: &UserAccount,
+    token_id: TokenId,
+    amount: &str,
+) -> Result<(), String> {
+    println!(
+        "[{}] `{}` repricing token `{:?}` in `{}` to N`{}`",
+        nft.account_id(),
+        user.account_id,
+        token_id,
+        market.account_id(),
+        amount
+    );
+    tx(call!(user, nft.nft_reapprove(token_id, market.valid_account_id(), U128(to_yocto(amount)))))
+        .map(|_| ())
+}
+
 pub fn batch_approve(
     nft: &ContractAccount<NftContract>,
     market: &ContractAccount<MarketContract>,
@@ -336,6 +418,25 @@ pub fn nft_revoke(
     }
 }
 
+pub fn batch_revoke(
+    nft: &ContractAccount<NftContract>,
+    market: &ContractAccount<MarketContract>,
+    user: &UserAccount,
+    tokens: Vec<TokenId>,
+) -> Result<(), String> {
+    println!(
+        "[{}] `{}` revoking tokens `{:?}` from `{}`",
+        nft.account_id(),
+        user.account_id,
+        tokens,
+        market.account_id(),
+    );
+    match tx(call!(user, nft.batch_revoke(tokens.clone(), market.valid_account_id()))) {
+        Ok(_) => Ok(()),
+        Err(msg) => Err(msg),
+    }
+}
+
 pub fn nft_on_approve(
     market: &ContractAccount<MarketContract>,
     user: &UserAccount,
@@ -396,6 +497,20 @@ pub fn buy_token(
     }
 }
 
+/// Returns the current block timestamp of the simulator (in milliseconds),
+/// as seen by contracts through `env::block_timestamp() / 1_000_000`.
+pub fn block_timestamp(user: &UserAccount) -> u64 {
+    user.borrow_runtime().current_block().block_timestamp / 1_000_000
+}
+
+/// Moves the simulator time forward by producing `seconds` empty blocks.
+/// Each block advances `block_timestamp` by exactly one second,
+/// so time can only be moved forward in whole seconds.
+/// Note that transactions produce blocks as well, hence also advance time.
+pub fn advance_time(user: &UserAccount, seconds: u64) {
+    user.borrow_runtime_mut().produce_blocks(seconds).unwrap();
+}
+
 pub trait CheckResult {
     fn failure(self, msg: String);
 }
@@ -409,7 +524,7 @@ impl<T: Debug> CheckResult for Result<T, String> {
     }
 }
 
-fn tx(x: ExecutionResult) -> Result<ExecutionResult, String> {
+pub fn tx(x: ExecutionResult) -> Result<ExecutionResult, String> {
     for line in x.logs() {
         println!("{}", Style::new().dimmed().paint(format!("[log :: {}]", line)));
     }
@@ -22,6 +22,21 @@ export enum CorePanics {
      */
     FractionGreaterThanOne,
 
+    /**
+     *  Thrown when basis points are more than `10000`, *i.e.*, more than `1`.
+     */
+    BasisPointsGreaterThanOne,
+
+    /**
+     *  Thrown when increasing a supply exceeds `u16::MAX`.
+     */
+    SupplyOverflow,
+
+    /**
+     *  Thrown when decreasing a supply goes below `0`.
+     */
+    SupplyUnderflow,
+
 }
 
 /**
@@ -143,8 +158,16 @@ export type Collectible = {
      */
     current_supply: number;
 
+    /**
+     *  When set, this `Collectible` is an open edition,
+     *  *i.e.*, any number of `Token`s can be minted out of it until this time (in milliseconds).
+     *  Open editions do not use `current_supply`.
+     */
+    open_until: Timestamp|null;
+
     /**
      *  The list of `TokenId`s actually minted out of this `Collectible`.
+     *  It is sorted by `TokenId`, since tokens are minted with increasing ids.
      */
     minted_tokens: TokenId[];
 
@@ -154,6 +177,82 @@ export type Collectible = {
      */
     royalty: Fraction;
 
+    /**
+     *  Indicates the royalty to be paid to `creator_id` on the first resale of a token,
+     *  if different from `royalty`.
+     *  Subsequent resales use `royalty`.
+     */
+    first_sale_royalty: Fraction|null;
+
+    /**
+     *  Indicates whether the royalties of this `Collectible` are fixed for good,
+     *  *i.e.*, no `Token` can be minted out of it with a different royalty.
+     */
+    royalty_locked: boolean;
+
+    /**
+     *  Minimum price (in yoctoNEARs) set by `creator_id` under which
+     *  tokens of this `Collectible` cannot be approved for resale, if any.
+     */
+    min_resale_price: U128|null;
+
+    /**
+     *  Indicates whether tokens of this `Collectible` can be approved with a zero `min_price`,
+     *  i.e., given away for free.
+     */
+    allow_zero_price: boolean;
+
+    /**
+     *  Indicates whether tokens of this `Collectible` can currently be claimed.
+     */
+    claimable: boolean;
+
+    /**
+     *  Maximum number of `Token`s each account can claim out of this `Collectible`, if any.
+     */
+    max_claims_per_account: number|null;
+
+    /**
+     *  Minimum time (in nanoseconds) each account must wait between two claims
+     *  of `Token`s out of this `Collectible`, if any.
+     */
+    claim_cooldown_ns: number|null;
+
+    /**
+     *  Price (in yoctoNEARs) paid to `creator_id` when claiming a `Token`, if any.
+     */
+    mint_price: U128|null;
+
+    /**
+     *  Small inline image as a base64 data URL, if any.
+     */
+    thumbnail: string|null;
+
+    /**
+     *  Template for the title of each `Token` minted out of this `Collectible`, if any,
+     *  *e.g.*, `"Parcel #{index}"`.
+     *  The `{index}` placeholder is replaced by the position (starting at `1`) of the token in `minted_tokens`.
+     */
+    title_template: string|null;
+
+    /**
+     *  Indicates whether each `Token` minted out of this `Collectible`
+     *  records its serial number in its `extra` metadata, *e.g.*, `{"serial":1}`.
+     */
+    serial_numbers: boolean;
+
+    /**
+     *  Number of `Token`s ever minted out of this `Collectible`, burned tokens included.
+     *  The serial number of a `Token` is the value of this counter right after it was minted.
+     */
+    minted_count: number;
+
+    /**
+     *  Represents when this `Collectible` was created, in milliseconds.
+     *  Unlike `metadata.issued_at`, it is not meant to describe its tokens.
+     */
+    created_at: Timestamp;
+
     /**
      *  Additional info provided by NEP-177.
      */
@@ -190,7 +289,7 @@ export type Token = {
 
     /**
      *  Represents when this `Token` was last modified, in nanoseconds.
-     *  Either when created or transferred.
+     *  Either when created, transferred or its approvals changed.
      */
     modified_at: Timestamp;
 
@@ -205,6 +304,13 @@ export type Token = {
      */
     approval_counter: U64;
 
+    /**
+     *  Indicates whether this `Token` has already been sold through `nft_transfer_payout`.
+     *  Used to determine which royalty applies to the next sale.
+     *  Not present in the stored layout of previous versions, see *State Layout* in the README.
+     */
+    has_been_sold: boolean;
+
     /**
      *  Additional info defined by NEP-177.
      *  This `metadata` effectively joins fields from its respective `gate_id`.
@@ -299,6 +405,12 @@ export type TokenApproval = {
      */
     min_price: U128;
 
+    /**
+     *  UNIX epoch datetime (in miliseconds) after which this approval can no longer be used.
+     *  Not present in the stored layout of previous versions, see *State Layout* in the README.
+     */
+    expires_at: Timestamp|null;
+
 }
 
 /**
@@ -345,6 +457,22 @@ export type NFTContractMetadata = {
 
 }
 
+/**
+ *  Describes the code a contract is running.
+ */
+export type SourceMetadata = {
+    /**
+     *  Version of the contract code, *e.g.*, the crate version.
+     */
+    version: string|null;
+
+    /**
+     *  Link to the source code of the contract.
+     */
+    link: string|null;
+
+}
+
 /**
  *  In our implementation of the standard,
  *  The `nft_approve` method must conform with the following:
@@ -358,6 +486,11 @@ export type NftApproveMsg = {
      */
     min_price: U128;
 
+    /**
+     *  UNIX epoch datetime (in miliseconds) when the approval expires, if any.
+     */
+    expires_at: Timestamp|null;
+
 }
 
 /**
@@ -429,6 +562,169 @@ export type TokenForSale = {
      */
     creator_id: AccountId|null;
 
+    /**
+     *  The listing fee paid when this token was listed.
+     *  It is refunded to `owner_id` when the listing is removed without a sale.
+     */
+    listing_fee: U128;
+
+    /**
+     *  UNIX epoch datetime (in milliseconds) when this token was listed.
+     */
+    created_at: Timestamp;
+
+}
+
+/**
+ *  Data logged in the `mg_list` event when a token is listed for sale.
+ */
+export type ListEvent = {
+    /**
+     *  The contract account where the listed token has been minted.
+     */
+    nft_id: AccountId;
+
+    /**
+     *  The token id being listed.
+     */
+    token_id: TokenId;
+
+    /**
+     *  The owner of the listed token.
+     */
+    owner_id: AccountId;
+
+    /**
+     *  The minimum price requested for the listed token.
+     */
+    min_price: U128;
+
+    /**
+     *  The `gate_id` of the listed token, if any.
+     */
+    gate_id: GateId|null;
+
+    /**
+     *  The `creator_id` of the collectible of the listed token, if any.
+     */
+    creator_id: AccountId|null;
+
+}
+
+/**
+ *  Data logged in the `mg_delist` event when a token is removed from sale.
+ */
+export type DelistEvent = {
+    /**
+     *  The contract account where the delisted token has been minted.
+     */
+    nft_id: AccountId;
+
+    /**
+     *  The token id being delisted.
+     */
+    token_id: TokenId;
+
+    /**
+     *  The owner of the delisted token.
+     */
+    owner_id: AccountId;
+
+}
+
+/**
+ *  Data logged in the `mg_withdraw` event when the admin withdraws free balance.
+ */
+export type WithdrawEvent = {
+    /**
+     *  The amount withdrawn (in yoctoNEARs).
+     */
+    amount: U128;
+
+    /**
+     *  The account receiving the withdrawn amount.
+     */
+    receiver_id: AccountId;
+
+}
+
+/**
+ *  Data logged in the `mg_payout_mismatch` event when a sale is paid in full to the seller
+ *  because the payout returned by the NFT contract does not add up to the sale amount.
+ */
+export type PayoutMismatchEvent = {
+    /**
+     *  The contract account where the sold token has been minted.
+     */
+    nft_id: AccountId;
+
+    /**
+     *  The token id being sold.
+     */
+    token_id: TokenId;
+
+    /**
+     *  The buyer of the token, which already holds it.
+     */
+    buyer_id: AccountId;
+
+    /**
+     *  The seller paid the whole sale `amount` instead.
+     */
+    owner_id: AccountId;
+
+    /**
+     *  The total of the payout returned by the NFT contract, if it could be parsed.
+     */
+    payout: U128|null;
+
+    /**
+     *  The sale amount.
+     */
+    amount: U128;
+
+}
+
+/**
+ *  A sale in progress, passed along the callbacks of `buy_token` and `batch_buy`.
+ */
+export type Sale = {
+    /**
+     *  The account buying the token.
+     */
+    buyer_id: AccountId;
+
+    /**
+     *  The account selling the token, *i.e.*, its owner when listed.
+     */
+    owner_id: AccountId;
+
+    /**
+     *  The contract account where the sold token has been minted.
+     */
+    nft_id: AccountId;
+
+    /**
+     *  The token id being sold.
+     */
+    token_id: TokenId;
+
+    /**
+     *  The sale amount paid out to the seller, the creator and MintGate.
+     */
+    amount: U128;
+
+    /**
+     *  The deposit attached by the buyer, refunded when the sale fails.
+     *  It is less than `amount` when the sale is topped up by a discount code.
+     */
+    deposit: U128;
+
+    /**
+     *  The `creator_id` of the collectible of the sold token, if any.
+     */
+    creator_id: AccountId|null;
+
 }
 
 /**
@@ -445,6 +741,11 @@ export enum Panics {
      */
     TokenKeyNotFound,
 
+    /**
+     *  Thrown when the `token_key` is not listed by `owner_id`.
+     */
+    TokenKeyNotOwnedBy,
+
     /**
      *  Thrown when buyer attempts to buy own token.
      */
@@ -455,6 +756,51 @@ export enum Panics {
      */
     NotEnoughDepositToBuyToken,
 
+    /**
+     *  Thrown when the attached deposit does not cover the listing fee.
+     */
+    NotEnoughDepositToList,
+
+    /**
+     *  Thrown when the prepaid gas is not enough to perform the cross-contract calls.
+     */
+    InsufficientGas,
+
+    /**
+     *  Thrown when the operation can only be performed by `admin_id`.
+     */
+    AdminRestrictedOperation,
+
+    /**
+     *  Thrown when withdrawing more than the free balance.
+     */
+    NotEnoughFreeBalance,
+
+    /**
+     *  Thrown when the discount `code` does not exist.
+     */
+    DiscountCodeNotFound,
+
+    /**
+     *  Thrown when the discount `code` is past its expiration.
+     */
+    DiscountCodeExpired,
+
+    /**
+     *  Thrown when the free balance cannot cover a discount.
+     */
+    NotEnoughPromoBudget,
+
+    /**
+     *  Thrown when more than `MAX_GATE_IDS_PER_QUERY` gate IDs are queried at once.
+     */
+    ExceedGateIdsToQuery,
+
+    /**
+     *  Thrown when more than `MAX_BATCH_SIZE` tokens are given to a batch method.
+     */
+    ExceedTokensInBatch,
+
 }
 
 /**
@@ -464,15 +810,141 @@ export enum Panics {
 export interface MarketContract {
     /**
      *  Initializes the Market contract.
+     *  The `listing_fee` is the deposit required to list each token for sale.
+     *  The `mintgate_fee_account_id` is used to keep track of the fees routed in each sale.
+     *  The `admin_id` is allowed to perform administrative operations,
+     *  it defaults to the account initializing the contract.
      */
-    init: {  };
+    init: { listing_fee: U128, mintgate_fee_account_id: ValidAccountId, admin_id: ValidAccountId|null };
+
+    /**
+     *  Returns the deposit required to list a token for sale.
+     */
+    get_listing_fee(): Promise<U128>;
+
+    /**
+     *  Returns the total amount of fees paid to `mintgate_fee_account_id` in all sales so far.
+     */
+    get_lifetime_fees(): Promise<U128>;
+
+    /**
+     *  Returns the total amount of royalties paid to `creator_id` in all sales so far.
+     *  Sales of tokens by their own creator are not counted,
+     *  since their royalty cannot be told apart from the sale proceeds.
+     */
+    get_royalties_earned(args: { creator_id: ValidAccountId }): Promise<U128>;
+
+    /**
+     *  Returns the sum of the `min_price` of all tokens currently for sale.
+     */
+    get_total_listed_value(): Promise<U128>;
+
+    /**
+     *  Returns the balance (in yoctoNEARs) that can be withdrawn with `admin_withdraw`.
+     *  That is, the contract balance not needed to cover its storage plus the storage reserve margin,
+     *  nor the listing fees held for the tokens for sale, nor the sale amounts in flight.
+     */
+    get_free_balance(): Promise<U128>;
+
+    /**
+     *  Returns the balance (in yoctoNEARs) kept on top of the storage staking requirement.
+     */
+    get_storage_reserve_margin(): Promise<U128>;
+
+    /**
+     *  Sets the balance (in yoctoNEARs) kept on top of the storage staking requirement,
+     *  see `get_free_balance`.
+     *  Only the contract `admin_id` is allowed to call this method.
+     */
+    admin_set_storage_reserve_margin(args: { margin: U128 }, gas?: any): Promise<void>;
+
+    /**
+     *  Transfers `amount` yoctoNEARs of the free balance to `to`,
+     *  *e.g.*, to recover funds stranded by a failed cross-contract call.
+     *  See `get_free_balance` for the amount that can be withdrawn.
+     *  Panics with the shortfall when `amount` would eat into the storage reserve, listing fees held
+     *  or sale amounts in flight.
+     *  Only the contract `admin_id` is allowed to call this method.
+     */
+    admin_withdraw(args: { amount: U128, to: ValidAccountId }, gas?: any): Promise<void>;
+
+    /**
+     *  Removes the listings of `token_ids` from the `nft_id` contract, regardless of their owner,
+     *  *e.g.*, tokens burned while their `nft_on_revoke` call failed.
+     *  The NFT contract is not called back, since the tokens are gone.
+     *  Listing fees held are refunded to the listing owners as in `nft_on_revoke`.
+     *  Token ids not listed are ignored.
+     *  Only the contract `admin_id` is allowed to call this method.
+     * 
+     *  Returns the number of listings removed.
+     */
+    admin_prune_orphans(args: { nft_id: ValidAccountId, token_ids: TokenId[] }, gas?: any): Promise<number>;
+
+    /**
+     *  Adds the discount `code`, or replaces it if it already exists.
+     *  Purchases made with `buy_token_with_code` get `discount` off the `min_price`
+     *  until `expires_at` (in milliseconds).
+     *  Only the contract `admin_id` is allowed to call this method.
+     */
+    admin_set_discount_code(args: { code: string, discount: Fraction, expires_at: Timestamp }, gas?: any): Promise<void>;
+
+    /**
+     *  Removes the discount `code`.
+     *  Returns whether the code existed.
+     *  Only the contract `admin_id` is allowed to call this method.
+     */
+    admin_remove_discount_code(args: { code: string }, gas?: any): Promise<boolean>;
+
+    /**
+     *  Returns the discount and expiration (in milliseconds) of `code`, if any.
+     */
+    get_discount_code(args: { code: string }): Promise<[Fraction, Timestamp]|null>;
+
+    /**
+     *  Returns all or paginated purchases made by `buyer_id` in this marketplace, oldest first.
+     *  Each purchase contains the token bought, the price paid and its timestamp (in milliseconds).
+     *  Only the last `MAX_PURCHASES_PER_BUYER` purchases are kept.
+     *  Pagination is given by:
+     * 
+     *  - `from_index` the index to start fetching purchases.
+     *  - `limit` indicates how many purchases will be at most returned.
+     */
+    get_purchases(args: { buyer_id: ValidAccountId, from_index: U64|null, limit: number|null }): Promise<[TokenKey, U128, Timestamp][]>;
+
+    /**
+     *  Returns up to `limit` of the most recent sales in this marketplace, newest first.
+     *  Each sale contains the token sold, the buyer, the price paid and its timestamp (in milliseconds).
+     *  Only the last `MAX_RECENT_SALES` sales are kept.
+     */
+    get_recent_sales(args: { limit: number|null }): Promise<[TokenKey, AccountId, U128, Timestamp][]>;
 
     /**
      *  Returns all available tokens for sale.
      *  Use the `nft_on_approve` method to add a token for sale.
+     *  Tokens are sorted by `nft_contract_id` and then by `token_id`.
      */
     get_tokens_for_sale(): Promise<TokenForSale[]>;
 
+    /**
+     *  Returns the tokens for sale listed at or after `since` (in milliseconds).
+     * 
+     *  There is no index by listing time, so this method scans the listings.
+     *  To bound its cost, pagination is applied to the scanned listings, not to the result:
+     * 
+     *  - `from_index` the index of the first listing to scan.
+     *  - `limit` indicates how many listings will be at most scanned.
+     * 
+     *  Hence a page might contain less than `limit` tokens, even if more pages follow.
+     *  Scanning stops after `get_tokens_for_sale().len()` listings.
+     */
+    get_listings_since(args: { since: Timestamp, from_index: U64|null, limit: number|null }): Promise<TokenForSale[]>;
+
+    /**
+     *  Returns the token for sale identified by `nft_id:token_id`, if listed.
+     *  Otherwise returns `None`.
+     */
+    get_token_for_sale(args: { nft_id: ValidAccountId, token_id: TokenId }): Promise<TokenForSale|null>;
+
     /**
      *  Returns all tokens for sale owned by `owner_id`.
      */
@@ -483,6 +955,36 @@ export interface MarketContract {
      */
     get_tokens_by_gate_id(args: { gate_id: ValidGateId }): Promise<TokenForSale[]>;
 
+    /**
+     *  Returns the number of tokens for sale whose collectible's gate ID is `gate_id`,
+     *  without reading the listings themselves.
+     * 
+     *  The marketplace does not know the total supply of a collectible.
+     *  To compute how much of it is listed, use the `copies` in its metadata,
+     *  as returned by `get_collectible_by_gate_id` on the NFT contract.
+     */
+    get_gate_listed_count(args: { gate_id: ValidGateId }): Promise<U64>;
+
+    /**
+     *  Returns the tokens for sale whose collectible's gate ID is any of `gate_ids`.
+     *  Tokens are returned grouped by gate ID, in the order given by `gate_ids`.
+     *  Repeated gate IDs are only considered once.
+     *  At most `MAX_GATE_IDS_PER_QUERY` gate IDs are allowed to bound the cost of this method.
+     *  Pagination is given by:
+     * 
+     *  - `from_index` the index to start fetching tokens, across all `gate_ids`.
+     *  - `limit` indicates how many tokens will be at most returned.
+     */
+    get_tokens_by_gate_ids(args: { gate_ids: ValidGateId[], from_index: U64|null, limit: number|null }): Promise<TokenForSale[]>;
+
+    /**
+     *  Returns the cheapest token for sale whose collectible's gate ID is `gate_id`,
+     *  i.e., the one with the lowest `min_price`.
+     *  Ties are broken by the lowest `token_id`.
+     *  Returns `None` when there are no tokens for sale for `gate_id`.
+     */
+    get_floor_listing(args: { gate_id: ValidGateId }): Promise<TokenForSale|null>;
+
     /**
      *  Returns all tokens for sale whose collectible's creator ID is `creator_id`.
      */
@@ -494,11 +996,70 @@ export interface MarketContract {
      *  The caller must attach at least `min_price` NEARs in order to pay for the given token.
      *  Moreover, the owner cannot buy his/her own tokens.
      * 
+     *  Before the token is transferred, `nft_contract_id::nft_token` is checked
+     *  to be still owned by the seller, see `resolve_purchase`.
      *  When the token is sold,
      *  royalties are paid by this marketplace according to `nft_contract_id::nft_transfer_payout`.
      */
     buy_token(args: { nft_contract_id: ValidAccountId, token_id: TokenId }, gas?: any, amount?: any): Promise<void>;
 
+    /**
+     *  Buys the token `nft_contract_id:token_id` as in `buy_token`,
+     *  applying the discount of `code` to the required deposit.
+     * 
+     *  The seller, creator and MintGate are still paid according to the full `min_price`.
+     *  The discounted amount is covered by this marketplace out of its free balance,
+     *  see `get_free_balance`.
+     *  Panics if `code` is unknown or has expired.
+     */
+    buy_token_with_code(args: { nft_contract_id: ValidAccountId, token_id: TokenId, code: string }, gas?: any, amount?: any): Promise<void>;
+
+    /**
+     *  Buys several tokens at once, as in `buy_token`.
+     *  Each purchase contains the NFT contract and the `TokenId` to buy,
+     *  and each token is paid exactly its `min_price`.
+     *  The attached deposit must cover the sum of all `min_price`s, any excess is refunded.
+     *  Each token is checked to be still owned by its seller before its transfer, as in `buy_token`.
+     *  When the check or the transfer of a token fails, its `min_price` is refunded to the buyer,
+     *  and in the former case, the listing fee is refunded to the seller.
+     */
+    batch_buy(args: { purchases: [ValidAccountId, TokenId][] }, gas?: any, amount?: any): Promise<void>;
+
+    /**
+     *  Updates the `min_price` of the token `nft_id:token_id` listed by `predecessor_account_id`.
+     */
+    update_price(args: { nft_id: ValidAccountId, token_id: TokenId, min_price: U128 }, gas?: any): Promise<void>;
+
+    /**
+     *  Updates the `min_price` of several listings, as in `update_price`.
+     *  Each update contains the NFT contract, the `TokenId` and the new minimum price.
+     *  Only listings of `predecessor_account_id` are updated.
+     *  Returns the updates that failed along with their error,
+     *  the remaining updates are applied nonetheless.
+     */
+    batch_update_price(args: { updates: [ValidAccountId, TokenId, U128][] }, gas?: any): Promise<[TokenKey, Panics][]>;
+
+    /**
+     *  Checks whether the listing `nft_id:token_id` is still owned by its `owner_id`.
+     *  The current owner is fetched from `nft_id::nft_token`,
+     *  and the listing is removed if the token has changed hands outside this marketplace,
+     *  or if it no longer exists.
+     */
+    sync_listing(args: { nft_id: ValidAccountId, token_id: TokenId }, gas?: any): Promise<void>;
+
+}
+
+/**
+ *  Contract Source Metadata (NEP-330) v1.0.0
+ * 
+ *  <https://github.com/near/NEPs/blob/master/neps/nep-0330.md>
+ */
+export interface ContractSourceMetadata {
+    /**
+     *  Returns the crate version and repository this contract was built from.
+     */
+    contract_source_metadata(): Promise<SourceMetadata>;
+
 }
 
 /**
@@ -508,33 +1069,63 @@ export interface MarketContract {
 export interface NonFungibleTokenApprovalsReceiver {
     /**
      *  Callback method to allow this contract to put a `Token` into the marketplace.
+     *  The attached deposit must cover the listing fee.
      *  The msg must contain the following fields:
      */
-    nft_on_approve(args: { token_id: TokenId, owner_id: ValidAccountId, approval_id: U64, msg: string }, gas?: any): Promise<void>;
+    nft_on_approve(args: { token_id: TokenId, owner_id: ValidAccountId, approval_id: U64, msg: string }, gas?: any, amount?: any): Promise<void>;
 
     /**
      *  Callback method to remove this `Token` from the marketplace.
+     *  If the `Token` is not listed, e.g., it was already removed, this method does nothing.
      */
     nft_on_revoke(args: { token_id: TokenId }, gas?: any): Promise<void>;
 
     /**
      *  Callback method to allow this contract to put multiple `Token`s into the marketplace.
+     *  When a `token_id` appears more than once, only its last entry is listed.
+     *  The attached deposit must cover the listing fee of every distinct token.
+     *  A single `mg_list` event is logged with all listed tokens.
      */
-    batch_on_approve(args: { tokens: [TokenId, MarketApproveMsg][], owner_id: ValidAccountId }, gas?: any): Promise<void>;
+    batch_on_approve(args: { tokens: [TokenId, MarketApproveMsg][], owner_id: ValidAccountId }, gas?: any, amount?: any): Promise<void>;
 
 }
 
-export interface MarketContract extends NonFungibleTokenApprovalsReceiver {}
+export interface MarketContract extends ContractSourceMetadata, NonFungibleTokenApprovalsReceiver {}
 
 export const MarketContractMethods = {
     viewMethods: [
+        "get_listing_fee",
+        "get_lifetime_fees",
+        "get_royalties_earned",
+        "get_total_listed_value",
+        "get_free_balance",
+        "get_storage_reserve_margin",
+        "get_discount_code",
+        "get_purchases",
+        "get_recent_sales",
         "get_tokens_for_sale",
+        "get_listings_since",
+        "get_token_for_sale",
         "get_tokens_by_owner_id",
         "get_tokens_by_gate_id",
+        "get_gate_listed_count",
+        "get_tokens_by_gate_ids",
+        "get_floor_listing",
         "get_tokens_by_creator_id",
+        "contract_source_metadata",
     ],
     changeMethods: [
+        "admin_set_storage_reserve_margin",
+        "admin_withdraw",
+        "admin_prune_orphans",
+        "admin_set_discount_code",
+        "admin_remove_discount_code",
         "buy_token",
+        "buy_token_with_code",
+        "batch_buy",
+        "update_price",
+        "batch_update_price",
+        "sync_listing",
         "nft_on_approve",
         "nft_on_revoke",
         "batch_on_approve",
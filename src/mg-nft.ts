@@ -22,6 +22,21 @@ export enum CorePanics {
      */
     FractionGreaterThanOne,
 
+    /**
+     *  Thrown when basis points are more than `10000`, *i.e.*, more than `1`.
+     */
+    BasisPointsGreaterThanOne,
+
+    /**
+     *  Thrown when increasing a supply exceeds `u16::MAX`.
+     */
+    SupplyOverflow,
+
+    /**
+     *  Thrown when decreasing a supply goes below `0`.
+     */
+    SupplyUnderflow,
+
 }
 
 /**
@@ -143,8 +158,16 @@ export type Collectible = {
      */
     current_supply: number;
 
+    /**
+     *  When set, this `Collectible` is an open edition,
+     *  *i.e.*, any number of `Token`s can be minted out of it until this time (in milliseconds).
+     *  Open editions do not use `current_supply`.
+     */
+    open_until: Timestamp|null;
+
     /**
      *  The list of `TokenId`s actually minted out of this `Collectible`.
+     *  It is sorted by `TokenId`, since tokens are minted with increasing ids.
      */
     minted_tokens: TokenId[];
 
@@ -154,6 +177,82 @@ export type Collectible = {
      */
     royalty: Fraction;
 
+    /**
+     *  Indicates the royalty to be paid to `creator_id` on the first resale of a token,
+     *  if different from `royalty`.
+     *  Subsequent resales use `royalty`.
+     */
+    first_sale_royalty: Fraction|null;
+
+    /**
+     *  Indicates whether the royalties of this `Collectible` are fixed for good,
+     *  *i.e.*, no `Token` can be minted out of it with a different royalty.
+     */
+    royalty_locked: boolean;
+
+    /**
+     *  Minimum price (in yoctoNEARs) set by `creator_id` under which
+     *  tokens of this `Collectible` cannot be approved for resale, if any.
+     */
+    min_resale_price: U128|null;
+
+    /**
+     *  Indicates whether tokens of this `Collectible` can be approved with a zero `min_price`,
+     *  i.e., given away for free.
+     */
+    allow_zero_price: boolean;
+
+    /**
+     *  Indicates whether tokens of this `Collectible` can currently be claimed.
+     */
+    claimable: boolean;
+
+    /**
+     *  Maximum number of `Token`s each account can claim out of this `Collectible`, if any.
+     */
+    max_claims_per_account: number|null;
+
+    /**
+     *  Minimum time (in nanoseconds) each account must wait between two claims
+     *  of `Token`s out of this `Collectible`, if any.
+     */
+    claim_cooldown_ns: number|null;
+
+    /**
+     *  Price (in yoctoNEARs) paid to `creator_id` when claiming a `Token`, if any.
+     */
+    mint_price: U128|null;
+
+    /**
+     *  Small inline image as a base64 data URL, if any.
+     */
+    thumbnail: string|null;
+
+    /**
+     *  Template for the title of each `Token` minted out of this `Collectible`, if any,
+     *  *e.g.*, `"Parcel #{index}"`.
+     *  The `{index}` placeholder is replaced by the position (starting at `1`) of the token in `minted_tokens`.
+     */
+    title_template: string|null;
+
+    /**
+     *  Indicates whether each `Token` minted out of this `Collectible`
+     *  records its serial number in its `extra` metadata, *e.g.*, `{"serial":1}`.
+     */
+    serial_numbers: boolean;
+
+    /**
+     *  Number of `Token`s ever minted out of this `Collectible`, burned tokens included.
+     *  The serial number of a `Token` is the value of this counter right after it was minted.
+     */
+    minted_count: number;
+
+    /**
+     *  Represents when this `Collectible` was created, in milliseconds.
+     *  Unlike `metadata.issued_at`, it is not meant to describe its tokens.
+     */
+    created_at: Timestamp;
+
     /**
      *  Additional info provided by NEP-177.
      */
@@ -190,7 +289,7 @@ export type Token = {
 
     /**
      *  Represents when this `Token` was last modified, in nanoseconds.
-     *  Either when created or transferred.
+     *  Either when created, transferred or its approvals changed.
      */
     modified_at: Timestamp;
 
@@ -205,6 +304,13 @@ export type Token = {
      */
     approval_counter: U64;
 
+    /**
+     *  Indicates whether this `Token` has already been sold through `nft_transfer_payout`.
+     *  Used to determine which royalty applies to the next sale.
+     *  Not present in the stored layout of previous versions, see *State Layout* in the README.
+     */
+    has_been_sold: boolean;
+
     /**
      *  Additional info defined by NEP-177.
      *  This `metadata` effectively joins fields from its respective `gate_id`.
@@ -299,6 +405,12 @@ export type TokenApproval = {
      */
     min_price: U128;
 
+    /**
+     *  UNIX epoch datetime (in miliseconds) after which this approval can no longer be used.
+     *  Not present in the stored layout of previous versions, see *State Layout* in the README.
+     */
+    expires_at: Timestamp|null;
+
 }
 
 /**
@@ -345,6 +457,22 @@ export type NFTContractMetadata = {
 
 }
 
+/**
+ *  Describes the code a contract is running.
+ */
+export type SourceMetadata = {
+    /**
+     *  Version of the contract code, *e.g.*, the crate version.
+     */
+    version: string|null;
+
+    /**
+     *  Link to the source code of the contract.
+     */
+    link: string|null;
+
+}
+
 /**
  *  In our implementation of the standard,
  *  The `nft_approve` method must conform with the following:
@@ -358,6 +486,11 @@ export type NftApproveMsg = {
      */
     min_price: U128;
 
+    /**
+     *  UNIX epoch datetime (in miliseconds) when the approval expires, if any.
+     */
+    expires_at: Timestamp|null;
+
 }
 
 /**
@@ -401,6 +534,18 @@ export enum Panic {
      */
     RoyaltyMaxThanAllowed,
 
+    /**
+     */
+    RoyaltyLocked,
+
+    /**
+     */
+    SwapNotAllowed,
+
+    /**
+     */
+    InvalidFeeSplit,
+
     /**
      */
     RoyaltyTooLarge,
@@ -409,6 +554,14 @@ export enum Panic {
      */
     GateIdAlreadyExists,
 
+    /**
+     */
+    TokenIdAlreadyExists,
+
+    /**
+     */
+    TokenIdAlreadyUsed,
+
     /**
      */
     ZeroSupplyNotAllowed,
@@ -421,6 +574,10 @@ export enum Panic {
      */
     AdminRestrictedOperation,
 
+    /**
+     */
+    AdminIsFeeAccount,
+
     /**
      */
     GateIdNotFound,
@@ -437,6 +594,34 @@ export enum Panic {
      */
     NotAuthorized,
 
+    /**
+     */
+    CreatorNotAllowed,
+
+    /**
+     */
+    OperatorNotAllowed,
+
+    /**
+     */
+    CreatorRestrictedOperation,
+
+    /**
+     */
+    CreatorOrAdminRestrictedOperation,
+
+    /**
+     */
+    GateNotClaimable,
+
+    /**
+     */
+    ClaimLimitReached,
+
+    /**
+     */
+    ClaimCooldownActive,
+
     /**
      */
     TokenIdNotFound,
@@ -449,6 +634,14 @@ export enum Panic {
      */
     OneApprovalAllowed,
 
+    /**
+     */
+    ZeroMinPrice,
+
+    /**
+     */
+    BelowResaleFloor,
+
     /**
      */
     SenderNotAuthToTransfer,
@@ -457,6 +650,10 @@ export enum Panic {
      */
     ReceiverIsOwner,
 
+    /**
+     */
+    ApprovalExpired,
+
     /**
      */
     EnforceApprovalFailed,
@@ -467,7 +664,11 @@ export enum Panic {
 
     /**
      */
-    MsgFormatMinPriceMissing,
+    MsgFormatNotJson,
+
+    /**
+     */
+    MsgFormatInvalidField,
 
     /**
      */
@@ -475,182 +676,1026 @@ export enum Panic {
 
     /**
      */
-    ExceedTokensToBatchApprove,
+    ApprovalNotFound,
 
     /**
      */
-    Errors,
+    RoyaltiesEnforced,
 
-}
+    /**
+     */
+    TokenLocked,
 
-/**
- *  Represents a list of errors when performing a batch update,
- *  identified by `TokenId`.
- */
-export type Panics = [TokenId, Panic][];
+    /**
+     */
+    TokenNotLockedBy,
 
-/**
- *  Methods for the NFT contract.
- *  Methods belonging to a NEP Standard are implemented in their own interfaces.
- */
-export interface NftContract {
     /**
-     *  Initializes the contract.
-     *  This contract methods needs to be explicitely called
-     *  since the default construction of the contract will panic.
-     * 
-     *  - `admin_id` is the valid account that is allowed to perform certain operations.
-     *  - `metadata` represents the general information of the contract.
-     *  - `min_royalty` and `max_royalty` indicates what must be the max and min royalty respectively when creating a collectible.
-     *  - `mintgate_fee` is the percetange to be paid to `mintgate_fee_account_id` for each sale.
      */
-    init: { admin_id: ValidAccountId, metadata: NFTContractMetadata, min_royalty: Fraction, max_royalty: Fraction, mintgate_fee: Fraction, mintgate_fee_account_id: ValidAccountId };
+    ExceedTokensToBatchApprove,
 
     /**
-     *  Creates a new `Collectible`, identified by `gate_id`.
-     *  The `supply` indicates maximum supply for this collectible.
-     *  The `royalty` indicates the royalty (as percentage) paid to the creator (`predecessor_account_id`).
-     *  This royalty is paid when any `Token` is being resold in any marketplace.
-     * 
-     *  The sum of `royalty` and `mintgate_fee` should be less than `1`.
-     *  Panics otherwise.
-     *  This is to be able to make payouts all participants.
-     * 
-     *  See <https://github.com/epam/mintgate/issues/3>.
      */
-    create_collectible(args: { creator_id: ValidAccountId, gate_id: ValidGateId, title: string, description: string, supply: number, royalty: Fraction, media: string|null, media_hash: string|null, reference: string|null, reference_hash: string|null }, gas?: any): Promise<void>;
+    ExceedTokensToBatchRevoke,
 
     /**
-     *  Returns the `Collectible` with the given `gate_id`.
-     *  Panics otherwise.
-     * 
-     *  See <https://github.com/epam/mintgate/issues/16>.
      */
-    get_collectible_by_gate_id(args: { gate_id: ValidGateId }): Promise<Collectible|null>;
+    MaxBatchSizeTooLarge,
 
     /**
-     *  Returns all `Collectible`s created by `creator_id`.
-     * 
-     *  See <https://github.com/epam/mintgate/issues/15>.
      */
-    get_collectibles_by_creator(args: { creator_id: ValidAccountId }): Promise<Collectible[]>;
+    ExceedGateIdsToQuery,
 
     /**
-     *  Deletes the given `Collectible` by `gate_id`.
-     *  The collectible can only be deleted if there are no minted tokens.
-     *  Moreover, only the `creator_id` of the collectible or
-     *  the contract `admin_id` are allowed to delete the collectible.
      */
-    delete_collectible(args: { gate_id: ValidGateId }, gas?: any): Promise<void>;
+    NotEnoughDepositToClaim,
 
     /**
-     *  Claims a `Token` for the `Collectible` indicated by `gate_id`.
-     *  The claim is on behalf the `predecessor_account_id`.
-     *  Returns a `TokenId` that represents this claim.
-     *  If the given `gate_id` has exhausted its supply, this call will panic.
-     * 
-     *  See <https://github.com/epam/mintgate/issues/6>.
      */
-    claim_token(args: { gate_id: ValidGateId }, gas?: any): Promise<TokenId>;
+    NotEnoughDepositToMint,
 
     /**
-     *  Burns (deletes) the `Token` identifed by `token_id`.
-     *  Only the `owner_id` can burn the token.
-     * 
-     *  After succefully delete the token,
-     *  a cross-contract call  is made to `nft_on_revoke` for each approval
-     *  to delist from their marketplaces.
      */
-    burn_token(args: { token_id: TokenId }, gas?: any): Promise<void>;
+    InsufficientGas,
 
     /**
-     *  Returns all `Token`s owned by `owner_id`.
      */
-    get_tokens_by_owner(args: { owner_id: ValidAccountId }): Promise<Token[]>;
+    Errors,
+
+}
+
+/**
+ *  Represents a list of errors when performing a batch update,
+ *  identified by `TokenId`.
+ */
+export type Panics = [TokenId, Panic][];
 
+/**
+ *  Data logged in the `mg_batch_approve_result` event once `batch_approve` is resolved.
+ */
+export type BatchApproveResultEvent = {
     /**
-     *  Returns all tokens claimed by `owner_id` belonging to `gate_id`.
-     * 
-     *  See <https://github.com/epam/mintgate/issues/14>.
+     *  The market account the tokens were approved for.
      */
-    get_tokens_by_owner_and_gate_id(args: { gate_id: ValidGateId, owner_id: ValidAccountId }): Promise<Token[]>;
+    account_id: AccountId;
 
     /**
-     *  Approves a batch of tokens, similar to `nft_approve`.
-     *  Each approval contains the `TokenId` to approve and the minimum price to sell the token for.
-     *  `account_id` indicates the market account contract where list these tokens.
+     *  The tokens approved and listed in `account_id`.
+     */
+    succeeded: TokenId[];
+
+    /**
+     *  The tokens that could not be approved.
      */
-    batch_approve(args: { tokens: [TokenId, U128][], account_id: ValidAccountId }, gas?: any): Promise<void>;
+    failed: TokenId[];
 
 }
 
 /**
- *  Non-Fungible Token (NEP-171) v1.0.0
- *  https://nomicon.io/Standards/NonFungibleToken/Core.html
- * 
- *  Payouts is part of an ongoing (yet not settled) NEP spec:
- *  <https://github.com/thor314/NEPs/blob/patch-5/specs/Standards/NonFungibleToken/payouts.md>
+ *  Approvals forwarded to the `nft_on_approve` or `batch_on_approve` of a market.
+ *  They are undone when the market call fails, *e.g.*, when the listing fee is short.
  */
-export interface NonFungibleTokenCore {
+export type PendingApprovals = {
     /**
-     *  Transfer the token `token_id` to the `receiver_id` account.
-     * 
-     *  See <https://github.com/epam/mintgate/issues/18>.
+     *  The owner of the approved tokens.
      */
-    nft_transfer(args: { receiver_id: ValidAccountId, token_id: TokenId, enforce_approval_id: U64|null, memo: string|null }, gas?: any): Promise<void>;
+    owner_id: AccountId;
 
     /**
-     *  Query whom to be paid out for a given `token_id`, derived from some `balance`.
-     *  For example, given the following settings for the NFT contract and collectible `gate_id`:
-     * 
-     *  - `mintgate_fee`: `25/1000` (2.5%)
-     *  - `royalty`: `30/100` (30%)
-     * 
-     *  Then `nft_payout(token_id, 5_000_000)` will return
-     * 
-     *  - `mintgate_fee_account_id` -> 125_000
-     *  - `collectible.creator_id` -> 3_375_000
-     *  - `token.owner_id` -> 1_500_000
-     * 
-     *  for any `token_id` claimed from `gate_id`.
-     * 
-     *  This is part of an ongoing (yet not settled) NEP spec:
-     *  <https://github.com/thor314/NEPs/blob/patch-5/specs/Standards/NonFungibleToken/payouts.md>
+     *  The market account the tokens are approved for.
      */
-    nft_payout(args: { token_id: TokenId, balance: U128 }): Promise<Payout>;
+    account_id: AccountId;
 
     /**
-     *  Attempts to transfer the token.
-     *  Afterwards returns the payout data.
-     *  Effectively it is calling `nft_transfer` followed by `nft_payout`.
-     * 
-     *  This is part of an ongoing (yet not settled) NEP spec:
-     *  <https://github.com/thor314/NEPs/blob/patch-5/specs/Standards/NonFungibleToken/payouts.md>
+     *  Each approved token along with its `approval_id`,
+     *  and its previous `min_price` when an existing approval was changed.
      */
-    nft_transfer_payout(args: { receiver_id: ValidAccountId, token_id: TokenId, approval_id: U64|null, memo: string|null, balance: U128|null }, gas?: any): Promise<Payout|null>;
+    tokens: [TokenId, U64, U128|null][];
 
     /**
-     *  Returns the token identified by `token_id`.
-     *  Or `null` if the `token_id` was not found.
-     * 
-     *  See <https://github.com/epam/mintgate/issues/17>.
+     *  The deposit forwarded to the market.
      */
-    nft_token(args: { token_id: TokenId }): Promise<Token|null>;
+    deposit: U128;
 
 }
 
 /**
- *  Non-Fungible Token Metadata (NEP-177) v1.0.0
+ *  Data logged in the `nft_burn` event of the `nep171` standard by `burn_token`.
  * 
- *  <https://nomicon.io/Standards/NonFungibleToken/Metadata.html>
+ *  <https://nomicon.io/Standards/NonFungibleToken/Event.html>
  */
-export interface NonFungibleTokenMetadata {
+export type NftBurnEvent = {
     /**
-     *  Returns the NFT metadata for this contract.
+     *  The owner of the tokens burned.
      */
-    nft_metadata(): Promise<NFTContractMetadata>;
+    owner_id: AccountId;
 
-}
+    /**
+     *  The tokens burned.
+     */
+    token_ids: TokenId[];
+
+    /**
+     *  The approved account that burned the tokens on behalf of `owner_id`, if any.
+     */
+    authorized_id: AccountId|null;
+
+}
+
+/**
+ *  The state of a `Collectible` returned by `export_collectible`,
+ *  so it can be recreated in another contract with `import_collectible`.
+ */
+export type CollectibleExport = {
+    /**
+     *  The exported `Collectible`.
+     */
+    collectible: Collectible;
+
+    /**
+     *  The tokens minted out of `collectible` that have not been burned, in minting order.
+     */
+    tokens: TokenExport[];
+
+}
+
+/**
+ *  The state of a `Token` exported along with its `Collectible`.
+ *  Approvals are not exported, since listings do not carry over to another contract.
+ */
+export type TokenExport = {
+    /**
+     */
+    token_id: TokenId;
+
+    /**
+     */
+    owner_id: AccountId;
+
+    /**
+     *  When the token was minted, in milliseconds.
+     */
+    created_at: Timestamp;
+
+    /**
+     */
+    has_been_sold: boolean;
+
+    /**
+     *  Per-token `extra` metadata, *e.g.*, its serial number, if any.
+     */
+    extra: string|null;
+
+    /**
+     *  Royalty given to the token at `claim_token_to`, if any.
+     */
+    royalty: Fraction|null;
+
+}
+
+/**
+ *  Optional arguments of `init`, any field left out takes its default.
+ */
+export type InitOptions = {
+    /**
+     *  Splits `mintgate_fee` among several accounts, with their share of it.
+     */
+    mintgate_fee_split: [ValidAccountId, Fraction][]|null;
+
+    /**
+     *  Maximum length in bytes allowed for the `title` of a `Collectible`.
+     */
+    max_title_len: number|null;
+
+    /**
+     *  Maximum length in bytes allowed for the `description` of a `Collectible`.
+     */
+    max_description_len: number|null;
+
+    /**
+     *  Maximum number of tokens given to `batch_approve` and `batch_revoke`.
+     */
+    max_batch_size: number|null;
+
+    /**
+     *  Whether `admin_id` must differ from `mintgate_fee_account_id`.
+     */
+    require_distinct_admin_fee: boolean;
+
+}
+
+/**
+ *  Optional arguments of `create_collectible`, any field left out is unset.
+ */
+export type CollectibleOptions = {
+    /**
+     *  Royalty paid on the first resale of each `Token`, instead of `royalty`.
+     */
+    first_sale_royalty: Fraction|null;
+
+    /**
+     *  The `royalty` given in basis points, it takes precedence over `royalty`.
+     */
+    royalty_bps: number|null;
+
+    /**
+     *  A small base64 data URL to render galleries without fetching `media`.
+     */
+    thumbnail: string|null;
+
+    /**
+     *  Whether the royalties are fixed for good.
+     */
+    royalty_locked: boolean;
+
+    /**
+     *  Title given to each `Token`, where `{index}` is replaced by its position.
+     */
+    title_template: string|null;
+
+    /**
+     *  Until when (in milliseconds) tokens can be claimed without limit, for open editions.
+     */
+    open_until: Timestamp|null;
+
+}
+
+/**
+ *  A `Collectible` along with whether an account can claim from it, as returned by `get_gate_view`.
+ */
+export type GateView = {
+    /**
+     *  The viewed `Collectible`.
+     */
+    collectible: Collectible;
+
+    /**
+     *  Whether the viewing account can claim a `Token` out of `collectible`.
+     */
+    eligibility: ClaimEligibility;
+
+}
+
+/**
+ *  Indicates whether an account can currently claim a `Token` out of a `Collectible`.
+ */
+export type ClaimEligibility = {
+    /**
+     *  Whether `claim_token` would succeed, *i.e.*, all of the conditions below hold.
+     */
+    can_claim: boolean;
+
+    /**
+     *  Whether claiming has not been paused by the creator.
+     */
+    claimable: boolean;
+
+    /**
+     *  Whether there are tokens left to claim.
+     */
+    has_supply: boolean;
+
+    /**
+     *  Whether the account has not reached `max_claims_per_account`, if any.
+     */
+    within_claim_limit: boolean;
+
+    /**
+     *  How many tokens the account can still claim, as in `remaining_claims_for`.
+     */
+    remaining_claims: number;
+
+    /**
+     *  The errors `claim_token` would fail with, empty when `can_claim`.
+     */
+    reasons: Panic[];
+
+}
+
+/**
+ *  Methods for the NFT contract.
+ *  Methods belonging to a NEP Standard are implemented in their own interfaces.
+ */
+export interface NftContract {
+    /**
+     *  Initializes the contract.
+     *  This contract methods needs to be explicitely called
+     *  since the default construction of the contract will panic.
+     * 
+     *  - `admin_id` is the valid account that is allowed to perform certain operations.
+     *  - `metadata` represents the general information of the contract.
+     *  - `min_royalty` and `max_royalty` indicates what must be the max and min royalty respectively when creating a collectible.
+     *  - `mintgate_fee` is the percetange to be paid to `mintgate_fee_account_id` for each sale.
+     * 
+     *  The following settings are optionally given in `options`:
+     * 
+     *  - `mintgate_fee_split` splits `mintgate_fee` among several accounts.
+     *    The shares must add up to `1`. Defaults to `mintgate_fee_account_id` receiving the whole fee.
+     *  - `max_title_len` and `max_description_len` limit the length of collectible `title` and `description`.
+     *    They default to `DEFAULT_MAX_TITLE_LEN` and `DEFAULT_MAX_DESCRIPTION_LEN` respectively.
+     *  - `max_batch_size` limits the number of tokens given to `batch_approve` and `batch_revoke`.
+     *    It defaults to `DEFAULT_MAX_BATCH_SIZE` and cannot exceed `MAX_BATCH_SIZE`.
+     *  - `require_distinct_admin_fee` rejects an `admin_id` equal to `mintgate_fee_account_id`,
+     *    for deployments whose policy requires separating both roles.
+     *    It defaults to `false`, *i.e.*, the same account is allowed for both.
+     * 
+     *  Note that accounts are only checked to be well-formed, not to exist.
+     */
+    init: { admin_id: ValidAccountId, metadata: NFTContractMetadata, min_royalty: Fraction, max_royalty: Fraction, mintgate_fee: Fraction, mintgate_fee_account_id: ValidAccountId, options: InitOptions|null };
+
+    /**
+     *  Creates a new `Collectible`, identified by `gate_id`.
+     *  The `supply` indicates maximum supply for this collectible.
+     *  The `royalty` indicates the royalty (as percentage) paid to the creator (`predecessor_account_id`).
+     *  This royalty is paid when any `Token` is being resold in any marketplace.
+     * 
+     *  The remaining optional arguments are given in `options`.
+     *  The `first_sale_royalty` replaces `royalty` for the first resale of each `Token`.
+     *  The `royalty_bps` allows to give `royalty` in basis points instead,
+     *  in which case it takes precedence over `royalty`.
+     * 
+     *  The sum of `royalty` and `mintgate_fee` should be less than `1`.
+     *  Panics otherwise.
+     *  This is to be able to make payouts all participants.
+     * 
+     *  The `thumbnail` is a small base64 data URL, *e.g.*, `data:image/png;base64,...`,
+     *  of at most `MAX_THUMBNAIL_LEN` bytes, to render galleries without fetching `media`.
+     * 
+     *  The `title_template`, *e.g.*, `"Parcel #{index}"`, gives each `Token` its own title,
+     *  where `{index}` is replaced by the position of the token in `minted_tokens`.
+     *  When not given, all tokens share `title`.
+     * 
+     *  When `royalty_locked` is `true`, the royalties cannot be changed afterwards,
+     *  *e.g.*, by giving a `royalty_override` in `claim_token_to`.
+     *  This guarantees collectors how any resale is paid out.
+     * 
+     *  When `open_until` (in milliseconds) is given, the collectible is an open edition,
+     *  *i.e.*, tokens can be claimed without limit until then.
+     *  Open editions must be created with a zero `supply`.
+     * 
+     *  When the creator allowlist is set, `creator_id` must be in it.
+     * 
+     *  See <https://github.com/epam/mintgate/issues/3>.
+     */
+    create_collectible(args: { creator_id: ValidAccountId, gate_id: ValidGateId, title: string, description: string, supply: number, royalty: Fraction, media: string|null, media_hash: string|null, reference: string|null, reference_hash: string|null, options: CollectibleOptions|null }, gas?: any): Promise<void>;
+
+    /**
+     *  Creates a new `Collectible` as in `create_collectible`
+     *  and claims its first `Token` for `creator_id` in the same call, *e.g.*, for 1-of-1 drops.
+     *  The remaining optional arguments of `create_collectible` are left unset.
+     * 
+     *  The attached deposit must cover the storage used by the new `Token`.
+     *  Any excess deposit is refunded to the `predecessor_account_id`.
+     *  Only the contract `admin_id` is allowed to call this method.
+     * 
+     *  Returns the `TokenId` of the claimed token.
+     */
+    create_and_claim(args: { creator_id: ValidAccountId, gate_id: ValidGateId, title: string, description: string, supply: number, royalty: Fraction }, gas?: any, amount?: any): Promise<TokenId>;
+
+    /**
+     *  Updates the `title` and/or `description` of the `Collectible` given by `gate_id`.
+     *  Arguments given as `None` are left unchanged.
+     *  Only the `creator_id` of the collectible is allowed to update it.
+     */
+    update_collectible(args: { gate_id: ValidGateId, title: string|null, description: string|null }, gas?: any): Promise<void>;
+
+    /**
+     *  Sets both `mintgate_fee` and `mintgate_fee_account_id` at once.
+     *  Updating both together avoids routing fees to a stale account at a new rate.
+     *  The fee split is kept, with the share of the previous `mintgate_fee_account_id` moved to `account_id`.
+     *  Use `set_fee_split` to change the shares.
+     *  The sum of `max_royalty` and the new `fee` must be less than `1`,
+     *  so the payout of any existing `Collectible` can still be computed.
+     *  Only the contract `admin_id` is allowed to call this method.
+     */
+    set_fee_config(args: { fee: Fraction, account_id: ValidAccountId }, gas?: any): Promise<void>;
+
+    /**
+     *  Sets the accounts among which `mintgate_fee` is split, with their share of it.
+     *  The shares must add up to `1`, as in `init`.
+     *  Only the contract `admin_id` is allowed to call this method.
+     */
+    set_fee_split(args: { split: [ValidAccountId, Fraction][] }, gas?: any): Promise<void>;
+
+    /**
+     *  Sets whether royalties are enforced.
+     *  When enforced, approved accounts, *e.g.*, marketplaces,
+     *  can only transfer tokens through `nft_transfer_payout` with a `balance` to pay out.
+     *  Transfers made by the token owner are not affected.
+     *  Royalties are not enforced by default.
+     *  Only the contract `admin_id` is allowed to call this method.
+     */
+    set_enforce_royalties(args: { enforce_royalties: boolean }, gas?: any): Promise<void>;
+
+    /**
+     *  Returns whether royalties are enforced, see `set_enforce_royalties`.
+     */
+    is_enforcing_royalties(): Promise<boolean>;
+
+    /**
+     *  Replaces the creator allowlist with `creator_ids`.
+     *  Only accounts in the allowlist can be the `creator_id` of new `Collectible`s.
+     *  An empty `creator_ids` removes the restriction.
+     *  Only the contract `admin_id` is allowed to call this method.
+     */
+    set_creator_allowlist(args: { creator_ids: ValidAccountId[] }, gas?: any): Promise<void>;
+
+    /**
+     *  Returns whether any account can be the `creator_id` of new `Collectible`s,
+     *  *i.e.*, whether the creator allowlist is empty.
+     */
+    is_open_minting(): Promise<boolean>;
+
+    /**
+     *  Adds `account_id` to the operator allowlist.
+     *  Only accounts in the allowlist can be approved for `Token`s, *e.g.*, marketplaces.
+     *  An empty allowlist allows any account.
+     *  Only the contract `admin_id` is allowed to call this method.
+     */
+    add_approved_operator(args: { account_id: ValidAccountId }, gas?: any): Promise<void>;
+
+    /**
+     *  Removes `account_id` from the operator allowlist.
+     *  Existing approvals for `account_id` are kept.
+     *  Returns whether `account_id` was in the allowlist.
+     *  Only the contract `admin_id` is allowed to call this method.
+     */
+    remove_approved_operator(args: { account_id: ValidAccountId }, gas?: any): Promise<boolean>;
+
+    /**
+     *  Returns the operator allowlist.
+     *  When empty, any account can be approved for `Token`s.
+     */
+    get_approved_operators(): Promise<AccountId[]>;
+
+    /**
+     *  Rebuilds the index of `Collectible`s created by `creator_id`
+     *  by scanning all `collectibles` for the ones whose `creator_id` matches.
+     *  This is a repair tool in case the index got out of sync with `collectibles`.
+     *  Its cost grows with the total number of `Collectible`s in the contract.
+     *  Only the contract `admin_id` is allowed to call this method.
+     */
+    admin_rebuild_creator_index(args: { creator_id: ValidAccountId }, gas?: any): Promise<void>;
+
+    /**
+     *  Returns the state of the `Collectible` given by `gate_id` along with its tokens,
+     *  to migrate it into another contract with `import_collectible`.
+     *  Only the contract `admin_id` is allowed to call this method.
+     *  Since view calls have no predecessor, it is exposed as a change method.
+     */
+    export_collectible(args: { gate_id: ValidGateId }, gas?: any): Promise<CollectibleExport>;
+
+    /**
+     *  Recreates a `Collectible` exported by `export_collectible` from another contract,
+     *  along with its tokens, keeping their `token_id`s and owners.
+     *  Tokens are imported with no approvals,
+     *  and the number of tokens claimed by each account is not carried over.
+     *  Panics if either the `gate_id` or any `token_id` already exists in this contract,
+     *  or when a `token_id` appears more than once in `export`.
+     *  It also panics if a `token_id` has already been used by this contract, *e.g.*, a burnt token,
+     *  or if a token has a `royalty` while the collectible royalty is locked.
+     *  Every royalty in `export` is checked against the bounds and fee of this contract,
+     *  as in `create_collectible`, since they might differ from those of the exporting contract.
+     *  Only the contract `admin_id` is allowed to call this method.
+     */
+    import_collectible(args: { export: CollectibleExport }, gas?: any): Promise<void>;
+
+    /**
+     *  Returns the `Collectible` with the given `gate_id`.
+     *  Panics otherwise.
+     * 
+     *  See <https://github.com/epam/mintgate/issues/16>.
+     */
+    get_collectible_by_gate_id(args: { gate_id: ValidGateId }): Promise<Collectible|null>;
+
+    /**
+     *  Returns the `Collectible`s with the given `gate_ids`, *e.g.*, to render a gallery in one call.
+     *  There is one entry per gate ID in the same order, `None` when the collectible was not found.
+     *  At most `MAX_GATE_IDS_PER_QUERY` gate IDs are allowed to bound the cost of this method.
+     */
+    get_collectibles_by_gate_ids(args: { gate_ids: ValidGateId[] }): Promise<Collectible|null[]>;
+
+    /**
+     *  Returns whether all tokens of the `Collectible` given by `gate_id` have been claimed,
+     *  or for an open edition, whether it is past its `open_until`.
+     *  Panics if `gate_id` is not found.
+     */
+    is_gate_sold_out(args: { gate_id: ValidGateId }): Promise<boolean>;
+
+    /**
+     *  Returns how many tokens `account_id` can still claim for the `Collectible` given by `gate_id`.
+     *  That is, the remaining supply bounded by the claims left under `max_claims_per_account`, if any.
+     *  Panics if `gate_id` is not found.
+     */
+    remaining_claims_for(args: { gate_id: ValidGateId, account_id: ValidAccountId }): Promise<number>;
+
+    /**
+     *  Returns the `Collectible` given by `gate_id` along with whether `account_id` can claim from it,
+     *  so drop pages need a single view call.
+     *  Panics if `gate_id` is not found.
+     */
+    get_gate_view(args: { gate_id: ValidGateId, account_id: ValidAccountId }): Promise<GateView>;
+
+    /**
+     *  Returns whether `account_id` has ever claimed a token from the `Collectible` given by `gate_id`.
+     *  Returns `false` when the collectible was not found.
+     */
+    has_claimed(args: { gate_id: ValidGateId, account_id: ValidAccountId }): Promise<boolean>;
+
+    /**
+     *  Returns the `thumbnail` of the `Collectible` given by `gate_id`, if any.
+     *  Returns `None` when the collectible was not found.
+     */
+    get_thumbnail(args: { gate_id: ValidGateId }): Promise<string|null>;
+
+    /**
+     *  Returns when the `Collectible` given by `gate_id` was created, in milliseconds.
+     *  Returns `None` when the collectible was not found.
+     */
+    get_collectible_created_at(args: { gate_id: ValidGateId }): Promise<Timestamp|null>;
+
+    /**
+     *  Returns the fraction of a resale kept by the token owner
+     *  for the `Collectible` given by `gate_id`,
+     *  *i.e.*, `1 - royalty - mintgate_fee`, reduced to lowest terms.
+     *  The first sale of a token might use `first_sale_royalty` instead, see `nft_payout`.
+     *  Returns `0` if `royalty` and `mintgate_fee` add up to more than `1`.
+     *  Panics if `gate_id` is not found.
+     */
+    owner_share(args: { gate_id: ValidGateId }): Promise<Fraction>;
+
+    /**
+     *  Returns all `Collectible`s created by `creator_id`.
+     * 
+     *  See <https://github.com/epam/mintgate/issues/15>.
+     */
+    get_collectibles_by_creator(args: { creator_id: ValidAccountId }): Promise<Collectible[]>;
+
+    /**
+     *  Returns all or paginated `Collectible`s created by `creator_id`,
+     *  each one along with its minted tokens count and its remaining supply.
+     *  The remaining supply of an open edition is `u16::MAX` until its `open_until` has passed,
+     *  as in `remaining_claims_for`.
+     *  Pagination is given by:
+     * 
+     *  - `from_index` the index to start fetching collectibles.
+     *  - `limit` indicates how many collectibles will be at most returned.
+     */
+    get_creator_collectibles_with_stats(args: { creator_id: ValidAccountId, from_index: U64|null, limit: number|null }): Promise<[Collectible, U64, U64][]>;
+
+    /**
+     *  Returns the `Collectible`s whose `royalty` is within `min` and `max`, both inclusive.
+     * 
+     *  There is no index by royalty, so this method scans the collectibles.
+     *  To bound its cost, pagination is applied to the scanned collectibles, not to the result:
+     * 
+     *  - `from_index` the index of the first collectible to scan.
+     *  - `limit` indicates how many collectibles will be at most scanned.
+     * 
+     *  Hence a page might contain less than `limit` collectibles, even if more pages follow.
+     */
+    get_collectibles_by_royalty(args: { min: Fraction, max: Fraction, from_index: U64|null, limit: number|null }): Promise<Collectible[]>;
+
+    /**
+     *  Returns the most recently created `Collectible`s, newest first.
+     *  Collectibles deleted since their creation are skipped,
+     *  whereas a gate ID created again after being deleted is listed once per creation.
+     * 
+     *  Pagination is applied to the creation log, not to the result:
+     * 
+     *  - `from_index` the number of most recent entries to skip.
+     *  - `limit` indicates how many entries will be at most scanned.
+     * 
+     *  Hence a page might contain less than `limit` collectibles, even if more pages follow.
+     */
+    get_recent_collectibles(args: { from_index: U64|null, limit: number|null }): Promise<Collectible[]>;
+
+    /**
+     *  Deletes the given `Collectible` by `gate_id`.
+     *  The collectible can only be deleted if there are no minted tokens.
+     *  Moreover, only the `creator_id` of the collectible or
+     *  the contract `admin_id` are allowed to delete the collectible.
+     */
+    delete_collectible(args: { gate_id: ValidGateId }, gas?: any): Promise<void>;
+
+    /**
+     *  Sets the minimum price under which tokens of the `Collectible` given by `gate_id`
+     *  cannot be approved for resale.
+     *  Use `None` to remove the floor.
+     *  Only the `creator_id` of the collectible is allowed to set its floor.
+     */
+    set_min_resale_price(args: { gate_id: ValidGateId, min_resale_price: U128|null }, gas?: any): Promise<void>;
+
+    /**
+     *  Sets the price (in yoctoNEARs) to claim each token of the `Collectible` given by `gate_id`.
+     *  Use `None` to make claims free again.
+     *  Only the `creator_id` of the collectible is allowed to set its mint price.
+     */
+    set_mint_price(args: { gate_id: ValidGateId, mint_price: U128|null }, gas?: any): Promise<void>;
+
+    /**
+     *  Sets the maximum number of tokens each account can claim
+     *  for the `Collectible` given by `gate_id`.
+     *  Use `None` to remove the limit.
+     *  Only the `creator_id` of the collectible is allowed to set its limit.
+     */
+    set_max_claims_per_account(args: { gate_id: ValidGateId, max_claims: number|null }, gas?: any): Promise<void>;
+
+    /**
+     *  Sets the minimum time (in nanoseconds) each account must wait between two claims
+     *  for the `Collectible` given by `gate_id`, *e.g.*, to throttle bots on free drops.
+     *  Use `None` to remove the cooldown.
+     *  Claims made while there was no cooldown are not taken into account.
+     *  Only the `creator_id` of the collectible is allowed to set its cooldown.
+     */
+    set_claim_cooldown(args: { gate_id: ValidGateId, cooldown_ns: number|null }, gas?: any): Promise<void>;
+
+    /**
+     *  Sets whether `Token`s minted from now on out of the `Collectible` given by `gate_id`
+     *  record their serial number in their `extra` metadata.
+     *  Serial numbers are never reused, even after a `Token` is burned.
+     *  Tokens already minted are not affected.
+     *  Only the `creator_id` of the collectible is allowed to change this setting.
+     */
+    set_serial_numbers(args: { gate_id: ValidGateId, serial_numbers: boolean }, gas?: any): Promise<void>;
+
+    /**
+     *  Sets whether tokens of the `Collectible` given by `gate_id`
+     *  can be approved with a zero `min_price`, i.e., given away for free.
+     *  Only the `creator_id` of the collectible is allowed to change this setting.
+     */
+    set_allow_zero_price(args: { gate_id: ValidGateId, allow_zero_price: boolean }, gas?: any): Promise<void>;
+
+    /**
+     *  Pauses or resumes claiming tokens of the `Collectible` given by `gate_id`.
+     *  Only the `creator_id` of the collectible or the contract `admin_id` are allowed to call it.
+     */
+    set_gate_claimable(args: { gate_id: ValidGateId, claimable: boolean }, gas?: any): Promise<void>;
+
+    /**
+     *  Returns whether `account_id` is allowed to delete the `Collectible` given by `gate_id`.
+     *  That is, the collectible exists, has no minted tokens,
+     *  and `account_id` is either its `creator_id` or the contract `admin_id`.
+     *  Returns `false` for an unknown `gate_id`.
+     */
+    can_delete_collectible(args: { gate_id: ValidGateId, account_id: ValidAccountId }): Promise<boolean>;
+
+    /**
+     *  Returns whether `account_id` can list the `Token` given by `token_id` for sale.
+     *  That is, the token exists, is owned by `account_id`,
+     *  and has no approval yet, since at most one approval is allowed per token.
+     *  Returns `false` for an unknown `token_id`.
+     */
+    can_list_token(args: { token_id: TokenId, account_id: ValidAccountId }): Promise<boolean>;
+
+    /**
+     *  Claims a `Token` for the `Collectible` indicated by `gate_id`.
+     *  The claim is on behalf the `predecessor_account_id`.
+     *  Returns a `TokenId` that represents this claim.
+     *  If the given `gate_id` has exhausted its supply, this call will panic.
+     * 
+     *  The attached deposit must cover the storage used by the new `Token`.
+     *  When the collectible has a `mint_price`, the deposit must cover it as well.
+     *  The `mint_price` is paid to the `creator_id`, minus the `mintgate_fee`
+     *  which is split as in `nft_payout`.
+     *  Any excess deposit is refunded to the `predecessor_account_id`.
+     * 
+     *  The claim is checked to be eligible, *i.e.*, claimable, not sold out, within the
+     *  `max_claims_per_account` limit and past the `claim_cooldown_ns`, before the deposit is charged.
+     *  A rejected claim panics, so the whole attached deposit is refunded.
+     * 
+     *  See <https://github.com/epam/mintgate/issues/6>.
+     */
+    claim_token(args: { gate_id: ValidGateId }, gas?: any, amount?: any): Promise<TokenId>;
+
+    /**
+     *  Claims a `Token` as in `claim_token`,
+     *  but returns the minted `Token` along with its metadata instead of only its `TokenId`.
+     */
+    claim_token_full(args: { gate_id: ValidGateId }, gas?: any, amount?: any): Promise<Token>;
+
+    /**
+     *  Claims a `Token` for the `Collectible` indicated by `gate_id` on behalf of `receiver_id`,
+     *  *e.g.*, to deliver a commissioned work.
+     *  Only the `creator_id` of the collectible is allowed to call this method.
+     * 
+     *  When `royalty_override` is given, it is used by `nft_payout` for this token
+     *  instead of the collectible `royalty` and `first_sale_royalty`.
+     *  It must satisfy the same bounds as the collectible `royalty`,
+     *  and it is not allowed when the collectible royalty is locked.
+     * 
+     *  The attached deposit must cover the storage used by the new `Token`.
+     *  Any excess deposit is refunded to the `predecessor_account_id`.
+     */
+    claim_token_to(args: { gate_id: ValidGateId, receiver_id: ValidAccountId, royalty_override: Fraction|null }, gas?: any, amount?: any): Promise<TokenId>;
+
+    /**
+     *  Burns (deletes) the `Token` identifed by `token_id`.
+     *  Only the `owner_id` or an approved account can burn the token,
+     *  as in `nft_transfer`.
+     * 
+     *  After succefully delete the token,
+     *  a cross-contract call  is made to `nft_on_revoke` for each approval
+     *  to delist from their marketplaces.
+     * 
+     *  Logs an `nft_burn` event, see `NftBurnEvent`,
+     *  whose `authorized_id` is set when the token is burned by an approved account.
+     */
+    burn_token(args: { token_id: TokenId }, gas?: any): Promise<void>;
+
+    /**
+     *  Allows holders of the `Collectible` given by `gate_id` to swap their tokens
+     *  for a token of `to_gate_id` with `swap_token`, *e.g.*, to upgrade them.
+     *  Use `None` to disallow swapping tokens of `gate_id`.
+     *  Only the `creator_id` of both collectibles or the contract `admin_id` are allowed to call it.
+     */
+    set_swap_target(args: { gate_id: ValidGateId, to_gate_id: ValidGateId|null }, gas?: any): Promise<void>;
+
+    /**
+     *  Returns the gate whose tokens can be claimed by swapping a token of `gate_id`, if any.
+     */
+    get_swap_target(args: { gate_id: ValidGateId }): Promise<GateId|null>;
+
+    /**
+     *  Burns the `Token` given by `token_id` and claims a `Token` of `to_gate_id` in its place,
+     *  all in a single call, so the burn is reverted if the claim fails.
+     *  Only the `owner_id` of the token can swap it,
+     *  and `to_gate_id` must be the swap target of its gate, see `set_swap_target`.
+     * 
+     *  The burn is made as in `burn_token` and the claim as in `claim_token`,
+     *  *i.e.*, `to_gate_id` must be eligible to claim from and the attached deposit
+     *  must cover the storage used by the new `Token` and the `mint_price`, if any.
+     * 
+     *  Returns the `TokenId` of the claimed token.
+     */
+    swap_token(args: { token_id: TokenId, to_gate_id: ValidGateId }, gas?: any, amount?: any): Promise<TokenId>;
+
+    /**
+     *  Returns all `Token`s owned by `owner_id`.
+     */
+    get_tokens_by_owner(args: { owner_id: ValidAccountId }): Promise<Token[]>;
+
+    /**
+     *  Returns all tokens claimed by `owner_id` belonging to `gate_id`.
+     * 
+     *  See <https://github.com/epam/mintgate/issues/14>.
+     */
+    get_tokens_by_owner_and_gate_id(args: { gate_id: ValidGateId, owner_id: ValidAccountId }): Promise<Token[]>;
+
+    /**
+     *  Returns all or paginated tokens owned by `account_id`,
+     *  each one along with whether it is listed, *i.e.*, whether it has any approval.
+     *  Since listing a token does not transfer it, listed tokens are returned only once.
+     *  Pagination is given by:
+     * 
+     *  - `from_index` the index to start fetching tokens.
+     *  - `limit` indicates how many tokens will be at most returned.
+     */
+    get_user_portfolio(args: { account_id: ValidAccountId, from_index: U64|null, limit: number|null }): Promise<[Token, boolean][]>;
+
+    /**
+     *  Returns all or paginated tokens minted for the `Collectible` given by `gate_id`,
+     *  together with their current `owner_id`, in minting order.
+     *  Burned tokens are skipped.
+     *  Panics if `gate_id` is not found.
+     *  Pagination is given by:
+     * 
+     *  - `from_index` the index to start fetching holders.
+     *  - `limit` indicates how many holders will be at most returned.
+     */
+    get_gate_holders(args: { gate_id: ValidGateId, from_index: U64|null, limit: number|null }): Promise<[TokenId, AccountId][]>;
+
+    /**
+     *  Returns the number of distinct accounts currently owning a `Token`
+     *  of the `Collectible` given by `gate_id`.
+     *  Panics if `gate_id` is not found.
+     * 
+     *  Note that this method reads every token in `minted_tokens`,
+     *  so its cost grows with the number of tokens minted for the collectible.
+     */
+    get_gate_holder_count(args: { gate_id: ValidGateId }): Promise<U64>;
+
+    /**
+     *  Returns the `TokenId` that will be given to the next minted `Token`,
+     *  without reserving it.
+     *  Note that another claim may take this id before the caller's own claim.
+     */
+    peek_next_token_id(): Promise<U64>;
+
+    /**
+     *  Returns the number of `Token`s ever minted by this contract, including burned ones.
+     *  Use `nft_total_supply` to get the number of live `Token`s instead.
+     */
+    nft_total_minted(): Promise<U64>;
+
+    /**
+     *  Returns all or paginated accounts currently owning at least one `Token`.
+     *  Pagination is given by:
+     * 
+     *  - `from_index` the index to start fetching owners.
+     *  - `limit` indicates how many owners will be at most returned.
+     */
+    get_owners(args: { from_index: U64|null, limit: number|null }): Promise<AccountId[]>;
+
+    /**
+     *  Returns the markets where the `Token` identified by `token_id` is listed,
+     *  together with the `min_price` approved for each one.
+     *  Listings are derived from the token approvals, sorted by market account id.
+     *  Note that currently at most one approval is allowed per `Token`.
+     */
+    nft_token_listings(args: { token_id: TokenId }): Promise<[AccountId, U128][]>;
+
+    /**
+     *  Returns the `min_price` approved for `market_id` to sell the `Token` identified by `token_id`,
+     *  or `None` if `market_id` is not approved.
+     *  Unlike `nft_token`, it does not fetch the collectible metadata.
+     *  Panics if `token_id` is not found.
+     */
+    nft_approval_price(args: { token_id: TokenId, market_id: ValidAccountId }): Promise<U128|null>;
+
+    /**
+     *  Returns the `approval_id` of `market_id` for the `Token` identified by `token_id`,
+     *  or `None` if `market_id` is not approved.
+     *  This is the value to give as `enforce_approval_id` to `nft_transfer`,
+     *  which checks it against the approval of its `receiver_id`.
+     *  Panics if `token_id` is not found.
+     */
+    nft_enforce_approval_id(args: { token_id: TokenId, market_id: ValidAccountId }): Promise<U64|null>;
+
+    /**
+     *  Returns the tokens approved for `market_id`, together with their approved `min_price`,
+     *  *e.g.*, for a market to reconcile its listings against the actual approvals.
+     * 
+     *  There is no index by market, so this method scans the tokens.
+     *  To bound its cost, pagination is applied to the scanned tokens, not to the result:
+     * 
+     *  - `from_index` the index of the first token to scan.
+     *  - `limit` indicates how many tokens will be at most scanned.
+     * 
+     *  Hence a page might contain less than `limit` tokens, even if more pages follow.
+     */
+    nft_tokens_approved_for(args: { market_id: ValidAccountId, from_index: U64|null, limit: number|null }): Promise<[TokenId, U128][]>;
+
+    /**
+     *  Approves a batch of tokens, similar to `nft_approve`.
+     *  Each approval contains the `TokenId` to approve and the minimum price to sell the token for.
+     *  `account_id` indicates the market account contract where list these tokens.
+     *  When the operator allowlist is not empty, `account_id` must be in it.
+     *  The attached deposit is forwarded to the market to cover its listing fee.
+     *  When the market rejects the batch, the approvals are undone and the deposit refunded,
+     *  see `resolve_batch_approve`.
+     *  The prepaid gas must cover `MIN_GAS_FOR_BATCH_APPROVE` plus `GAS_FOR_BATCH_APPROVE_ITEM` per token.
+     */
+    batch_approve(args: { tokens: [TokenId, U128][], account_id: ValidAccountId }, gas?: any, amount?: any): Promise<void>;
+
+    /**
+     *  Changes the `min_price` of the existing approval of `token_id` for `account_id`,
+     *  keeping its `approval_id` and `expires_at`.
+     *  `nft_on_approve` is called on `account_id` with the new price,
+     *  so the market updates the listing without a revoke round-trip.
+     *  The attached deposit is forwarded to the market to cover its listing fee.
+     *  When the market rejects the new price, the previous `min_price` is restored
+     *  and the deposit refunded, see `resolve_approve`.
+     */
+    nft_reapprove(args: { token_id: TokenId, account_id: ValidAccountId, new_min_price: U128 }, gas?: any, amount?: any): Promise<void>;
+
+    /**
+     *  Locks `token_id` on behalf of the calling market, *e.g.*, when an auction for it starts.
+     *  Only an account holding a non-expired approval for the token can lock it.
+     * 
+     *  While locked, the owner cannot transfer, burn nor revoke the approval of the token,
+     *  so the token cannot be taken out from under the bidders.
+     *  The locking market can still transfer it through the sale path, *e.g.*, `nft_transfer_payout`,
+     *  which releases the lock.
+     *  The lock is also released when the approval of the locking market expires.
+     */
+    lock_token(args: { token_id: TokenId }, gas?: any): Promise<void>;
+
+    /**
+     *  Releases the lock on `token_id`, *e.g.*, when its auction is cancelled.
+     *  Only the market that locked the token can unlock it.
+     */
+    unlock_token(args: { token_id: TokenId }, gas?: any): Promise<void>;
+
+    /**
+     *  Returns the market that has locked `token_id`, if any.
+     *  Returns `None` when the token is not locked or does not exist.
+     */
+    get_token_lock(args: { token_id: TokenId }): Promise<AccountId|null>;
+
+    /**
+     *  Revokes `account_id` from a batch of tokens, similar to `nft_revoke`.
+     *  `nft_on_revoke` is called on `account_id` for every revoked token to delist it.
+     *  Tokens that cannot be revoked are reported back as a `Panic::Errors`,
+     *  once the remaining tokens have been revoked.
+     *  The prepaid gas must cover `MIN_GAS_FOR_BATCH_REVOKE` plus `GAS_FOR_BATCH_REVOKE_ITEM` per token.
+     */
+    batch_revoke(args: { tokens: TokenId[], account_id: ValidAccountId }, gas?: any): Promise<void>;
+
+}
+
+/**
+ *  Non-Fungible Token (NEP-171) v1.0.0
+ *  https://nomicon.io/Standards/NonFungibleToken/Core.html
+ * 
+ *  Payouts is part of an ongoing (yet not settled) NEP spec:
+ *  <https://github.com/thor314/NEPs/blob/patch-5/specs/Standards/NonFungibleToken/payouts.md>
+ */
+export interface NonFungibleTokenCore {
+    /**
+     *  Transfer the token `token_id` to the `receiver_id` account.
+     *  When royalties are enforced, only the owner can use this method,
+     *  see `set_enforce_royalties`.
+     * 
+     *  See <https://github.com/epam/mintgate/issues/18>.
+     */
+    nft_transfer(args: { receiver_id: ValidAccountId, token_id: TokenId, enforce_approval_id: U64|null, memo: string|null }, gas?: any): Promise<void>;
+
+    /**
+     *  Query whom to be paid out for a given `token_id`, derived from some `balance`.
+     *  For example, given the following settings for the NFT contract and collectible `gate_id`:
+     * 
+     *  - `mintgate_fee`: `25/1000` (2.5%)
+     *  - `royalty`: `30/100` (30%)
+     * 
+     *  Then `nft_payout(token_id, 5_000_000)` will return
+     * 
+     *  - `mintgate_fee_account_id` -> 125_000
+     *  - `collectible.creator_id` -> 3_375_000
+     *  - `token.owner_id` -> 1_500_000
+     * 
+     *  for any `token_id` claimed from `gate_id`.
+     * 
+     *  If the collectible has a `first_sale_royalty` and the token has not been sold yet,
+     *  `first_sale_royalty` is used instead of `royalty`.
+     *  A royalty override given to the token at `claim_token_to` takes precedence over both.
+     * 
+     *  When the royalty amount is zero, the creator is left out of the payout,
+     *  so no zero-value transfer is issued for it.
+     * 
+     *  An account appearing more than once, *e.g.*, an owner who is also the creator
+     *  or a recipient of the fee split, gets a single entry with the combined amount.
+     * 
+     *  This is part of an ongoing (yet not settled) NEP spec:
+     *  <https://github.com/thor314/NEPs/blob/patch-5/specs/Standards/NonFungibleToken/payouts.md>
+     */
+    nft_payout(args: { token_id: TokenId, balance: U128 }): Promise<Payout>;
+
+    /**
+     *  Attempts to transfer the token.
+     *  Afterwards returns the payout data.
+     *  Effectively it is calling `nft_transfer` followed by `nft_payout`.
+     *  The token is then marked as sold.
+     *  When royalties are enforced, approved accounts must give a `balance` to pay out.
+     * 
+     *  This is part of an ongoing (yet not settled) NEP spec:
+     *  <https://github.com/thor314/NEPs/blob/patch-5/specs/Standards/NonFungibleToken/payouts.md>
+     */
+    nft_transfer_payout(args: { receiver_id: ValidAccountId, token_id: TokenId, approval_id: U64|null, memo: string|null, balance: U128|null }, gas?: any): Promise<Payout|null>;
+
+    /**
+     *  Returns the token identified by `token_id`.
+     *  Or `null` if the `token_id` was not found.
+     * 
+     *  See <https://github.com/epam/mintgate/issues/17>.
+     */
+    nft_token(args: { token_id: TokenId }): Promise<Token|null>;
+
+    /**
+     *  Transfers the token `token_id` to `receiver_id` as in `nft_transfer`,
+     *  and then calls `receiver_id::nft_on_transfer`.
+     *  The token is returned back to its previous owner, along with its approvals,
+     *  when `nft_on_transfer` returns `true` or fails.
+     */
+    nft_transfer_call(args: { receiver_id: ValidAccountId, token_id: TokenId, approval_id: U64|null, memo: string|null, msg: string }, gas?: any): Promise<void>;
+
+}
+
+/**
+ *  Non-Fungible Token Metadata (NEP-177) v1.0.0
+ * 
+ *  <https://nomicon.io/Standards/NonFungibleToken/Metadata.html>
+ */
+export interface NonFungibleTokenMetadata {
+    /**
+     *  Returns the NFT metadata for this contract.
+     */
+    nft_metadata(): Promise<NFTContractMetadata>;
+
+}
+
+/**
+ *  Contract Source Metadata (NEP-330) v1.0.0
+ * 
+ *  <https://github.com/near/NEPs/blob/master/neps/nep-0330.md>
+ */
+export interface ContractSourceMetadata {
+    /**
+     *  Returns the crate version and repository this contract was built from.
+     */
+    contract_source_metadata(): Promise<SourceMetadata>;
+
+}
 
 /**
  *  Non-Fungible Token Approval Management (NEP-178) v1.0.0
@@ -663,8 +1708,16 @@ export interface NonFungibleTokenApprovalMgmt {
      *  The `msg` argument allows the caller to pass into additional information.
      *  A contract implementing the `nft_on_approve` methods must be
      *  deployed into `account_id`.
+     *  The attached deposit is forwarded to `nft_on_approve`, e.g., to pay a listing fee.
+     *  When `nft_on_approve` fails, *e.g.*, the listing fee is short,
+     *  the approval is removed and the deposit refunded, see `resolve_approve`.
+     *  When `msg` contains `expires_at`, `account_id` cannot transfer the token after that time.
+     *  When the operator allowlist is not empty, `account_id` must be in it.
+     * 
+     *  Only the token owner is allowed to approve,
+     *  an already approved account cannot approve further accounts.
      */
-    nft_approve(args: { token_id: TokenId, account_id: ValidAccountId, msg: string|null }, gas?: any): Promise<void>;
+    nft_approve(args: { token_id: TokenId, account_id: ValidAccountId, msg: string|null }, gas?: any, amount?: any): Promise<void>;
 
     /**
      *  Revokes approval for `token_id` from `account_id`.
@@ -686,6 +1739,7 @@ export interface NonFungibleTokenApprovalMgmt {
 export interface NonFungibleTokenEnumeration {
     /**
      *  Returns the total token supply.
+     *  Burned tokens are not counted, see `nft_total_minted`.
      */
     nft_total_supply(): Promise<U64>;
 
@@ -721,17 +1775,46 @@ export interface NonFungibleTokenEnumeration {
 
 }
 
-export interface NftContract extends NonFungibleTokenCore, NonFungibleTokenMetadata, NonFungibleTokenApprovalMgmt, NonFungibleTokenEnumeration {}
+export interface NftContract extends NonFungibleTokenCore, NonFungibleTokenMetadata, ContractSourceMetadata, NonFungibleTokenApprovalMgmt, NonFungibleTokenEnumeration {}
 
 export const NftContractMethods = {
     viewMethods: [
+        "is_enforcing_royalties",
+        "is_open_minting",
+        "get_approved_operators",
         "get_collectible_by_gate_id",
+        "get_collectibles_by_gate_ids",
+        "is_gate_sold_out",
+        "remaining_claims_for",
+        "get_gate_view",
+        "has_claimed",
+        "get_thumbnail",
+        "get_collectible_created_at",
+        "owner_share",
         "get_collectibles_by_creator",
+        "get_creator_collectibles_with_stats",
+        "get_collectibles_by_royalty",
+        "get_recent_collectibles",
+        "can_delete_collectible",
+        "can_list_token",
+        "get_swap_target",
         "get_tokens_by_owner",
         "get_tokens_by_owner_and_gate_id",
+        "get_user_portfolio",
+        "get_gate_holders",
+        "get_gate_holder_count",
+        "peek_next_token_id",
+        "nft_total_minted",
+        "get_owners",
+        "nft_token_listings",
+        "nft_approval_price",
+        "nft_enforce_approval_id",
+        "nft_tokens_approved_for",
+        "get_token_lock",
         "nft_payout",
         "nft_token",
         "nft_metadata",
+        "contract_source_metadata",
         "nft_total_supply",
         "nft_tokens",
         "nft_supply_for_owner",
@@ -740,12 +1823,39 @@ export const NftContractMethods = {
     ],
     changeMethods: [
         "create_collectible",
+        "create_and_claim",
+        "update_collectible",
+        "set_fee_config",
+        "set_fee_split",
+        "set_enforce_royalties",
+        "set_creator_allowlist",
+        "add_approved_operator",
+        "remove_approved_operator",
+        "admin_rebuild_creator_index",
+        "export_collectible",
+        "import_collectible",
         "delete_collectible",
+        "set_min_resale_price",
+        "set_mint_price",
+        "set_max_claims_per_account",
+        "set_claim_cooldown",
+        "set_serial_numbers",
+        "set_allow_zero_price",
+        "set_gate_claimable",
         "claim_token",
+        "claim_token_full",
+        "claim_token_to",
         "burn_token",
+        "set_swap_target",
+        "swap_token",
         "batch_approve",
+        "nft_reapprove",
+        "lock_token",
+        "unlock_token",
+        "batch_revoke",
         "nft_transfer",
         "nft_transfer_payout",
+        "nft_transfer_call",
         "nft_approve",
         "nft_revoke",
         "nft_revoke_all",
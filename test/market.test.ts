@@ -3,7 +3,15 @@ import { utils } from 'near-api-js';
 
 import type { Account } from 'near-api-js';
 
-import { MAX_GAS_ALLOWED, createAddTestCollectible, generateGateId, getShare, formatNsToMs, logger } from './utils';
+import {
+  MAX_GAS_ALLOWED,
+  CLAIM_DEPOSIT,
+  createAddTestCollectible,
+  generateGateId,
+  getShare,
+  formatNsToMs,
+  logger,
+} from './utils';
 import { MINTGATE_FEE } from './initialData';
 import { Panics } from '../src/mg-market';
 
@@ -44,14 +52,14 @@ describe('Market contract', () => {
   describe('get_tokens_for_sale', () => {
     it('returns a list of tokens for sale', async () => {
       const numberOfTokensToAdd = 3;
-      const message: NftApproveMsg = { min_price: '5' };
+      const message: NftApproveMsg = { min_price: '5', expires_at: null };
       const newTokensIds: string[] = [];
 
       const gateId = await generateGateId();
       await addTestCollectible(bob, { gate_id: gateId });
 
       for (let i = 0; i < numberOfTokensToAdd; i += 1) {
-        newTokensIds.push(await bob.contract.claim_token({ gate_id: gateId }));
+        newTokensIds.push(await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT));
       }
 
       await Promise.all(
@@ -86,7 +94,7 @@ describe('Market contract', () => {
       await addTestCollectible(alice, { gate_id: gateId });
 
       for (let i = 0; i < numberOfTokensToCreate; i += 1) {
-        newTokensIds.push(await bob.contract.claim_token({ gate_id: gateId }));
+        newTokensIds.push(await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT));
       }
 
       await Promise.all(
@@ -136,7 +144,7 @@ describe('Market contract', () => {
     const creatorShare = getShare(+priceHrNear, royalty);
     const sellerShare = +priceHrNear - mintgateShare - creatorShare;
 
-    const message: NftApproveMsg = { min_price: priceInternalNear! };
+    const message: NftApproveMsg = { min_price: priceInternalNear!, expires_at: null };
 
     let gateId: string;
     let tokenId: string;
@@ -159,7 +167,7 @@ describe('Market contract', () => {
         royalty,
       });
 
-      tokenId = await alice.contract.claim_token({ gate_id: gateId });
+      tokenId = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
       await alice.contract.nft_approve(
         {
           token_id: tokenId,
@@ -345,7 +353,7 @@ describe('Market contract', () => {
           royalty,
         });
 
-        tokenId2 = await bob.contract.claim_token({ gate_id: gateId2 });
+        tokenId2 = await bob.contract.claim_token({ gate_id: gateId2 }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
         await bob.contract.nft_approve(
           {
             token_id: tokenId2,
@@ -437,7 +445,7 @@ describe('Market contract', () => {
           royalty,
         });
 
-        const tokenId2 = await seller.contract.claim_token({ gate_id: gateId2 });
+        const tokenId2 = await seller.contract.claim_token({ gate_id: gateId2 }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
         await seller.contract.nft_approve(
           {
@@ -512,7 +520,7 @@ describe('Market contract', () => {
           royalty,
         });
 
-        const tokenId2 = await seller.contract.claim_token({ gate_id: gateId2 });
+        const tokenId2 = await seller.contract.claim_token({ gate_id: gateId2 }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
         await seller.contract.nft_approve(
           {
@@ -588,9 +596,15 @@ describe('Market contract', () => {
           royalty,
         });
 
-        const executionOutcome = await seller.functionCall(bob.contractAccount.accountId, 'claim_token', {
-          gate_id: gateId2,
-        });
+        const executionOutcome = await seller.functionCall(
+          bob.contractAccount.accountId,
+          'claim_token',
+          {
+            gate_id: gateId2,
+          },
+          MAX_GAS_ALLOWED,
+          CLAIM_DEPOSIT
+        );
 
         if (
           !(typeof executionOutcome.status === 'object' && typeof executionOutcome.status.SuccessValue === 'string')
@@ -676,7 +690,7 @@ describe('Market contract', () => {
           royalty,
         });
 
-        const tokenId2 = await alice.contract.claim_token({ gate_id: gateId2 });
+        const tokenId2 = await alice.contract.claim_token({ gate_id: gateId2 }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
         await alice.contract.nft_approve(
           {
             token_id: tokenId2,
@@ -753,7 +767,7 @@ describe('Market contract', () => {
       const sellerShare2 = +depositNearLarge - mintgateShare2 - creatorShare2;
 
       beforeAll(async () => {
-        const tokenId2 = await alice.contract.claim_token({ gate_id: gateId });
+        const tokenId2 = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
         await alice.contract.nft_approve(
           {
             token_id: tokenId2,
@@ -849,7 +863,7 @@ describe('Market contract', () => {
 
     describe('errors', () => {
       it('throws if buyer and seller are the same person', async () => {
-        const tokenId2 = await alice.contract.claim_token({ gate_id: gateId });
+        const tokenId2 = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
         const approveMessage: MarketApproveMsg = {
           min_price: '5',
           gate_id: '',
@@ -888,7 +902,7 @@ describe('Market contract', () => {
       });
 
       it('throws on buying not approved token', async () => {
-        const tokenId3 = await alice.contract.claim_token({ gate_id: gateId });
+        const tokenId3 = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
         await expect(
           merchant2.contract.buy_token(
@@ -912,7 +926,7 @@ describe('Market contract', () => {
       });
 
       it('throws if not enough deposit provided', async () => {
-        const tokenId4 = await alice.contract.claim_token({ gate_id: gateId });
+        const tokenId4 = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
         const notEnoughDeposit = new BN(priceInternalNear!).sub(new BN(1));
 
         await alice.contract.nft_approve(
@@ -968,7 +982,7 @@ describe('Market contract', () => {
 
       await addTestCollectible(bob, { gate_id: gateId });
 
-      tokenId = await alice.contract.claim_token({ gate_id: gateId });
+      tokenId = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
       await alice.contractAccount.functionCall(merchant.contract.contractId, 'nft_on_approve', {
         token_id: tokenId,
@@ -1033,7 +1047,7 @@ describe('Market contract', () => {
         gate_id: gateId,
       });
 
-      tokenId = await alice.contract.claim_token({ gate_id: gateId });
+      tokenId = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
       await alice.contract.nft_approve(
         {
           token_id: tokenId,
@@ -1074,22 +1088,14 @@ describe('Market contract', () => {
       expect(tokensByCreatorId).not.toContainEqual(expect.objectContaining({ token_id: tokenId }));
     });
 
-    describe('errors', () => {
-      it('throws when revoking not approved token', async () => {
-        const tokenId2 = await alice.contract.claim_token({ gate_id: gateId });
+    describe('not listed tokens', () => {
+      it('does nothing when revoking not approved token', async () => {
+        const tokenId2 = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
+        const tokensForSaleBefore = await merchant.contract.get_tokens_for_sale();
 
-        await expect(
-          alice.contractAccount.functionCall(merchant.contract.contractId, 'nft_on_revoke', { token_id: tokenId2 })
-        ).rejects.toThrow(
-          expect.objectContaining({
-            type: 'GuestPanic',
-            panic_msg: JSON.stringify({
-              err: Panics[Panics.TokenKeyNotFound],
-              token_key: [bob.contractAccount.accountId, tokenId2],
-              msg: `Token Key \`${bob.contractAccount.accountId}:U64(${tokenId2})\` was not found`,
-            }),
-          })
-        );
+        await alice.contractAccount.functionCall(merchant.contract.contractId, 'nft_on_revoke', { token_id: tokenId2 });
+
+        expect(await merchant.contract.get_tokens_for_sale()).toEqual(tokensForSaleBefore);
       });
     });
   });
@@ -1118,7 +1124,9 @@ describe('Market contract', () => {
       await addTestCollectible(bob, { gate_id: gateId });
 
       tokensIds = await Promise.all(
-        Array.from({ length: numberOfTokensToAdd }, () => bob.contract.claim_token({ gate_id: gateId }))
+        Array.from({ length: numberOfTokensToAdd }, () =>
+          bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT)
+        )
       );
 
       await alice.contractAccount.functionCall(
@@ -61,6 +61,7 @@ const measure = async () => {
     metadata: contractMetadata,
     mintgate_fee: MINTGATE_FEE,
     mintgate_fee_account_id: nftFeeUser.accountId,
+    options: null,
     ...royalty,
   };
 
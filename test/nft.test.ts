@@ -5,6 +5,7 @@ import type { Account } from 'near-api-js';
 
 import {
   MAX_GAS_ALLOWED,
+  CLAIM_DEPOSIT,
   createAddTestCollectible,
   generateGateId,
   isWithinLastMs,
@@ -424,6 +425,7 @@ describe('Nft contract', () => {
             media_hash: null,
             reference: null,
             reference_hash: null,
+            options: null,
           })
         ).rejects.toThrow(
           expect.objectContaining({
@@ -559,7 +561,7 @@ describe('Nft contract', () => {
         const gateId = await generateGateId();
 
         await addTestCollectible(alice, { gate_id: gateId });
-        await alice.contract.claim_token({ gate_id: gateId });
+        await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
         await expect(alice.contract.delete_collectible({ gate_id: gateId })).rejects.toThrow(
           expect.objectContaining({
@@ -607,7 +609,7 @@ describe('Nft contract', () => {
 
       initialTokensOfBob = await bob.contract.get_tokens_by_owner({ owner_id: bob.accountId });
 
-      tokenId = await bob.contract.claim_token({ gate_id: gateId });
+      tokenId = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
       logger.data("Claimed token's id", tokenId);
       logger.data('Claimed claimer', bob.accountId);
@@ -678,7 +680,9 @@ describe('Nft contract', () => {
 
         logger.data('Attempting to claim a token for gate id', nonExistentId);
 
-        await expect(alice.contract.claim_token({ gate_id: nonExistentId })).rejects.toThrow(
+        await expect(
+          alice.contract.claim_token({ gate_id: nonExistentId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT)
+        ).rejects.toThrow(
           expect.objectContaining({
             type: 'GuestPanic',
             panic_msg: JSON.stringify({
@@ -700,9 +704,11 @@ describe('Nft contract', () => {
 
         logger.data('Attempting to claim 2 tokens for gate id created with supply of', 1);
 
-        await alice.contract.claim_token({ gate_id: gateIdNoSupply });
+        await alice.contract.claim_token({ gate_id: gateIdNoSupply }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
-        await expect(alice.contract.claim_token({ gate_id: gateIdNoSupply })).rejects.toThrow(
+        await expect(
+          alice.contract.claim_token({ gate_id: gateIdNoSupply }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT)
+        ).rejects.toThrow(
           expect.objectContaining({
             type: 'GuestPanic',
             panic_msg: JSON.stringify({
@@ -727,7 +733,7 @@ describe('Nft contract', () => {
       gateId = await generateGateId();
       await addTestCollectible(alice, { gate_id: gateId, supply: initialSupply });
 
-      tokenId = await alice.contract.claim_token({ gate_id: gateId });
+      tokenId = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
       await alice.contract.nft_approve(
         {
@@ -776,7 +782,7 @@ describe('Nft contract', () => {
 
     describe('errors', () => {
       it('throws if the initiator does not own the token', async () => {
-        const tokenId2 = await alice.contract.claim_token({ gate_id: gateId });
+        const tokenId2 = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
         await expect(bob.contract.burn_token({ token_id: tokenId2 }, MAX_GAS_ALLOWED)).rejects.toThrow(
           expect.objectContaining({
@@ -824,7 +830,7 @@ describe('Nft contract', () => {
       logger.data('Tokens before', tokensOfAliceBefore.length);
 
       for (let i = 0; i < numberOfTokensToClaim; i += 1) {
-        await alice.contract.claim_token({ gate_id: gateId });
+        await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
       }
 
       tokensOfAliceAfter = await alice.contract.get_tokens_by_owner({ owner_id: alice.accountId });
@@ -885,8 +891,8 @@ describe('Nft contract', () => {
       ]);
 
       for (let i = 0; i < numberOfTokensToClaim; i += 1) {
-        await alice.contract.claim_token({ gate_id: gateId1 });
-        await alice.contract.claim_token({ gate_id: gateId2 });
+        await alice.contract.claim_token({ gate_id: gateId1 }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
+        await alice.contract.claim_token({ gate_id: gateId2 }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
       }
 
       logger.data('Tokens claimed for new collectible', numberOfTokensToClaim);
@@ -947,7 +953,9 @@ describe('Nft contract', () => {
       await addTestCollectible(alice, { gate_id: gateId });
 
       tokensIds = await Promise.all(
-        Array.from({ length: numberOfTokensToApprove }, () => alice.contract.claim_token({ gate_id: gateId }))
+        Array.from({ length: numberOfTokensToApprove }, () =>
+          alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT)
+        )
       );
 
       await alice.contract.batch_approve(
@@ -971,6 +979,7 @@ describe('Nft contract', () => {
           [merchant.contract.contractId]: {
             approval_id: '1',
             min_price: randomMinPrice,
+            expires_at: null,
           },
         }))
       );
@@ -1008,10 +1017,10 @@ describe('Nft contract', () => {
 
       beforeAll(async () => {
         [alreadyApprovedTokenId] = tokensIds;
-        foreignTokenId = await bob.contract.claim_token({ gate_id: gateId });
+        foreignTokenId = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
-        validTokenId = await alice.contract.claim_token({ gate_id: gateId });
-        validTokenId2 = await alice.contract.claim_token({ gate_id: gateId });
+        validTokenId = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
+        validTokenId2 = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
         try {
           await alice.contract.batch_approve(
@@ -1047,6 +1056,7 @@ describe('Nft contract', () => {
             [merchant.contract.contractId]: {
               approval_id: '1',
               min_price: randomMinPrice,
+              expires_at: null,
             },
           }))
         );
@@ -1102,12 +1112,14 @@ describe('Nft contract', () => {
         expect(tokensIdsForSale).not.toContain(foreignTokenId);
       });
 
-      it('throws if number of tokens to approve exceeds 10', async () => {
-        const numberOfTokensToApprove = 11;
+      it('throws if number of tokens to approve exceeds 50', async () => {
+        const numberOfTokensToApprove = 51;
 
-        const tokenId = await alice.contract.claim_token({ gate_id: gateId });
+        const tokenId = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
         const tokensIdsNew = await Promise.all(
-          Array.from({ length: numberOfTokensToApprove - 1 }, () => alice.contract.claim_token({ gate_id: gateId }))
+          Array.from({ length: numberOfTokensToApprove - 1 }, () =>
+            alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT)
+          )
         );
 
         tokensIdsNew.push(tokenId);
@@ -1126,7 +1138,7 @@ describe('Nft contract', () => {
             type: 'GuestPanic',
             panic_msg: JSON.stringify({
               err: Panic[Panic.ExceedTokensToBatchApprove],
-              msg: 'At most 10 tokens are allowed to approve in batch',
+              msg: 'At most 50 tokens are allowed to approve in batch',
             }),
           })
         );
@@ -1169,7 +1181,7 @@ describe('Nft contract', () => {
       let token: Token | null;
 
       beforeAll(async () => {
-        bobsTokenId = await bob.contract.claim_token({ gate_id: gateId });
+        bobsTokenId = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
         initialTokensOfAlice = await alice.contract.get_tokens_by_owner({ owner_id: alice.accountId });
         logger.data('New owner initially had tokens', initialTokensOfAlice.length);
@@ -1224,7 +1236,7 @@ describe('Nft contract', () => {
       });
 
       it("doesn't throw if sender is approved by owner", async () => {
-        const tokenId = await bob.contract.claim_token({ gate_id: gateId });
+        const tokenId = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
         logger.data("Token's owner is", bob.accountId);
 
         await bob.contract.nft_approve(
@@ -1250,7 +1262,7 @@ describe('Nft contract', () => {
       });
 
       it('clears approvals', async () => {
-        const tokenId = await bob.contract.claim_token({ gate_id: gateId });
+        const tokenId = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
         logger.data("Token's owner is", bob.accountId);
 
         await bob.contract.nft_approve(
@@ -1290,7 +1302,7 @@ describe('Nft contract', () => {
       let token: Token | null;
 
       beforeAll(async () => {
-        alicesTokenId = await alice.contract.claim_token({ gate_id: gateId });
+        alicesTokenId = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
         token = await alice.contract.nft_token({ token_id: alicesTokenId });
       });
@@ -1394,7 +1406,7 @@ describe('Nft contract', () => {
       let payout: Payout;
 
       beforeAll(async () => {
-        const tokenId = await alice.contract.claim_token({ gate_id: gateId });
+        const tokenId = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
         payout = await alice.contract.nft_payout({
           token_id: tokenId,
@@ -1419,7 +1431,7 @@ describe('Nft contract', () => {
       let payout: Payout;
 
       beforeAll(async () => {
-        const tokenId = await bob.contract.claim_token({ gate_id: gateId });
+        const tokenId = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
         payout = await bob.contract.nft_payout({
           token_id: tokenId,
@@ -1482,7 +1494,7 @@ describe('Nft contract', () => {
     });
 
     it('returns the correct payout if receiver is not creator', async () => {
-      tokenId = await bob.contract.claim_token({ gate_id: gateId });
+      tokenId = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
       const payoutReceived = await bob.contract.nft_transfer_payout({
         ...args,
@@ -1497,7 +1509,7 @@ describe('Nft contract', () => {
     });
 
     it('returns the correct payout if receiver is creator', async () => {
-      tokenId = await alice.contract.claim_token({ gate_id: gateId });
+      tokenId = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
       const payoutReceived = await alice.contract.nft_transfer_payout({
         ...args,
@@ -1514,7 +1526,7 @@ describe('Nft contract', () => {
       let token: Token;
 
       beforeAll(async () => {
-        tokenId = await alice.contract.claim_token({ gate_id: gateId });
+        tokenId = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
         await alice.contract.nft_transfer_payout({
           ...args,
@@ -1555,7 +1567,7 @@ describe('Nft contract', () => {
         gate_id: gateId,
       });
 
-      const tokenId = await bob.contract.claim_token({ gate_id: gateId });
+      const tokenId = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
       logger.data('Claimed token with id', tokenId);
 
       const tokensOfBob = await bob.contract.get_tokens_by_owner({ owner_id: bob.accountId });
@@ -1584,13 +1596,14 @@ describe('Nft contract', () => {
 
     const message: NftApproveMsg = {
       min_price: '5',
+      expires_at: null,
     };
 
     beforeAll(async () => {
       gateId = await generateGateId();
       await addTestCollectible(alice, { gate_id: gateId });
 
-      tokenId = await bob.contract.claim_token({ gate_id: gateId });
+      tokenId = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
       token = await bob.contract.nft_token({ token_id: tokenId });
       logger.data('Token before approval', token);
@@ -1620,6 +1633,7 @@ describe('Nft contract', () => {
       expect(token!.approvals[merchant.contract.contractId]).toEqual({
         approval_id: String(Object.keys(token!.approvals).length),
         min_price: message.min_price,
+        expires_at: null,
       });
     });
 
@@ -1659,9 +1673,10 @@ describe('Nft contract', () => {
           expect.objectContaining({
             type: 'GuestPanic',
             panic_msg: JSON.stringify({
-              err: Panic[Panic.MsgFormatMinPriceMissing],
-              reason: 'missing field `min_price` at line 1 column 2',
-              msg: `Could not find min_price in msg: missing field \`min_price\` at line 1 column 2`,
+              err: Panic[Panic.MsgFormatInvalidField],
+              field: 'min_price',
+              reason: 'field is missing, expected a string of yoctoNEARs',
+              msg: 'Invalid field `min_price` in msg: field is missing, expected a string of yoctoNEARs',
             }),
           })
         );
@@ -1671,11 +1686,14 @@ describe('Nft contract', () => {
         logger.data('Attempting to approve token without message');
 
         await expect(
-          alice.contract.nft_approve({
-            token_id: tokenId,
-            account_id: merchant.contract.contractId,
-            msg: null,
-          })
+          alice.contract.nft_approve(
+            {
+              token_id: tokenId,
+              account_id: merchant.contract.contractId,
+              msg: null,
+            },
+            MAX_GAS_ALLOWED
+          )
         ).rejects.toThrow(
           expect.objectContaining({
             type: 'GuestPanic',
@@ -1691,7 +1709,7 @@ describe('Nft contract', () => {
         logger.data('Attempting to approve token, approver', alice.accountId);
         logger.data('Attempting to approve token, owner', bob.accountId);
 
-        const tokenId2 = await bob.contract.claim_token({ gate_id: gateId });
+        const tokenId2 = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
         await expect(
           alice.contract.nft_approve(
@@ -1716,7 +1734,7 @@ describe('Nft contract', () => {
       });
 
       it('throws for already approved token ', async () => {
-        const tokenId2 = await alice.contract.claim_token({ gate_id: gateId });
+        const tokenId2 = await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
         await alice.contract.nft_approve(
           {
@@ -1731,11 +1749,14 @@ describe('Nft contract', () => {
         logger.data('Attempting to approve token with approvals:', token2!.approvals);
 
         await expect(
-          alice.contract.nft_approve({
-            token_id: tokenId2,
-            account_id: merchant.contract.contractId,
-            msg: JSON.stringify(message),
-          })
+          alice.contract.nft_approve(
+            {
+              token_id: tokenId2,
+              account_id: merchant.contract.contractId,
+              msg: JSON.stringify(message),
+            },
+            MAX_GAS_ALLOWED
+          )
         ).rejects.toThrow(
           expect.objectContaining({
             type: 'GuestPanic',
@@ -1751,11 +1772,14 @@ describe('Nft contract', () => {
         const nonExistentTokenId = '222222222222222';
 
         await expect(
-          alice.contract.nft_approve({
-            token_id: nonExistentTokenId,
-            account_id: merchant.contract.contractId,
-            msg: JSON.stringify(message),
-          })
+          alice.contract.nft_approve(
+            {
+              token_id: nonExistentTokenId,
+              account_id: merchant.contract.contractId,
+              msg: JSON.stringify(message),
+            },
+            MAX_GAS_ALLOWED
+          )
         ).rejects.toThrow(
           expect.objectContaining({
             type: 'GuestPanic',
@@ -1779,10 +1803,11 @@ describe('Nft contract', () => {
       gateId = await generateGateId();
       await addTestCollectible(alice, { gate_id: gateId });
 
-      tokenId = await bob.contract.claim_token({ gate_id: gateId });
+      tokenId = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
       const msg: NftApproveMsg = {
         min_price: '5',
+        expires_at: null,
       };
 
       await bob.contract.nft_approve(
@@ -1855,7 +1880,7 @@ describe('Nft contract', () => {
     });
 
     it('throw if token is not approved for market', async () => {
-      const tokenId2 = await bob.contract.claim_token({ gate_id: gateId });
+      const tokenId2 = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
       const token2 = await bob.contract.nft_token({ token_id: tokenId2 });
 
       logger.data("Attempting to revoke token, token's approvals", token2!.approvals);
@@ -1908,7 +1933,7 @@ describe('Nft contract', () => {
       gateId = await generateGateId();
       await addTestCollectible(alice, { gate_id: gateId });
 
-      tokenId = await bob.contract.claim_token({ gate_id: gateId });
+      tokenId = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
       await bob.contract.nft_approve(
         {
@@ -1960,6 +1985,7 @@ describe('Nft contract', () => {
       [merchant.contract.contractId, `${merchant2.contract.contractId}-1`].forEach((contractId) => {
         const msg: NftApproveMsg = {
           min_price: '6',
+          expires_at: null,
         };
         approvePromises.push(
           bob.contract.nft_approve(
@@ -2050,9 +2076,9 @@ describe('Nft contract', () => {
 
       for (let i = 0; i < numberOfTokensToAdd; i += 1) {
         if (i % 2) {
-          alicesTokens.push(await alice.contract.claim_token({ gate_id: gateId }));
+          alicesTokens.push(await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT));
         } else {
-          bobsTokens.push(await bob.contract.claim_token({ gate_id: gateId }));
+          bobsTokens.push(await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT));
         }
       }
 
@@ -2105,10 +2131,12 @@ describe('Nft contract', () => {
       tokensBefore = await bob.contract.nft_tokens({ from_index: null, limit: null });
       logger.data('Tokens before', tokensBefore.length);
 
-      const firstTokenId = await bob.contract.claim_token({ gate_id: gateId });
+      const firstTokenId = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
       newTokensIds = await Promise.all(
-        Array.from({ length: numberOfTokensToClaim - 1 }, async () => bob.contract.claim_token({ gate_id: gateId }))
+        Array.from({ length: numberOfTokensToClaim - 1 }, async () =>
+          bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT)
+        )
       );
       newTokensIds.push(firstTokenId);
 
@@ -2177,10 +2205,12 @@ describe('Nft contract', () => {
       tokensAmtOwnedBefore = await bob.contract.nft_supply_for_owner({ account_id: alice.accountId });
       logger.data('Tokens owned by alice before', tokensAmtOwnedBefore);
 
-      await alice.contract.claim_token({ gate_id: gateId });
+      await alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
       await Promise.all(
-        Array.from({ length: numberOfTokensToClaim - 1 }, async () => alice.contract.claim_token({ gate_id: gateId }))
+        Array.from({ length: numberOfTokensToClaim - 1 }, async () =>
+          alice.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT)
+        )
       );
 
       tokensAmtOwnedAfter = await bob.contract.nft_supply_for_owner({ account_id: alice.accountId });
@@ -2221,10 +2251,12 @@ describe('Nft contract', () => {
       });
       logger.data('Tokens before', tokensBefore.length);
 
-      const firstTokenId = await bob.contract.claim_token({ gate_id: gateId });
+      const firstTokenId = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
       newTokensIds = await Promise.all(
-        Array.from({ length: numberOfTokensToClaim - 1 }, async () => bob.contract.claim_token({ gate_id: gateId }))
+        Array.from({ length: numberOfTokensToClaim - 1 }, async () =>
+          bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT)
+        )
       );
       newTokensIds.push(firstTokenId);
 
@@ -2299,7 +2331,7 @@ describe('Nft contract', () => {
 
       await addTestCollectible(bob, { gate_id: gateId });
 
-      const tokenId = await bob.contract.claim_token({ gate_id: gateId });
+      const tokenId = await bob.contract.claim_token({ gate_id: gateId }, MAX_GAS_ALLOWED, CLAIM_DEPOSIT);
 
       expect(await bob.contract.nft_token_uri({ token_id: tokenId })).toBe(
         `${contractMetadata.base_uri}${contractMetadata.base_uri!.endsWith('/') ? '' : '/'}${gateId}`
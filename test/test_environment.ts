@@ -40,6 +40,7 @@ export default class LocalTestEnvironment extends NodeEnvironment {
       metadata: contractMetadata,
       mintgate_fee: MINTGATE_FEE,
       mintgate_fee_account_id: nftFeeUser.accountId,
+      options: null,
       ...royalty,
     };
 
@@ -57,7 +58,7 @@ export default class LocalTestEnvironment extends NodeEnvironment {
       'target/wasm32-unknown-unknown/release/mg_market.wasm',
       {
         func: 'init',
-        args: {},
+        args: { listing_fee: '0', mintgate_fee_account_id: nftFeeUser.accountId },
       }
     );
 
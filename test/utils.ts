@@ -129,3 +129,6 @@ export const getShare = (totalAmount: number, { num, den }: Fraction): number =>
 export const validGateIdRegEx = /^[a-z\d_-]{1,32}$/gi;
 
 export const MAX_GAS_ALLOWED = new BN(300000000000000);
+
+// Deposit attached to `claim_token` to cover storage, the excess is refunded.
+export const CLAIM_DEPOSIT = new BN('100000000000000000000000');